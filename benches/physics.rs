@@ -0,0 +1,31 @@
+//! Physics update cost for a large pile of dynamic circles.
+//! Run with `cargo bench --bench physics`.
+
+use balltest_rs::{components::Aabb, systems::PhysicsSystem, world::World};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::time::Duration;
+
+const BALLS: usize = 10_000;
+const DT: Duration = Duration::from_millis(8);
+
+fn update_10k_circles(c: &mut Criterion) {
+    let mut world = World::with_capacity(BALLS);
+    world.spawn_random_balls(BALLS, Aabb::from_center([0.0, 0.0], [0.95, 0.95]), 0x5eed);
+    let mut physics = PhysicsSystem::new();
+    // Let the pile settle into a steady number of contacts first
+    for _ in 0..30 {
+        physics.update(&mut world, DT);
+    }
+
+    c.bench_function("update 10k dynamic circles", |b| {
+        b.iter(|| physics.update(&mut world, DT));
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // An update is far too slow at this size for the default 100 samples
+    config = Criterion::default().sample_size(10);
+    targets = update_10k_circles
+}
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,  // Quad vertex position (covers the whole screen)
+}
+
+struct InstanceInput {
+    @location(1) top: vec4<f32>,       // Color at the top edge (linear RGBA)
+    @location(2) bottom: vec4<f32>,    // Color at the bottom edge (linear RGBA)
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.clip_position = vec4<f32>(vertex.position, 0.0, 1.0);
+
+    // Blend vertically; the rasterizer interpolates between the quad's corners
+    let t = vertex.position.y * 0.5 + 0.5;
+    out.color = mix(instance.bottom, instance.top, t);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,28 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,  // Quad vertex position (covers the whole screen)
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// The scene, rendered at the scaled resolution
+@group(0) @binding(0) var t_scene: texture_2d<f32>;
+@group(0) @binding(1) var s_scene: sampler;
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.clip_position = vec4<f32>(vertex.position, 0.0, 1.0);
+    out.uv = vec2<f32>(vertex.position.x * 0.5 + 0.5, 0.5 - vertex.position.y * 0.5);
+
+    return out;
+}
+
+// Linear filtering does the up- (or down-) sampling
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(t_scene, s_scene, in.uv);
+}
@@ -1,24 +1,47 @@
+use crate::entity::Entity;
+use crate::prefab::Prefab;
+use crate::world::EntityId;
+use rand::{SeedableRng, rngs::StdRng};
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
-    pub position: [f32; 2], // NDC
-    pub rotation: f32,      // radians
-    pub scale: [f32; 2],    // NDC
+    pub position: [f32; 2],      // NDC
+    pub prev_position: [f32; 2], // NDC, position at the start of the last sim step
+    pub rotation: f32,           // radians
+    pub scale: [f32; 2],         // NDC
+    pub layer: i32,              // Draw order, higher layers draw on top
 }
 
 impl Transform {
     pub fn new(position: [f32; 2]) -> Self {
         Self {
             position,
+            prev_position: position,
             rotation: 0.0,
             scale: [1.0, 1.0],
+            layer: 0,
         }
     }
+
+    /// Position blended between the previous and current sim step (alpha in 0.0–1.0)
+    pub fn interpolated_position(&self, alpha: f32) -> [f32; 2] {
+        [
+            self.prev_position[0] + (self.position[0] - self.prev_position[0]) * alpha,
+            self.prev_position[1] + (self.position[1] - self.prev_position[1]) * alpha,
+        ]
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Physics {
     pub velocity: [f32; 2],
     pub acceleration: [f32; 2],
+    #[cfg_attr(feature = "serde", serde(with = "non_finite_f32"))]
     pub mass: f32,
 
     // behavior flags
@@ -28,6 +51,16 @@ pub struct Physics {
     // collision properties
     pub restitution: f32,
     pub friction: f32,
+
+    // air properties
+    pub drag_coefficient: f32, // Quadratic drag, scaled by cross-section width (0.0 = none)
+
+    // Reports overlaps (PhysicsEvent::SensorEntered) but never pushes or gets pushed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sensor: bool,
+    // Held in place like a static body, keeping velocity for when it's unfrozen
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frozen: bool,
 }
 
 impl Physics {
@@ -40,6 +73,9 @@ impl Physics {
             dynamic: true,
             restitution: 0.8,
             friction: 0.5,
+            drag_coefficient: 0.0,
+            sensor: false,
+            frozen: false,
         }
     }
     pub fn new_static() -> Self {
@@ -52,31 +88,670 @@ impl Physics {
         }
     }
 
+    /// Static trigger area, e.g. a goal; see `World::on_sensor_overlap`
+    pub fn new_sensor() -> Self {
+        Self {
+            sensor: true,
+            ..Self::new_static()
+        }
+    }
+
     pub fn with_velocity(mut self, velocity: [f32; 2]) -> Self {
         self.velocity = velocity;
         self
     }
+
+    pub fn with_drag(mut self, drag_coefficient: f32) -> Self {
+        self.drag_coefficient = drag_coefficient;
+        self
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+/// Sim time left before the entity is despawned by `LifetimeSystem`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lifetime {
+    pub remaining: Duration,
+}
+
+impl Lifetime {
+    pub fn new(remaining: Duration) -> Self {
+        Self { remaining }
+    }
+}
+
+/// Integer shown and changed by game rules, e.g. a score; see `World::add_to_counter`
+/// and `World::bind_text_to_counter`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Counter {
+    pub value: i64,
+}
+
+impl Counter {
+    pub fn new(value: i64) -> Self {
+        Self { value }
+    }
+}
+
+/// Box centered on its entity inside which dynamic bodies fall with `gravity`
+/// instead of the physics system's, e.g. water, a reversed-gravity room or a
+/// sideways tunnel. Where zones overlap the smallest wins, then the last added.
+/// Usually put on a static, invisible or outlined entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GravityZone {
+    pub extents: [f32; 2], // Half width and height
+    pub gravity: [f32; 2],
+}
+
+impl GravityZone {
+    pub fn new(extents: [f32; 2], gravity: [f32; 2]) -> Self {
+        Self { extents, gravity }
+    }
+
+    /// Area covered when the entity sits at `center`
+    pub fn bounds(&self, center: [f32; 2]) -> Aabb {
+        Aabb::from_center(center, self.extents)
+    }
+}
+
+/// What a `ColorAnimation` does after reaching its last keyframe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopMode {
+    Once,     // Hold the last keyframe and remove the animation
+    Loop,     // Start over from the first keyframe
+    PingPong, // Play back to the first keyframe, then forward again
+}
+
+/// Sweeps the shape's color through `keyframes`, evenly spaced over `duration`
+/// of sim time. Ticked by `ColorAnimationSystem`.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorAnimation {
+    pub keyframes: Vec<[f32; 3]>,
+    pub duration: Duration, // First keyframe to last
+    pub mode: LoopMode,
+    pub elapsed: Duration,
+    pub(crate) from_current: bool, // Shape's color becomes the first keyframe on the first tick
+}
+
+impl ColorAnimation {
+    pub fn new(keyframes: Vec<[f32; 3]>, duration: Duration, mode: LoopMode) -> Self {
+        Self {
+            keyframes,
+            duration,
+            mode,
+            elapsed: Duration::ZERO,
+            from_current: false,
+        }
+    }
+
+    /// Fade from whatever color the shape has when the animation starts to `target`
+    pub fn to(target: [f32; 3], duration: Duration) -> Self {
+        Self {
+            from_current: true,
+            ..Self::new(vec![target], duration, LoopMode::Once)
+        }
+    }
+
+    /// Color `elapsed` into the animation, or None without keyframes
+    pub fn color_at(&self, elapsed: Duration) -> Option<[f32; 3]> {
+        let last = self.keyframes.len().checked_sub(1)?;
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.duration.as_secs_f32()
+        };
+        let t = match self.mode {
+            LoopMode::Once => t.min(1.0),
+            LoopMode::Loop => t.fract(),
+            LoopMode::PingPong => {
+                let phase = t % 2.0;
+                if phase > 1.0 { 2.0 - phase } else { phase }
+            }
+        };
+
+        let position = t * last as f32;
+        let index = (position.floor() as usize).min(last.saturating_sub(1));
+        let (from, to) = (self.keyframes[index], self.keyframes[(index + 1).min(last)]);
+        let f = position - index as f32;
+        Some(std::array::from_fn(|i| from[i] + (to[i] - from[i]) * f))
+    }
+
+    /// A `Once` animation that has reached its last keyframe
+    pub fn is_finished(&self) -> bool {
+        self.mode == LoopMode::Once && self.elapsed >= self.duration
+    }
+}
+
+/// What an `Emitter` spawns
+#[derive(Clone)]
+pub enum EmitterSource {
+    /// Every part of the prefab, offset from the emitter's position
+    Prefab(Prefab),
+    /// The returned entity, its position taken as an offset from the emitter's
+    Factory(Arc<dyn Fn() -> Entity>),
+}
+
+impl std::fmt::Debug for EmitterSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitterSource::Prefab(prefab) => f.debug_tuple("Prefab").field(prefab).finish(),
+            EmitterSource::Factory(_) => f.write_str("Factory(..)"),
+        }
+    }
+}
+
+/// Spawns from `source` every `interval` of sim time while fewer than
+/// `max_alive` of its spawns are still around. Ticked by `EmitterSystem`.
+#[derive(Clone, Debug)]
+pub struct Emitter {
+    pub source: EmitterSource,
+    pub interval: Duration,
+    pub max_alive: usize,
+    pub direction: f32,  // Radians, 0 = +x; center of the launch cone
+    pub spread: f32,     // Full width of the launch cone (radians)
+    pub speed: [f32; 2], // Launch speed range, min to max (NDC/sec)
+    pub enabled: bool,
+    pub(crate) elapsed: Duration,       // Sim time since the last spawn
+    pub(crate) children: Vec<EntityId>, // Root ids of spawns alive as of the last tick
+    pub(crate) rng: Option<StdRng>,     // None draws from `World::rng`
+}
+
+impl Emitter {
+    pub fn new(source: EmitterSource, interval: Duration) -> Self {
+        Self {
+            source,
+            interval,
+            max_alive: 100,
+            direction: std::f32::consts::FRAC_PI_2,
+            spread: 0.0,
+            speed: [0.0, 0.0],
+            enabled: true,
+            elapsed: Duration::ZERO,
+            children: Vec::new(),
+            rng: None,
+        }
+    }
+
+    pub fn with_max_alive(mut self, max_alive: usize) -> Self {
+        self.max_alive = max_alive;
+        self
+    }
+
+    /// Launch spawns within `spread` radians around `direction`, at a speed
+    /// picked from `min_speed..=max_speed`
+    pub fn with_launch(
+        mut self,
+        direction: f32,
+        spread: f32,
+        min_speed: f32,
+        max_speed: f32,
+    ) -> Self {
+        self.direction = direction;
+        self.spread = spread.abs();
+        self.speed = [min_speed.min(max_speed), min_speed.max(max_speed)];
+        self
+    }
+
+    /// Own seed for the launch randomness, instead of drawing from `World::rng`.
+    /// The same seed gives the same spawns.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Some(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Root ids of this emitter's spawns still alive as of the last tick
+    pub fn children(&self) -> &[EntityId] {
+        &self.children
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shape {
     Circle {
         radius: f32,     // NDC
-        color: [f32; 3], // RGB-format
+        color: [f32; 3], // sRGB, as it appears on screen
+        opacity: f32,    // 0.0 (invisible) to 1.0 (opaque)
+        texture: Option<TextureId>,
+        edge_softness: Option<f32>, // Overrides the renderer's edge softness
+        color_outer: Option<[f32; 3]>, // Fades from `color` at the center to this at the rim
     },
     Text {
         content: String, // I.E. "Hey whats up guys"
         font_size: f32,
         color: [f32; 3],
+        opacity: f32,
+        // Laid-out width and height (NDC), filled in by Renderer::measure_text
+        measured_size: Option<[f32; 2]>,
+        space: TextSpace,
     },
     Rectangle {
         length: f32, // NDC
         height: f32, // NDC
         color: [f32; 3],
+        opacity: f32,
+        texture: Option<TextureId>,
+        // Rounds the corners when drawn (NDC), clamped to half the smaller side.
+        // Collision still treats the rectangle as sharp-cornered.
+        corner_radius: f32,
+    },
+    Polygon {
+        vertices: Vec<[f32; 2]>,  // Convex outline relative to the transform (NDC)
+        triangles: Vec<[f32; 2]>, // Fan triangulation of `vertices`, 3 points per triangle
+        color: [f32; 3],
+        opacity: f32,
     },
 }
 
+/// Where a text shape is positioned
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextSpace {
+    /// Centered on the transform position, in NDC like every other shape
+    World,
+    /// Pinned to a window corner (HUD). `offset_px` is in logical pixels from
+    /// that corner; the transform is ignored.
+    Screen { anchor: Corner, offset_px: [f32; 2] },
+}
+
+/// A window corner
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Per-entity script, run once per sim step by `BehaviorSystem`. The closure must be
+/// `Clone` so entities (and prefabs, snapshots) stay cloneable; each clone gets its
+/// own copy of the captured state. Not saved by `World::save_json`.
+pub struct Behavior(Box<dyn BehaviorFn>);
+
+// FnMut + Clone, object safe
+trait BehaviorFn: Send {
+    fn call(&mut self, ctx: BehaviorCtx<'_>);
+    fn clone_box(&self) -> Box<dyn BehaviorFn>;
+}
+
+impl<F: FnMut(BehaviorCtx<'_>) + Send + Clone + 'static> BehaviorFn for F {
+    fn call(&mut self, ctx: BehaviorCtx<'_>) {
+        self(ctx)
+    }
+
+    fn clone_box(&self) -> Box<dyn BehaviorFn> {
+        Box::new(self.clone())
+    }
+}
+
+impl Behavior {
+    pub fn new(script: impl FnMut(BehaviorCtx<'_>) + Send + Clone + 'static) -> Self {
+        Self(Box::new(script))
+    }
+
+    pub(crate) fn run(&mut self, ctx: BehaviorCtx<'_>) {
+        self.0.call(ctx)
+    }
+}
+
+impl Clone for Behavior {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+impl std::fmt::Debug for Behavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Behavior(..)")
+    }
+}
+
+/// What a `Behavior` can touch: its own entity's transform and physics, the sim
+/// clock, and deferred spawns, despawns and physics changes applied after every
+/// behavior has run
+pub struct BehaviorCtx<'a> {
+    pub id: EntityId,
+    pub transform: &'a mut Transform,
+    pub physics: Option<&'a mut Physics>,
+    pub sim_time: Duration, // Sim time at the start of this step
+    pub dt: Duration,
+    pub(crate) spawns: &'a mut Vec<Entity>,
+    pub(crate) despawns: &'a mut Vec<EntityId>,
+    pub(crate) physics_changes: &'a mut Vec<(EntityId, Option<Physics>)>,
+}
+
+impl BehaviorCtx<'_> {
+    /// Add `entity` once this step's behaviors have all run
+    pub fn spawn(&mut self, entity: Entity) {
+        self.spawns.push(entity);
+    }
+
+    /// Remove `id` once this step's behaviors have all run
+    pub fn despawn(&mut self, id: EntityId) {
+        self.despawns.push(id);
+    }
+
+    /// Remove the entity running this behavior
+    pub fn despawn_self(&mut self) {
+        self.despawns.push(self.id);
+    }
+
+    /// Give `id` physics once this step's behaviors have all run, replacing any it had
+    pub fn set_physics(&mut self, id: EntityId, physics: Physics) {
+        self.physics_changes.push((id, Some(physics)));
+    }
+
+    /// Take `id`'s physics away once this step's behaviors have all run
+    pub fn remove_physics(&mut self, id: EntityId) {
+        self.physics_changes.push((id, None));
+    }
+}
+
+/// Application data attached to an entity (owner, score value, hit points...);
+/// see `Entity::with_user_data`. Cloned along with the entity, so prefabs and
+/// snapshots carry it. Not saved by `World::save_json`.
+pub struct UserData(Box<dyn UserValue>);
+
+// Any + Clone, object safe
+trait UserValue: Any + Send {
+    fn clone_box(&self) -> Box<dyn UserValue>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any + Send + Clone> UserValue for T {
+    fn clone_box(&self) -> Box<dyn UserValue> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl UserData {
+    pub fn new<T: Any + Send + Clone>(value: T) -> Self {
+        Self(Box::new(value))
+    }
+
+    /// The value, if it's a `T`
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref()
+    }
+
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.0.as_any_mut().downcast_mut()
+    }
+}
+
+impl Clone for UserData {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+impl std::fmt::Debug for UserData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UserData(..)")
+    }
+}
+
+/// Handle to a texture loaded by the renderer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureId(pub(crate) usize);
+
+/// Handle to a tag registered with `World::register_tag`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagId(pub(crate) u8);
+
+/// Handle to a group created with `World::create_group`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupId(pub(crate) u32);
+
+/// Set of tags on one entity, one bit per `TagId`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tags(u64);
+
+impl Tags {
+    pub fn contains(self, tag: TagId) -> bool {
+        self.0 & (1 << tag.0) != 0
+    }
+
+    pub fn insert(&mut self, tag: TagId) {
+        self.0 |= 1 << tag.0;
+    }
+
+    pub fn remove(&mut self, tag: TagId) {
+        self.0 &= !(1 << tag.0);
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Axis-aligned bounding box (NDC)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl Aabb {
+    pub fn from_center(center: [f32; 2], half_extents: [f32; 2]) -> Self {
+        Self {
+            min: [center[0] - half_extents[0], center[1] - half_extents[1]],
+            max: [center[0] + half_extents[0], center[1] + half_extents[1]],
+        }
+    }
+
+    pub fn center(&self) -> [f32; 2] {
+        [
+            (self.min[0] + self.max[0]) / 2.0,
+            (self.min[1] + self.max[1]) / 2.0,
+        ]
+    }
+
+    /// True if the boxes touch or overlap
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.max[0]
+            && self.max[0] >= other.min[0]
+            && self.min[1] <= other.max[1]
+            && self.max[1] >= other.min[1]
+    }
+
+    /// True if `point` is inside or on the edge
+    pub fn contains_point(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.min[0]
+            && point[0] <= self.max[0]
+            && point[1] >= self.min[1]
+            && point[1] <= self.max[1]
+    }
+
+    pub fn area(&self) -> f32 {
+        (self.max[0] - self.min[0]) * (self.max[1] - self.min[1])
+    }
+
+    /// Overlapping region of two boxes, if any
+    pub fn intersection(&self, other: &Aabb) -> Option<Aabb> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        Some(Aabb {
+            min: [self.min[0].max(other.min[0]), self.min[1].max(other.min[1])],
+            max: [self.max[0].min(other.max[0]), self.max[1].min(other.max[1])],
+        })
+    }
+
+    /// Smallest box containing both
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: [self.min[0].min(other.min[0]), self.min[1].min(other.min[1])],
+            max: [self.max[0].max(other.max[0]), self.max[1].max(other.max[1])],
+        }
+    }
+}
+
+impl Shape {
+    pub fn opacity(&self) -> f32 {
+        match self {
+            Shape::Circle { opacity, .. }
+            | Shape::Text { opacity, .. }
+            | Shape::Rectangle { opacity, .. }
+            | Shape::Polygon { opacity, .. } => *opacity,
+        }
+    }
+
+    pub fn color(&self) -> [f32; 3] {
+        match self {
+            Shape::Circle { color, .. }
+            | Shape::Text { color, .. }
+            | Shape::Rectangle { color, .. }
+            | Shape::Polygon { color, .. } => *color,
+        }
+    }
+
+    pub fn set_color(&mut self, value: [f32; 3]) {
+        match self {
+            Shape::Circle { color, .. }
+            | Shape::Text { color, .. }
+            | Shape::Rectangle { color, .. }
+            | Shape::Polygon { color, .. } => *color = value,
+        }
+    }
+
+    pub fn set_opacity(&mut self, value: f32) {
+        match self {
+            Shape::Circle { opacity, .. }
+            | Shape::Text { opacity, .. }
+            | Shape::Rectangle { opacity, .. }
+            | Shape::Polygon { opacity, .. } => *opacity = value.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Bounding box of the shape placed at `transform`, scaled and rotated the
+    /// way it's drawn. Text is drawn upright at its font size, so it ignores both.
+    pub fn bounds(&self, transform: &Transform) -> Aabb {
+        let [scale_x, scale_y] = transform.scale;
+        let (sin, cos) = transform.rotation.sin_cos();
+        let half = match self {
+            Shape::Polygon { vertices, .. } if !vertices.is_empty() => {
+                // The box around the placed vertices; polygons aren't centered
+                let [x0, y0] = transform.position;
+                let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+                for v in vertices {
+                    let (x, y) = (v[0] * scale_x, v[1] * scale_y);
+                    let placed = [x0 + x * cos - y * sin, y0 + x * sin + y * cos];
+                    for axis in 0..2 {
+                        min[axis] = min[axis].min(placed[axis]);
+                        max[axis] = max[axis].max(placed[axis]);
+                    }
+                }
+                return Aabb { min, max };
+            }
+            Shape::Text { .. } => self.half_extents(),
+            Shape::Circle { radius, .. } => {
+                // Extents of the rotated ellipse
+                let (a, b) = (radius * scale_x, radius * scale_y);
+                [(a * cos).hypot(b * sin), (a * sin).hypot(b * cos)]
+            }
+            _ => {
+                let [half_x, half_y] = self.half_extents();
+                let (x, y) = (half_x * scale_x.abs(), half_y * scale_y.abs());
+                [
+                    (x * cos).abs() + (y * sin).abs(),
+                    (x * sin).abs() + (y * cos).abs(),
+                ]
+            }
+        };
+        Aabb::from_center(transform.position, half)
+    }
+
+    /// Half width and half height of the shape's axis-aligned bounding box (NDC)
+    pub fn half_extents(&self) -> [f32; 2] {
+        match self {
+            Shape::Circle { radius, .. } => [*radius, *radius],
+            Shape::Rectangle { length, height, .. } => [length / 2.0, height / 2.0],
+            Shape::Polygon { vertices, .. } => vertices.iter().fold([0.0, 0.0], |half, v| {
+                [half[0].max(v[0].abs()), half[1].max(v[1].abs())]
+            }),
+            Shape::Text { measured_size, .. } => match measured_size {
+                Some([width, height]) => [width / 2.0, height / 2.0],
+                // Same approximation contains_point uses for unmeasured text
+                None => [0.1, 0.1],
+            },
+        }
+    }
+
+    /// Width of the shape as seen by a flow moving along `direction` (unit vector).
+    /// Rectangles project their rotated extents onto the direction's normal,
+    /// so a plank moving edge-on presents a much smaller width than flat-on.
+    pub fn cross_section(&self, rotation: f32, direction: [f32; 2]) -> f32 {
+        match self {
+            Shape::Circle { radius, .. } => radius * 2.0,
+            Shape::Rectangle { length, height, .. } => {
+                let normal = [-direction[1], direction[0]];
+                let (sin, cos) = rotation.sin_cos();
+                let axis_x = [cos, sin];
+                let axis_y = [-sin, cos];
+
+                let along_x = (normal[0] * axis_x[0] + normal[1] * axis_x[1]).abs();
+                let along_y = (normal[0] * axis_y[0] + normal[1] * axis_y[1]).abs();
+
+                length * along_x + height * along_y
+            }
+            Shape::Polygon { vertices, .. } => {
+                // Spread of the rotated vertices along the flow's normal
+                let normal = [-direction[1], direction[0]];
+                let (sin, cos) = rotation.sin_cos();
+                let (min, max) = vertices.iter().fold((f32::MAX, f32::MIN), |(min, max), v| {
+                    let x = v[0] * cos - v[1] * sin;
+                    let y = v[0] * sin + v[1] * cos;
+                    let d = x * normal[0] + y * normal[1];
+                    (min.min(d), max.max(d))
+                });
+                if vertices.is_empty() { 0.0 } else { max - min }
+            }
+            Shape::Text { .. } => {
+                let half = self.half_extents();
+                half[0] + half[1]
+            }
+        }
+    }
+}
+
+/// Split a convex polygon into a triangle fan around its first vertex.
+/// Returns no triangles for fewer than 3 vertices.
+pub fn fan_triangulate(vertices: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    let mut triangles = Vec::with_capacity(vertices.len().saturating_sub(2) * 3);
+    for i in 1..vertices.len().saturating_sub(1) {
+        triangles.extend_from_slice(&[vertices[0], vertices[i], vertices[i + 1]]);
+    }
+    triangles
+}
+
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Clickable {
     pub enabled: bool,
     pub hovered: bool,
@@ -90,3 +765,103 @@ impl Clickable {
         }
     }
 }
+
+impl Default for Clickable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// serde_json writes non-finite floats as null and can't read them back, so
+// infinities (the mass of static bodies) are written as "inf" / "-inf" instead
+#[cfg(feature = "serde")]
+mod non_finite_f32 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(f32),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_f32(*value)
+        } else if value.is_nan() {
+            serializer.serialize_str("nan")
+        } else if *value > 0.0 {
+            serializer.serialize_str("inf")
+        } else {
+            serializer.serialize_str("-inf")
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(value),
+            Repr::Text(text) => match text.as_str() {
+                "inf" => Ok(f32::INFINITY),
+                "-inf" => Ok(f32::NEG_INFINITY),
+                "nan" => Ok(f32::NAN),
+                other => Err(serde::de::Error::custom(format!(
+                    "expected a number, \"inf\", \"-inf\" or \"nan\", found \"{other}\""
+                ))),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+
+    fn assert_bounds(bounds: Aabb, min: [f32; 2], max: [f32; 2]) {
+        let close = |a: [f32; 2], b: [f32; 2]| (0..2).all(|i| (a[i] - b[i]).abs() < 1e-5);
+        assert!(
+            close(bounds.min, min) && close(bounds.max, max),
+            "{bounds:?}"
+        );
+    }
+
+    #[test]
+    fn bounds_follow_rotation_and_scale() {
+        // A 0.4 x 0.2 plank turned a quarter turn stands 0.4 tall
+        let plank = Entity::new_rectangle([0.5, 0.0], 0.4, 0.2, [1.0; 3]);
+        let mut transform = *plank.transform();
+        transform.rotation = std::f32::consts::FRAC_PI_2;
+        assert_bounds(plank.shape().bounds(&transform), [0.4, -0.2], [0.6, 0.2]);
+        // At 45° each half side reaches (0.2 + 0.1) / √2 along both axes
+        transform.rotation = std::f32::consts::FRAC_PI_4;
+        let reach = 0.3 / 2.0_f32.sqrt();
+        assert_bounds(
+            plank.shape().bounds(&transform),
+            [0.5 - reach, -reach],
+            [0.5 + reach, reach],
+        );
+
+        // A circle stretched 2x wide is an ellipse; turned a quarter, it's 2x tall
+        let ball = Entity::new_circle([0.0, 0.0], 0.1, [1.0; 3]);
+        let mut transform = *ball.transform();
+        transform.scale = [2.0, 1.0];
+        assert_bounds(ball.shape().bounds(&transform), [-0.2, -0.1], [0.2, 0.1]);
+        transform.rotation = std::f32::consts::FRAC_PI_2;
+        assert_bounds(ball.shape().bounds(&transform), [-0.1, -0.2], [0.1, 0.2]);
+
+        // Unrotated, unscaled shapes keep their plain extents
+        let transform = *plank.transform();
+        assert_bounds(plank.shape().bounds(&transform), [0.3, -0.1], [0.7, 0.1]);
+    }
+
+    #[test]
+    fn polygon_bounds_hug_the_placed_vertices() {
+        let triangle = vec![[0.0, 0.0], [0.2, 0.0], [0.0, 0.1]];
+        let entity = Entity::new_polygon([0.5, 0.5], triangle, [1.0; 3]).unwrap();
+        let mut transform = *entity.transform();
+        transform.scale = [2.0, 1.0];
+        assert_bounds(entity.shape().bounds(&transform), [0.5, 0.5], [0.9, 0.6]);
+        transform.rotation = std::f32::consts::PI;
+        assert_bounds(entity.shape().bounds(&transform), [0.1, 0.4], [0.5, 0.5]);
+    }
+}
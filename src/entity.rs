@@ -1,192 +1,640 @@
-use crate::components::{Clickable, Physics, Shape, Transform};
+use crate::components::{
+    Behavior, Clickable, ColorAnimation, Corner, Counter, Emitter, GravityZone, GroupId, Lifetime,
+    Physics, Shape, TagId, Tags, TextSpace, TextureId, Transform, UserData, fan_triangulate,
+};
+use crate::palette::Palette;
+use std::any::Any;
+use std::time::Duration;
 
-/// Entity is an enum of different types, each with their own component composition
+/// Entity is a shape plus optional components. The shape decides how it's drawn,
+/// collided and picked.
 #[derive(Clone, Debug)]
-pub enum Entity {
-    Circle {
-        transform: Transform,
-        physics: Option<Physics>,
-        shape: Shape, // Must be Shape::Circle variant
-        clickable: Option<Clickable>,
-    },
-    Text {
-        transform: Transform,
-        physics: Option<Physics>,
-        shape: Shape, // Must be Shape::Text variant
-        clickable: Option<Clickable>,
-    },
-    Rectangle {
-        transform: Transform,
-        physics: Option<Physics>,
-        shape: Shape, // Must be Shape::Rectangle variant
-        clickable: Option<Clickable>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entity {
+    transform: Transform,
+    shape: Shape,
+    physics: Option<Physics>,
+    clickable: Option<Clickable>,
+    name: Option<String>,
+    palette_slot: Option<usize>, // Color follows this palette slot across themes
+    tags: Tags,
+    group: Option<GroupId>, // Kept in step with the world's member lists
+    persistent: bool,       // Never evicted by the world's entity cap
+    lifetime: Option<Lifetime>,
+    color_animation: Option<ColorAnimation>,
+    gravity_zone: Option<GravityZone>,
+    counter: Option<Counter>,
+    #[cfg_attr(feature = "serde", serde(skip))] // Runtime state: children, RNG, closures
+    emitter: Option<Emitter>,
+    #[cfg_attr(feature = "serde", serde(skip))] // Closures
+    behavior: Option<Behavior>,
+    #[cfg_attr(feature = "serde", serde(skip))] // Arbitrary application types
+    user_data: Option<UserData>,
+    // Change tracking; see `World::drain_changes`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    changed: bool, // Set by every mutable accessor
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_modified: u32, // World change tick of the last drained change
+    // World sim clock when the entity was added; see `World::entity_age`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spawn_step: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spawned_at: Duration,
+}
+
+/// Why an entity isn't valid; see `Entity::validate`
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntityError {
+    /// A radius, side length or font size that isn't a positive finite number
+    BadSize {
+        field: &'static str,
+        value: f32,
     },
+    NonFinitePosition([f32; 2]),
+    NonFiniteVelocity([f32; 2]),
+    /// Mass that is neither positive nor infinite
+    BadMass(f32),
+    TooFewVertices(usize),
+}
+
+impl std::fmt::Display for EntityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityError::BadSize { field, value } => {
+                write!(f, "{field} must be positive and finite, got {value}")
+            }
+            EntityError::NonFinitePosition(position) => {
+                write!(f, "position {position:?} is not finite")
+            }
+            EntityError::NonFiniteVelocity(velocity) => {
+                write!(f, "velocity {velocity:?} is not finite")
+            }
+            EntityError::BadMass(mass) => {
+                write!(f, "mass must be positive or infinite, got {mass}")
+            }
+            EntityError::TooFewVertices(count) => {
+                write!(f, "polygon has {count} vertices, needs at least 3")
+            }
+        }
+    }
 }
 
+impl std::error::Error for EntityError {}
+
 impl Entity {
-    // Factories!!
-    pub fn new_circle(position: [f32; 2], radius: f32, color: [f32; 3]) -> Self {
-        Entity::Circle {
+    /// Entity with just a transform and `shape`; add components with the `with_*` builders
+    pub fn new(position: [f32; 2], shape: Shape) -> Self {
+        Entity {
             transform: Transform::new(position),
+            shape,
             physics: None,
-            shape: Shape::Circle { radius, color },
             clickable: None,
+            name: None,
+            palette_slot: None,
+            tags: Tags::default(),
+            group: None,
+            persistent: false,
+            lifetime: None,
+            gravity_zone: None,
+            counter: None,
+            color_animation: None,
+            emitter: None,
+            behavior: None,
+            user_data: None,
+            changed: true,
+            last_modified: 0,
+            spawn_step: 0,
+            spawned_at: Duration::ZERO,
+        }
+    }
+
+    // Factories!!
+    pub fn new_circle(position: [f32; 2], radius: f32, color: [f32; 3]) -> Self {
+        Self::new(
+            position,
+            Shape::Circle {
+                radius,
+                color,
+                opacity: 1.0,
+                texture: None,
+                edge_softness: None,
+                color_outer: None,
+            },
+        )
+    }
+
+    /// Circle fading from `inner` at the center to `outer` at the rim
+    pub fn new_circle_gradient(
+        position: [f32; 2],
+        radius: f32,
+        inner: [f32; 3],
+        outer: [f32; 3],
+    ) -> Self {
+        let mut circle = Self::new_circle(position, radius, inner);
+        if let Shape::Circle { color_outer, .. } = circle.shape_mut() {
+            *color_outer = Some(outer);
         }
+        circle
     }
 
     pub fn new_rectangle(position: [f32; 2], length: f32, height: f32, color: [f32; 3]) -> Self {
-        Entity::Rectangle {
-            transform: Transform::new(position),
-            physics: None,
-            shape: Shape::Rectangle {
+        Self::new(
+            position,
+            Shape::Rectangle {
                 length,
                 height,
                 color,
+                opacity: 1.0,
+                texture: None,
+                corner_radius: 0.0,
             },
-            clickable: None,
+        )
+    }
+
+    /// Convex polygon from vertices relative to `position`, in order around the outline.
+    /// Returns None for fewer than 3 vertices.
+    pub fn new_polygon(
+        position: [f32; 2],
+        vertices: Vec<[f32; 2]>,
+        color: [f32; 3],
+    ) -> Option<Self> {
+        if vertices.len() < 3 {
+            return None;
         }
+
+        Some(Self::new(
+            position,
+            Shape::Polygon {
+                triangles: fan_triangulate(&vertices),
+                vertices,
+                color,
+                opacity: 1.0,
+            },
+        ))
     }
 
     pub fn new_text(position: [f32; 2], content: String, font_size: f32, color: [f32; 3]) -> Self {
-        Entity::Text {
-            transform: Transform::new(position),
-            physics: None,
-            shape: Shape::Text {
+        Self::new(
+            position,
+            Shape::Text {
                 content,
                 font_size,
                 color,
+                opacity: 1.0,
+                measured_size: None,
+                space: TextSpace::World,
             },
-            clickable: None,
-        }
+        )
     }
 
-    // Builder-style methods for adding components (now works for ALL entity types)
+    // Builder-style methods for adding components
     pub fn with_physics(mut self, physics: Physics) -> Self {
-        match &mut self {
-            Entity::Circle { physics: p, .. }
-            | Entity::Text { physics: p, .. }
-            | Entity::Rectangle { physics: p, .. } => {
-                *p = Some(physics);
-            }
-        }
+        self.physics = Some(physics);
         self
     }
 
     pub fn with_clickable(mut self, clickable: Clickable) -> Self {
-        match &mut self {
-            Entity::Circle { clickable: c, .. }
-            | Entity::Text { clickable: c, .. }
-            | Entity::Rectangle { clickable: c, .. } => {
-                *c = Some(clickable);
+        self.clickable = Some(clickable);
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.shape_mut().set_opacity(opacity);
+        self
+    }
+
+    /// Draw the shape with `texture`, tinted by its color. Has no effect on text or polygons.
+    pub fn with_texture(mut self, texture: TextureId) -> Self {
+        match self.shape_mut() {
+            Shape::Circle { texture: t, .. } | Shape::Rectangle { texture: t, .. } => {
+                *t = Some(texture);
             }
+            Shape::Text { .. } | Shape::Polygon { .. } => {}
         }
         self
     }
 
-    // Component accessors (immutable)
-    pub fn transform(&self) -> &Transform {
-        match self {
-            Entity::Circle { transform, .. }
-            | Entity::Text { transform, .. }
-            | Entity::Rectangle { transform, .. } => transform,
+    /// Edge antialiasing width for this circle, as a multiple of the default.
+    /// Has no effect on other shapes.
+    pub fn with_edge_softness(mut self, softness: f32) -> Self {
+        if let Shape::Circle { edge_softness, .. } = self.shape_mut() {
+            *edge_softness = Some(softness.max(0.01));
         }
+        self
     }
 
-    pub fn transform_mut(&mut self) -> &mut Transform {
-        match self {
-            Entity::Circle { transform, .. }
-            | Entity::Text { transform, .. }
-            | Entity::Rectangle { transform, .. } => transform,
+    /// Draw this rectangle with rounded corners. Only affects rendering; collision
+    /// keeps the sharp box. Has no effect on other shapes.
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        if let Shape::Rectangle { corner_radius, .. } = self.shape_mut() {
+            *corner_radius = radius.max(0.0);
         }
+        self
     }
 
-    pub fn physics(&self) -> Option<&Physics> {
-        match self {
-            Entity::Circle { physics, .. }
-            | Entity::Text { physics, .. }
-            | Entity::Rectangle { physics, .. } => physics.as_ref(),
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.transform_mut().layer = layer;
+        self
+    }
+
+    /// Pin text to a window corner instead of the world, `offset_px` logical pixels
+    /// in from the corner. Has no effect on other shapes.
+    pub fn with_screen_anchor(mut self, anchor: Corner, offset_px: [f32; 2]) -> Self {
+        if let Shape::Text { space, .. } = self.shape_mut() {
+            *space = TextSpace::Screen { anchor, offset_px };
         }
+        self
+    }
+
+    /// Name shown by the renderer's debug labels and found by `World::find_by_name`
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Keep this entity when the world's entity cap evicts to make room;
+    /// see `World::set_max_entities`
+    pub fn with_persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+
+    /// Despawn after `remaining` of sim time; see `LifetimeSystem`
+    pub fn with_lifetime(mut self, remaining: Duration) -> Self {
+        self.lifetime = Some(Lifetime::new(remaining));
+        self
+    }
+
+    pub fn with_counter(mut self, value: i64) -> Self {
+        self.counter = Some(Counter::new(value));
+        self
+    }
+
+    /// Override gravity for dynamic bodies inside `zone`, centered on this entity
+    pub fn with_gravity_zone(mut self, zone: GravityZone) -> Self {
+        self.gravity_zone = Some(zone);
+        self
+    }
+
+    /// Animate the shape's color; see `ColorAnimationSystem`
+    pub fn with_color_animation(mut self, animation: ColorAnimation) -> Self {
+        self.color_animation = Some(animation);
+        self
+    }
+
+    /// Spawn copies of the emitter's source from this entity's position; see `EmitterSystem`
+    pub fn with_emitter(mut self, emitter: Emitter) -> Self {
+        self.emitter = Some(emitter);
+        self
+    }
+
+    /// Run `behavior` every sim step; see `BehaviorSystem`
+    pub fn with_behavior(mut self, behavior: Behavior) -> Self {
+        self.behavior = Some(behavior);
+        self
+    }
+
+    /// Attach application data, read back with `user_data::<T>()`. Replaces any
+    /// earlier value, whatever its type.
+    pub fn with_user_data<T: Any + Send + Clone>(mut self, value: T) -> Self {
+        self.user_data = Some(UserData::new(value));
+        self
+    }
+
+    /// Tag from `World::register_tag`; query with `World::iter_tagged`
+    pub fn with_tag(mut self, tag: TagId) -> Self {
+        self.add_tag(tag);
+        self
+    }
+
+    /// Group from `World::create_group`. Copies spawned from this entity, by the
+    /// spawn helpers, prefabs or emitters, join the same group.
+    pub fn with_group(mut self, group: GroupId) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    pub fn add_tag(&mut self, tag: TagId) {
+        self.changed = true;
+        self.tags.insert(tag);
+    }
+
+    pub fn remove_tag(&mut self, tag: TagId) {
+        self.changed = true;
+        self.tags.remove(tag);
+    }
+
+    pub fn has_tag(&self, tag: TagId) -> bool {
+        self.tags.contains(tag)
+    }
+
+    /// Color this shape from `slot` of `palette`. `World::set_theme` recolors it
+    /// when the theme changes.
+    pub fn with_palette_color(mut self, palette: &Palette, slot: usize) -> Self {
+        self.shape.set_color(palette.color(slot));
+        self.palette_slot = Some(slot);
+        self
+    }
+
+    // Component accessors
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        self.changed = true;
+        &mut self.transform
+    }
+
+    pub fn physics(&self) -> Option<&Physics> {
+        self.physics.as_ref()
     }
 
     pub fn physics_mut(&mut self) -> Option<&mut Physics> {
-        match self {
-            Entity::Circle { physics, .. }
-            | Entity::Text { physics, .. }
-            | Entity::Rectangle { physics, .. } => physics.as_mut(),
-        }
+        self.changed = true;
+        self.physics.as_mut()
+    }
+
+    /// Give the entity physics, replacing any it had. The physics system picks it
+    /// up from its next update, e.g. turning a static decoration into a falling body.
+    pub fn set_physics(&mut self, physics: Physics) {
+        self.changed = true;
+        self.physics = Some(physics);
+    }
+
+    /// Take the entity's physics away. It stays where it is and collides like any
+    /// other entity without physics, as an immovable obstacle.
+    pub fn remove_physics(&mut self) -> Option<Physics> {
+        self.changed = true;
+        self.physics.take()
+    }
+
+    pub fn transform_and_physics_mut(&mut self) -> (&mut Transform, Option<&mut Physics>) {
+        self.changed = true;
+        (&mut self.transform, self.physics.as_mut())
     }
 
     pub fn physics_and_transform_mut(&mut self) -> Option<(&mut Physics, &mut Transform)> {
-        match self {
-            Entity::Circle {
-                physics: Some(p),
-                transform,
-                ..
-            } => Some((p, transform)),
-            Entity::Text {
-                physics: Some(p),
-                transform,
-                ..
-            } => Some((p, transform)),
-            Entity::Rectangle {
-                physics: Some(p),
-                transform,
-                ..
-            } => Some((p, transform)),
-            _ => None,
-        }
+        self.changed = true;
+        let physics = self.physics.as_mut()?;
+        Some((physics, &mut self.transform))
     }
 
     pub fn shape(&self) -> &Shape {
-        match self {
-            Entity::Circle { shape, .. }
-            | Entity::Text { shape, .. }
-            | Entity::Rectangle { shape, .. } => shape,
-        }
+        &self.shape
     }
 
     pub fn shape_mut(&mut self) -> &mut Shape {
-        match self {
-            Entity::Circle { shape, .. }
-            | Entity::Text { shape, .. }
-            | Entity::Rectangle { shape, .. } => shape,
-        }
+        self.changed = true;
+        &mut self.shape
     }
 
     pub fn clickable(&self) -> Option<&Clickable> {
-        match self {
-            Entity::Circle { clickable, .. }
-            | Entity::Text { clickable, .. }
-            | Entity::Rectangle { clickable, .. } => clickable.as_ref(),
-        }
+        self.clickable.as_ref()
     }
 
     pub fn clickable_mut(&mut self) -> Option<&mut Clickable> {
-        match self {
-            Entity::Circle { clickable, .. }
-            | Entity::Text { clickable, .. }
-            | Entity::Rectangle { clickable, .. } => clickable.as_mut(),
+        self.changed = true;
+        self.clickable.as_mut()
+    }
+
+    pub fn set_clickable(&mut self, clickable: Clickable) {
+        self.changed = true;
+        self.clickable = Some(clickable);
+    }
+
+    pub fn remove_clickable(&mut self) -> Option<Clickable> {
+        self.changed = true;
+        self.clickable.take()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Only World may rename a spawned entity, so its name lookup stays in sync
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        self.changed = true;
+        self.name = name;
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    pub fn group(&self) -> Option<GroupId> {
+        self.group
+    }
+
+    // Only World may regroup a spawned entity, so its member lists stay in sync
+    pub(crate) fn set_group(&mut self, group: Option<GroupId>) {
+        self.changed = true;
+        self.group = group;
+    }
+
+    /// Palette slot the color came from, if it was set through the palette
+    pub fn palette_slot(&self) -> Option<usize> {
+        self.palette_slot
+    }
+
+    pub fn lifetime(&self) -> Option<&Lifetime> {
+        self.lifetime.as_ref()
+    }
+
+    pub fn lifetime_mut(&mut self) -> &mut Option<Lifetime> {
+        self.changed = true;
+        &mut self.lifetime
+    }
+
+    pub fn counter(&self) -> Option<&Counter> {
+        self.counter.as_ref()
+    }
+
+    pub fn counter_mut(&mut self) -> Option<&mut Counter> {
+        self.changed = true;
+        self.counter.as_mut()
+    }
+
+    pub fn gravity_zone(&self) -> Option<&GravityZone> {
+        self.gravity_zone.as_ref()
+    }
+
+    pub fn gravity_zone_mut(&mut self) -> Option<&mut GravityZone> {
+        self.changed = true;
+        self.gravity_zone.as_mut()
+    }
+
+    pub fn color_animation(&self) -> Option<&ColorAnimation> {
+        self.color_animation.as_ref()
+    }
+
+    pub fn color_animation_mut(&mut self) -> &mut Option<ColorAnimation> {
+        self.changed = true;
+        &mut self.color_animation
+    }
+
+    pub fn behavior(&self) -> Option<&Behavior> {
+        self.behavior.as_ref()
+    }
+
+    pub fn behavior_mut(&mut self) -> &mut Option<Behavior> {
+        self.changed = true;
+        &mut self.behavior
+    }
+
+    pub fn emitter(&self) -> Option<&Emitter> {
+        self.emitter.as_ref()
+    }
+
+    pub fn emitter_mut(&mut self) -> &mut Option<Emitter> {
+        self.changed = true;
+        &mut self.emitter
+    }
+
+    pub fn tags(&self) -> Tags {
+        self.tags
+    }
+
+    /// The attached user data, if there is some and it's a `T`
+    pub fn user_data<T: Any>(&self) -> Option<&T> {
+        self.user_data.as_ref()?.get()
+    }
+
+    pub fn user_data_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.changed = true;
+        self.user_data.as_mut()?.get_mut()
+    }
+
+    /// Replace the user data; None removes it
+    pub fn set_user_data(&mut self, user_data: Option<UserData>) {
+        self.changed = true;
+        self.user_data = user_data;
+    }
+
+    /// Whether this entity was changed through a mutable accessor since the world's
+    /// change tick was `tick`; see `World::drain_changes`
+    pub fn changed_since(&self, tick: u32) -> bool {
+        self.changed || self.last_modified >= tick
+    }
+
+    /// Sim step the entity was added to its world on
+    pub fn spawn_step(&self) -> u64 {
+        self.spawn_step
+    }
+
+    /// Sim time the entity was added to its world at
+    pub fn spawned_at(&self) -> Duration {
+        self.spawned_at
+    }
+
+    pub(crate) fn set_spawned(&mut self, step: u64, time: Duration) {
+        self.spawn_step = step;
+        self.spawned_at = time;
+    }
+
+    pub(crate) fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    // Called by World::drain_changes; true if there was a change to stamp
+    pub(crate) fn stamp_change(&mut self, tick: u32) -> bool {
+        let changed = self.changed;
+        if changed {
+            self.last_modified = tick;
+            self.changed = false;
+        }
+        changed
+    }
+
+    /// Check that the entity's numbers make sense.
+    /// `World::add_entity` runs this in debug builds.
+    pub fn validate(&self) -> Result<(), EntityError> {
+        let position = self.transform().position;
+        if !position.iter().all(|v| v.is_finite()) {
+            return Err(EntityError::NonFinitePosition(position));
+        }
+        if let Some(physics) = self.physics() {
+            if !physics.velocity.iter().all(|v| v.is_finite()) {
+                return Err(EntityError::NonFiniteVelocity(physics.velocity));
+            }
+            if physics.mass.is_nan() || physics.mass <= 0.0 {
+                return Err(EntityError::BadMass(physics.mass));
+            }
+        }
+
+        let positive = |field: &'static str, value: f32| {
+            if value > 0.0 && value.is_finite() {
+                Ok(())
+            } else {
+                Err(EntityError::BadSize { field, value })
+            }
+        };
+        match self.shape() {
+            Shape::Circle { radius, .. } => positive("radius", *radius),
+            Shape::Rectangle { length, height, .. } => {
+                positive("length", *length).and(positive("height", *height))
+            }
+            Shape::Text { font_size, .. } => positive("font_size", *font_size),
+            Shape::Polygon { vertices, .. } if vertices.len() < 3 => {
+                Err(EntityError::TooFewVertices(vertices.len()))
+            }
+            Shape::Polygon { .. } => Ok(()),
         }
     }
 
-    /// Check if a point (in NDC coordinates) is inside this entity
+    /// Check if a point (in NDC coordinates) is inside this entity, as drawn
+    /// (rotation and scale included)
     pub fn contains_point(&self, point: [f32; 2]) -> bool {
         let transform = self.transform();
         let dx = point[0] - transform.position[0];
         let dy = point[1] - transform.position[1];
 
+        // Undo rotation and scale so the test happens in the shape's own frame
+        let (sin, cos) = transform.rotation.sin_cos();
+        let [scale_x, scale_y] = transform.scale;
+        if scale_x == 0.0 || scale_y == 0.0 {
+            return false;
+        }
+        let local_x = (dx * cos + dy * sin) / scale_x;
+        let local_y = (-dx * sin + dy * cos) / scale_y;
+
         match self.shape() {
             Shape::Circle { radius, .. } => {
-                let dist_sq = dx * dx + dy * dy;
+                let dist_sq = local_x * local_x + local_y * local_y;
                 dist_sq <= radius * radius
             }
             Shape::Rectangle { length, height, .. } => {
                 let half_w = length / 2.0;
                 let half_h = height / 2.0;
-                dx.abs() <= half_w && dy.abs() <= half_h
+                local_x.abs() <= half_w && local_y.abs() <= half_h
             }
+            Shape::Polygon { vertices, .. } => {
+                // Inside a convex polygon when on the same side of every edge
+                let mut sign = 0.0;
+                for (i, a) in vertices.iter().enumerate() {
+                    let b = vertices[(i + 1) % vertices.len()];
+                    let cross = (b[0] - a[0]) * (local_y - a[1]) - (b[1] - a[1]) * (local_x - a[0]);
+                    if cross * sign < 0.0 {
+                        return false;
+                    }
+                    if cross != 0.0 {
+                        sign = cross;
+                    }
+                }
+                vertices.len() >= 3
+            }
+            // HUD text isn't part of the world
+            Shape::Text {
+                space: TextSpace::Screen { .. },
+                ..
+            } => false,
+            // Text is drawn centered and unrotated, so test against the plain offset
+            Shape::Text {
+                measured_size: Some([width, height]),
+                ..
+            } => dx.abs() <= width / 2.0 && dy.abs() <= height / 2.0,
             Shape::Text { .. } => {
-                // radius-based approximation bc i dont wanna do bounding box right now
+                // Not measured yet, fall back to a rough radius
                 let dist_sq = dx * dx + dy * dy;
                 dist_sq <= 0.1 * 0.1 // Approximate clickable radius
             }
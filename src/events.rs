@@ -0,0 +1,158 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Typed event bus owned by `World`. Any `'static` type can be an event.
+///
+/// Double-buffered: `send` queues into a back buffer, and `update` (once per
+/// frame) makes those events the readable ones, dropping whatever wasn't read
+/// from the frame before. Events sent while handling others therefore show up
+/// on the next frame instead of getting lost.
+pub struct Events {
+    queues: HashMap<TypeId, Box<dyn AnyQueue>>,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Self {
+            queues: HashMap::new(),
+        }
+    }
+
+    pub fn send<T: 'static>(&mut self, event: T) {
+        self.queue_mut::<T>().back.push(event);
+    }
+
+    /// Readable events of type `T`, oldest first, without consuming them
+    pub fn read<T: 'static>(&self) -> &[T] {
+        self.queues
+            .get(&TypeId::of::<T>())
+            .and_then(|queue| queue.as_any().downcast_ref::<Queue<T>>())
+            .map_or(&[], |queue| &queue.front)
+    }
+
+    /// Take the readable events of type `T`, oldest first
+    pub fn drain<T: 'static>(&mut self) -> std::vec::Drain<'_, T> {
+        self.queue_mut::<T>().front.drain(..)
+    }
+
+    /// Swap buffers for every event type: events sent since the last update become
+    /// readable and unread ones from before are dropped. Call once per frame, after
+    /// the sim steps and before anything reacts to events.
+    pub fn update(&mut self) {
+        for queue in self.queues.values_mut() {
+            queue.swap();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.queues.clear();
+    }
+
+    fn queue_mut<T: 'static>(&mut self) -> &mut Queue<T> {
+        self.queues
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                Box::new(Queue::<T> {
+                    front: Vec::new(),
+                    back: Vec::new(),
+                })
+            })
+            .as_any_mut()
+            .downcast_mut()
+            .expect("event queue stored under another type's id")
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Events belong to the frame they were sent in, so a copied world starts without any
+impl Clone for Events {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+struct Queue<T> {
+    front: Vec<T>, // Readable this frame
+    back: Vec<T>,  // Sent since the last update
+}
+
+// Lets `update` swap every queue without knowing its event type
+trait AnyQueue {
+    fn swap(&mut self);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> AnyQueue for Queue<T> {
+    fn swap(&mut self) {
+        self.front.clear();
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Hit(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Scored(&'static str);
+
+    #[test]
+    fn event_types_are_kept_apart() {
+        let mut events = Events::new();
+        events.send(Hit(1));
+        events.send(Scored("left"));
+        events.send(Hit(2));
+        events.update();
+
+        assert_eq!(events.read::<Hit>(), [Hit(1), Hit(2)]);
+        assert_eq!(events.read::<Scored>(), [Scored("left")]);
+        // Draining one type leaves the other alone
+        assert_eq!(events.drain::<Hit>().collect::<Vec<_>>(), [Hit(1), Hit(2)]);
+        assert!(events.read::<Hit>().is_empty());
+        assert_eq!(events.read::<Scored>(), [Scored("left")]);
+        assert!(events.read::<u64>().is_empty());
+    }
+
+    #[test]
+    fn events_become_readable_on_the_next_update() {
+        let mut events = Events::new();
+        events.send(Hit(1));
+        assert!(events.read::<Hit>().is_empty());
+        events.update();
+        assert_eq!(events.read::<Hit>(), [Hit(1)]);
+
+        // Sent while the frame's events are being read: next frame, not lost
+        events.send(Hit(2));
+        assert_eq!(events.read::<Hit>(), [Hit(1)]);
+        events.update();
+        assert_eq!(events.read::<Hit>(), [Hit(2)]);
+    }
+
+    #[test]
+    fn unread_events_from_two_frames_ago_are_gone() {
+        let mut events = Events::new();
+        events.send(Hit(1));
+        events.update();
+        events.update();
+        assert!(events.read::<Hit>().is_empty());
+        events.update();
+        assert!(events.read::<Hit>().is_empty());
+    }
+}
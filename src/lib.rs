@@ -0,0 +1,10 @@
+pub mod components;
+pub mod entity;
+pub mod events;
+pub mod palette;
+pub mod prefab;
+pub mod random;
+pub mod scene;
+pub mod scene_stack;
+pub mod systems;
+pub mod world;
@@ -0,0 +1,41 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,  // Quad vertex position
+}
+
+struct InstanceInput {
+    @location(1) start: vec2<f32>,     // Line start (NDC)
+    @location(2) end: vec2<f32>,       // Line end (NDC)
+    @location(3) thickness: f32,       // Line thickness (NDC)
+    @location(4) color: vec4<f32>,     // Line color (RGBA)
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Stretch the quad along the segment (x) and across it by the thickness (y).
+    // Zero-length lines are filtered out by LinePass, so normalize is safe here.
+    let along = instance.end - instance.start;
+    let dir = normalize(along);
+    let perp = vec2<f32>(-dir.y, dir.x);
+    let t = vertex.position.x * 0.5 + 0.5;
+    let world_pos = instance.start + along * t + perp * vertex.position.y * instance.thickness * 0.5;
+
+    out.clip_position = vec4<f32>(world_pos, 0.0, 1.0);
+    out.color = instance.color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,161 @@
+/// Built-in color themes. Every theme fills the same named slots, so switching
+/// themes keeps "red" meaning red-ish.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Theme {
+    #[default]
+    Default,
+    Pastel,
+    HighContrast,
+    ColorblindSafe, // Okabe–Ito
+}
+
+// Slot names, ordered so consecutive slots stay easy to tell apart
+pub const SLOT_NAMES: [&str; 8] = [
+    "red", "blue", "yellow", "green", "purple", "orange", "cyan", "gray",
+];
+
+// sRGB colors for each theme, in SLOT_NAMES order
+const DEFAULT_COLORS: [[f32; 3]; 8] = [
+    [0.9, 0.2, 0.2],
+    [0.25, 0.45, 0.95],
+    [0.95, 0.85, 0.2],
+    [0.3, 0.8, 0.3],
+    [0.6, 0.35, 0.9],
+    [1.0, 0.6, 0.1],
+    [0.2, 0.75, 0.85],
+    [0.6, 0.6, 0.6],
+];
+const PASTEL_COLORS: [[f32; 3]; 8] = [
+    [1.0, 0.7, 0.7],
+    [0.68, 0.78, 1.0],
+    [1.0, 0.95, 0.65],
+    [0.7, 0.93, 0.7],
+    [0.82, 0.72, 1.0],
+    [1.0, 0.82, 0.62],
+    [0.68, 0.92, 0.95],
+    [0.82, 0.82, 0.82],
+];
+const HIGH_CONTRAST_COLORS: [[f32; 3]; 8] = [
+    [1.0, 0.0, 0.0],
+    [0.0, 0.4, 1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.7, 0.0, 1.0],
+    [1.0, 0.5, 0.0],
+    [0.0, 1.0, 1.0],
+    [1.0, 1.0, 1.0],
+];
+const COLORBLIND_SAFE_COLORS: [[f32; 3]; 8] = [
+    [0.835, 0.369, 0.0],   // Vermillion
+    [0.0, 0.447, 0.698],   // Blue
+    [0.941, 0.894, 0.259], // Yellow
+    [0.0, 0.62, 0.451],    // Bluish green
+    [0.8, 0.475, 0.655],   // Reddish purple
+    [0.902, 0.624, 0.0],   // Orange
+    [0.337, 0.706, 0.914], // Sky blue
+    [0.6, 0.6, 0.6],
+];
+
+/// Shared set of named colors for spawning code. Entities created with
+/// `Entity::with_palette_color` remember their slot and follow theme changes
+/// made through `World::set_theme`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    theme: Theme,
+    next: usize, // Slot handed out by the next call to next_slot
+}
+
+impl Palette {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, next: 0 }
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Colors of the current theme, in slot order
+    pub fn colors(&self) -> &'static [[f32; 3]] {
+        match self.theme {
+            Theme::Default => &DEFAULT_COLORS,
+            Theme::Pastel => &PASTEL_COLORS,
+            Theme::HighContrast => &HIGH_CONTRAST_COLORS,
+            Theme::ColorblindSafe => &COLORBLIND_SAFE_COLORS,
+        }
+    }
+
+    pub fn slot_count(&self) -> usize {
+        SLOT_NAMES.len()
+    }
+
+    /// Color in `slot`; slots past the end wrap around
+    pub fn color(&self, slot: usize) -> [f32; 3] {
+        self.colors()[slot % self.slot_count()]
+    }
+
+    /// Slot index for a name like "red", if the palette has it
+    pub fn slot(&self, name: &str) -> Option<usize> {
+        SLOT_NAMES.iter().position(|&slot_name| slot_name == name)
+    }
+
+    pub fn named(&self, name: &str) -> Option<[f32; 3]> {
+        self.slot(name).map(|slot| self.color(slot))
+    }
+
+    /// Next slot in order, cycling through every color before repeating
+    pub fn next_slot(&mut self) -> usize {
+        let slot = self.next;
+        self.next = (self.next + 1) % self.slot_count();
+        slot
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(Theme::Default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_slot_cycles_through_every_color_before_repeating() {
+        let mut palette = Palette::default();
+        let slots: Vec<usize> = (0..SLOT_NAMES.len() + 2)
+            .map(|_| palette.next_slot())
+            .collect();
+        assert_eq!(
+            slots[..SLOT_NAMES.len()],
+            (0..SLOT_NAMES.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(slots[SLOT_NAMES.len()..], [0, 1]);
+        assert_eq!(
+            palette.color(SLOT_NAMES.len() + 1),
+            palette.color(1),
+            "slots wrap"
+        );
+    }
+
+    #[test]
+    fn every_theme_fills_the_named_slots() {
+        for theme in [
+            Theme::Default,
+            Theme::Pastel,
+            Theme::HighContrast,
+            Theme::ColorblindSafe,
+        ] {
+            let palette = Palette::new(theme);
+            assert_eq!(palette.colors().len(), SLOT_NAMES.len(), "{theme:?}");
+            assert_eq!(palette.named("blue"), Some(palette.color(1)));
+        }
+        assert_eq!(Palette::default().named("magenta"), None);
+    }
+}
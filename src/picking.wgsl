@@ -0,0 +1,62 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,  // Quad vertex position
+}
+
+struct InstanceInput {
+    @location(1) center: vec2<f32>,    // Shape center (NDC)
+    @location(2) half_size: vec2<f32>, // Half width and height after scaling (NDC)
+    @location(3) rotation: f32,        // Radians, counter-clockwise
+    @location(4) corner_radius: f32,   // Rectangles only (NDC), 0 = sharp
+    @location(5) kind: u32,            // 0 = ellipse, 1 = rectangle
+    @location(6) id: u32,              // Entity index + 1; 0 is left for empty pixels
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_pos: vec2<f32>,                         // Position relative to the center, unrotated
+    @location(1) @interpolate(flat) half_size: vec2<f32>,
+    @location(2) @interpolate(flat) corner_radius: f32,
+    @location(3) @interpolate(flat) kind: u32,
+    @location(4) @interpolate(flat) id: u32,
+}
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Same placement as the visible shapes: size, rotate, then translate
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let local = vertex.position * instance.half_size;
+    let rotated = vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
+
+    out.clip_position = vec4<f32>(rotated + instance.center, 0.0, 1.0);
+    out.local_pos = local;
+    out.half_size = instance.half_size;
+    out.corner_radius = min(instance.corner_radius, min(instance.half_size.x, instance.half_size.y));
+    out.kind = instance.kind;
+    out.id = instance.id;
+    return out;
+}
+
+// Signed distance to a box with rounded corners, negative inside
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// A pixel belongs to a shape where its drawn coverage is at least half
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) u32 {
+    if (in.kind == 0u) {
+        if (length(in.local_pos / in.half_size) > 1.0) {
+            discard;
+        }
+    } else if (rounded_box(in.local_pos, in.half_size, in.corner_radius) > 0.0) {
+        discard;
+    }
+    return in.id;
+}
@@ -0,0 +1,24 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,  // Triangle vertex, already transformed (NDC)
+    @location(1) color: vec4<f32>,     // Polygon color (RGBA)
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.clip_position = vec4<f32>(vertex.position, 0.0, 1.0);
+    out.color = vertex.color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
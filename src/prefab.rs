@@ -0,0 +1,53 @@
+use crate::entity::Entity;
+
+/// Blueprint for a group of entities spawned together, e.g. a compound paddle.
+/// Part positions are offsets from wherever the prefab is spawned.
+#[derive(Clone, Debug)]
+pub struct Prefab {
+    parts: Vec<Entity>,          // Root first, then children in spawn order
+    joints: Vec<(usize, usize)>, // Pairs of part indices
+}
+
+impl Prefab {
+    /// Prefab of a single entity, spawned centered on the spawn position
+    pub fn from_entity(entity: Entity) -> Self {
+        Self::from_parts(vec![at_offset(entity, [0.0, 0.0])])
+    }
+
+    /// Prefab from entities whose positions are already offsets from the origin
+    pub fn from_parts(parts: Vec<Entity>) -> Self {
+        Self {
+            parts,
+            joints: Vec::new(),
+        }
+    }
+
+    /// Add `entity` `offset` away from the root's spawn position
+    pub fn with_child(mut self, entity: Entity, offset: [f32; 2]) -> Self {
+        self.parts.push(at_offset(entity, offset));
+        self
+    }
+
+    /// Join parts `a` and `b` (indices in part order, root 0) with a rod as long
+    /// as they are apart in the prefab
+    pub fn with_joint(mut self, a: usize, b: usize) -> Self {
+        self.joints.push((a, b));
+        self
+    }
+
+    pub fn parts(&self) -> &[Entity] {
+        &self.parts
+    }
+
+    pub fn joints(&self) -> &[(usize, usize)] {
+        &self.joints
+    }
+}
+
+// `entity` moved to `offset`, with no motion left to interpolate
+fn at_offset(mut entity: Entity, offset: [f32; 2]) -> Entity {
+    let transform = entity.transform_mut();
+    transform.position = offset;
+    transform.prev_position = offset;
+    entity
+}
@@ -0,0 +1,82 @@
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+
+/// The world's random number generator. Spawn helpers and unseeded emitters draw
+/// from it, so the same seed and the same calls give the same world.
+/// Implements `RngCore`, so the whole `rand::Rng` API works on it too.
+#[derive(Clone, Debug)]
+pub struct WorldRng {
+    rng: StdRng,
+    seed: u64,
+}
+
+impl WorldRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+        }
+    }
+
+    /// Seeded from OS entropy. The seed is printed so the run can be reproduced
+    /// with `World::set_seed`.
+    pub fn from_entropy() -> Self {
+        let seed = rand::random();
+        println!("World seed: {seed}");
+        Self::new(seed)
+    }
+
+    /// Seed this generator started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Uniform in `min..=max`, in either order
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        self.rng.random_range(min.min(max)..=min.max(max))
+    }
+
+    /// Random direction, length 1
+    pub fn unit_vec2(&mut self) -> [f32; 2] {
+        let angle = self.rng.random_range(0.0..std::f32::consts::TAU);
+        [angle.cos(), angle.sin()]
+    }
+}
+
+impl RngCore for WorldRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_work_in_either_order() {
+        let mut rng = WorldRng::new(1);
+        for _ in 0..100 {
+            let forward = rng.range_f32(-0.5, 2.0);
+            let backward = rng.range_f32(2.0, -0.5);
+            assert!((-0.5..=2.0).contains(&forward) && (-0.5..=2.0).contains(&backward));
+        }
+        assert_eq!(rng.range_f32(0.25, 0.25), 0.25);
+    }
+
+    #[test]
+    fn unit_vectors_have_length_one() {
+        let mut rng = WorldRng::new(2);
+        for _ in 0..100 {
+            let [x, y] = rng.unit_vec2();
+            assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+        }
+    }
+}
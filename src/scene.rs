@@ -0,0 +1,461 @@
+#![cfg(feature = "scene")]
+
+use crate::components::{GravityZone, Physics};
+use crate::entity::Entity;
+use crate::prefab::Prefab;
+use crate::world::{TagError, World};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Why a scene file couldn't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// Malformed RON or an unknown field; the position says where in the file
+    Parse(ron::error::SpannedError),
+    /// A declaration that parsed but can't be built
+    Entity {
+        index: usize,
+        name: Option<String>,
+        message: String,
+    },
+    Include {
+        include: String,
+        message: String,
+    },
+    Tag(TagError),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "could not read the scene file: {err}"),
+            LoadError::Parse(err) => write!(f, "invalid scene at {}: {}", err.position, err.code),
+            LoadError::Entity {
+                index,
+                name: Some(name),
+                message,
+            } => write!(f, "entity #{index} ('{name}'): {message}"),
+            LoadError::Entity {
+                index,
+                name: None,
+                message,
+            } => write!(f, "entity #{index}: {message}"),
+            LoadError::Include { include, message } => {
+                write!(f, "include \"{include}\": {message}")
+            }
+            LoadError::Tag(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Read the scene at `path` and add its entities to `world`. Returns how many were added.
+pub fn load(path: impl AsRef<Path>, world: &mut World) -> Result<usize, LoadError> {
+    let source = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+    load_str(&source, world)
+}
+
+/// Like `load`, for scene text already in memory. Includes are spawned first,
+/// then the declared entities in file order.
+pub fn load_str(source: &str, world: &mut World) -> Result<usize, LoadError> {
+    let scene: SceneFile = ron::from_str(source).map_err(LoadError::Parse)?;
+    let before = world.len();
+
+    for include in &scene.include {
+        spawn_include(include, world).map_err(|message| LoadError::Include {
+            include: include.clone(),
+            message,
+        })?;
+    }
+
+    for entity in build_entities(scene.entities, world)? {
+        world.add_entity(entity);
+    }
+
+    Ok(world.len() - before)
+}
+
+/// Prefab from a scene fragment: the declared entities, first one as the root,
+/// with positions as offsets from the spawn point. Tags are registered and
+/// palette colors taken from `world`, which the prefab should be spawned into.
+/// Includes aren't allowed.
+pub fn load_prefab_str(source: &str, world: &mut World) -> Result<Prefab, LoadError> {
+    let scene: SceneFile = ron::from_str(source).map_err(LoadError::Parse)?;
+    if let Some(include) = scene.include.first() {
+        return Err(LoadError::Include {
+            include: include.clone(),
+            message: "prefabs can't include presets".into(),
+        });
+    }
+    Ok(Prefab::from_parts(build_entities(scene.entities, world)?))
+}
+
+fn build_entities(decls: Vec<EntityDecl>, world: &mut World) -> Result<Vec<Entity>, LoadError> {
+    decls
+        .into_iter()
+        .enumerate()
+        .map(|(index, decl)| {
+            let name = decl.name.clone();
+            decl.build(world).map_err(|err| match err {
+                DeclError::Tag(err) => LoadError::Tag(err),
+                DeclError::Invalid(message) => LoadError::Entity {
+                    index,
+                    name,
+                    message,
+                },
+            })
+        })
+        .collect()
+}
+
+// Top level of a scene file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    #[serde(default)]
+    include: Vec<String>, // Preset calls like "ball_pit(200, seed=42)"
+    #[serde(default)]
+    entities: Vec<EntityDecl>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntityDecl {
+    #[serde(default)]
+    name: Option<String>,
+    shape: ShapeDecl,
+    position: [f32; 2],
+    #[serde(default)]
+    rotation: f32, // radians
+    #[serde(default)]
+    layer: i32,
+    #[serde(default)]
+    color: Option<ColorDecl>, // None takes the palette's next slot
+    #[serde(default)]
+    opacity: Option<f32>,
+    #[serde(default)]
+    physics: Option<PhysicsDecl>,
+    #[serde(default)]
+    gravity_zone: Option<GravityZoneDecl>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum ShapeDecl {
+    Circle { radius: f32 },
+    Rectangle { length: f32, height: f32 },
+    Polygon { vertices: Vec<[f32; 2]> },
+    Text { content: String, font_size: f32 },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GravityZoneDecl {
+    extents: [f32; 2],
+    gravity: [f32; 2],
+}
+
+#[derive(Deserialize)]
+enum ColorDecl {
+    Rgb(f32, f32, f32), // sRGB
+    Palette(String),    // Slot name like "red"; follows theme changes
+}
+
+// Unset fields keep the defaults of Physics::new / Physics::new_static
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhysicsDecl {
+    #[serde(default = "default_dynamic")]
+    dynamic: bool,
+    #[serde(default)]
+    velocity: [f32; 2],
+    #[serde(default)]
+    mass: Option<f32>,
+    #[serde(default)]
+    gravity: Option<bool>,
+    #[serde(default)]
+    restitution: Option<f32>,
+    #[serde(default)]
+    friction: Option<f32>,
+    #[serde(default)]
+    drag: Option<f32>,
+    #[serde(default)]
+    sensor: bool,
+}
+
+fn default_dynamic() -> bool {
+    true
+}
+
+enum DeclError {
+    Invalid(String),
+    Tag(TagError),
+}
+
+impl EntityDecl {
+    fn build(self, world: &mut World) -> Result<Entity, DeclError> {
+        let position = self.position;
+        let mut entity = match self.shape {
+            ShapeDecl::Circle { radius } => Entity::new_circle(position, radius, [1.0; 3]),
+            ShapeDecl::Rectangle { length, height } => {
+                Entity::new_rectangle(position, length, height, [1.0; 3])
+            }
+            ShapeDecl::Polygon { vertices } => Entity::new_polygon(position, vertices, [1.0; 3])
+                .ok_or_else(|| DeclError::Invalid("a polygon needs at least 3 vertices".into()))?,
+            ShapeDecl::Text { content, font_size } => {
+                Entity::new_text(position, content, font_size, [1.0; 3])
+            }
+        };
+
+        entity = match self.color {
+            Some(ColorDecl::Rgb(r, g, b)) => {
+                entity.shape_mut().set_color([r, g, b]);
+                entity
+            }
+            Some(ColorDecl::Palette(slot_name)) => {
+                let slot = world.palette().slot(&slot_name).ok_or_else(|| {
+                    DeclError::Invalid(format!("no palette color named \"{slot_name}\""))
+                })?;
+                entity.with_palette_color(world.palette(), slot)
+            }
+            None => {
+                let slot = world.palette_mut().next_slot();
+                entity.with_palette_color(world.palette(), slot)
+            }
+        };
+        if let Some(opacity) = self.opacity {
+            entity = entity.with_opacity(opacity);
+        }
+        entity.transform_mut().rotation = self.rotation;
+        entity = entity.with_layer(self.layer);
+
+        if let Some(decl) = self.physics {
+            entity = entity.with_physics(decl.build());
+        }
+        if let Some(decl) = self.gravity_zone {
+            entity = entity.with_gravity_zone(GravityZone::new(decl.extents, decl.gravity));
+        }
+        if let Some(name) = self.name {
+            entity = entity.with_name(name);
+        }
+        for tag in &self.tags {
+            entity.add_tag(world.register_tag(tag).map_err(DeclError::Tag)?);
+        }
+        entity
+            .validate()
+            .map_err(|err| DeclError::Invalid(err.to_string()))?;
+        Ok(entity)
+    }
+}
+
+impl PhysicsDecl {
+    fn build(self) -> Physics {
+        let mut physics = if self.dynamic {
+            Physics::new()
+        } else {
+            Physics::new_static()
+        };
+        physics.velocity = self.velocity;
+        if let Some(mass) = self.mass {
+            physics.mass = mass;
+        }
+        if let Some(gravity) = self.gravity {
+            physics.apply_gravity = gravity;
+        }
+        if let Some(restitution) = self.restitution {
+            physics.restitution = restitution;
+        }
+        if let Some(friction) = self.friction {
+            physics.friction = friction;
+        }
+        if let Some(drag) = self.drag {
+            physics.drag_coefficient = drag;
+        }
+        physics.sensor = self.sensor;
+        physics
+    }
+}
+
+/// Run a preset call such as `ball_pit(200, seed=42)`. Presets:
+/// - `ball_pit(count, seed=0)`: `count` small dynamic balls scattered over the upper half
+/// - `walls(thickness=0.05)`: static floor, ceiling and side walls around the NDC square
+fn spawn_include(include: &str, world: &mut World) -> Result<(), String> {
+    let call = PresetCall::parse(include)?;
+    match call.name {
+        "ball_pit" => {
+            call.expect_args(&["count", "seed"])?;
+            let count = call.arg(0, "count")?.ok_or("ball_pit needs a count")? as usize;
+            let seed = call.arg(1, "seed")?.unwrap_or(0.0) as u64;
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..count {
+                let position = [rng.random_range(-0.9..0.9), rng.random_range(0.0..0.9)];
+                let radius = rng.random_range(0.015..0.035);
+                let slot = world.palette_mut().next_slot();
+                let ball =
+                    Entity::new_circle(position, radius, [1.0; 3])
+                        .with_palette_color(world.palette(), slot)
+                        .with_physics(Physics::new().with_velocity([
+                            rng.random_range(-0.3..0.3),
+                            rng.random_range(-0.3..0.3),
+                        ]));
+                world.add_entity(ball);
+            }
+            Ok(())
+        }
+        "walls" => {
+            call.expect_args(&["thickness"])?;
+            let t = call.arg(0, "thickness")?.unwrap_or(0.05) as f32;
+            let color = [0.6, 0.6, 0.6];
+            for (position, length, height) in [
+                ([0.0, -1.0 + t / 2.0], 2.0, t),
+                ([0.0, 1.0 - t / 2.0], 2.0, t),
+                ([-1.0 + t / 2.0, 0.0], t, 2.0),
+                ([1.0 - t / 2.0, 0.0], t, 2.0),
+            ] {
+                let wall = Entity::new_rectangle(position, length, height, color)
+                    .with_physics(Physics::new_static());
+                world.add_entity(wall);
+            }
+            Ok(())
+        }
+        other => Err(format!("unknown preset \"{other}\"")),
+    }
+}
+
+/// `name(positional, ..., key=value, ...)` with numeric arguments
+struct PresetCall<'a> {
+    name: &'a str,
+    positional: Vec<f64>,
+    named: Vec<(&'a str, f64)>,
+}
+
+impl<'a> PresetCall<'a> {
+    fn parse(include: &'a str) -> Result<Self, String> {
+        let include = include.trim();
+        let (name, args) = match include.split_once('(') {
+            Some((name, rest)) => {
+                let args = rest
+                    .strip_suffix(')')
+                    .ok_or("missing closing parenthesis")?;
+                (name.trim(), args)
+            }
+            None => (include, ""),
+        };
+
+        let mut call = Self {
+            name,
+            positional: Vec::new(),
+            named: Vec::new(),
+        };
+        for arg in args.split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+            let parse = |value: &str| {
+                value
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("\"{}\" is not a number", value.trim()))
+            };
+            match arg.split_once('=') {
+                Some((key, value)) => call.named.push((key.trim(), parse(value)?)),
+                None if call.named.is_empty() => call.positional.push(parse(arg)?),
+                None => return Err("positional arguments must come before named ones".into()),
+            }
+        }
+        Ok(call)
+    }
+
+    /// Reject named arguments the preset doesn't take, and extra positional ones
+    fn expect_args(&self, params: &[&str]) -> Result<(), String> {
+        if self.positional.len() > params.len() {
+            return Err(format!(
+                "{} takes at most {} arguments",
+                self.name,
+                params.len()
+            ));
+        }
+        match self.named.iter().find(|(key, _)| !params.contains(key)) {
+            Some((key, _)) => Err(format!(
+                "{} has no argument \"{key}\" (expected one of: {})",
+                self.name,
+                params.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Argument `param`, given either by position or by name
+    fn arg(&self, position: usize, param: &str) -> Result<Option<f64>, String> {
+        let named = self.named.iter().find(|(key, _)| *key == param);
+        match (self.positional.get(position), named) {
+            (Some(_), Some(_)) => Err(format!("\"{param}\" given twice")),
+            (Some(value), None) | (None, Some((_, value))) => Ok(Some(*value)),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "scene"))]
+mod tests {
+    use super::*;
+    use crate::components::Shape;
+
+    fn load_bundled(file: &str) -> World {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("scenes")
+            .join(file);
+        let mut world = World::new();
+        let added = load(&path, &mut world).expect("bundled scene should load");
+        assert_eq!(added, world.len());
+        world
+    }
+
+    #[test]
+    fn ball_pit_scene_loads_walls_balls_and_the_wrecking_ball() {
+        let world = load_bundled("ball_pit.ron");
+
+        // 4 walls and 200 balls from the includes, then the declared ball
+        assert_eq!(world.len(), 205);
+        let walls = world
+            .iter()
+            .filter(|(_, entity)| matches!(entity.shape(), Shape::Rectangle { .. }))
+            .count();
+        assert_eq!(walls, 4);
+        let wrecking_ball = world.find_by_name("wrecking_ball").unwrap();
+        assert_eq!(world.newest(), Some(wrecking_ball));
+        let physics = world.get(wrecking_ball).unwrap().physics().unwrap();
+        assert_eq!(physics.mass, 20.0);
+        assert_eq!(physics.restitution, 0.3);
+
+        // The pit is seeded, so it's laid out the same every time
+        let positions = |world: &World| {
+            world
+                .iter()
+                .map(|(_, entity)| entity.transform().position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&load_bundled("ball_pit.ron")), positions(&world));
+    }
+
+    #[test]
+    fn playground_scene_loads_its_hand_placed_entities() {
+        let world = load_bundled("playground.ron");
+
+        assert_eq!(world.len(), 6);
+        for name in ["floor", "ramp", "anchor_left", "bouncer", "drifter"] {
+            assert!(world.find_by_name(name).is_some(), "no {name}");
+        }
+        let ground = world.tag("ground").unwrap();
+        assert_eq!(world.iter_tagged(ground).count(), 2);
+        let ball = world.tag("ball").unwrap();
+        assert_eq!(world.iter_tagged(ball).count(), 2);
+        let labels = world
+            .iter()
+            .filter(|(_, entity)| matches!(entity.shape(), Shape::Text { .. }))
+            .count();
+        assert_eq!(labels, 1);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::world::World;
+
+/// Worlds layered like screens, e.g. a title screen pushed over a running
+/// simulation. Only the top one runs: the main loop hands `active_mut` to the
+/// systems each frame, and the worlds below stay in memory untouched until
+/// they're on top again. The systems notice the switch through `World::id` and
+/// drop their per-world state, so one renderer serves every scene.
+pub struct SceneStack {
+    // Bottom first; never empty
+    worlds: Vec<World>,
+}
+
+impl SceneStack {
+    /// Stack holding just `base`, which can't be popped
+    pub fn new(base: World) -> Self {
+        Self { worlds: vec![base] }
+    }
+
+    /// Make `world` the active one, pausing the current one underneath it
+    pub fn push(&mut self, world: World) {
+        self.worlds.push(world);
+    }
+
+    /// Remove the active world and resume the one below, exactly where it was
+    /// left. None if only the base world is left.
+    pub fn pop(&mut self) -> Option<World> {
+        if self.worlds.len() == 1 {
+            return None;
+        }
+        self.worlds.pop()
+    }
+
+    pub fn active(&self) -> &World {
+        self.worlds.last().expect("scene stack is never empty")
+    }
+
+    pub fn active_mut(&mut self) -> &mut World {
+        self.worlds.last_mut().expect("scene stack is never empty")
+    }
+
+    /// Number of worlds, the base included
+    pub fn depth(&self) -> usize {
+        self.worlds.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Aabb, Clickable, Physics};
+    use crate::entity::Entity;
+    use crate::systems::{
+        BehaviorSystem, ColorAnimationSystem, EmitterSystem, LifetimeSystem, PhysicsSystem,
+    };
+    use std::time::Duration;
+
+    const DT: Duration = Duration::from_millis(8);
+
+    /// Balls piling onto a floor, so contacts are in play when the scene switches
+    fn pit() -> World {
+        let mut world = World::new();
+        world.add_entity(
+            Entity::new_rectangle([0.0, -0.9], 1.8, 0.1, [0.5, 0.5, 0.5])
+                .with_physics(Physics::new_static()),
+        );
+        world.spawn_random_balls(20, Aabb::from_center([0.0, -0.6], [0.4, 0.25]), 5);
+        world
+    }
+
+    fn positions(world: &World) -> Vec<[f32; 2]> {
+        world
+            .iter()
+            .map(|(_, entity)| entity.transform().position)
+            .collect()
+    }
+
+    #[test]
+    fn popping_back_resumes_the_world_below_where_it_left_off() {
+        let mut reference = pit();
+        let mut uninterrupted = PhysicsSystem::new();
+        for _ in 0..200 {
+            uninterrupted.update(&mut reference, DT);
+        }
+
+        let mut physics = PhysicsSystem::new();
+        let mut scenes = SceneStack::new(pit());
+        for _ in 0..100 {
+            physics.update(scenes.active_mut(), DT);
+        }
+        let paused = positions(scenes.active());
+
+        let mut title = World::new();
+        title.add_entity(
+            Entity::new_circle([0.0, 0.5], 0.1, [1.0, 1.0, 1.0]).with_physics(Physics::new()),
+        );
+        scenes.push(title);
+        assert_eq!(scenes.depth(), 2);
+        for _ in 0..60 {
+            physics.update(scenes.active_mut(), DT);
+        }
+        assert_eq!(scenes.active().sim_steps(), 60);
+
+        let title = scenes.pop().unwrap();
+        assert_eq!(title.len(), 1);
+        assert_eq!(positions(scenes.active()), paused);
+        assert_eq!(scenes.active().sim_steps(), 100);
+        for _ in 0..100 {
+            physics.update(scenes.active_mut(), DT);
+        }
+        assert_eq!(positions(scenes.active()), positions(&reference));
+    }
+
+    #[test]
+    fn the_base_world_cannot_be_popped() {
+        let mut scenes = SceneStack::new(World::new());
+        assert!(scenes.pop().is_none());
+        assert_eq!(scenes.depth(), 1);
+
+        scenes.push(World::new());
+        assert!(scenes.pop().is_some());
+        assert!(scenes.pop().is_none());
+    }
+
+    /// The per-step systems the main loop runs on the active scene
+    struct Systems {
+        behaviors: BehaviorSystem,
+        physics: PhysicsSystem,
+        lifetimes: LifetimeSystem,
+        emitters: EmitterSystem,
+        animations: ColorAnimationSystem,
+    }
+
+    impl Systems {
+        fn new() -> Self {
+            Self {
+                behaviors: BehaviorSystem::new(),
+                physics: PhysicsSystem::new(),
+                lifetimes: LifetimeSystem::new(),
+                emitters: EmitterSystem::new(),
+                animations: ColorAnimationSystem::new(),
+            }
+        }
+
+        fn run(&mut self, world: &mut World, steps: u32) {
+            for _ in 0..steps {
+                let sim_time = world.sim_time();
+                self.behaviors.update(world, sim_time, DT);
+                self.physics.update(world, DT);
+                self.lifetimes.update(world, DT);
+                self.emitters.update(world, DT);
+                self.animations.update(world, DT);
+            }
+        }
+    }
+
+    #[test]
+    fn switching_to_a_title_and_back_resumes_the_simulation_exactly() {
+        let mut reference = pit();
+        Systems::new().run(&mut reference, 200);
+
+        let mut systems = Systems::new();
+        let mut scenes = SceneStack::new(pit());
+        systems.run(scenes.active_mut(), 100);
+
+        // Picking and dragging on the title only touch the title
+        let mut title = World::new();
+        let button = title
+            .add_entity(
+                Entity::new_circle([0.0, -0.6], 0.2, [1.0, 1.0, 1.0])
+                    .with_physics(Physics::new())
+                    .with_clickable(Clickable::new()),
+            )
+            .id()
+            .unwrap();
+        scenes.push(title);
+        let title = scenes.active_mut();
+        assert_eq!(title.find_at_point([0.0, -0.6], true), Some(button));
+        assert_eq!(title.start_drag([0.0, -0.6]), Some(button));
+        title.drag_to([0.3, 0.0]);
+        title.fling([1.0, 0.0]);
+        title.update_hover(Some([0.3, 0.0]));
+        systems.run(scenes.active_mut(), 60);
+
+        scenes.pop().unwrap();
+        systems.run(scenes.active_mut(), 100);
+        let world = scenes.active();
+        assert_eq!(world.sim_steps(), 200);
+        assert_eq!(positions(world), positions(&reference));
+        assert!(world.dragged().is_none() && world.hovered().is_none());
+    }
+}
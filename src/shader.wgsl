@@ -2,56 +2,187 @@ struct VertexInput {
     @location(0) position: vec2<f32>,  // Quad vertex position
 }
 
+// Values of `kind`, matching SHAPE_CIRCLE and SHAPE_RECTANGLE in renderer.rs
+const SHAPE_CIRCLE: u32 = 0u;
+const SHAPE_RECTANGLE: u32 = 1u;
+
 struct InstanceInput {
-    @location(1) center: vec2<f32>,    // Circle center (NDC)
-    @location(2) radius: f32,          // Circle radius (NDC)
-    @location(3) color: vec3<f32>,     // Circle color (RGB)
+    @location(1) center: vec2<f32>,      // Shape center (NDC)
+    @location(2) size: vec2<f32>,        // Circles: radius in x. Rectangles: width and height (NDC)
+    @location(3) color: vec4<f32>,       // Shape color (RGBA)
+    @location(4) color_outer: vec3<f32>, // Rim color, same as `color` for flat shapes
+    @location(5) rotation: f32,          // Radians, counter-clockwise
+    @location(6) scale: vec2<f32>,       // Non-uniform scale makes a circle an ellipse
+    @location(7) thickness: f32,         // Ring thickness (NDC), 0 = filled; circles only
+    @location(8) corner_radius: f32,     // Rounded corner radius (NDC), 0 = sharp; rectangles only
+    @location(9) edge_softness: f32,     // Edge width multiplier, 0 = use globals
+    @location(10) kind: u32,             // SHAPE_CIRCLE or SHAPE_RECTANGLE
 }
 
 // Data passed from vertex shader to fragment shader
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,  // Required output
-    @location(0) local_pos: vec2<f32>,            // Position relative to circle center
-    @location(1) color: vec3<f32>,                // Circle color
+    @location(0) local_pos: vec2<f32>,            // Position relative to the center, unrotated
+    @location(1) color: vec4<f32>,                // Shape color
     @location(2) radius: f32,                     // Circle radius
+    @location(3) thickness: f32,                  // Ring thickness, 0 = filled
+    @location(4) uv: vec2<f32>,                   // Texture coordinate across the shape
+    @location(5) edge_softness: f32,              // Edge width multiplier
+    @location(6) color_outer: vec3<f32>,          // Rim color
+    @location(7) half_size: vec2<f32>,            // Rectangle half width and height, scaled
+    @location(8) corner_radius: f32,              // Rectangle corner radius, scaled and clamped
+    @location(9) @interpolate(flat) kind: u32,
+}
+
+// Per-frame values written by the renderer (matches `Globals` in renderer.rs)
+struct Globals {
+    time: f32,                    // Elapsed sim time (seconds)
+    edge_pulse: f32,              // Circle edge softness pulse strength, 0 = off
+    screen_size: vec2<f32>,       // Pixels
+    camera_position: vec2<f32>,   // Reserved for the camera
+    camera_zoom: f32,
+    edge_softness: f32,           // Edge width multiplier, 1 = default
+    glow: f32,                    // Circle halo strength, 0 = off
+    glow_extent: f32,             // Halo reach past the radius, as a fraction of it
 }
 
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+// Only bound for textured shapes (fs_textured)
+@group(1) @binding(0) var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1) var s_diffuse: sampler;
+
 @vertex
 fn vs_main(
     vertex: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    
-    // Scale quad by radius, then translate to circle center
-    let world_pos = vertex.position * instance.radius + instance.center;
-    
+
+    // Quad corner in the shape's own frame: `local` before scaling, `scaled` after
+    var local: vec2<f32>;
+    var scaled: vec2<f32>;
+    var unit = vertex.position;
+    if (instance.kind == SHAPE_CIRCLE) {
+        // Filled circles grow their quad to make room for the halo
+        var reach = 1.0;
+        if (globals.glow > 0.0 && instance.thickness == 0.0) {
+            reach = 1.0 + globals.glow_extent;
+        }
+        local = vertex.position * instance.size.x * reach;
+        scaled = local * instance.scale;
+        unit = vertex.position * reach;
+
+        // Unscaled local position keeps the distance check circular in circle space
+        out.local_pos = local;
+    } else {
+        let half_size = instance.size / 2.0 * instance.scale;
+        scaled = vertex.position * half_size;
+        out.local_pos = scaled;
+        out.half_size = half_size;
+
+        // Corners can't be rounder than half the shorter side
+        let radius = instance.corner_radius * min(instance.scale.x, instance.scale.y);
+        out.corner_radius = min(radius, min(half_size.x, half_size.y));
+    }
+
+    // Rotate, then translate
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let rotated = vec2<f32>(scaled.x * c - scaled.y * s, scaled.x * s + scaled.y * c);
+    let world_pos = rotated + instance.center;
+
     // Output final NDC position (GPU needs vec4 with z=0, w=1 for 2D)
     out.clip_position = vec4<f32>(world_pos, 0.0, 1.0);
-    
-    // Pass local position (for distance check in fragment shader)
-    out.local_pos = vertex.position * instance.radius;
-    
-    // Pass through color and radius
+
     out.color = instance.color;
-    out.radius = instance.radius;
-    
+    out.color_outer = instance.color_outer;
+    out.radius = instance.size.x;
+    out.thickness = instance.thickness;
+    out.edge_softness = select(globals.edge_softness, instance.edge_softness, instance.edge_softness > 0.0);
+    out.kind = instance.kind;
+
+    // The shape's bounding square (-1..1) maps to the full texture, v pointing down
+    out.uv = vec2<f32>(unit.x * 0.5 + 0.5, 0.5 - unit.y * 0.5);
+
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let dist = length(in.local_pos);
+// Signed distance to a box with rounded corners, negative inside
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
 
-    // How wide the edge should be (in local space)
-    let edge_width = fwidth(dist);
+// Antialiased coverage of a circle (or ring) `dist` from its center
+fn circle_coverage(in: VertexOutput, dist: f32, dist_width: f32) -> f32 {
+    // How wide the edge should be (in local space), optionally breathing over time
+    let pulse = 1.0 + globals.edge_pulse * (0.5 + 0.5 * sin(globals.time * 6.2831853));
+    let edge_width = dist_width * pulse * in.edge_softness;
 
     // Smooth alpha transition at the circle boundary
-    let alpha = 1.0 - smoothstep(
+    var alpha = 1.0 - smoothstep(
         in.radius - edge_width,
         in.radius + edge_width,
         dist,
     );
 
-    return vec4<f32>(in.color, alpha);
+    // Rings also fade out on the inside edge
+    if (in.thickness > 0.0) {
+        let inner = in.radius - in.thickness;
+        alpha *= smoothstep(inner - edge_width, inner + edge_width, dist);
+    } else if (globals.glow > 0.0 && dist > in.radius) {
+        // Halo fades out quadratically across the extra reach
+        let reach = max(in.radius * globals.glow_extent, 1e-6);
+        let fade = 1.0 - clamp((dist - in.radius) / reach, 0.0, 1.0);
+        alpha = max(alpha, globals.glow * fade * fade);
+    }
+
+    return alpha;
+}
+
+// Coverage of a rectangle `dist` from its rounded outline; sharp rectangles stay fully opaque
+fn rect_coverage(in: VertexOutput, dist: f32, dist_width: f32) -> f32 {
+    if (in.corner_radius <= 0.0) {
+        return 1.0;
+    }
+    let edge_width = dist_width * in.edge_softness;
+    return 1.0 - smoothstep(-edge_width, edge_width, dist);
+}
+
+// Antialiased coverage of the shape at this fragment
+fn coverage(in: VertexOutput) -> f32 {
+    // Derivatives must be taken outside the per-kind branch
+    let circle_dist = length(in.local_pos);
+    let circle_width = fwidth(circle_dist);
+    let rect_dist = rounded_box(in.local_pos, in.half_size, in.corner_radius);
+    let rect_width = fwidth(rect_dist);
+
+    if (in.kind == SHAPE_CIRCLE) {
+        return circle_coverage(in, circle_dist, circle_width);
+    }
+    return rect_coverage(in, rect_dist, rect_width);
+}
+
+// Instance color, blended toward the rim color by distance from the center
+fn fill_color(in: VertexOutput) -> vec4<f32> {
+    // Flat shapes skip the blend so their output stays exact
+    if (all(in.color_outer == in.color.rgb)) {
+        return in.color;
+    }
+    let t = clamp(length(in.local_pos) / in.radius, 0.0, 1.0);
+    return vec4<f32>(mix(in.color.rgb, in.color_outer, t), in.color.a);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = fill_color(in);
+    return vec4<f32>(color.rgb, color.a * coverage(in));
+}
+
+// Texture tinted by the instance color
+@fragment
+fn fs_textured(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(t_diffuse, s_diffuse, in.uv) * fill_color(in);
+    return vec4<f32>(texel.rgb, texel.a * coverage(in));
 }
@@ -0,0 +1,128 @@
+use crate::world::World;
+use std::time::Duration;
+
+/// Advances `ColorAnimation` components and writes the result into each shape's
+/// color. Runs on sim steps, so pausing the sim pauses animations.
+pub struct ColorAnimationSystem;
+
+impl ColorAnimationSystem {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Call once per fixed sim step with the sim dt
+    pub fn update(&mut self, world: &mut World, dt: Duration) {
+        for entity in world.entities_mut() {
+            if entity.color_animation().is_none() {
+                continue; // Leaves the entity out of the change tracking
+            }
+            // Take the animation out so the shape can be borrowed alongside it
+            let Some(mut animation) = entity.color_animation_mut().take() else {
+                continue;
+            };
+            if animation.from_current {
+                animation.keyframes.insert(0, entity.shape().color());
+                animation.from_current = false;
+            }
+
+            animation.elapsed += dt;
+            if let Some(color) = animation.color_at(animation.elapsed) {
+                entity.shape_mut().set_color(color);
+            }
+
+            if !animation.is_finished() {
+                *entity.color_animation_mut() = Some(animation);
+            }
+        }
+    }
+}
+
+impl Default for ColorAnimationSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{ColorAnimation, LoopMode};
+    use crate::entity::Entity;
+
+    const RED: [f32; 3] = [1.0, 0.0, 0.0];
+    const BLUE: [f32; 3] = [0.0, 0.0, 1.0];
+    const GREEN: [f32; 3] = [0.0, 1.0, 0.0];
+
+    fn at(animation: &ColorAnimation, millis: u64) -> [f32; 3] {
+        animation.color_at(Duration::from_millis(millis)).unwrap()
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        let close = actual
+            .iter()
+            .zip(expected)
+            .all(|(a, b)| (a - b).abs() < 1e-5);
+        assert!(close, "{actual:?} vs {expected:?}");
+    }
+
+    #[test]
+    fn keyframes_interpolate_evenly_over_the_duration() {
+        let animation = ColorAnimation::new(
+            vec![RED, BLUE, GREEN],
+            Duration::from_secs(1),
+            LoopMode::Once,
+        );
+        assert_close(at(&animation, 0), RED);
+        assert_close(at(&animation, 250), [0.5, 0.0, 0.5]);
+        assert_close(at(&animation, 500), BLUE);
+        assert_close(at(&animation, 750), [0.0, 0.5, 0.5]);
+        assert_close(at(&animation, 1000), GREEN);
+        // Once holds the last keyframe
+        assert_close(at(&animation, 5000), GREEN);
+        assert!(
+            ColorAnimation::new(Vec::new(), Duration::from_secs(1), LoopMode::Once)
+                .color_at(Duration::ZERO)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn loop_restarts_and_ping_pong_comes_back() {
+        let looping = ColorAnimation::new(vec![RED, BLUE], Duration::from_secs(1), LoopMode::Loop);
+        assert_close(at(&looping, 1250), [0.75, 0.0, 0.25]);
+        assert_close(at(&looping, 2000), RED);
+
+        let ping_pong =
+            ColorAnimation::new(vec![RED, BLUE], Duration::from_secs(1), LoopMode::PingPong);
+        assert_close(at(&ping_pong, 500), [0.5, 0.0, 0.5]);
+        assert_close(at(&ping_pong, 1000), BLUE);
+        assert_close(at(&ping_pong, 1250), [0.25, 0.0, 0.75]);
+        assert_close(at(&ping_pong, 2000), RED);
+    }
+
+    #[test]
+    fn system_colors_the_shape_and_drops_finished_animations() {
+        let mut world = World::new();
+        let id = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.0], 0.1, RED)
+                    .with_color_animation(ColorAnimation::to(BLUE, Duration::from_millis(80))),
+            )
+            .id()
+            .unwrap();
+        let mut animations = ColorAnimationSystem::new();
+        let dt = Duration::from_millis(8);
+
+        for _ in 0..5 {
+            animations.update(&mut world, dt);
+        }
+        // Halfway from the shape's own color
+        assert_close(world.get(id).unwrap().shape().color(), [0.5, 0.0, 0.5]);
+        for _ in 0..5 {
+            animations.update(&mut world, dt);
+        }
+        let entity = world.get(id).unwrap();
+        assert_close(entity.shape().color(), BLUE);
+        assert!(entity.color_animation().is_none());
+    }
+}
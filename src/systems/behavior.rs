@@ -0,0 +1,200 @@
+use crate::components::{BehaviorCtx, Physics};
+use crate::entity::Entity;
+use crate::world::{EntityId, World};
+use std::time::Duration;
+
+/// Runs every entity's `Behavior` in entity order, then applies the physics
+/// changes, spawns and despawns they asked for, along with spawns queued on the
+/// world (`World::queue_spawn`). Call before `PhysicsSystem::update`,
+/// so forces and velocities set by behaviors are integrated the same step, and
+/// entities given physics join in from that update on.
+pub struct BehaviorSystem {
+    // Deferred commands from this step's behaviors, kept to avoid reallocating
+    spawns: Vec<Entity>,
+    despawns: Vec<EntityId>,
+    physics_changes: Vec<(EntityId, Option<Physics>)>,
+}
+
+impl BehaviorSystem {
+    pub fn new() -> Self {
+        Self {
+            spawns: Vec::new(),
+            despawns: Vec::new(),
+            physics_changes: Vec::new(),
+        }
+    }
+
+    /// Call once per fixed sim step with `TimeSystem::sim_time` and the sim dt
+    pub fn update(&mut self, world: &mut World, sim_time: Duration, dt: Duration) {
+        for index in 0..world.len() {
+            let Some(id) = world.id_at(index) else {
+                continue;
+            };
+            let entity = &mut world.entities_mut()[index];
+            if entity.behavior().is_none() {
+                continue; // Leaves the entity out of the change tracking
+            }
+            // Take the behavior out so it can borrow its own entity
+            let Some(mut behavior) = entity.behavior_mut().take() else {
+                continue;
+            };
+            let (transform, physics) = entity.transform_and_physics_mut();
+            behavior.run(BehaviorCtx {
+                id,
+                transform,
+                physics,
+                sim_time,
+                dt,
+                spawns: &mut self.spawns,
+                despawns: &mut self.despawns,
+                physics_changes: &mut self.physics_changes,
+            });
+            *entity.behavior_mut() = Some(behavior);
+        }
+
+        for (id, physics) in self.physics_changes.drain(..) {
+            match physics {
+                Some(physics) => world.set_physics(id, physics),
+                None => {
+                    world.remove_physics(id);
+                }
+            }
+        }
+        for entity in self.spawns.drain(..) {
+            world.add_entity(entity);
+        }
+        world.flush_spawns();
+        for &id in &self.despawns {
+            world.queue_removal(id);
+        }
+        self.despawns.clear();
+        world.flush_removals();
+        world.debug_validate("behaviors");
+    }
+}
+
+impl Default for BehaviorSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Behavior;
+    use crate::systems::PhysicsSystem;
+
+    const DT: Duration = Duration::from_millis(8);
+
+    fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+    }
+
+    /// Runs behaviors then physics for `steps` fixed steps, starting at `start`
+    fn run(world: &mut World, behaviors: &mut BehaviorSystem, start: Duration, steps: u32) {
+        let mut physics = PhysicsSystem::new();
+        for step in 0..steps {
+            behaviors.update(world, start + DT * step, DT);
+            physics.update(world, DT);
+        }
+    }
+
+    #[test]
+    fn homing_behavior_steers_a_ball_onto_its_target() {
+        let target = [0.5, 0.25];
+        let homing = Behavior::new(move |ctx: BehaviorCtx<'_>| {
+            let position = ctx.transform.position;
+            let Some(physics) = ctx.physics else {
+                return;
+            };
+            // Head straight for the target, slowing down on the way in
+            physics.velocity = [
+                (target[0] - position[0]) * 4.0,
+                (target[1] - position[1]) * 4.0,
+            ];
+        });
+        let mut world = World::new();
+        let mut physics = Physics::new();
+        physics.apply_gravity = false;
+        physics.velocity = [0.0, -1.0];
+        let id = world
+            .add_entity(
+                Entity::new_circle([-0.5, -0.5], 0.05, [1.0, 1.0, 1.0])
+                    .with_physics(physics)
+                    .with_behavior(homing),
+            )
+            .id()
+            .unwrap();
+        let mut behaviors = BehaviorSystem::new();
+
+        let start = distance(world.get(id).unwrap().transform().position, target);
+        run(&mut world, &mut behaviors, Duration::ZERO, 25);
+        let halfway = distance(world.get(id).unwrap().transform().position, target);
+        run(&mut world, &mut behaviors, DT * 25, 225);
+        let end = distance(world.get(id).unwrap().transform().position, target);
+
+        assert!(halfway < start, "{halfway} should be closer than {start}");
+        assert!(end < 0.01, "ball ended {end} from its target");
+    }
+
+    #[test]
+    fn self_destruct_behavior_despawns_its_entity_after_two_seconds() {
+        let fuse = Duration::from_secs(2);
+        let self_destruct = Behavior::new(move |mut ctx: BehaviorCtx<'_>| {
+            if ctx.sim_time >= fuse {
+                ctx.despawn_self();
+            }
+        });
+        let mut world = World::new();
+        let doomed = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.0], 0.05, [1.0, 1.0, 1.0]).with_behavior(self_destruct),
+            )
+            .id()
+            .unwrap();
+        let bystander = world
+            .add_entity(Entity::new_circle([0.5, 0.0], 0.05, [1.0, 1.0, 1.0]))
+            .id()
+            .unwrap();
+        let mut behaviors = BehaviorSystem::new();
+
+        // 250 steps of 8ms end just before the 2s mark
+        let mut sim_time = Duration::ZERO;
+        for _ in 0..250 {
+            behaviors.update(&mut world, sim_time, DT);
+            sim_time += DT;
+        }
+        assert!(world.contains(doomed), "despawned before its fuse ran out");
+
+        behaviors.update(&mut world, sim_time, DT);
+        assert!(!world.contains(doomed));
+        assert!(world.contains(bystander));
+    }
+
+    #[test]
+    fn spawns_and_physics_changes_apply_after_every_behavior_ran() {
+        let spawner = Behavior::new(|mut ctx: BehaviorCtx<'_>| {
+            if ctx.sim_time.is_zero() {
+                ctx.spawn(Entity::new_circle([0.0, 0.5], 0.05, [1.0, 1.0, 1.0]));
+                ctx.set_physics(ctx.id, Physics::new());
+            }
+        });
+        let mut world = World::new();
+        let id = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.0], 0.05, [1.0, 1.0, 1.0]).with_behavior(spawner),
+            )
+            .id()
+            .unwrap();
+        let mut behaviors = BehaviorSystem::new();
+
+        behaviors.update(&mut world, Duration::ZERO, DT);
+        assert_eq!(world.len(), 2);
+        assert!(world.get(id).unwrap().physics().is_some());
+
+        // The behavior is put back and runs again, but only spawns once
+        behaviors.update(&mut world, DT, DT);
+        assert_eq!(world.len(), 2);
+    }
+}
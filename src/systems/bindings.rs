@@ -0,0 +1,544 @@
+use super::input::InputCommand;
+use super::renderer::DebugDrawFlags;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::path::Path;
+use winit::keyboard::{KeyCode, ModifiersState};
+
+/// Why a binding was refused
+#[derive(Debug)]
+pub enum BindingError {
+    /// The key and modifiers already trigger `existing`; unbind it first
+    Conflict {
+        key: KeyCode,
+        modifiers: ModifiersState,
+        existing: InputCommand,
+    },
+    /// Only discrete commands can be bound; ones carrying a position stay on the mouse
+    NotBindable(InputCommand),
+}
+
+impl std::fmt::Display for BindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingError::Conflict {
+                key,
+                modifiers,
+                existing,
+            } => write!(f, "{modifiers:?}+{key:?} is already bound to {existing:?}"),
+            BindingError::NotBindable(command) => {
+                write!(f, "{command:?} can't be bound to a key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BindingError {}
+
+/// Why key bindings couldn't be saved or loaded
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum BindingsFileError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for BindingsFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingsFileError::Io(err) => write!(f, "could not access the bindings file: {err}"),
+            BindingsFileError::Json(err) => write!(f, "invalid bindings JSON: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for BindingsFileError {}
+
+/// Which discrete command each key press triggers, read by `InputSystem::handle_key`.
+/// A binding matches only with exactly its modifiers held, so `P` and `Ctrl+P`
+/// can do different things. Several keys may trigger the same command.
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    bindings: HashMap<(KeyCode, ModifiersState), InputCommand>,
+}
+
+impl KeyBindings {
+    /// No bindings at all
+    pub fn empty() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Make `key` with exactly `modifiers` held trigger `command`
+    pub fn bind(
+        &mut self,
+        key: KeyCode,
+        modifiers: ModifiersState,
+        command: InputCommand,
+    ) -> Result<(), BindingError> {
+        if command_name(&command).is_none() {
+            return Err(BindingError::NotBindable(command));
+        }
+        if let Some(&existing) = self.bindings.get(&(key, modifiers)) {
+            return Err(BindingError::Conflict {
+                key,
+                modifiers,
+                existing,
+            });
+        }
+        self.bindings.insert((key, modifiers), command);
+        Ok(())
+    }
+
+    /// Remove the binding for `key` with `modifiers`, returning what it triggered
+    pub fn unbind(&mut self, key: KeyCode, modifiers: ModifiersState) -> Option<InputCommand> {
+        self.bindings.remove(&(key, modifiers))
+    }
+
+    /// Command for `key` pressed with exactly `modifiers` held
+    pub fn get(&self, key: KeyCode, modifiers: ModifiersState) -> Option<InputCommand> {
+        self.bindings.get(&(key, modifiers)).copied()
+    }
+
+    /// Write every binding to `path` as JSON, keys and commands by name
+    #[cfg(feature = "serde")]
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), BindingsFileError> {
+        let file = std::fs::File::create(path).map_err(BindingsFileError::Io)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)
+            .map_err(BindingsFileError::Json)
+    }
+
+    /// Read bindings written by `save_json`, replacing the defaults entirely
+    #[cfg(feature = "serde")]
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, BindingsFileError> {
+        let file = std::fs::File::open(path).map_err(BindingsFileError::Io)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(BindingsFileError::Json)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let none = ModifiersState::empty();
+        let defaults = [
+            (KeyCode::Escape, InputCommand::Exit),
+            (KeyCode::F2, InputCommand::ToggleLabels),
+            (KeyCode::KeyP, InputCommand::TogglePause),
+            (KeyCode::Space, InputCommand::TogglePause),
+            (KeyCode::KeyF, InputCommand::ToggleFreeze),
+            (KeyCode::F3, InputCommand::ToggleStats),
+            (
+                KeyCode::F4,
+                InputCommand::ToggleDebugDraw(DebugDrawFlags::CONTACTS),
+            ),
+            (
+                KeyCode::F5,
+                InputCommand::ToggleDebugDraw(DebugDrawFlags::AABBS),
+            ),
+            (
+                KeyCode::F6,
+                InputCommand::ToggleDebugDraw(DebugDrawFlags::VELOCITIES),
+            ),
+            (KeyCode::F7, InputCommand::CyclePresentMode),
+            (KeyCode::F8, InputCommand::ToggleTrails),
+            (KeyCode::F9, InputCommand::CycleColorMode),
+            (KeyCode::F10, InputCommand::ToggleGrid),
+            (KeyCode::F11, InputCommand::ToggleFullscreen),
+            (KeyCode::F12, InputCommand::ToggleRecording),
+            (KeyCode::Backspace, InputCommand::StepBack),
+            (KeyCode::KeyT, InputCommand::ToggleTitle),
+            (KeyCode::Delete, InputCommand::DeleteSelected),
+        ];
+        Self {
+            bindings: defaults
+                .into_iter()
+                .map(|(key, command)| ((key, none), command))
+                .collect(),
+        }
+    }
+}
+
+// Saved names of the bindable commands; None for the ones that can't be bound
+fn command_name(command: &InputCommand) -> Option<&'static str> {
+    Some(match *command {
+        InputCommand::Exit => "Exit",
+        InputCommand::TogglePause => "TogglePause",
+        InputCommand::ToggleFreeze => "ToggleFreeze",
+        InputCommand::ToggleStats => "ToggleStats",
+        InputCommand::ToggleDebugDraw(DebugDrawFlags::CONTACTS) => "ToggleContacts",
+        InputCommand::ToggleDebugDraw(DebugDrawFlags::AABBS) => "ToggleAabbs",
+        InputCommand::ToggleDebugDraw(DebugDrawFlags::VELOCITIES) => "ToggleVelocities",
+        InputCommand::ToggleDebugDraw(DebugDrawFlags::GRAVITY_ZONES) => "ToggleGravityZones",
+        InputCommand::CyclePresentMode => "CyclePresentMode",
+        InputCommand::ToggleTrails => "ToggleTrails",
+        InputCommand::CycleColorMode => "CycleColorMode",
+        InputCommand::ToggleGrid => "ToggleGrid",
+        InputCommand::ToggleLabels => "ToggleLabels",
+        InputCommand::ToggleFullscreen => "ToggleFullscreen",
+        InputCommand::ToggleRecording => "ToggleRecording",
+        InputCommand::StepBack => "StepBack",
+        InputCommand::ToggleTitle => "ToggleTitle",
+        InputCommand::DeleteSelected => "DeleteSelected",
+        _ => return None,
+    })
+}
+
+#[cfg(feature = "serde")]
+fn command_from_name(name: &str) -> Option<InputCommand> {
+    Some(match name {
+        "Exit" => InputCommand::Exit,
+        "TogglePause" => InputCommand::TogglePause,
+        "ToggleFreeze" => InputCommand::ToggleFreeze,
+        "ToggleStats" => InputCommand::ToggleStats,
+        "ToggleContacts" => InputCommand::ToggleDebugDraw(DebugDrawFlags::CONTACTS),
+        "ToggleAabbs" => InputCommand::ToggleDebugDraw(DebugDrawFlags::AABBS),
+        "ToggleVelocities" => InputCommand::ToggleDebugDraw(DebugDrawFlags::VELOCITIES),
+        "ToggleGravityZones" => InputCommand::ToggleDebugDraw(DebugDrawFlags::GRAVITY_ZONES),
+        "CyclePresentMode" => InputCommand::CyclePresentMode,
+        "ToggleTrails" => InputCommand::ToggleTrails,
+        "CycleColorMode" => InputCommand::CycleColorMode,
+        "ToggleGrid" => InputCommand::ToggleGrid,
+        "ToggleLabels" => InputCommand::ToggleLabels,
+        "ToggleFullscreen" => InputCommand::ToggleFullscreen,
+        "ToggleRecording" => InputCommand::ToggleRecording,
+        "StepBack" => InputCommand::StepBack,
+        "ToggleTitle" => InputCommand::ToggleTitle,
+        "DeleteSelected" => InputCommand::DeleteSelected,
+        _ => return None,
+    })
+}
+
+// Keys that can be saved, by their KeyCode variant name
+#[cfg(feature = "serde")]
+macro_rules! named_keys {
+    ($($key:ident),* $(,)?) => {
+        fn key_name(key: KeyCode) -> Option<&'static str> {
+            match key {
+                $(KeyCode::$key => Some(stringify!($key)),)*
+                _ => None,
+            }
+        }
+
+        fn key_from_name(name: &str) -> Option<KeyCode> {
+            match name {
+                $(stringify!($key) => Some(KeyCode::$key),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+named_keys![
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Escape,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Backquote,
+    Backslash,
+    BracketLeft,
+    BracketRight,
+    Comma,
+    Equal,
+    Minus,
+    Period,
+    Quote,
+    Semicolon,
+    Slash,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+];
+
+// One binding as saved: names rather than winit types, which aren't serializable here
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedBinding {
+    key: String,
+    #[serde(default)]
+    ctrl: bool,
+    #[serde(default)]
+    shift: bool,
+    #[serde(default)]
+    alt: bool,
+    #[serde(default)]
+    logo: bool, // Windows / Command key
+    command: String,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyBindings {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+
+        // A binding that can't be written fails the save rather than going missing from it
+        let mut saved = self
+            .bindings
+            .iter()
+            .map(|(&(key, modifiers), command)| {
+                Ok(SavedBinding {
+                    key: key_name(key)
+                        .ok_or_else(|| S::Error::custom(format!("can't save key {key:?}")))?
+                        .to_string(),
+                    ctrl: modifiers.control_key(),
+                    shift: modifiers.shift_key(),
+                    alt: modifiers.alt_key(),
+                    logo: modifiers.super_key(),
+                    command: command_name(command)
+                        .ok_or_else(|| S::Error::custom(format!("can't save command {command:?}")))?
+                        .to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, S::Error>>()?;
+        // Stable file contents regardless of map order
+        saved.sort_by(|a, b| {
+            (&a.key, a.ctrl, a.shift, a.alt, a.logo).cmp(&(&b.key, b.ctrl, b.shift, b.alt, b.logo))
+        });
+        saved.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyBindings {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let mut bindings = KeyBindings::empty();
+        for saved in Vec::<SavedBinding>::deserialize(deserializer)? {
+            let key = key_from_name(&saved.key)
+                .ok_or_else(|| D::Error::custom(format!("unknown key {:?}", saved.key)))?;
+            let command = command_from_name(&saved.command)
+                .ok_or_else(|| D::Error::custom(format!("unknown command {:?}", saved.command)))?;
+            let mut modifiers = ModifiersState::empty();
+            modifiers.set(ModifiersState::CONTROL, saved.ctrl);
+            modifiers.set(ModifiersState::SHIFT, saved.shift);
+            modifiers.set(ModifiersState::ALT, saved.alt);
+            modifiers.set(ModifiersState::SUPER, saved.logo);
+            bindings
+                .bind(key, modifiers, command)
+                .map_err(D::Error::custom)?;
+        }
+        Ok(bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_can_be_rebound_to_enter() {
+        let mut bindings = KeyBindings::default();
+        let none = ModifiersState::empty();
+        assert!(matches!(
+            bindings.unbind(KeyCode::KeyP, none),
+            Some(InputCommand::TogglePause)
+        ));
+        bindings
+            .bind(KeyCode::Enter, none, InputCommand::TogglePause)
+            .unwrap();
+
+        assert!(bindings.get(KeyCode::KeyP, none).is_none());
+        assert!(matches!(
+            bindings.get(KeyCode::Enter, none),
+            Some(InputCommand::TogglePause)
+        ));
+        // Space still pauses too
+        assert!(matches!(
+            bindings.get(KeyCode::Space, none),
+            Some(InputCommand::TogglePause)
+        ));
+    }
+
+    #[test]
+    fn bindings_need_exactly_their_modifiers() {
+        let mut bindings = KeyBindings::empty();
+        let ctrl_shift = ModifiersState::CONTROL | ModifiersState::SHIFT;
+        bindings
+            .bind(KeyCode::KeyR, ctrl_shift, InputCommand::ToggleRecording)
+            .unwrap();
+        bindings
+            .bind(
+                KeyCode::KeyR,
+                ModifiersState::empty(),
+                InputCommand::StepBack,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            bindings.get(KeyCode::KeyR, ctrl_shift),
+            Some(InputCommand::ToggleRecording)
+        ));
+        assert!(matches!(
+            bindings.get(KeyCode::KeyR, ModifiersState::empty()),
+            Some(InputCommand::StepBack)
+        ));
+        assert!(
+            bindings
+                .get(KeyCode::KeyR, ModifiersState::CONTROL)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn conflicting_and_positional_bindings_are_refused() {
+        let mut bindings = KeyBindings::default();
+        let err = bindings
+            .bind(
+                KeyCode::Space,
+                ModifiersState::empty(),
+                InputCommand::ToggleGrid,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BindingError::Conflict {
+                key: KeyCode::Space,
+                existing: InputCommand::TogglePause,
+                ..
+            }
+        ));
+        // The refused binding left the existing one alone
+        assert!(matches!(
+            bindings.get(KeyCode::Space, ModifiersState::empty()),
+            Some(InputCommand::TogglePause)
+        ));
+
+        let click = InputCommand::Click {
+            position: [0.0, 0.0],
+            extend: false,
+        };
+        assert!(matches!(
+            bindings.bind(KeyCode::KeyC, ModifiersState::empty(), click),
+            Err(BindingError::NotBindable(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_bindings_load_back_with_their_modifiers() {
+        let mut bindings = KeyBindings::empty();
+        bindings
+            .bind(
+                KeyCode::Enter,
+                ModifiersState::empty(),
+                InputCommand::TogglePause,
+            )
+            .unwrap();
+        bindings
+            .bind(
+                KeyCode::KeyS,
+                ModifiersState::CONTROL | ModifiersState::ALT,
+                InputCommand::ToggleStats,
+            )
+            .unwrap();
+
+        let json = serde_json::to_string(&bindings).unwrap();
+        let loaded: KeyBindings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.bindings.len(), 2);
+        assert!(matches!(
+            loaded.get(KeyCode::Enter, ModifiersState::empty()),
+            Some(InputCommand::TogglePause)
+        ));
+        assert!(matches!(
+            loaded.get(KeyCode::KeyS, ModifiersState::CONTROL | ModifiersState::ALT),
+            Some(InputCommand::ToggleStats)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unsaveable_keys_and_duplicate_entries_are_errors() {
+        let mut bindings = KeyBindings::empty();
+        bindings
+            .bind(
+                KeyCode::AudioVolumeMute,
+                ModifiersState::empty(),
+                InputCommand::Exit,
+            )
+            .unwrap();
+        let err = serde_json::to_string(&bindings).unwrap_err();
+        assert!(err.to_string().contains("AudioVolumeMute"), "{err}");
+
+        let json = r#"[
+            {"key": "KeyG", "command": "ToggleGrid"},
+            {"key": "KeyG", "command": "ToggleTrails"}
+        ]"#;
+        let err = serde_json::from_str::<KeyBindings>(json).unwrap_err();
+        assert!(err.to_string().contains("already bound"), "{err}");
+    }
+}
@@ -0,0 +1,174 @@
+use crate::components::EmitterSource;
+use crate::world::{EntityId, World};
+use rand::{Rng, RngCore};
+use std::time::Duration;
+
+/// Ticks every entity's `Emitter`, spawning on sim time. Emitters are found by
+/// id up front, since spawns can evict entities under the world's entity cap.
+pub struct EmitterSystem {
+    // Entities with an emitter at the start of the update, kept to avoid reallocating
+    emitters: Vec<EntityId>,
+}
+
+impl EmitterSystem {
+    pub fn new() -> Self {
+        Self {
+            emitters: Vec::new(),
+        }
+    }
+
+    /// Call once per fixed sim step with the sim dt
+    pub fn update(&mut self, world: &mut World, dt: Duration) {
+        // Entities spawned here aren't in the list, so they aren't ticked until next step
+        self.emitters.clear();
+        for (index, entity) in world.entities().iter().enumerate() {
+            if entity.emitter().is_some() {
+                self.emitters.extend(world.id_at(index));
+            }
+        }
+
+        for &emitter_id in &self.emitters {
+            // Take the emitter out so spawning can borrow the world
+            let Some(entity) = world.get_mut(emitter_id) else {
+                continue;
+            };
+            let Some(mut emitter) = entity.emitter_mut().take() else {
+                continue;
+            };
+            let origin = entity.transform().position;
+
+            emitter.children.retain(|&id| world.index_of(id).is_some());
+            if emitter.enabled {
+                emitter.elapsed += dt;
+            }
+            while emitter.enabled
+                && !emitter.interval.is_zero()
+                && emitter.elapsed >= emitter.interval
+            {
+                if emitter.children.len() >= emitter.max_alive {
+                    // Fire as soon as a slot frees up, without a backlog of missed spawns
+                    emitter.elapsed = emitter.interval;
+                    break;
+                }
+                emitter.elapsed -= emitter.interval;
+
+                let ids = match &emitter.source {
+                    EmitterSource::Prefab(prefab) => world.spawn_prefab(prefab, origin),
+                    EmitterSource::Factory(factory) => {
+                        let mut entity = factory();
+                        let transform = entity.transform_mut();
+                        transform.position[0] += origin[0];
+                        transform.position[1] += origin[1];
+                        transform.prev_position = transform.position;
+                        world.add_entity(entity).id().into_iter().collect()
+                    }
+                };
+
+                let rng: &mut dyn RngCore = match &mut emitter.rng {
+                    Some(rng) => rng,
+                    None => world.rng(),
+                };
+                let half_spread = emitter.spread / 2.0;
+                let angle = emitter.direction + rng.random_range(-half_spread..=half_spread);
+                let speed = rng.random_range(emitter.speed[0]..=emitter.speed[1]);
+                let velocity = [angle.cos() * speed, angle.sin() * speed];
+                for &id in &ids {
+                    if let Some(physics) = world.get_mut(id).and_then(|entity| entity.physics_mut())
+                    {
+                        physics.velocity = velocity;
+                    }
+                }
+                // A prefab spawn counts once, alive for as long as its root is
+                emitter.children.extend(ids.first().copied());
+            }
+
+            // Gone if a spawn evicted the emitter itself
+            if let Some(entity) = world.get_mut(emitter_id) {
+                *entity.emitter_mut() = Some(emitter);
+            }
+        }
+    }
+}
+
+impl Default for EmitterSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Emitter, Physics};
+    use crate::entity::Entity;
+    use std::sync::Arc;
+
+    const DT: Duration = Duration::from_millis(8);
+
+    // World holding only an emitter of balls at the origin
+    fn emitting(emitter: Emitter) -> (World, EntityId) {
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_circle([0.0, 0.0], 0.01, [1.0; 3]).with_emitter(emitter))
+            .id()
+            .unwrap();
+        (world, id)
+    }
+
+    fn balls(interval: Duration) -> Emitter {
+        let factory =
+            || Entity::new_circle([0.0, 0.0], 0.02, [1.0; 3]).with_physics(Physics::new());
+        Emitter::new(EmitterSource::Factory(Arc::new(factory)), interval)
+    }
+
+    #[test]
+    fn spawns_follow_sim_time() {
+        // One spawn every 5 steps
+        let (mut world, _) = emitting(balls(DT * 5));
+        let mut emitters = EmitterSystem::new();
+        for step in 1..=50 {
+            emitters.update(&mut world, DT);
+            assert_eq!(world.len() - 1, step / 5, "after {step} steps");
+        }
+    }
+
+    #[test]
+    fn max_alive_caps_spawns_until_one_goes() {
+        let (mut world, emitter) = emitting(balls(DT).with_max_alive(3));
+        let mut emitters = EmitterSystem::new();
+        for _ in 0..20 {
+            emitters.update(&mut world, DT);
+        }
+        assert_eq!(world.len(), 4);
+
+        // Freeing a slot lets the next tick spawn right away, once
+        let first = world.get(emitter).unwrap().emitter().unwrap().children()[0];
+        world.queue_removal(first);
+        world.flush_removals();
+        assert_eq!(world.len(), 3);
+        emitters.update(&mut world, DT);
+        assert_eq!(world.len(), 4);
+        emitters.update(&mut world, DT);
+        assert_eq!(world.len(), 4);
+    }
+
+    #[test]
+    fn same_seed_launches_the_same_way() {
+        let velocities = |seed| {
+            let emitter = balls(DT).with_launch(0.0, 1.0, 0.1, 0.5).with_seed(seed);
+            let (mut world, _) = emitting(emitter);
+            let mut emitters = EmitterSystem::new();
+            for _ in 0..10 {
+                emitters.update(&mut world, DT);
+            }
+            world
+                .entities()
+                .iter()
+                .filter_map(|entity| entity.physics().map(|physics| physics.velocity))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(velocities(7).len(), 10);
+        assert_eq!(velocities(7), velocities(7));
+        assert_ne!(velocities(7), velocities(8));
+    }
+}
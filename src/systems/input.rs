@@ -1,15 +1,38 @@
+use super::bindings::KeyBindings;
+use super::renderer::{DebugDrawFlags, WorldViewport};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use winit::{
     dpi::PhysicalPosition,
-    event::MouseButton,
+    event::{MouseButton, MouseScrollDelta},
     keyboard::{KeyCode, ModifiersState},
 };
 
+/// Trackpad pixels treated as one wheel notch, so a notch and a flick scroll about as far
+const PIXELS_PER_NOTCH: f32 = 50.0;
+
+/// How far back `InputSystem::cursor_velocity` looks
+const CURSOR_HISTORY: Duration = Duration::from_millis(100);
+
 /// InputSystem translates raw input events into application commands/state
 pub struct InputSystem {
     pub modifiers: ModifiersState,
     pub cursor_position: Option<PhysicalPosition<f64>>,
     pub cursor_ndc: Option<[f32; 2]>,
     pub window_size: (u32, u32),
+    // Matches the renderer's letterbox; clicks in the bars have no NDC position
+    pub letterbox_aspect: Option<f32>,
+    // While the left button is held: where it went down, then the last drag
+    // position; and whether it's moved since going down
+    press_origin: Option<[f32; 2]>,
+    dragging: bool,
+    // Scroll amount per wheel notch, and whether to flip its direction
+    pub scroll_sensitivity: f32,
+    pub invert_scroll: bool,
+    // Recent cursor NDC positions, oldest first, trimmed to CURSOR_HISTORY
+    cursor_history: VecDeque<(Instant, [f32; 2])>,
+    // Which key triggers which command; rebindable at runtime
+    pub bindings: KeyBindings,
 }
 
 impl InputSystem {
@@ -19,6 +42,13 @@ impl InputSystem {
             cursor_position: None,
             cursor_ndc: None,
             window_size: (800, 600),
+            letterbox_aspect: None,
+            press_origin: None,
+            dragging: false,
+            scroll_sensitivity: 1.0,
+            invert_scroll: false,
+            cursor_history: VecDeque::new(),
+            bindings: KeyBindings::default(),
         }
     }
 
@@ -31,49 +61,117 @@ impl InputSystem {
 
         // Recalculate NDC if we have a cursor position
         if let Some(pos) = self.cursor_position {
-            self.cursor_ndc = Some(self.physical_to_ndc(pos, width, height));
+            self.cursor_ndc = self.physical_to_ndc(pos, width, height);
         }
     }
 
-    pub fn update_cursor(&mut self, position: PhysicalPosition<f64>) {
-        self.cursor_position = Some(position);
-        self.cursor_ndc =
-            Some(self.physical_to_ndc(position, self.window_size.0, self.window_size.1));
+    /// Keep in step with `Renderer::set_letterbox`
+    pub fn set_letterbox(&mut self, aspect: Option<f32>) {
+        self.letterbox_aspect = aspect;
+        if let Some(pos) = self.cursor_position {
+            let (width, height) = self.window_size;
+            self.cursor_ndc = self.physical_to_ndc(pos, width, height);
+        }
     }
 
-    pub fn handle_key(&self, keycode: KeyCode) -> Option<InputCommand> {
-        match keycode {
-            KeyCode::Escape => Some(InputCommand::Exit),
-            KeyCode::KeyP => Some(InputCommand::TogglePause),
-            KeyCode::Space => Some(InputCommand::TogglePause),
-            KeyCode::KeyC if self.modifiers.control_key() => {
-                println!("CTRL+C pressed");
-                None // Could be InputCommand::Copy
-            }
-            KeyCode::KeyV if self.modifiers.control_key() => {
-                println!("CTRL+V pressed");
-                None // Could be InputCommand::Paste
+    /// The cursor left the window: no position, so nothing is hovered
+    pub fn cursor_left(&mut self) {
+        self.cursor_position = None;
+        self.cursor_ndc = None;
+        self.cursor_history.clear();
+    }
+
+    /// Track the cursor. While the left button is held, the first move gives
+    /// `DragStart` at the press position and later ones `DragMove`.
+    pub fn update_cursor(&mut self, position: PhysicalPosition<f64>) -> Option<InputCommand> {
+        self.update_cursor_at(position, Instant::now())
+    }
+
+    /// `update_cursor` for a move that happened at `time`
+    pub fn update_cursor_at(
+        &mut self,
+        position: PhysicalPosition<f64>,
+        time: Instant,
+    ) -> Option<InputCommand> {
+        self.cursor_position = Some(position);
+        self.cursor_ndc = self.physical_to_ndc(position, self.window_size.0, self.window_size.1);
+        if let Some(ndc) = self.cursor_ndc {
+            self.cursor_history.push_back((time, ndc));
+            while let Some(&(oldest, _)) = self.cursor_history.front()
+                && time.saturating_duration_since(oldest) > CURSOR_HISTORY
+            {
+                self.cursor_history.pop_front();
             }
-            _ => None,
         }
+
+        let origin = self.press_origin?;
+        if !self.dragging {
+            self.dragging = true;
+            return Some(InputCommand::DragStart { position: origin });
+        }
+        // Moves over the letterbox bars are skipped; the drag picks up again past them
+        let position = self.cursor_ndc?;
+        self.press_origin = Some(position);
+        Some(InputCommand::DragMove { position })
     }
 
-    pub fn handle_mouse_button(&self, button: MouseButton, pressed: bool) -> Option<InputCommand> {
+    /// Command bound to `keycode` with the currently held modifiers
+    pub fn handle_key(&self, keycode: KeyCode) -> Option<InputCommand> {
+        self.bindings.get(keycode, self.modifiers)
+    }
+
+    /// Presses give the click commands. A plain left press also starts tracking
+    /// a drag, and its release gives `DragEnd` if the cursor moved meanwhile. An
+    /// alt-press duplicates and drags the copy, so its release always gives `DragEnd`.
+    /// Shift-click extends the selection, so deleting is on the middle button;
+    /// ctrl-click is kept free since macOS turns it into a right click.
+    pub fn handle_mouse_button(
+        &mut self,
+        button: MouseButton,
+        pressed: bool,
+    ) -> Option<InputCommand> {
         if !pressed {
-            return None;
+            if button != MouseButton::Left {
+                return None;
+            }
+            let last = self.press_origin.take()?;
+            let dragged = std::mem::take(&mut self.dragging);
+            return dragged.then(|| InputCommand::DragEnd {
+                position: self.cursor_ndc.unwrap_or(last),
+                velocity: self.cursor_velocity(),
+            });
         }
 
         match button {
             MouseButton::Left => {
                 if let Some(ndc) = self.cursor_ndc {
-                    Some(InputCommand::Click { position: ndc })
+                    if self.modifiers.alt_key() {
+                        // The copy is grabbed right away, so even a release without
+                        // moving ends the drag
+                        self.press_origin = Some(ndc);
+                        self.dragging = true;
+                        Some(InputCommand::Duplicate { position: ndc })
+                    } else {
+                        self.press_origin = Some(ndc);
+                        self.dragging = false;
+                        Some(InputCommand::Click {
+                            position: ndc,
+                            extend: self.modifiers.shift_key(),
+                        })
+                    }
                 } else {
                     None
                 }
             }
+            MouseButton::Middle => self
+                .cursor_ndc
+                .map(|ndc| InputCommand::Delete { position: ndc }),
             MouseButton::Right => {
                 if let Some(ndc) = self.cursor_ndc {
-                    Some(InputCommand::RightClick { position: ndc })
+                    Some(InputCommand::RightClick {
+                        position: ndc,
+                        static_body: self.modifiers.shift_key(),
+                    })
                 } else {
                     None
                 }
@@ -82,24 +180,66 @@ impl InputSystem {
         }
     }
 
-    /// Convert physical pixel position to NDC coordinates
+    /// Cursor velocity in NDC/sec over the last 100 ms, e.g. to throw a dragged
+    /// entity; see `cursor_velocity_at`
+    pub fn cursor_velocity(&self) -> [f32; 2] {
+        self.cursor_velocity_at(Instant::now())
+    }
+
+    /// Cursor velocity as of `now`: distance from the oldest position in the last
+    /// 100 ms to where the cursor is, over that time. A cursor that has stopped
+    /// counts as sitting still up to `now`, so the velocity dies down instead of
+    /// keeping the speed of the gesture before it.
+    pub fn cursor_velocity_at(&self, now: Instant) -> [f32; 2] {
+        let Some(&(_, newest)) = self.cursor_history.back() else {
+            return [0.0, 0.0];
+        };
+        let Some(&(start, oldest)) = self
+            .cursor_history
+            .iter()
+            .find(|(time, _)| now.saturating_duration_since(*time) <= CURSOR_HISTORY)
+        else {
+            return [0.0, 0.0];
+        };
+        let elapsed = now.saturating_duration_since(start).as_secs_f32();
+        if elapsed <= 0.0 {
+            return [0.0, 0.0];
+        }
+        [
+            (newest[0] - oldest[0]) / elapsed,
+            (newest[1] - oldest[1]) / elapsed,
+        ]
+    }
+
+    /// `Scroll` at the cursor, in wheel notches (positive away from the user) times
+    /// `scroll_sensitivity`. Trackpad pixel deltas are scaled to match. None when
+    /// the cursor isn't over the world or nothing vertical scrolled.
+    pub fn handle_scroll(&self, delta: MouseScrollDelta) -> Option<InputCommand> {
+        let position = self.cursor_ndc?;
+        let notches = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(pixels) => pixels.y as f32 / PIXELS_PER_NOTCH,
+        };
+        let delta = scroll_amount(notches, self.scroll_sensitivity, self.invert_scroll);
+        (delta != 0.0).then_some(InputCommand::Scroll { delta, position })
+    }
+
+    /// Convert physical pixel position to NDC coordinates. None for positions in
+    /// the letterbox bars, or when the window has no area.
     pub fn physical_to_ndc(
         &self,
         position: PhysicalPosition<f64>,
         width: u32,
         height: u32,
-    ) -> [f32; 2] {
-        let width = width as f32;
-        let height = height as f32;
-
-        if width <= 0.0 || height <= 0.0 {
-            return [0.0, 0.0];
-        }
-
-        let x = (position.x as f32 / width) * 2.0 - 1.0;
-        let y = 1.0 - (position.y as f32 / height) * 2.0;
+    ) -> Option<[f32; 2]> {
+        WorldViewport::for_window(width, height, self.letterbox_aspect)
+            .pixel_to_ndc(position.x as f32, position.y as f32)
+    }
+}
 
-        [x, y]
+impl Default for InputSystem {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -108,6 +248,416 @@ impl InputSystem {
 pub enum InputCommand {
     Exit,
     TogglePause,
-    Click { position: [f32; 2] },
-    RightClick { position: [f32; 2] },
+    ToggleFreeze, // World::freeze_all / unfreeze_all; rendering and time keep going
+    ToggleStats,
+    ToggleDebugDraw(DebugDrawFlags),
+    CyclePresentMode,
+    ToggleTrails,
+    CycleColorMode,
+    ToggleGrid,
+    ToggleLabels,
+    ToggleFullscreen,
+    ToggleRecording,
+    StepBack,    // Rewind to the previous automatic snapshot
+    ToggleTitle, // Push the title scene over the simulation, or pop back to it
+    // Offered to the clickables under `position` through `ClickDispatch`; if none
+    // consumes it, select what's there (shift-click, `extend`, adds to the
+    // selection instead of replacing it)
+    Click {
+        position: [f32; 2],
+        extend: bool,
+    },
+    // Spawn a ball at `position` with `World::queue_ball_at`; shift-right-click
+    // (`static_body`) spawns a static one
+    RightClick {
+        position: [f32; 2],
+        static_body: bool,
+    },
+    // Middle-click: remove the entity under `position`
+    Delete {
+        position: [f32; 2],
+    },
+    DeleteSelected, // Delete key: remove every selected entity
+    // Alt-press: copy the entity under `position` in place (`World::duplicate`) and
+    // grab the copy with `World::start_drag`; `DragMove` and `DragEnd` follow
+    Duplicate {
+        position: [f32; 2],
+    },
+    // Left button held and moved: grab what's under the press `position` with
+    // `World::start_drag`, follow the cursor with `World::drag_to`, then `World::fling`
+    DragStart {
+        position: [f32; 2],
+    },
+    DragMove {
+        position: [f32; 2],
+    },
+    // `velocity` is the cursor's at release (NDC/sec), for `World::fling`
+    DragEnd {
+        position: [f32; 2],
+        velocity: [f32; 2],
+    },
+    // Wheel or trackpad scroll anchored at the cursor `position`, so a camera could
+    // zoom toward it; the demo has none and resizes the balls it spawns instead.
+    // See `InputSystem::handle_scroll`.
+    Scroll {
+        delta: f32,
+        position: [f32; 2],
+    },
+}
+
+/// Scroll amount for `notches` of wheel movement
+pub fn scroll_amount(notches: f32, sensitivity: f32, invert: bool) -> f32 {
+    let amount = notches * sensitivity;
+    if invert { -amount } else { amount }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Input with the cursor over the middle of an 800x600 window
+    fn centered() -> InputSystem {
+        let mut input = InputSystem::new();
+        input.update_cursor(PhysicalPosition::new(400.0, 300.0));
+        input
+    }
+
+    fn press(
+        input: &mut InputSystem,
+        button: MouseButton,
+        modifiers: ModifiersState,
+    ) -> Option<InputCommand> {
+        input.update_modifiers(modifiers);
+        let command = input.handle_mouse_button(button, true);
+        input.handle_mouse_button(button, false);
+        command
+    }
+
+    #[test]
+    fn shift_click_extends_the_selection() {
+        let mut input = centered();
+        assert!(matches!(
+            press(&mut input, MouseButton::Left, ModifiersState::empty()),
+            Some(InputCommand::Click { extend: false, .. })
+        ));
+        assert!(matches!(
+            press(&mut input, MouseButton::Left, ModifiersState::SHIFT),
+            Some(InputCommand::Click { extend: true, .. })
+        ));
+    }
+
+    #[test]
+    fn middle_click_deletes_and_ctrl_click_is_a_plain_click() {
+        let mut input = centered();
+        assert!(matches!(
+            press(&mut input, MouseButton::Middle, ModifiersState::empty()),
+            Some(InputCommand::Delete {
+                position: [0.0, 0.0]
+            })
+        ));
+        // macOS reports ctrl-click as a right click; elsewhere it mustn't delete
+        assert!(matches!(
+            press(&mut input, MouseButton::Left, ModifiersState::CONTROL),
+            Some(InputCommand::Click { extend: false, .. })
+        ));
+        assert!(matches!(
+            press(&mut input, MouseButton::Left, ModifiersState::ALT),
+            Some(InputCommand::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn cursor_leaving_the_window_has_no_position_to_hover() {
+        let mut input = centered();
+        assert_eq!(input.cursor_ndc, Some([0.0, 0.0]));
+        input.cursor_left();
+        assert_eq!(input.cursor_ndc, None);
+        input.update_cursor(PhysicalPosition::new(800.0, 0.0));
+        assert_eq!(input.cursor_ndc, Some([1.0, 1.0]));
+    }
+
+    #[test]
+    fn press_move_move_release_gives_a_drag_sequence() {
+        let mut input = centered();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut commands = Vec::new();
+        commands.extend(input.handle_mouse_button(MouseButton::Left, true));
+        commands.extend(input.update_cursor_at(PhysicalPosition::new(480.0, 300.0), at(10)));
+        commands.extend(input.update_cursor_at(PhysicalPosition::new(600.0, 150.0), at(20)));
+        commands.extend(input.handle_mouse_button(MouseButton::Left, false));
+
+        assert!(matches!(
+            commands[..],
+            [
+                InputCommand::Click {
+                    position: [0.0, 0.0],
+                    extend: false
+                },
+                // Grabs what was under the press, not under the first move
+                InputCommand::DragStart {
+                    position: [0.0, 0.0]
+                },
+                InputCommand::DragMove {
+                    position: [0.5, 0.5]
+                },
+                InputCommand::DragEnd {
+                    position: [0.5, 0.5],
+                    ..
+                },
+            ]
+        ));
+
+        // The button is up, so moving no longer drags
+        assert!(
+            input
+                .update_cursor_at(PhysicalPosition::new(400.0, 300.0), at(30))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn press_and_release_without_moving_is_just_a_click() {
+        let mut input = centered();
+        assert!(matches!(
+            input.handle_mouse_button(MouseButton::Left, true),
+            Some(InputCommand::Click { .. })
+        ));
+        assert!(
+            input
+                .handle_mouse_button(MouseButton::Left, false)
+                .is_none()
+        );
+        // Other buttons never drag
+        input.handle_mouse_button(MouseButton::Right, true);
+        assert!(
+            input
+                .update_cursor(PhysicalPosition::new(500.0, 300.0))
+                .is_none()
+        );
+        assert!(
+            input
+                .handle_mouse_button(MouseButton::Right, false)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn right_click_spawns_and_shift_right_click_spawns_static() {
+        let mut input = centered();
+        input.update_cursor(PhysicalPosition::new(600.0, 150.0));
+        assert!(matches!(
+            press(&mut input, MouseButton::Right, ModifiersState::empty()),
+            Some(InputCommand::RightClick {
+                position: [0.5, 0.5],
+                static_body: false
+            })
+        ));
+        assert!(matches!(
+            press(&mut input, MouseButton::Right, ModifiersState::SHIFT),
+            Some(InputCommand::RightClick {
+                position: [0.5, 0.5],
+                static_body: true
+            })
+        ));
+        // Over the letterbox bars there's nowhere to spawn
+        input.set_letterbox(Some(1.0));
+        input.update_cursor(PhysicalPosition::new(10.0, 300.0));
+        assert!(press(&mut input, MouseButton::Right, ModifiersState::empty()).is_none());
+    }
+
+    fn scroll(input: &InputSystem, delta: MouseScrollDelta) -> Option<f32> {
+        match input.handle_scroll(delta)? {
+            InputCommand::Scroll { delta, position } => {
+                assert_eq!(position, input.cursor_ndc.unwrap());
+                Some(delta)
+            }
+            command => panic!("scrolling gave {command:?}"),
+        }
+    }
+
+    #[test]
+    fn wheel_notches_and_trackpad_pixels_scroll_alike() {
+        let mut input = centered();
+        assert_eq!(
+            scroll(&input, MouseScrollDelta::LineDelta(0.0, 1.0)),
+            Some(1.0)
+        );
+        assert_eq!(
+            scroll(&input, MouseScrollDelta::LineDelta(0.0, -2.0)),
+            Some(-2.0)
+        );
+        let flick = MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 50.0));
+        assert_eq!(scroll(&input, flick), Some(1.0));
+        let nudge = MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, -25.0));
+        assert_eq!(scroll(&input, nudge), Some(-0.5));
+
+        // Horizontal-only scrolling, or a cursor outside the window, gives nothing
+        assert_eq!(scroll(&input, MouseScrollDelta::LineDelta(3.0, 0.0)), None);
+        input.cursor_left();
+        assert_eq!(scroll(&input, MouseScrollDelta::LineDelta(0.0, 1.0)), None);
+    }
+
+    #[test]
+    fn scroll_sensitivity_scales_and_inversion_flips() {
+        let mut input = centered();
+        input.scroll_sensitivity = 0.25;
+        assert_eq!(
+            scroll(&input, MouseScrollDelta::LineDelta(0.0, 2.0)),
+            Some(0.5)
+        );
+        input.invert_scroll = true;
+        assert_eq!(
+            scroll(&input, MouseScrollDelta::LineDelta(0.0, 2.0)),
+            Some(-0.5)
+        );
+        let flick = MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 100.0));
+        assert_eq!(scroll(&input, flick), Some(-0.5));
+
+        assert_eq!(scroll_amount(3.0, 2.0, false), 6.0);
+        assert_eq!(scroll_amount(3.0, 2.0, true), -6.0);
+        assert_eq!(scroll_amount(-1.0, 0.5, true), 0.5);
+    }
+
+    /// Feeds the cursor one pixel position every 10ms from `start`, returning
+    /// the time of the last one
+    fn trace(input: &mut InputSystem, start: Instant, pixels: &[(f64, f64)]) -> Instant {
+        let mut time = start;
+        for (n, &(x, y)) in pixels.iter().enumerate() {
+            time = start + Duration::from_millis(10 * n as u64);
+            input.update_cursor_at(PhysicalPosition::new(x, y), time);
+        }
+        time
+    }
+
+    fn assert_near(velocity: [f32; 2], expected: [f32; 2], tolerance: f32) {
+        assert!(
+            (velocity[0] - expected[0]).abs() <= tolerance
+                && (velocity[1] - expected[1]).abs() <= tolerance,
+            "velocity {velocity:?}, expected about {expected:?}"
+        );
+    }
+
+    #[test]
+    fn moving_cursor_velocity_is_its_speed_over_the_last_100ms() {
+        let mut input = InputSystem::new();
+        // 4px per 10ms across an 800px window is 1 NDC/sec, and 3px down of 600 is -1
+        let pixels: Vec<_> = (0..20)
+            .map(|n| (200.0 + 4.0 * n as f64, 100.0 + 3.0 * n as f64))
+            .collect();
+        let last = trace(&mut input, Instant::now(), &pixels);
+        assert_near(input.cursor_velocity_at(last), [1.0, -1.0], 1e-3);
+    }
+
+    #[test]
+    fn stopped_cursor_velocity_dies_down_instead_of_going_stale() {
+        let mut input = InputSystem::new();
+        // A fast flick, then nothing
+        let pixels: Vec<_> = (0..10).map(|n| (200.0 + 40.0 * n as f64, 300.0)).collect();
+        let last = trace(&mut input, Instant::now(), &pixels);
+        assert_near(input.cursor_velocity_at(last), [10.0, 0.0], 1e-2);
+
+        // Halfway through the window it reads as half the speed, then zero
+        let half = input.cursor_velocity_at(last + Duration::from_millis(50));
+        assert!(half[0] < 8.0 && half[0] > 0.0, "{half:?}");
+        assert_eq!(
+            input.cursor_velocity_at(last + Duration::from_millis(150)),
+            [0.0, 0.0]
+        );
+        // Leaving the window forgets the history too
+        input.cursor_left();
+        assert_eq!(input.cursor_velocity_at(last), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn jittery_cursor_velocity_stays_near_zero() {
+        let mut input = InputSystem::new();
+        let pixels: Vec<_> = (0..30)
+            .map(|n| {
+                let wobble = if n % 2 == 0 { 2.0 } else { -2.0 };
+                (400.0 + wobble, 300.0 - wobble)
+            })
+            .collect();
+        let last = trace(&mut input, Instant::now(), &pixels);
+        // Each sample is 4px off the last, or 1 NDC/sec if read step by step
+        assert_near(input.cursor_velocity_at(last), [0.0, 0.0], 0.15);
+    }
+
+    #[test]
+    fn letterboxed_pixels_map_to_the_world_and_bars_map_to_nothing() {
+        let mut input = InputSystem::new();
+        input.set_letterbox(Some(1.0));
+        let at = |input: &InputSystem, x, y, width, height| {
+            input.physical_to_ndc(PhysicalPosition::new(x, y), width, height)
+        };
+
+        // 800x600: a 600x600 world with 100 pixel bars left and right
+        assert_eq!(at(&input, 400.0, 300.0, 800, 600), Some([0.0, 0.0]));
+        assert_eq!(at(&input, 100.0, 0.0, 800, 600), Some([-1.0, 1.0]));
+        assert_eq!(at(&input, 700.0, 600.0, 800, 600), Some([1.0, -1.0]));
+        assert_eq!(at(&input, 250.0, 450.0, 800, 600), Some([-0.5, -0.5]));
+        assert_eq!(at(&input, 50.0, 300.0, 800, 600), None);
+        assert_eq!(at(&input, 750.0, 300.0, 800, 600), None);
+
+        // 600x800: bars above and below instead
+        assert_eq!(at(&input, 300.0, 100.0, 600, 800), Some([0.0, 1.0]));
+        assert_eq!(at(&input, 300.0, 50.0, 600, 800), None);
+        assert_eq!(at(&input, 300.0, 750.0, 600, 800), None);
+
+        // Pixel -> NDC -> pixel lands where it started
+        let viewport = WorldViewport::for_window(800, 600, Some(1.0));
+        let ndc = at(&input, 523.0, 87.0, 800, 600).unwrap();
+        let (x, y) = viewport.ndc_to_pixel(ndc);
+        assert!((x - 523.0).abs() < 1e-3 && (y - 87.0).abs() < 1e-3);
+
+        // Without a letterbox the whole window is the world
+        input.set_letterbox(None);
+        assert_eq!(at(&input, 0.0, 0.0, 800, 600), Some([-1.0, 1.0]));
+    }
+
+    #[test]
+    fn alt_press_move_release_duplicates_and_drags_the_copy() {
+        let mut input = centered();
+        input.update_modifiers(ModifiersState::ALT);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut commands = Vec::new();
+        commands.extend(input.handle_mouse_button(MouseButton::Left, true));
+        commands.extend(input.update_cursor_at(PhysicalPosition::new(500.0, 300.0), at(10)));
+        commands.extend(input.update_cursor_at(PhysicalPosition::new(600.0, 150.0), at(20)));
+        commands.extend(input.handle_mouse_button(MouseButton::Left, false));
+
+        assert!(matches!(
+            commands[..],
+            [
+                // Grabs the copy, so there's no separate DragStart
+                InputCommand::Duplicate {
+                    position: [0.0, 0.0]
+                },
+                InputCommand::DragMove {
+                    position: [0.25, 0.0]
+                },
+                InputCommand::DragMove {
+                    position: [0.5, 0.5]
+                },
+                InputCommand::DragEnd {
+                    position: [0.5, 0.5],
+                    ..
+                },
+            ]
+        ));
+
+        // Without moving, the release still lets go of the copy
+        assert!(matches!(
+            input.handle_mouse_button(MouseButton::Left, true),
+            Some(InputCommand::Duplicate { .. })
+        ));
+        assert!(matches!(
+            input.handle_mouse_button(MouseButton::Left, false),
+            Some(InputCommand::DragEnd {
+                position: [0.5, 0.5],
+                ..
+            })
+        ));
+    }
 }
@@ -0,0 +1,135 @@
+use crate::world::{EntityId, World};
+use std::time::Duration;
+
+/// Events the lifetime system sends through `World::events`
+#[derive(Clone, Copy, Debug)]
+pub enum LifetimeEvent {
+    /// Entity's lifetime ran out and it was removed. `id` no longer resolves.
+    Expired {
+        id: EntityId,
+        last_position: [f32; 2],
+    },
+}
+
+/// Counts down `Lifetime` components and despawns entities whose time is up.
+/// Runs on sim steps, so paused time never shortens a lifetime.
+pub struct LifetimeSystem {
+    // Scratch list of expired entities, kept to avoid reallocating every step
+    expired: Vec<(usize, [f32; 2])>,
+}
+
+impl LifetimeSystem {
+    pub fn new() -> Self {
+        Self {
+            expired: Vec::new(),
+        }
+    }
+
+    /// Call once per fixed sim step with the sim dt
+    pub fn update(&mut self, world: &mut World, dt: Duration) {
+        self.expired.clear();
+        for (index, entity) in world.entities_mut().iter_mut().enumerate() {
+            let position = entity.transform().position;
+            // Checked first, since handing out the lifetime marks the entity changed
+            if entity.lifetime().is_some()
+                && let Some(lifetime) = entity.lifetime_mut()
+            {
+                lifetime.remaining = lifetime.remaining.saturating_sub(dt);
+                if lifetime.remaining.is_zero() {
+                    self.expired.push((index, position));
+                }
+            }
+        }
+
+        for &(index, last_position) in &self.expired {
+            if let Some(id) = world.id_at(index) {
+                world.queue_removal(id);
+                world
+                    .events()
+                    .send(LifetimeEvent::Expired { id, last_position });
+            }
+        }
+        world.flush_removals();
+    }
+}
+
+impl Default for LifetimeSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+    use crate::systems::TimeSystem;
+    use std::time::Instant;
+
+    #[test]
+    fn one_second_lifetime_lasts_exactly_125_default_steps() {
+        let mut world = World::new();
+        let doomed = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.0], 0.05, [1.0; 3])
+                    .with_lifetime(Duration::from_secs(1)),
+            )
+            .id()
+            .unwrap();
+        let dt = TimeSystem::new().sim_dt();
+        let mut lifetimes = LifetimeSystem::new();
+
+        for _ in 0..124 {
+            lifetimes.update(&mut world, dt);
+        }
+        assert!(world.contains(doomed));
+        lifetimes.update(&mut world, dt);
+        assert!(!world.contains(doomed));
+        world.events().update();
+        let expired = world.events().read::<LifetimeEvent>();
+        assert!(matches!(expired, [LifetimeEvent::Expired { id, .. }] if *id == doomed));
+    }
+
+    #[test]
+    fn paused_time_does_not_shorten_a_lifetime() {
+        let mut world = World::new();
+        let entity = Entity::new_circle([0.0, 0.0], 0.05, [1.0; 3]);
+        let doomed = world
+            .add_entity(entity.with_lifetime(Duration::from_secs(1)))
+            .id()
+            .unwrap();
+        let mut time = TimeSystem::new();
+        let dt = time.sim_dt();
+        let mut lifetimes = LifetimeSystem::new();
+        // Sim steps run over `frames` frames one sim step apart
+        let mut run_frames = |time: &mut TimeSystem, world: &mut World, frames| {
+            let start = Instant::now();
+            let mut steps_run = 0;
+            for frame in 1..=frames {
+                let (steps, ..) = time.tick(start + dt * frame);
+                for _ in 0..steps {
+                    lifetimes.update(world, dt);
+                }
+                steps_run += steps;
+            }
+            steps_run
+        };
+        let remaining = |world: &World| world.get(doomed).unwrap().lifetime().unwrap().remaining;
+
+        let before_pause = run_frames(&mut time, &mut world, 60);
+        assert!(before_pause > 0);
+        assert_eq!(
+            remaining(&world),
+            Duration::from_secs(1) - dt * before_pause
+        );
+
+        // Ten seconds of frames while paused take nothing off
+        time.toggle_pause();
+        assert_eq!(run_frames(&mut time, &mut world, 1250), 0);
+        time.toggle_pause();
+        assert_eq!(
+            remaining(&world),
+            Duration::from_secs(1) - dt * before_pause
+        );
+    }
+}
@@ -1,9 +1,31 @@
+mod animation;
+mod behavior;
+mod bindings;
+mod emitter;
 mod input;
+mod lifetime;
 mod physics;
 mod renderer;
+mod rules;
 mod timing;
 
-pub use input::{InputCommand, InputSystem};
-pub use physics::PhysicsSystem;
-pub use renderer::Renderer;
+pub use animation::ColorAnimationSystem;
+pub use behavior::BehaviorSystem;
+#[cfg(feature = "serde")]
+pub use bindings::BindingsFileError;
+pub use bindings::{BindingError, KeyBindings};
+pub use emitter::EmitterSystem;
+pub use input::{InputCommand, InputSystem, scroll_amount};
+pub use lifetime::{LifetimeEvent, LifetimeSystem};
+pub use physics::{
+    BoundaryBehavior, Contact, PhysicsDiagnostics, PhysicsEvent, PhysicsSystem, WorldBounds,
+};
+pub use renderer::{
+    Background, ColorMode, DEFAULT_TRAIL_LENGTH, DebugDrawFlags, DrawOrder, FlashConfig,
+    FrameOutcome, FullscreenState, GridConfig, ImpactConfig, InitError, Letterbox, RecordingStats,
+    RenderError, Renderer, RendererConfig, RendererOptions, ShadowConfig, ShadowShape, SkipReason,
+    StatsCorner, StatsStyle, WorldViewport, flash_intensity, impact_squash, shadow_shape,
+    speed_color, timestamp_delta_ms,
+};
+pub use rules::RuleSystem;
 pub use timing::TimeSystem;
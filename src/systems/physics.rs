@@ -1,15 +1,245 @@
-use crate::{components::Shape, world::World};
+use crate::{
+    components::{Aabb, Shape, Transform},
+    entity::Entity,
+    world::{EntityId, World, WorldId},
+};
+use std::collections::HashSet;
 use std::time::Duration;
 
+/// What happens to dynamic entities that leave the world bounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryBehavior {
+    /// Entities keep simulating wherever they end up
+    None,
+    /// Entities whose AABB fully exits the bounds are removed
+    Despawn,
+}
+
+/// Axis-aligned box (NDC) that entities are expected to stay inside
+#[derive(Clone, Copy, Debug)]
+pub struct WorldBounds {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub behavior: BoundaryBehavior,
+}
+
+impl WorldBounds {
+    pub fn new(min: [f32; 2], max: [f32; 2], behavior: BoundaryBehavior) -> Self {
+        Self { min, max, behavior }
+    }
+
+    /// Check if a bounding box lies completely outside the bounds
+    pub fn fully_outside(&self, aabb: &Aabb) -> bool {
+        let world = Aabb {
+            min: self.min,
+            max: self.max,
+        };
+        !world.overlaps(aabb)
+    }
+}
+
+/// Events the physics system sends through `World::events`
+#[derive(Clone, Copy, Debug)]
+pub enum PhysicsEvent {
+    /// Entity left the world bounds and was removed. `id` no longer resolves.
+    EntityDespawned {
+        id: EntityId,
+        last_position: [f32; 2],
+    },
+    /// Two entities bounced off each other. `impulse` is the normal impulse the
+    /// solver applied on first contact; `normal` points from A toward B.
+    Impact {
+        a: EntityId,
+        b: EntityId,
+        normal: [f32; 2],
+        impulse: f32,
+    },
+    /// `other` started overlapping the sensor `sensor` (`Physics::sensor`). Sent
+    /// once per overlap, on the first update the two touch.
+    SensorEntered { sensor: EntityId, other: EntityId },
+}
+
+/// A contact found during the last physics update
+#[derive(Clone, Copy, Debug)]
+pub struct Contact {
+    pub a: EntityId,
+    pub b: EntityId,
+    pub point: [f32; 2],  // NDC, roughly the middle of the overlap
+    pub normal: [f32; 2], // Points from A toward B
+    pub depth: f32,
+}
+
+/// Summary of the last physics update, for overlays and debugging
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhysicsDiagnostics {
+    pub contacts: u32,       // Contacts found at the start of collision resolution
+    pub sleeping: u32,       // Dynamic bodies put to rest by the sleep threshold
+    pub kinetic_energy: f32, // Sum of 0.5 * m * v² over dynamic bodies
+}
+
+// Whether two entities collide; see `PhysicsSystem::with_collision_filter`
+type CollisionFilter = Box<dyn Fn(&Entity, &Entity) -> bool>;
+
 pub struct PhysicsSystem {
     gravity: [f32; 2],
     collision_iterations: u32,
     // Velocity threshold for considering an object "at rest"
     sleep_velocity_threshold: f32,
-    // How much energy is lost per second when sliding (contact friction)
-    contact_friction_coefficient: f32,
     // Air resistance (always applied)
     air_damping: f32,
+    bounds: WorldBounds,
+    diagnostics: PhysicsDiagnostics,
+    // Contacts found on the first solver iteration of the last update
+    contacts: Vec<Contact>,
+    // Scratch list of entities leaving the bounds, kept to avoid reallocating
+    exited: Vec<(EntityId, [f32; 2])>,
+    // Pairs for which this returns false pass through each other
+    collision_filter: Option<CollisionFilter>,
+    // Hot per-body data for the current update, kept to avoid reallocating
+    bodies: Bodies,
+    // (sensor, other) pairs overlapping as of the last update, and this update's in entity order
+    sensor_overlaps: HashSet<(EntityId, EntityId)>,
+    new_sensor_overlaps: Vec<(EntityId, EntityId)>,
+    // World the contacts and diagnostics came from; another one resets them
+    world: Option<WorldId>,
+}
+
+const HAS_PHYSICS: u8 = 1 << 0;
+const DYNAMIC: u8 = 1 << 1;
+const SENSOR: u8 = 1 << 2;
+
+/// Structure-of-arrays copy of the data the integration and solver loops touch,
+/// indexed like `World::entities`. Rebuilt each update and written back at the end,
+/// so the hot loops walk flat arrays instead of whole entities.
+#[derive(Default)]
+struct Bodies {
+    positions: Vec<[f32; 2]>,
+    velocities: Vec<[f32; 2]>,
+    accelerations: Vec<[f32; 2]>, // Including gravity
+    drag: Vec<f32>,               // Coefficient * cross-section / mass, 0.0 for none
+    inv_mass: Vec<f32>,           // 0.0 for static and infinite-mass bodies
+    restitution: Vec<f32>,
+    friction: Vec<f32>,
+    flags: Vec<u8>, // HAS_PHYSICS | DYNAMIC | SENSOR
+    // Gravity zones this update, in the order they win: smallest first, then newest
+    zones: Vec<(Aabb, [f32; 2])>,
+}
+
+impl Bodies {
+    fn gather(&mut self, world: &World, gravity: [f32; 2]) {
+        self.positions.clear();
+        self.velocities.clear();
+        self.accelerations.clear();
+        self.drag.clear();
+        self.inv_mass.clear();
+        self.restitution.clear();
+        self.friction.clear();
+        self.flags.clear();
+
+        self.zones.clear();
+        for entity in world.entities().iter().rev() {
+            if let Some(zone) = entity.gravity_zone() {
+                self.zones
+                    .push((zone.bounds(entity.transform().position), zone.gravity));
+            }
+        }
+        self.zones.sort_by(|a, b| a.0.area().total_cmp(&b.0.area()));
+
+        for entity in world.entities() {
+            let transform = entity.transform();
+            self.positions.push(transform.position);
+
+            let Some(physics) = entity.physics() else {
+                // Immovable obstacle, with the solver's defaults
+                self.velocities.push([0.0, 0.0]);
+                self.accelerations.push([0.0, 0.0]);
+                self.drag.push(0.0);
+                self.inv_mass.push(0.0);
+                self.restitution.push(0.5);
+                self.friction.push(0.3);
+                self.flags.push(0);
+                continue;
+            };
+
+            let mut acceleration = physics.acceleration;
+            if physics.apply_gravity {
+                let gravity = self
+                    .zones
+                    .iter()
+                    .find(|(bounds, _)| bounds.contains_point(transform.position))
+                    .map_or(gravity, |&(_, gravity)| gravity);
+                acceleration[0] += gravity[0];
+                acceleration[1] += gravity[1];
+            }
+
+            // Cross-section facing the flow, taken from the velocity at the start of the step
+            let speed = (physics.velocity[0] * physics.velocity[0]
+                + physics.velocity[1] * physics.velocity[1])
+                .sqrt();
+            let drag = if physics.drag_coefficient > 0.0 && speed > 0.0 && physics.mass.is_finite()
+            {
+                let direction = [physics.velocity[0] / speed, physics.velocity[1] / speed];
+                let width = entity.shape().cross_section(transform.rotation, direction);
+                physics.drag_coefficient * width / physics.mass
+            } else {
+                0.0
+            };
+
+            self.velocities.push(physics.velocity);
+            self.accelerations.push(acceleration);
+            self.drag.push(drag);
+            let dynamic = physics.dynamic && !physics.frozen;
+            self.inv_mass.push(if dynamic && physics.mass.is_finite() {
+                1.0 / physics.mass
+            } else {
+                0.0
+            });
+            self.restitution.push(physics.restitution);
+            self.friction.push(physics.friction);
+            let mut flags = HAS_PHYSICS;
+            if dynamic {
+                flags |= DYNAMIC;
+            }
+            if physics.sensor {
+                flags |= SENSOR;
+            }
+            self.flags.push(flags);
+        }
+    }
+
+    /// Copy positions and velocities of dynamic bodies back and clear their acceleration
+    fn write_back(&self, world: &mut World) {
+        for (idx, entity) in world.entities_mut().iter_mut().enumerate() {
+            if self.flags[idx] & DYNAMIC == 0 {
+                continue;
+            }
+            // Bodies at rest stay out of the change tracking
+            let unchanged = entity.transform().position == self.positions[idx]
+                && entity.physics().is_some_and(|physics| {
+                    physics.velocity == self.velocities[idx] && physics.acceleration == [0.0, 0.0]
+                });
+            if unchanged {
+                continue;
+            }
+            if let Some((physics, transform)) = entity.physics_and_transform_mut() {
+                transform.position = self.positions[idx];
+                physics.velocity = self.velocities[idx];
+                physics.acceleration = [0.0, 0.0];
+            }
+        }
+    }
+
+    fn is_dynamic(&self, idx: usize) -> bool {
+        self.flags[idx] & DYNAMIC != 0
+    }
+
+    fn has_physics(&self, idx: usize) -> bool {
+        self.flags[idx] & HAS_PHYSICS != 0
+    }
+
+    fn is_sensor(&self, idx: usize) -> bool {
+        self.flags[idx] & SENSOR != 0
+    }
 }
 
 impl PhysicsSystem {
@@ -18,101 +248,277 @@ impl PhysicsSystem {
             gravity: [0.0, -0.5],
             collision_iterations: 4,
             sleep_velocity_threshold: 0.001,
-            contact_friction_coefficient: 2.0, // NDC units/sec² of deceleration
-            air_damping: 0.98,                 // Per-frame multiplier (1.0 = no damping)
+            air_damping: 0.98, // Per-frame multiplier (1.0 = no damping)
+            bounds: WorldBounds::new([-1.0, -1.0], [1.0, 1.0], BoundaryBehavior::None),
+            diagnostics: PhysicsDiagnostics::default(),
+            contacts: Vec::new(),
+            exited: Vec::new(),
+            collision_filter: None,
+            bodies: Bodies::default(),
+            sensor_overlaps: HashSet::new(),
+            new_sensor_overlaps: Vec::new(),
+            world: None,
         }
     }
 
+    pub fn with_bounds(mut self, bounds: WorldBounds) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Only let pairs for which `filter` returns true collide, e.g. by tag:
+    /// `move |a, b| !(a.has_tag(enemy) && b.has_tag(enemy))`
+    pub fn with_collision_filter(
+        mut self,
+        filter: impl Fn(&Entity, &Entity) -> bool + 'static,
+    ) -> Self {
+        self.collision_filter = Some(Box::new(filter));
+        self
+    }
+
+    pub fn bounds(&self) -> &WorldBounds {
+        &self.bounds
+    }
+
+    pub fn diagnostics(&self) -> &PhysicsDiagnostics {
+        &self.diagnostics
+    }
+
+    /// Contacts from the last update, as seen before the solver moved anything
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    /// Forget the last update's contacts and diagnostics. Happens on its own when
+    /// `update` is handed a different world (`World::id`), e.g. after a scene switch
+    /// or `World::restore`.
+    pub fn reset(&mut self) {
+        self.contacts.clear();
+        self.sensor_overlaps.clear();
+        self.diagnostics = PhysicsDiagnostics::default();
+    }
+
     pub fn update(&mut self, world: &mut World, dt: Duration) {
         let dt_secs = dt.as_secs_f32();
+        if self.world != Some(world.id()) {
+            self.reset();
+            self.world = Some(world.id());
+        }
+        world.advance_sim_clock(dt);
 
-        // === PHASE 1: Apply forces and integrate velocity ===
+        // Remember where everything was so the renderer can interpolate
+        // Only touching entities that moved keeps resting ones out of the change tracking
         for entity in world.entities_mut() {
-            if let Some((physics, _)) = entity.physics_and_transform_mut() {
-                if !physics.dynamic {
-                    continue;
-                }
+            let transform = entity.transform();
+            if transform.prev_position != transform.position {
+                let transform = entity.transform_mut();
+                transform.prev_position = transform.position;
+            }
+        }
 
-                // Apply gravity
-                if physics.apply_gravity {
-                    physics.acceleration[0] += self.gravity[0];
-                    physics.acceleration[1] += self.gravity[1];
-                }
+        let mut bodies = std::mem::take(&mut self.bodies);
+        bodies.gather(world, self.gravity);
+        let mut sleeping = 0;
 
-                // Update velocity from acceleration
-                physics.velocity[0] += physics.acceleration[0] * dt_secs;
-                physics.velocity[1] += physics.acceleration[1] * dt_secs;
+        // === PHASE 1: Apply forces and integrate velocity ===
+        for idx in 0..bodies.flags.len() {
+            if !bodies.is_dynamic(idx) {
+                continue;
+            }
+            let velocity = &mut bodies.velocities[idx];
+            let acceleration = bodies.accelerations[idx];
 
-                // Apply air damping (subtle air resistance)
-                physics.velocity[0] *= self.air_damping;
-                physics.velocity[1] *= self.air_damping;
+            // Update velocity from acceleration
+            velocity[0] += acceleration[0] * dt_secs;
+            velocity[1] += acceleration[1] * dt_secs;
 
-                // Sleep very slow objects to prevent jitter
-                let speed_sq = physics.velocity[0] * physics.velocity[0]
-                    + physics.velocity[1] * physics.velocity[1];
-                if speed_sq < self.sleep_velocity_threshold * self.sleep_velocity_threshold {
-                    physics.velocity = [0.0, 0.0];
-                }
+            // Apply quadratic drag against the direction of motion
+            let speed = (velocity[0] * velocity[0] + velocity[1] * velocity[1]).sqrt();
+            let drag = bodies.drag[idx];
+            if drag > 0.0 && speed > 0.0 {
+                let direction = [velocity[0] / speed, velocity[1] / speed];
+                let decel = drag * speed * speed;
+                // Drag can at most stop the entity, never reverse it
+                let dv = (decel * dt_secs).min(speed);
+                velocity[0] -= direction[0] * dv;
+                velocity[1] -= direction[1] * dv;
+            }
 
-                // Reset acceleration for next frame
-                physics.acceleration = [0.0, 0.0];
+            // Apply air damping (subtle air resistance)
+            velocity[0] *= self.air_damping;
+            velocity[1] *= self.air_damping;
+
+            // Sleep very slow objects to prevent jitter
+            let speed_sq = velocity[0] * velocity[0] + velocity[1] * velocity[1];
+            if speed_sq < self.sleep_velocity_threshold * self.sleep_velocity_threshold {
+                *velocity = [0.0, 0.0];
+                sleeping += 1;
             }
         }
 
         // === PHASE 2: Integrate position ===
-        for entity in world.entities_mut() {
-            if let Some((physics, transform)) = entity.physics_and_transform_mut() {
-                if !physics.dynamic {
+        for idx in 0..bodies.flags.len() {
+            if !bodies.is_dynamic(idx) {
+                continue;
+            }
+            let velocity = bodies.velocities[idx];
+            let position = &mut bodies.positions[idx];
+            position[0] += velocity[0] * dt_secs;
+            position[1] += velocity[1] * dt_secs;
+        }
+        bodies.write_back(world);
+
+        // === PHASE 3: Despawn entities that left the world ===
+        if self.bounds.behavior == BoundaryBehavior::Despawn {
+            self.exited.clear();
+            for (idx, entity) in world.entities().iter().enumerate() {
+                if !bodies.is_dynamic(idx) {
                     continue;
                 }
 
-                transform.position[0] += physics.velocity[0] * dt_secs;
-                transform.position[1] += physics.velocity[1] * dt_secs;
+                let transform = entity.transform();
+                if self.bounds.fully_outside(&entity.shape().bounds(transform))
+                    && let Some(id) = world.id_at(idx)
+                {
+                    self.exited.push((id, transform.position));
+                }
+            }
+
+            for &(id, last_position) in &self.exited {
+                world.queue_removal(id);
+                world
+                    .events()
+                    .send(PhysicsEvent::EntityDespawned { id, last_position });
+            }
+            world.flush_removals();
+
+            // Removal shifted indices
+            if !self.exited.is_empty() {
+                bodies.gather(world, self.gravity);
             }
         }
 
-        // === PHASE 3: Detect and resolve collisions ===
-        for _ in 0..self.collision_iterations {
-            self.resolve_collisions(world, dt_secs);
+        // === PHASE 4: Detect and resolve collisions ===
+        self.contacts.clear();
+        self.new_sensor_overlaps.clear();
+        for iteration in 0..self.collision_iterations {
+            self.resolve_collisions(world, &mut bodies, iteration == 0);
+            solve_joints(world, &mut bodies);
         }
+        for &(sensor, other) in &self.new_sensor_overlaps {
+            if !self.sensor_overlaps.contains(&(sensor, other)) {
+                world
+                    .events()
+                    .send(PhysicsEvent::SensorEntered { sensor, other });
+            }
+        }
+        self.sensor_overlaps.clear();
+        self.sensor_overlaps
+            .extend(self.new_sensor_overlaps.iter().copied());
+        bodies.write_back(world);
+        self.bodies = bodies;
+
+        self.diagnostics = PhysicsDiagnostics {
+            contacts: self.contacts.len() as u32,
+            sleeping,
+            kinetic_energy: kinetic_energy(world),
+        };
+        world.debug_validate("physics update");
     }
 
-    fn resolve_collisions(&mut self, world: &mut World, dt_secs: f32) {
-        let entity_count = world.entities().len();
+    /// Run one solver pass over all pairs, optionally recording the contacts found
+    fn resolve_collisions(&mut self, world: &mut World, bodies: &mut Bodies, record: bool) {
+        let entity_count = bodies.flags.len();
 
         for i in 0..entity_count {
             for j in (i + 1)..entity_count {
-                let collision_data = {
-                    let entities = world.entities();
-                    self.check_collision(&entities[i], &entities[j])
-                };
+                if !bodies.has_physics(i) && !bodies.has_physics(j) {
+                    continue;
+                }
 
-                if let Some((normal, depth)) = collision_data {
-                    self.resolve_collision_pair(world, i, j, normal, depth, dt_secs);
+                let entities = world.entities();
+                if let Some(filter) = &self.collision_filter
+                    && !filter(&entities[i], &entities[j])
+                {
+                    continue;
+                }
+                let (shape_a, shape_b) = (entities[i].shape(), entities[j].shape());
+                let (pos_a, pos_b) = (bodies.positions[i], bodies.positions[j]);
+
+                if let Some((normal, depth)) = self.check_collision(shape_a, pos_a, shape_b, pos_b)
+                {
+                    // Sensors only note the overlap, on the first pass
+                    if bodies.is_sensor(i) || bodies.is_sensor(j) {
+                        if record
+                            && bodies.is_sensor(i) != bodies.is_sensor(j)
+                            && let (Some(a), Some(b)) = (world.id_at(i), world.id_at(j))
+                        {
+                            let pair = if bodies.is_sensor(i) { (a, b) } else { (b, a) };
+                            self.new_sensor_overlaps.push(pair);
+                        }
+                        continue;
+                    }
+                    if record && let (Some(a), Some(b)) = (world.id_at(i), world.id_at(j)) {
+                        let transform_a = Transform {
+                            position: pos_a,
+                            ..*entities[i].transform()
+                        };
+                        let transform_b = Transform {
+                            position: pos_b,
+                            ..*entities[j].transform()
+                        };
+                        self.contacts.push(Contact {
+                            a,
+                            b,
+                            point: contact_point(
+                                (shape_a, &transform_a),
+                                (shape_b, &transform_b),
+                                normal,
+                                depth,
+                            ),
+                            normal,
+                            depth,
+                        });
+                    }
+                    let impulse = resolve_collision_pair(bodies, i, j, normal, depth);
+                    // Later iterations only clean up what the first one left
+                    if record
+                        && let Some(impulse) = impulse
+                        && let (Some(a), Some(b)) = (world.id_at(i), world.id_at(j))
+                    {
+                        world.events().send(PhysicsEvent::Impact {
+                            a,
+                            b,
+                            normal,
+                            impulse,
+                        });
+                    }
                 }
             }
         }
     }
 
+    /// Narrow-phase test between two shapes at the given positions.
+    ///
+    /// Returns `(normal, depth)` where `normal` is a unit vector pointing from A toward B.
+    /// Every `check_*` helper follows the same convention, so separating the pair means
+    /// moving A along `-normal` and B along `+normal`, split by inverse mass.
+    /// Text and polygons don't collide yet.
     fn check_collision(
         &self,
-        entity_a: &crate::entity::Entity,
-        entity_b: &crate::entity::Entity,
+        shape_a: &Shape,
+        pos_a: [f32; 2],
+        shape_b: &Shape,
+        pos_b: [f32; 2],
     ) -> Option<([f32; 2], f32)> {
-        if entity_a.physics().is_none() && entity_b.physics().is_none() {
-            return None;
-        }
-
-        let pos_a = entity_a.transform().position;
-        let pos_b = entity_b.transform().position;
-
-        match (entity_a.shape(), entity_b.shape()) {
+        match (shape_a, shape_b) {
             (Shape::Circle { radius: r_a, .. }, Shape::Circle { radius: r_b, .. }) => {
                 self.check_circle_circle(pos_a, *r_a, pos_b, *r_b)
             }
             (Shape::Circle { radius, .. }, Shape::Rectangle { length, height, .. }) => {
                 self.check_circle_rect(pos_a, *radius, pos_b, *length, *height)
             }
+            // Helper returns circle -> rect, so flip to get A (rect) -> B (circle)
             (Shape::Rectangle { length, height, .. }, Shape::Circle { radius, .. }) => self
                 .check_circle_rect(pos_b, *radius, pos_a, *length, *height)
                 .map(|(n, d)| ([-n[0], -n[1]], d)),
@@ -154,6 +560,7 @@ impl PhysicsSystem {
         }
     }
 
+    /// Normal points from the circle toward the rectangle
     fn check_circle_rect(
         &self,
         circle_pos: [f32; 2],
@@ -169,8 +576,8 @@ impl PhysicsSystem {
         let closest_x = (circle_pos[0] - rect_pos[0]).clamp(-half_w, half_w) + rect_pos[0];
         let closest_y = (circle_pos[1] - rect_pos[1]).clamp(-half_h, half_h) + rect_pos[1];
 
-        let dx = circle_pos[0] - closest_x;
-        let dy = circle_pos[1] - closest_y;
+        let dx = closest_x - circle_pos[0];
+        let dy = closest_y - circle_pos[1];
         let dist_sq = dx * dx + dy * dy;
 
         if dist_sq < radius * radius {
@@ -187,16 +594,16 @@ impl PhysicsSystem {
 
                 if dx_to_edge < dy_to_edge {
                     let sign = if circle_pos[0] > rect_pos[0] {
-                        1.0
-                    } else {
                         -1.0
+                    } else {
+                        1.0
                     };
                     Some(([sign, 0.0], radius + dx_to_edge))
                 } else {
                     let sign = if circle_pos[1] > rect_pos[1] {
-                        1.0
-                    } else {
                         -1.0
+                    } else {
+                        1.0
                     };
                     Some(([0.0, sign], radius + dy_to_edge))
                 }
@@ -240,154 +647,722 @@ impl PhysicsSystem {
             None
         }
     }
+}
 
-    fn resolve_collision_pair(
-        &mut self,
-        world: &mut World,
-        idx_a: usize,
-        idx_b: usize,
-        normal: [f32; 2],
-        depth: f32,
-        dt_secs: f32,
-    ) {
-        // Gather immutable data first
-        let (
-            mass_a,
-            mass_b,
-            dynamic_a,
-            dynamic_b,
-            restitution_a,
-            restitution_b,
-            friction_a,
-            friction_b,
-        ) = {
-            let entities = world.entities();
-            let phys_a = entities[idx_a].physics();
-            let phys_b = entities[idx_b].physics();
-
-            let mass_a = phys_a.map(|p| p.mass).unwrap_or(f32::INFINITY);
-            let mass_b = phys_b.map(|p| p.mass).unwrap_or(f32::INFINITY);
-            let dynamic_a = phys_a.map(|p| p.dynamic).unwrap_or(false);
-            let dynamic_b = phys_b.map(|p| p.dynamic).unwrap_or(false);
-            let restitution_a = phys_a.map(|p| p.restitution).unwrap_or(0.5);
-            let restitution_b = phys_b.map(|p| p.restitution).unwrap_or(0.5);
-            let friction_a = phys_a.map(|p| p.friction).unwrap_or(0.3);
-            let friction_b = phys_b.map(|p| p.friction).unwrap_or(0.3);
+impl Default for PhysicsSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            (
-                mass_a,
-                mass_b,
-                dynamic_a,
-                dynamic_b,
-                restitution_a,
-                restitution_b,
-                friction_a,
-                friction_b,
-            )
-        };
+/// Push the pair apart and exchange momentum. Returns the normal impulse
+/// applied, or None if the pair was already separating (or both static).
+fn resolve_collision_pair(
+    bodies: &mut Bodies,
+    idx_a: usize,
+    idx_b: usize,
+    normal: [f32; 2],
+    depth: f32,
+) -> Option<f32> {
+    let dynamic_a = bodies.is_dynamic(idx_a);
+    let dynamic_b = bodies.is_dynamic(idx_b);
+
+    // Both static = no collision response
+    if !dynamic_a && !dynamic_b {
+        return None;
+    }
+
+    // === POSITION CORRECTION ===
+    let inv_mass_a = bodies.inv_mass[idx_a];
+    let inv_mass_b = bodies.inv_mass[idx_b];
+    let total_inv_mass = inv_mass_a + inv_mass_b;
 
-        // Both static = no collision response
-        if !dynamic_a && !dynamic_b {
-            return;
+    if total_inv_mass > 0.0 {
+        let correction = [
+            normal[0] * depth / total_inv_mass,
+            normal[1] * depth / total_inv_mass,
+        ];
+
+        if inv_mass_a > 0.0 {
+            let position = &mut bodies.positions[idx_a];
+            position[0] -= correction[0] * inv_mass_a;
+            position[1] -= correction[1] * inv_mass_a;
         }
 
-        // === POSITION CORRECTION ===
-        let inv_mass_a = if dynamic_a && mass_a.is_finite() {
-            1.0 / mass_a
-        } else {
-            0.0
-        };
-        let inv_mass_b = if dynamic_b && mass_b.is_finite() {
-            1.0 / mass_b
-        } else {
-            0.0
-        };
-        let total_inv_mass = inv_mass_a + inv_mass_b;
+        if inv_mass_b > 0.0 {
+            let position = &mut bodies.positions[idx_b];
+            position[0] += correction[0] * inv_mass_b;
+            position[1] += correction[1] * inv_mass_b;
+        }
+    }
 
-        if total_inv_mass > 0.0 {
-            let correction = [
-                normal[0] * depth / total_inv_mass,
-                normal[1] * depth / total_inv_mass,
-            ];
+    // === VELOCITY RESOLUTION ===
+    let vel_a = bodies.velocities[idx_a];
+    let vel_b = bodies.velocities[idx_b];
 
-            let entities = world.entities_mut();
+    // Velocity of B relative to A; positive along the normal means separating
+    let rel_vel = [vel_b[0] - vel_a[0], vel_b[1] - vel_a[1]];
+    let vel_along_normal = rel_vel[0] * normal[0] + rel_vel[1] * normal[1];
 
-            if dynamic_a && inv_mass_a > 0.0 {
-                let transform = entities[idx_a].transform_mut();
-                transform.position[0] -= correction[0] * inv_mass_a;
-                transform.position[1] -= correction[1] * inv_mass_a;
-            }
+    // Objects separating - no impulse needed
+    if vel_along_normal > 0.0 {
+        return None;
+    }
 
-            if dynamic_b && inv_mass_b > 0.0 {
-                let transform = entities[idx_b].transform_mut();
-                transform.position[0] += correction[0] * inv_mass_b;
-                transform.position[1] += correction[1] * inv_mass_b;
-            }
+    // Combined restitution (how bouncy the collision is)
+    let restitution = (bodies.restitution[idx_a] * bodies.restitution[idx_b]).sqrt(); // Geometric mean
+
+    // Calculate impulse magnitude
+    let j = -(1.0 + restitution) * vel_along_normal / total_inv_mass;
+    let impulse_n = [normal[0] * j, normal[1] * j];
+
+    // === FRICTION (tangential impulse) ===
+    let tangent = [-normal[1], normal[0]]; // Perpendicular to normal
+    let vel_along_tangent = rel_vel[0] * tangent[0] + rel_vel[1] * tangent[1];
+
+    let friction = (bodies.friction[idx_a] + bodies.friction[idx_b]) * 0.5;
+
+    // Coulomb friction: friction force can't exceed normal force
+    let friction_impulse_mag =
+        (-vel_along_tangent / total_inv_mass).clamp(-j.abs() * friction, j.abs() * friction);
+    let impulse_t = [
+        tangent[0] * friction_impulse_mag,
+        tangent[1] * friction_impulse_mag,
+    ];
+
+    // Combined impulse
+    let total_impulse = [impulse_n[0] + impulse_t[0], impulse_n[1] + impulse_t[1]];
+
+    // Apply impulses
+    if inv_mass_a > 0.0 {
+        let velocity = &mut bodies.velocities[idx_a];
+        velocity[0] -= total_impulse[0] * inv_mass_a;
+        velocity[1] -= total_impulse[1] * inv_mass_a;
+    }
+
+    if inv_mass_b > 0.0 {
+        let velocity = &mut bodies.velocities[idx_b];
+        velocity[0] += total_impulse[0] * inv_mass_b;
+        velocity[1] += total_impulse[1] * inv_mass_b;
+    }
+
+    Some(j)
+}
+
+/// Approximate contact point between two overlapping entities: the middle of the
+/// penetration for circles, the center of the overlap box for rectangle pairs
+fn contact_point(
+    (shape_a, transform_a): (&Shape, &Transform),
+    (shape_b, transform_b): (&Shape, &Transform),
+    normal: [f32; 2],
+    depth: f32,
+) -> [f32; 2] {
+    let pos_a = transform_a.position;
+    let pos_b = transform_b.position;
+
+    match (shape_a, shape_b) {
+        (Shape::Circle { radius, .. }, _) => {
+            let reach = radius - depth / 2.0;
+            [pos_a[0] + normal[0] * reach, pos_a[1] + normal[1] * reach]
+        }
+        (_, Shape::Circle { radius, .. }) => {
+            let reach = radius - depth / 2.0;
+            [pos_b[0] - normal[0] * reach, pos_b[1] - normal[1] * reach]
+        }
+        (shape_a, shape_b) => {
+            let bounds_a = shape_a.bounds(transform_a);
+            let bounds_b = shape_b.bounds(transform_b);
+            bounds_a
+                .intersection(&bounds_b)
+                .map(|overlap| overlap.center())
+                .unwrap_or([(pos_a[0] + pos_b[0]) / 2.0, (pos_a[1] + pos_b[1]) / 2.0])
         }
+    }
+}
 
-        // === VELOCITY RESOLUTION ===
-        let (vel_a, vel_b) = {
-            let entities = world.entities();
-            let vel_a = entities[idx_a]
-                .physics()
-                .map(|p| p.velocity)
-                .unwrap_or([0.0, 0.0]);
-            let vel_b = entities[idx_b]
-                .physics()
-                .map(|p| p.velocity)
-                .unwrap_or([0.0, 0.0]);
-            (vel_a, vel_b)
+/// Pull jointed bodies back to their rod length and cancel their relative velocity
+/// along the rod, splitting the correction by inverse mass
+fn solve_joints(world: &World, bodies: &mut Bodies) {
+    for joint in world.joints() {
+        let (Some(a), Some(b)) = (world.index_of(joint.a), world.index_of(joint.b)) else {
+            continue;
         };
+        let total = bodies.inv_mass[a] + bodies.inv_mass[b];
+        let [pa, pb] = [bodies.positions[a], bodies.positions[b]];
+        let delta = [pb[0] - pa[0], pb[1] - pa[1]];
+        let distance = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt();
+        if total == 0.0 || distance <= f32::EPSILON {
+            continue;
+        }
+        let normal = [delta[0] / distance, delta[1] / distance];
+        let (share_a, share_b) = (bodies.inv_mass[a] / total, bodies.inv_mass[b] / total);
 
-        let rel_vel = [vel_a[0] - vel_b[0], vel_a[1] - vel_b[1]];
-        let vel_along_normal = rel_vel[0] * normal[0] + rel_vel[1] * normal[1];
+        let error = distance - joint.length;
+        let [va, vb] = [bodies.velocities[a], bodies.velocities[b]];
+        let stretch = (vb[0] - va[0]) * normal[0] + (vb[1] - va[1]) * normal[1];
+        for (axis, n) in normal.into_iter().enumerate() {
+            bodies.positions[a][axis] += n * error * share_a;
+            bodies.positions[b][axis] -= n * error * share_b;
+            bodies.velocities[a][axis] += n * stretch * share_a;
+            bodies.velocities[b][axis] -= n * stretch * share_b;
+        }
+    }
+}
+
+/// Total kinetic energy of all dynamic, finite-mass bodies
+fn kinetic_energy(world: &World) -> f32 {
+    world
+        .entities()
+        .iter()
+        .filter_map(|entity| entity.physics())
+        .filter(|physics| physics.dynamic && physics.mass.is_finite())
+        .map(|physics| {
+            let speed_sq = physics.velocity[0] * physics.velocity[0]
+                + physics.velocity[1] * physics.velocity[1];
+            0.5 * physics.mass * speed_sq
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{GravityZone, Physics};
+    use proptest::prelude::*;
+
+    const DT: Duration = Duration::from_millis(8);
+
+    fn ball(position: [f32; 2], velocity: [f32; 2]) -> Entity {
+        Entity::new_circle(position, 0.05, [1.0, 1.0, 1.0])
+            .with_physics(Physics::new().with_velocity(velocity))
+    }
+
+    fn despawning() -> PhysicsSystem {
+        PhysicsSystem::new().with_bounds(WorldBounds::new(
+            [-1.0, -1.0],
+            [1.0, 1.0],
+            BoundaryBehavior::Despawn,
+        ))
+    }
+
+    #[test]
+    fn ball_leaving_the_bounds_is_despawned_and_others_still_resolve() {
+        let mut world = World::new();
+        let stays = world
+            .add_entity(ball([-0.5, 0.0], [0.0, 0.0]))
+            .id()
+            .unwrap();
+        // Just inside the right edge, moving out fast enough to clear it in one step
+        let leaves = world
+            .add_entity(ball([0.96, 0.0], [20.0, 0.0]))
+            .id()
+            .unwrap();
+        let floor = world
+            .add_entity(
+                Entity::new_rectangle([0.0, -0.8], 1.5, 0.1, [0.5, 0.5, 0.5])
+                    .with_physics(Physics::new_static()),
+            )
+            .id()
+            .unwrap();
+
+        let mut physics = despawning();
+        physics.update(&mut world, DT);
+
+        assert!(!world.contains(leaves));
+        assert_eq!(world.len(), 2);
+        for id in [stays, floor] {
+            let index = world.index_of(id).expect("survivor should resolve");
+            assert_eq!(world.id_at(index), Some(id));
+        }
+
+        world.events().update();
+        let despawned: Vec<_> = world
+            .events()
+            .read::<PhysicsEvent>()
+            .iter()
+            .filter_map(|event| match *event {
+                PhysicsEvent::EntityDespawned { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(despawned, [leaves]);
+    }
 
-        // Objects separating - no impulse needed
-        if vel_along_normal > 0.0 {
-            return;
+    /// An enemy heading right into a ball heading left, which is an enemy too if
+    /// `both_enemies`, under "enemies don't hit each other". Velocities after one step.
+    fn head_on_with_enemy_filter(both_enemies: bool) -> (usize, [f32; 2]) {
+        let mut world = World::new();
+        let enemy = world.register_tag("enemy").unwrap();
+        let mut ids = Vec::new();
+        for (x, tagged) in [(-0.04, true), (0.04, both_enemies)] {
+            let mut entity = ball([x, 0.0], [-x * 10.0, 0.0]);
+            entity.physics_mut().unwrap().apply_gravity = false;
+            if tagged {
+                entity.add_tag(enemy);
+            }
+            ids.push(world.add_entity(entity).id().unwrap());
         }
 
-        // Combined restitution (how bouncy the collision is)
-        let restitution = (restitution_a * restitution_b).sqrt(); // Geometric mean
+        let mut physics = PhysicsSystem::new()
+            .with_collision_filter(move |a, b| !(a.has_tag(enemy) && b.has_tag(enemy)));
+        physics.update(&mut world, DT);
+        let velocity = |id| world.get(id).unwrap().physics().unwrap().velocity[0];
+        (
+            physics.contacts().len(),
+            [velocity(ids[0]), velocity(ids[1])],
+        )
+    }
 
-        // Calculate impulse magnitude
-        let j = -(1.0 + restitution) * vel_along_normal / total_inv_mass;
-        let impulse_n = [normal[0] * j, normal[1] * j];
+    #[test]
+    fn collision_filter_lets_tagged_pairs_pass_through() {
+        let (contacts, [left, right]) = head_on_with_enemy_filter(true);
+        assert_eq!(contacts, 0);
+        assert!(
+            left > 0.0 && right < 0.0,
+            "should keep going: {left}, {right}"
+        );
 
-        // === FRICTION (tangential impulse) ===
-        let tangent = [-normal[1], normal[0]]; // Perpendicular to normal
-        let vel_along_tangent = rel_vel[0] * tangent[0] + rel_vel[1] * tangent[1];
+        let (contacts, [left, right]) = head_on_with_enemy_filter(false);
+        assert_eq!(contacts, 1);
+        assert!(left < 0.0 && right > 0.0, "should bounce: {left}, {right}");
+    }
 
-        let friction = (friction_a + friction_b) * 0.5;
+    #[test]
+    fn static_bodies_outside_the_bounds_stay() {
+        let mut world = World::new();
+        let id = world
+            .add_entity(
+                Entity::new_circle([3.0, 0.0], 0.05, [1.0, 1.0, 1.0])
+                    .with_physics(Physics::new_static()),
+            )
+            .id()
+            .unwrap();
 
-        // Coulomb friction: friction force can't exceed normal force
-        let friction_impulse_mag =
-            (-vel_along_tangent / total_inv_mass).clamp(-j.abs() * friction, j.abs() * friction);
-        let impulse_t = [
-            tangent[0] * friction_impulse_mag,
-            tangent[1] * friction_impulse_mag,
-        ];
+        despawning().update(&mut world, DT);
+        assert!(world.contains(id));
+    }
 
-        // Combined impulse
-        let total_impulse = [impulse_n[0] + impulse_t[0], impulse_n[1] + impulse_t[1]];
+    /// Shapes for the contact normal properties: circles and rectangles in the
+    /// size range the demo scenes use
+    fn shape() -> impl Strategy<Value = Shape> {
+        prop_oneof![
+            (0.01f32..0.3).prop_map(|radius| {
+                Entity::new_circle([0.0, 0.0], radius, [1.0; 3])
+                    .shape()
+                    .clone()
+            }),
+            (0.01f32..0.6, 0.01f32..0.6).prop_map(|(length, height)| {
+                Entity::new_rectangle([0.0, 0.0], length, height, [1.0; 3])
+                    .shape()
+                    .clone()
+            }),
+        ]
+    }
 
-        // Apply impulses
-        {
-            let entities = world.entities_mut();
+    proptest! {
+        /// Normals point from A toward B: pushing A back along -normal and B
+        /// along +normal, split by inverse mass, separates them. Covers every
+        /// circle/rect pairing in both orders.
+        #[test]
+        fn separating_along_the_normal_never_increases_overlap(
+            shape_a in shape(),
+            shape_b in shape(),
+            pos_a in prop::array::uniform2(-0.3f32..0.3),
+            pos_b in prop::array::uniform2(-0.3f32..0.3),
+            inv_mass in prop::array::uniform2(0.0f32..1.0),
+        ) {
+            let physics = PhysicsSystem::new();
+            let Some((normal, depth)) = physics.check_collision(&shape_a, pos_a, &shape_b, pos_b)
+            else {
+                return Ok(());
+            };
+            prop_assert!(depth > 0.0);
+            prop_assert!((normal[0].hypot(normal[1]) - 1.0).abs() < 1e-4, "{normal:?}");
 
-            if dynamic_a && inv_mass_a > 0.0 {
-                if let Some(physics) = entities[idx_a].physics_mut() {
-                    physics.velocity[0] -= total_impulse[0] * inv_mass_a;
-                    physics.velocity[1] -= total_impulse[1] * inv_mass_a;
+            // Static pairs are pushed apart like equal masses for the property's sake
+            let total = inv_mass[0] + inv_mass[1];
+            let share_a = if total > 0.0 { inv_mass[0] / total } else { 0.5 };
+            let moved_a = [
+                pos_a[0] - normal[0] * depth * share_a,
+                pos_a[1] - normal[1] * depth * share_a,
+            ];
+            let moved_b = [
+                pos_b[0] + normal[0] * depth * (1.0 - share_a),
+                pos_b[1] + normal[1] * depth * (1.0 - share_a),
+            ];
+            let after = physics
+                .check_collision(&shape_a, moved_a, &shape_b, moved_b)
+                .map_or(0.0, |(_, depth)| depth);
+            prop_assert!(after <= depth, "overlap grew from {depth} to {after}");
+            prop_assert!(after < 1e-4, "still overlapping by {after} (was {depth})");
+
+        }
+    }
+
+    #[test]
+    fn soa_step_matches_a_direct_per_entity_step() {
+        // Spread out so nothing touches: this pins the integration phases, which
+        // are what moved from entities onto the body arrays
+        let mut world = World::new();
+        for i in 0..24 {
+            let position = [-0.9 + (i % 6) as f32 * 0.35, 0.8 - (i / 6) as f32 * 0.4];
+            let velocity = [0.3 - (i % 5) as f32 * 0.15, 0.2 * (i % 3) as f32];
+            let physics = Physics::new()
+                .with_velocity(velocity)
+                .with_drag(0.1 * (i % 4) as f32);
+            world.add_entity(Entity::new_circle(position, 0.02, [1.0; 3]).with_physics(physics));
+        }
+        let mut direct = world.clone();
+        let mut physics = PhysicsSystem::new();
+        let dt = DT.as_secs_f32();
+
+        for step in 0..100 {
+            physics.update(&mut world, DT);
+            assert_eq!(physics.diagnostics().contacts, 0);
+
+            for entity in direct.entities_mut() {
+                let rotation = entity.transform().rotation;
+                let width = |direction| entity.shape().cross_section(rotation, direction);
+                let Some(body) = entity.physics() else {
+                    continue;
+                };
+                let mut velocity = body.velocity;
+                let speed = velocity[0].hypot(velocity[1]);
+                let drag = if body.drag_coefficient > 0.0 && speed > 0.0 {
+                    body.drag_coefficient * width([velocity[0] / speed, velocity[1] / speed])
+                        / body.mass
+                } else {
+                    0.0
+                };
+
+                velocity[0] += (body.acceleration[0] + physics.gravity[0]) * dt;
+                velocity[1] += (body.acceleration[1] + physics.gravity[1]) * dt;
+                let speed = (velocity[0] * velocity[0] + velocity[1] * velocity[1]).sqrt();
+                if drag > 0.0 && speed > 0.0 {
+                    let dv = (drag * speed * speed * dt).min(speed);
+                    velocity[0] -= velocity[0] / speed * dv;
+                    velocity[1] -= velocity[1] / speed * dv;
+                }
+                velocity[0] *= physics.air_damping;
+                velocity[1] *= physics.air_damping;
+                let speed_sq = velocity[0] * velocity[0] + velocity[1] * velocity[1];
+                if speed_sq < physics.sleep_velocity_threshold * physics.sleep_velocity_threshold {
+                    velocity = [0.0, 0.0];
                 }
+
+                let (body, transform) = entity.physics_and_transform_mut().unwrap();
+                body.velocity = velocity;
+                transform.position[0] += velocity[0] * dt;
+                transform.position[1] += velocity[1] * dt;
             }
 
-            if dynamic_b && inv_mass_b > 0.0 {
-                if let Some(physics) = entities[idx_b].physics_mut() {
-                    physics.velocity[0] += total_impulse[0] * inv_mass_b;
-                    physics.velocity[1] += total_impulse[1] * inv_mass_b;
+            for (soa, direct) in world.entities().iter().zip(direct.entities()) {
+                assert_eq!(
+                    soa.transform().position,
+                    direct.transform().position,
+                    "step {step}"
+                );
+                assert_eq!(
+                    soa.physics().unwrap().velocity,
+                    direct.physics().unwrap().velocity
+                );
+            }
+        }
+    }
+
+    // FNV-1a over the bits of every position and velocity, step after step
+    fn trace_hash(world: &mut World, physics: &mut PhysicsSystem, steps: usize) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for _ in 0..steps {
+            physics.update(world, DT);
+            for entity in world.entities() {
+                let velocity = entity
+                    .physics()
+                    .map_or([0.0; 2], |physics| physics.velocity);
+                let position = entity.transform().position;
+                for value in position.into_iter().chain(velocity) {
+                    for byte in value.to_bits().to_le_bytes() {
+                        hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+                    }
                 }
             }
         }
+        hash
+    }
+
+    #[test]
+    fn deterministic_scene_matches_its_golden_trace() {
+        // Seeded balls piling onto a floor between two walls. A change to this
+        // value means trajectories changed: intended for a physics change, a bug
+        // for a refactor.
+        let mut world = World::new();
+        for (position, length, height) in [
+            ([0.0, -0.9], 1.8, 0.1),
+            ([-0.9, 0.0], 0.1, 1.8),
+            ([0.9, 0.0], 0.1, 1.8),
+        ] {
+            world.add_entity(
+                Entity::new_rectangle(position, length, height, [0.5; 3])
+                    .with_physics(Physics::new_static()),
+            );
+        }
+        world.spawn_random_balls(40, Aabb::from_center([0.0, 0.2], [0.6, 0.5]), 0x901d);
+        let mut physics = PhysicsSystem::new();
+        assert_eq!(
+            trace_hash(&mut world, &mut physics, 300),
+            0x3bc3_ced8_3fe6_b39c
+        );
+    }
+
+    #[test]
+    fn contact_ids_survive_despawns_that_shift_entities() {
+        let mut world = World::new();
+        let spawn =
+            |world: &mut World, x: f32| world.add_entity(ball([x, 0.0], [0.0, 0.0])).id().unwrap();
+        let first = spawn(&mut world, -0.8);
+        let a = spawn(&mut world, 0.0);
+        let b = spawn(&mut world, 0.08);
+        let pair = |physics: &PhysicsSystem| {
+            let [contact] = physics.contacts() else {
+                panic!("expected one contact, got {:?}", physics.contacts());
+            };
+            [contact.a, contact.b]
+        };
+        // The solver pushes the pair apart each update; put them back in contact
+        let overlap = |world: &mut World, a, b| {
+            let x = world.get(a).unwrap().transform().position[0];
+            world.get_mut(b).unwrap().transform_mut().position = [x + 0.08, 0.0];
+        };
+
+        let mut physics = PhysicsSystem::new();
+        physics.update(&mut world, DT);
+        assert_eq!(pair(&physics), [a, b]);
+
+        // Despawning the first entity moves the pair down an index
+        world.queue_removal(first);
+        world.flush_removals();
+        assert_eq!((world.index_of(a), world.index_of(b)), (Some(0), Some(1)));
+        overlap(&mut world, a, b);
+        physics.update(&mut world, DT);
+        assert_eq!(pair(&physics), [a, b]);
+
+        // A newcomer reusing the freed slot gets an id of its own
+        for id in [a, b] {
+            world.queue_removal(id);
+        }
+        world.flush_removals();
+        let c = spawn(&mut world, 0.5);
+        let d = spawn(&mut world, 0.58);
+        physics.update(&mut world, DT);
+        let [c_seen, d_seen] = pair(&physics);
+        assert_eq!([c_seen, d_seen], [c, d]);
+        assert!(!world.contains(first) && !world.contains(a) && !world.contains(b));
+        assert!(![first, a, b].contains(&c_seen) && ![first, a, b].contains(&d_seen));
+    }
+
+    #[test]
+    fn decoration_given_physics_starts_falling_on_the_next_step() {
+        let mut world = World::new();
+        let sign = world
+            .add_entity(Entity::new_circle([0.0, 0.5], 0.05, [1.0, 1.0, 1.0]))
+            .id()
+            .unwrap();
+        let mut physics = PhysicsSystem::new();
+        physics.update(&mut world, DT);
+        assert_eq!(world.get(sign).unwrap().transform().position, [0.0, 0.5]);
+
+        // Knock the sign off the wall
+        world.set_physics(sign, Physics::new());
+        assert_eq!(world.get(sign).unwrap().transform().position, [0.0, 0.5]);
+        physics.update(&mut world, DT);
+        let entity = world.get(sign).unwrap();
+        assert!(entity.transform().position[1] < 0.5);
+        assert!(entity.physics().unwrap().velocity[1] < 0.0);
+    }
+
+    #[test]
+    fn removing_physics_holds_an_entity_in_place_as_an_obstacle() {
+        let mut world = World::new();
+        let post = world
+            .add_entity(ball([0.0, 0.0], [0.0, -1.0]))
+            .id()
+            .unwrap();
+        let faller = world.add_entity(ball([0.0, 0.4], [0.0, 0.0])).id().unwrap();
+        let mut physics = PhysicsSystem::new();
+        physics.update(&mut world, DT);
+
+        let removed = world.remove_physics(post).unwrap();
+        assert!(removed.velocity[1] < 0.0);
+        assert!(world.remove_physics(post).is_none());
+        let held = world.get(post).unwrap().transform().position;
+        for _ in 0..200 {
+            physics.update(&mut world, DT);
+        }
+
+        // The post stays put, and the falling ball comes to rest on top of it
+        // instead of passing through
+        assert_eq!(world.get(post).unwrap().transform().position, held);
+        let landed = world.get(faller).unwrap().transform().position;
+        assert!(
+            landed[1] > held[1] + 0.09,
+            "ball at {landed:?} sank into the post at {held:?}"
+        );
+    }
+
+    #[test]
+    fn ball_reverses_in_an_upward_gravity_zone_and_falls_again_outside() {
+        let mut world = World::new();
+        // Zone from y = -0.8 to -0.2, on a sensor so it doesn't block the ball
+        let (center, extents) = ([0.0, -0.5], [0.5, 0.3]);
+        world.add_entity(
+            Entity::new_rectangle(center, 1.0, 0.6, [0.2, 0.2, 0.8])
+                .with_opacity(0.0)
+                .with_physics(Physics::new_sensor())
+                .with_gravity_zone(GravityZone::new(extents, [0.0, 20.0])),
+        );
+        let id = world
+            .add_entity(ball([0.0, 0.0], [0.0, -1.0]))
+            .id()
+            .unwrap();
+        let mut physics = PhysicsSystem::new();
+        assert!(physics.gravity[1] < 0.0);
+        let top = center[1] + extents[1];
+
+        let mut trace = Vec::new();
+        for _ in 0..250 {
+            physics.update(&mut world, DT);
+            let entity = world.get(id).unwrap();
+            trace.push((
+                entity.transform().position[1],
+                entity.physics().unwrap().velocity[1],
+            ));
+        }
+
+        let entered = trace.iter().position(|&(y, _)| y < top).unwrap();
+        let left = entered
+            + trace[entered..]
+                .iter()
+                .position(|&(y, _)| y >= top)
+                .unwrap();
+        let inside = &trace[entered..left];
+        assert!(inside.iter().all(|&(y, _)| y > center[1] - extents[1]));
+        // Slows down and turns around inside the zone
+        assert!(inside.windows(2).all(|pair| pair[1].1 > pair[0].1));
+        assert!(inside.last().unwrap().1 > 0.0);
+        // Then rises out of it, slowing under normal gravity until it falls back in
+        let back = left + trace[left..].iter().position(|&(y, _)| y < top).unwrap();
+        let outside = &trace[left..back];
+        assert!(outside[0].1 > 0.0);
+        assert!(outside.windows(2).all(|pair| pair[1].1 < pair[0].1));
+        assert!(outside.last().unwrap().1 < 0.0);
+    }
+
+    #[test]
+    fn innermost_gravity_zone_wins_where_zones_overlap() {
+        let zone = |extents: [f32; 2], gravity| {
+            Entity::new_rectangle([0.0, 0.0], 0.1, 0.1, [0.0, 0.0, 0.0])
+                .with_physics(Physics::new_sensor())
+                .with_gravity_zone(GravityZone::new(extents, gravity))
+        };
+        let mut world = World::new();
+        world.add_entity(zone([0.2, 0.2], [1.0, 0.0]));
+        world.add_entity(zone([0.9, 0.9], [-1.0, 0.0]));
+        let inner = world.add_entity(ball([0.1, 0.0], [0.0, 0.0])).id().unwrap();
+        let outer = world.add_entity(ball([0.6, 0.0], [0.0, 0.0])).id().unwrap();
+        let free = world
+            .add_entity(ball([0.0, 0.95], [0.0, 0.0]))
+            .id()
+            .unwrap();
+        PhysicsSystem::new().update(&mut world, DT);
+
+        let velocity = |id| world.get(id).unwrap().physics().unwrap().velocity;
+        assert!(velocity(inner)[0] > 0.0 && velocity(inner)[1] == 0.0);
+        assert!(velocity(outer)[0] < 0.0 && velocity(outer)[1] == 0.0);
+        assert!(velocity(free)[0] == 0.0 && velocity(free)[1] < 0.0);
+    }
+
+    /// Speed after falling long enough to settle at terminal velocity
+    fn terminal_speed(rotation: f32) -> f32 {
+        let mut plank = Entity::new_rectangle([0.0, 0.0], 0.4, 0.02, [1.0, 1.0, 1.0])
+            .with_physics(Physics::new().with_drag(0.5));
+        plank.transform_mut().rotation = rotation;
+        let mut world = World::new();
+        let id = world.add_entity(plank).id().unwrap();
+        let mut physics = PhysicsSystem::new();
+        physics.air_damping = 1.0;
+        for _ in 0..2000 {
+            physics.update(&mut world, DT);
+        }
+        let [x, y] = world.get(id).unwrap().physics().unwrap().velocity;
+        (x * x + y * y).sqrt()
+    }
+
+    #[test]
+    fn plank_falling_edge_on_reaches_a_higher_terminal_velocity_than_flat() {
+        let flat = terminal_speed(0.0);
+        let edge_on = terminal_speed(std::f32::consts::FRAC_PI_2);
+        // Drag scales with the width facing the flow, 0.4 flat vs 0.02 edge-on
+        assert!(edge_on > flat * 3.0, "edge-on {edge_on}, flat {flat}");
+        // Flat has settled: terminal speed is sqrt(g * m / (c * width))
+        assert!(
+            (flat - (0.5f32 / (0.5 * 0.4)).sqrt()).abs() < 0.01,
+            "flat {flat}"
+        );
+    }
+
+    #[test]
+    fn a_joint_leaves_with_the_ball_that_fell_out_of_bounds() {
+        let mut world = World::new();
+        let low = world
+            .add_entity(ball([0.0, -0.9], [0.0, -2.0]))
+            .id()
+            .unwrap();
+        let high = world
+            .add_entity(ball([0.0, -0.6], [0.0, -2.0]))
+            .id()
+            .unwrap();
+        assert!(world.add_joint(low, high));
+
+        let mut physics = despawning();
+        let mut steps = 0;
+        while world.contains(low) {
+            physics.update(&mut world, DT);
+            steps += 1;
+            assert!(steps < 100, "the low ball never left");
+        }
+        assert!(world.contains(high), "the rod kept the other ball inside");
+        assert!(world.joints().is_empty());
+
+        // The survivor keeps simulating, and leaves on its own
+        while world.contains(high) {
+            physics.update(&mut world, DT);
+            steps += 1;
+            assert!(steps < 200, "the high ball never left");
+        }
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    fn a_joint_holds_a_ball_at_its_length_from_an_anchor() {
+        let mut world = World::new();
+        let anchor = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.5], 0.02, [1.0, 1.0, 1.0])
+                    .with_physics(Physics::new_static()),
+            )
+            .id()
+            .unwrap();
+        let bob = world.add_entity(ball([0.3, 0.5], [0.0, 0.0])).id().unwrap();
+        assert!(world.add_joint(anchor, bob));
+
+        let mut physics = PhysicsSystem::new();
+        for _ in 0..240 {
+            physics.update(&mut world, DT);
+        }
+        let [x, y] = world.get(bob).unwrap().transform().position;
+        let length = (x * x + (y - 0.5) * (y - 0.5)).sqrt();
+        assert!((length - 0.3).abs() < 1e-3, "rod stretched to {length}");
+        assert!(y < 0.5, "the bob swung down");
+        assert_eq!(world.get(anchor).unwrap().transform().position, [0.0, 0.5]);
     }
 }
@@ -0,0 +1,180 @@
+use super::ReadbackState;
+use std::sync::{Arc, OnceLock};
+
+// Readback buffers for GPU timestamps; a frame goes untimed when all are in flight
+const TIMESTAMP_SLOTS: usize = 2;
+
+/// GPU milliseconds between two timestamp query values, given the queue's
+/// nanoseconds per tick. None if the counter went backwards (some drivers reset it).
+pub fn timestamp_delta_ms(start: u64, end: u64, period_ns: f32) -> Option<f32> {
+    let ticks = end.checked_sub(start)?;
+    Some((ticks as f64 * period_ns as f64 / 1_000_000.0) as f32)
+}
+
+/// Times the frame's render passes on the GPU with a pair of timestamp queries.
+/// Results are read back a frame or more later so present never waits on them.
+pub(super) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    period_ns: f32,
+    readbacks: Vec<TimestampReadback>,
+}
+
+struct TimestampReadback {
+    buffer: wgpu::Buffer,
+    state: ReadbackState,
+}
+
+impl GpuTimer {
+    /// None when the device can't write timestamps
+    pub(super) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let size = 2 * wgpu::QUERY_SIZE as wgpu::BufferAddress;
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readbacks = (0..TIMESTAMP_SLOTS)
+            .map(|_| TimestampReadback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                state: ReadbackState::Free,
+            })
+            .collect();
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            period_ns: queue.get_timestamp_period(),
+            readbacks,
+        })
+    }
+
+    /// Timestamp at the start of the first pass (`begin`) and/or the end of the last
+    pub(super) fn pass_writes(
+        &self,
+        begin: bool,
+        end: bool,
+    ) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: begin.then_some(0),
+            end_of_pass_write_index: end.then_some(1),
+        }
+    }
+
+    /// Copy this frame's timestamps into a free readback buffer, if there is one
+    pub(super) fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(readback) = self
+            .readbacks
+            .iter_mut()
+            .find(|readback| matches!(readback.state, ReadbackState::Free))
+        else {
+            return;
+        };
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &readback.buffer,
+            0,
+            self.resolve_buffer.size(),
+        );
+        readback.state = ReadbackState::Copied;
+    }
+
+    /// Start mapping this frame's copy; call after the frame is submitted
+    pub(super) fn map_copies(&mut self) {
+        for readback in &mut self.readbacks {
+            if let ReadbackState::Copied = readback.state {
+                let done = Arc::new(OnceLock::new());
+                let callback_done = done.clone();
+                readback
+                    .buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let _ = callback_done.set(result.is_ok());
+                    });
+                readback.state = ReadbackState::Mapping(done);
+            }
+        }
+    }
+
+    /// Frame times (ms) of every readback that finished, without waiting on the rest
+    pub(super) fn collect(&mut self, device: &wgpu::Device, mut record: impl FnMut(f32)) {
+        let _ = device.poll(wgpu::PollType::Poll);
+        for readback in &mut self.readbacks {
+            let ReadbackState::Mapping(done) = &readback.state else {
+                continue;
+            };
+            let Some(&mapped) = done.get() else {
+                continue;
+            };
+
+            if mapped {
+                let data = readback.buffer.slice(..).get_mapped_range();
+                let ticks: &[u64] = bytemuck::cast_slice(&data);
+                if let Some(ms) = timestamp_delta_ms(ticks[0], ticks[1], self.period_ns) {
+                    record(ms);
+                }
+            }
+            readback.buffer.unmap();
+            readback.state = ReadbackState::Free;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::renderer::FrameStats;
+    use std::time::Instant;
+
+    #[test]
+    fn timestamp_ticks_convert_to_milliseconds() {
+        assert_eq!(timestamp_delta_ms(1_000, 3_000_000, 1.0), Some(2.999));
+        // Some GPUs tick slower than once a nanosecond
+        assert_eq!(timestamp_delta_ms(0, 250_000, 40.0), Some(10.0));
+        assert_eq!(timestamp_delta_ms(500, 500, 1.0), Some(0.0));
+        // Large counters don't lose the sub-millisecond part
+        let start = u64::MAX / 2;
+        let delta = timestamp_delta_ms(start, start + 1_234_567, 1.0).unwrap();
+        assert!((delta - 1.234567).abs() < 1e-6, "{delta}");
+        assert_eq!(timestamp_delta_ms(10, 5, 1.0), None, "counter reset");
+    }
+
+    #[test]
+    fn gpu_time_reads_n_a_until_measured() {
+        let mut stats = FrameStats::new(Instant::now());
+        stats.update();
+        assert!(
+            stats.summary().contains("GPU time:   n/a"),
+            "{}",
+            stats.summary()
+        );
+
+        stats.record_gpu_time(2.0);
+        stats.record_gpu_time(3.0);
+        stats.update();
+        assert_eq!(stats.avg_gpu_time_ms, Some(2.5));
+        assert!(
+            stats.summary().contains("GPU time:  2.50 ms"),
+            "{}",
+            stats.summary()
+        );
+    }
+}
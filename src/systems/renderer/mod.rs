@@ -0,0 +1,4982 @@
+use super::physics::{Contact, PhysicsDiagnostics, PhysicsEvent};
+use crate::components::{Aabb, Corner, Shape, TextSpace, TextureId, Transform};
+use crate::entity::Entity;
+use crate::world::{EntityId, World, WorldId};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use wgpu::util::DeviceExt;
+use winit::window::Window;
+
+mod gpu_timer;
+mod passes;
+mod picker;
+mod recorder;
+
+use gpu_timer::GpuTimer;
+pub use gpu_timer::timestamp_delta_ms;
+use passes::*;
+use picker::GpuPicker;
+use recorder::FrameRecorder;
+pub use recorder::RecordingStats;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+/// A circle or ring, turned into a `ShapeInstance` for the GPU
+#[derive(Clone, Copy, Debug)]
+struct CircleInstance {
+    position: [f32; 2],
+    radius: f32,
+    color: [f32; 4],       // RGBA, alpha multiplies the edge coverage
+    thickness: f32,        // 0 = filled, >0 = ring of this NDC thickness
+    rotation: f32,         // radians, counter-clockwise
+    scale: [f32; 2],       // non-uniform scale stretches the circle into an ellipse
+    edge_softness: f32,    // 0 = use the global edge softness
+    color_outer: [f32; 3], // RGB at the rim; equal to `color` for flat circles
+}
+
+/// A rectangle, turned into a `ShapeInstance` for the GPU
+#[derive(Clone, Copy, Debug)]
+struct RectInstance {
+    position: [f32; 2],
+    length: f32,
+    height: f32,
+    color: [f32; 4],
+    rotation: f32,   // radians, counter-clockwise
+    scale: [f32; 2], // multiplies length and height
+    corner_radius: f32,
+}
+
+// Values of `ShapeInstance::kind`, matching the constants in shader.wgsl
+const SHAPE_CIRCLE: u32 = 0;
+const SHAPE_RECTANGLE: u32 = 1;
+
+/// Circles and rectangles share one instance layout and pipeline, so an
+/// untextured scene draws every shape in a single call whatever the mix
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShapeInstance {
+    position: [f32; 2],
+    size: [f32; 2], // Circles: radius, radius. Rectangles: length, height
+    color: [f32; 4],
+    color_outer: [f32; 3], // RGB at the rim; equal to `color` for flat shapes
+    rotation: f32,
+    scale: [f32; 2],
+    thickness: f32,     // Circles only
+    corner_radius: f32, // Rectangles only
+    edge_softness: f32, // 0 = use the global edge softness
+    kind: u32,
+}
+
+impl From<CircleInstance> for ShapeInstance {
+    fn from(circle: CircleInstance) -> Self {
+        Self {
+            position: circle.position,
+            size: [circle.radius, circle.radius],
+            color: circle.color,
+            color_outer: circle.color_outer,
+            rotation: circle.rotation,
+            scale: circle.scale,
+            thickness: circle.thickness,
+            corner_radius: 0.0,
+            edge_softness: circle.edge_softness,
+            kind: SHAPE_CIRCLE,
+        }
+    }
+}
+
+impl From<RectInstance> for ShapeInstance {
+    fn from(rect: RectInstance) -> Self {
+        Self {
+            position: rect.position,
+            size: [rect.length, rect.height],
+            color: rect.color,
+            color_outer: [rect.color[0], rect.color[1], rect.color[2]],
+            rotation: rect.rotation,
+            scale: rect.scale,
+            thickness: 0.0,
+            corner_radius: rect.corner_radius,
+            edge_softness: 0.0,
+            kind: SHAPE_RECTANGLE,
+        }
+    }
+}
+
+/// Per-frame values visible to the shape shader (group 0). Layout matches
+/// `Globals` in shader.wgsl.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Globals {
+    time: f32,                 // Elapsed sim time (seconds)
+    edge_pulse: f32,           // Circle edge softness pulse strength, 0 = off
+    screen_size: [f32; 2],     // Pixels
+    camera_position: [f32; 2], // Reserved for the camera
+    camera_zoom: f32,
+    edge_softness: f32, // Circle edge antialiasing width multiplier, 1 = default
+    glow: f32,          // Circle halo strength, 0 = off
+    glow_extent: f32,   // Halo reach beyond the radius, as a fraction of it
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PolygonVertex {
+    position: [f32; 2], // NDC, transform already applied
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineInstance {
+    start: [f32; 2],
+    end: [f32; 2],
+    thickness: f32,
+    color: [f32; 4], // RGBA
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct GradientInstance {
+    top: [f32; 4],
+    bottom: [f32; 4],
+}
+
+const QUAD_VERTICES: &[Vertex] = &[
+    Vertex {
+        position: [-1.0, -1.0],
+    },
+    Vertex {
+        position: [1.0, -1.0],
+    },
+    Vertex {
+        position: [1.0, 1.0],
+    },
+    Vertex {
+        position: [-1.0, -1.0],
+    },
+    Vertex {
+        position: [1.0, 1.0],
+    },
+    Vertex {
+        position: [-1.0, 1.0],
+    },
+];
+
+/// Instance vertex buffer that grows to fit whatever is uploaded
+struct InstanceBuffer {
+    label: &'static str,
+    buffer: wgpu::Buffer,
+    capacity: u64, // bytes
+}
+
+impl InstanceBuffer {
+    fn new(device: &wgpu::Device, label: &'static str, capacity: u64) -> Self {
+        Self {
+            label,
+            buffer: Self::create(device, label, capacity),
+            capacity,
+        }
+    }
+
+    fn create(device: &wgpu::Device, label: &'static str, capacity: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: capacity,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Write `data` at the start of the buffer, reallocating first if it doesn't fit
+    fn upload<T: bytemuck::Pod>(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[T]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let needed = bytes.len() as u64;
+        if needed > self.capacity {
+            self.capacity = needed.next_power_of_two();
+            self.buffer = Self::create(device, self.label, self.capacity);
+        }
+        queue.write_buffer(&self.buffer, 0, bytes);
+    }
+
+    fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..)
+    }
+}
+
+/// Everything collect_instances builds for one frame
+/// Which pipeline draws a batch
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BatchKind {
+    Shapes, // Circles and rectangles
+    Polygons,
+}
+
+/// A run of consecutive same-kind, same-texture shapes in draw order
+struct DrawBatch {
+    kind: BatchKind,
+    texture: Option<TextureId>,
+    range: Range<u32>, // Instances, or vertices for polygons
+}
+
+/// Shaped text for one entity, kept until its content or size changes
+struct CachedText {
+    content: String,
+    font_size: f32,
+    buffer: glyphon::Buffer,
+    // Set whenever the entity is seen during a frame; unused entries get evicted
+    used: bool,
+}
+
+/// Screen position and color for one cached text or label buffer
+struct TextPlacement {
+    entity: EntityId, // Key of the buffer in its cache
+    left: f32,
+    top: f32,
+    scale: f32, // HUD text follows the window's DPI scale, world text doesn't
+    color: [f32; 3],
+    opacity: f32,
+    bounds: glyphon::TextBounds, // World text is clipped to the world viewport
+}
+
+impl TextPlacement {
+    fn text_area<'a>(&self, buffer: &'a glyphon::Buffer) -> glyphon::TextArea<'a> {
+        glyphon::TextArea {
+            buffer,
+            left: self.left,
+            top: self.top,
+            scale: self.scale,
+            bounds: self.bounds,
+            default_color: glyphon::Color::rgba(
+                (self.color[0] * 255.0) as u8,
+                (self.color[1] * 255.0) as u8,
+                (self.color[2] * 255.0) as u8,
+                (self.opacity * 255.0) as u8,
+            ),
+            custom_glyphs: &[],
+        }
+    }
+}
+
+/// Text buffers keyed by entity so shaping only happens when text changes
+struct TextCache {
+    entries: HashMap<EntityId, CachedText>,
+    // World change tick as of the last sweep; entities unchanged since then skip
+    // comparing their text. None until the first sweep.
+    seen_tick: Option<u32>,
+}
+
+impl TextCache {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            seen_tick: None,
+        }
+    }
+
+    /// Whether `entity` may hold different text than when last cached
+    fn is_stale(&self, entity: &Entity) -> bool {
+        self.seen_tick.is_none_or(|tick| entity.changed_since(tick))
+    }
+
+    /// Make sure the entry for `id` holds shaped `content`, rebuilding it only if
+    /// stale. With `changed` false an existing entry is trusted without comparing.
+    fn update(
+        &mut self,
+        font_system: &mut glyphon::FontSystem,
+        id: EntityId,
+        content: &str,
+        font_size: f32,
+        changed: bool,
+    ) {
+        if let Some(cached) = self.entries.get_mut(&id)
+            && (!changed || (cached.content == content && cached.font_size == font_size))
+        {
+            cached.used = true;
+            return;
+        }
+
+        let mut buffer = glyphon::Buffer::new(
+            font_system,
+            glyphon::Metrics::new(font_size, font_size * 1.4),
+        );
+        buffer.set_size(font_system, None, None);
+        buffer.set_text(
+            font_system,
+            content,
+            &glyphon::Attrs::new().family(glyphon::Family::SansSerif),
+            glyphon::Shaping::Advanced,
+            None,
+        );
+        buffer.shape_until_scroll(font_system, false);
+
+        self.entries.insert(
+            id,
+            CachedText {
+                content: content.to_string(),
+                font_size,
+                buffer,
+                used: true,
+            },
+        );
+    }
+
+    fn get(&self, id: EntityId) -> Option<&glyphon::Buffer> {
+        self.entries.get(&id).map(|cached| &cached.buffer)
+    }
+
+    /// Drop entries for entities that weren't seen since the last sweep, and
+    /// remember `change_tick` (`World::change_tick`) for the next frame
+    fn evict_unused(&mut self, change_tick: u32) {
+        self.seen_tick = Some(change_tick);
+        self.entries.retain(|_, cached| cached.used);
+        for cached in self.entries.values_mut() {
+            cached.used = false;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.seen_tick = None;
+    }
+}
+
+/// Default number of sim steps a trail remembers
+pub const DEFAULT_TRAIL_LENGTH: usize = 16;
+
+// Moving further than this in one sim step counts as a teleport and restarts the trail
+const TRAIL_TELEPORT_DISTANCE: f32 = 0.5;
+
+/// Recent sim positions for one circle, oldest first
+struct Trail {
+    positions: VecDeque<[f32; 2]>,
+    used: bool,
+}
+
+/// Position history per circle entity. Keyed by id rather than index so a trail
+/// stays with its ball when earlier entities are removed.
+struct TrailCache {
+    entries: HashMap<EntityId, Trail>,
+    length: usize,
+}
+
+impl TrailCache {
+    fn new(length: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            length,
+        }
+    }
+
+    /// Append `position` to the trail for `id`, dropping the oldest entry when full
+    fn record(&mut self, id: EntityId, position: [f32; 2]) {
+        let length = self.length;
+        let trail = self.entries.entry(id).or_insert_with(|| Trail {
+            positions: VecDeque::with_capacity(length),
+            used: false,
+        });
+        trail.used = true;
+
+        if let Some(last) = trail.positions.back() {
+            let dx = position[0] - last[0];
+            let dy = position[1] - last[1];
+            if dx * dx + dy * dy > TRAIL_TELEPORT_DISTANCE * TRAIL_TELEPORT_DISTANCE {
+                trail.positions.clear();
+            }
+        }
+
+        trail.positions.push_back(position);
+        while trail.positions.len() > length {
+            trail.positions.pop_front();
+        }
+    }
+
+    fn get(&self, id: EntityId) -> Option<&VecDeque<[f32; 2]>> {
+        self.entries.get(&id).map(|trail| &trail.positions)
+    }
+
+    fn set_length(&mut self, length: usize) {
+        self.length = length;
+        for trail in self.entries.values_mut() {
+            while trail.positions.len() > length {
+                trail.positions.pop_front();
+            }
+        }
+    }
+
+    /// Drop trails for entities that weren't recorded since the last sweep
+    fn evict_unused(&mut self) {
+        self.entries.retain(|_, trail| trail.used);
+        for trail in self.entries.values_mut() {
+            trail.used = false;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Squash-and-stretch for circles that take a hard hit. Purely visual: only the
+/// drawn instance deforms, physics never sees it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImpactConfig {
+    pub enabled: bool,
+    pub threshold: f32, // Impulses at or below this leave the circle round
+    pub squash_per_impulse: f32, // Squash gained per unit of impulse above the threshold
+    pub max_squash: f32, // Largest fraction a circle flattens along the normal
+    pub duration: Duration, // Time to spring back to round
+}
+
+impl Default for ImpactConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.05,
+            squash_per_impulse: 0.25,
+            max_squash: 0.35,
+            duration: Duration::from_millis(100),
+        }
+    }
+}
+
+/// How flattened a circle still is `elapsed` into an impact that started at
+/// `squash`. Eases out to 0 at `duration`.
+pub fn impact_squash(squash: f32, elapsed: Duration, duration: Duration) -> f32 {
+    if elapsed >= duration || duration.is_zero() {
+        return 0.0;
+    }
+    let remaining = 1.0 - elapsed.as_secs_f32() / duration.as_secs_f32();
+    squash * remaining * remaining
+}
+
+/// The most recent hard hit on one circle
+struct Impact {
+    angle: f32, // Direction of the contact normal (radians)
+    squash: f32,
+    start: Instant,
+}
+
+/// Running impact deformations keyed by entity id like `TrailCache`
+struct ImpactCache {
+    entries: HashMap<EntityId, Impact>,
+    duration: Duration,
+    now: Instant, // Frame time deformations are evaluated at, set by `advance`
+}
+
+impl ImpactCache {
+    fn new(duration: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            duration,
+            now: Instant::now(),
+        }
+    }
+
+    /// Start a deformation for `id`, unless the one already running is still
+    /// stronger. Overlapping hits never add up.
+    fn record(&mut self, id: EntityId, normal: [f32; 2], squash: f32, now: Instant) {
+        if let Some(current) = self.entries.get(&id)
+            && impact_squash(current.squash, now - current.start, self.duration) >= squash
+        {
+            return;
+        }
+
+        self.entries.insert(
+            id,
+            Impact {
+                angle: normal[1].atan2(normal[0]),
+                squash,
+                start: now,
+            },
+        );
+    }
+
+    /// Normal angle and squash for `id` as of the current frame, if it's still deformed
+    fn get(&self, id: EntityId) -> Option<(f32, f32)> {
+        let impact = self.entries.get(&id)?;
+        let elapsed = self.now.saturating_duration_since(impact.start);
+        let squash = impact_squash(impact.squash, elapsed, self.duration);
+        (squash > 0.0).then_some((impact.angle, squash))
+    }
+
+    /// Move to a new frame's time and drop deformations that have run out
+    fn advance(&mut self, now: Instant) {
+        self.now = now;
+        let duration = self.duration;
+        self.entries
+            .retain(|_, impact| now - impact.start < duration);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Brief color flash on entities that just collided, fading back to their own color
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlashConfig {
+    pub enabled: bool,
+    pub color: [f32; 3],            // sRGB highlight blended toward
+    pub intensity_per_impulse: f32, // Blend amount per unit of impulse, capped at 1
+    pub duration: Duration,         // Time to fade out completely
+}
+
+impl Default for FlashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: [1.0, 1.0, 1.0],
+            intensity_per_impulse: 1.0,
+            duration: Duration::from_millis(200),
+        }
+    }
+}
+
+/// How much of the flash color is left `elapsed` into a flash that started at
+/// `intensity`. Fades linearly to 0 at `duration`.
+pub fn flash_intensity(intensity: f32, elapsed: Duration, duration: Duration) -> f32 {
+    if elapsed >= duration || duration.is_zero() {
+        return 0.0;
+    }
+    intensity * (1.0 - elapsed.as_secs_f32() / duration.as_secs_f32())
+}
+
+/// The latest flash on one entity
+struct Flash {
+    intensity: f32,
+    start: Instant,
+}
+
+/// Running flashes keyed by entity id, evaluated at the current frame like `ImpactCache`
+struct FlashCache {
+    entries: HashMap<EntityId, Flash>,
+    color: [f32; 3],
+    duration: Duration,
+    now: Instant,
+}
+
+impl FlashCache {
+    fn new(config: &FlashConfig) -> Self {
+        Self {
+            entries: HashMap::new(),
+            color: config.color,
+            duration: config.duration,
+            now: Instant::now(),
+        }
+    }
+
+    /// Restart the flash on `id`. A hit during the fade starts over at full
+    /// intensity instead of adding to what's left.
+    fn record(&mut self, id: EntityId, intensity: f32, now: Instant) {
+        let flash = self.entries.entry(id).or_insert(Flash {
+            intensity: 0.0,
+            start: now,
+        });
+        flash.intensity = flash.intensity.max(intensity);
+        flash.start = now;
+    }
+
+    /// `color` blended toward the flash color if `id` is still flashing
+    fn apply(&self, id: EntityId, color: [f32; 3]) -> [f32; 3] {
+        let Some(flash) = self.entries.get(&id) else {
+            return color;
+        };
+        let elapsed = self.now.saturating_duration_since(flash.start);
+        let t = flash_intensity(flash.intensity, elapsed, self.duration);
+        self.blend(color, t)
+    }
+
+    /// `color` blended `t` of the way toward the flash color
+    fn blend(&self, color: [f32; 3], t: f32) -> [f32; 3] {
+        [
+            color[0] + (self.color[0] - color[0]) * t,
+            color[1] + (self.color[1] - color[1]) * t,
+            color[2] + (self.color[2] - color[2]) * t,
+        ]
+    }
+
+    fn advance(&mut self, now: Instant) {
+        self.now = now;
+        let duration = self.duration;
+        self.entries.retain(|_, flash| now - flash.start < duration);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Blend toward the flash color for hovered clickables unless `set_hover_tint` says otherwise
+const DEFAULT_HOVER_TINT: f32 = 0.15;
+
+/// Render-side reactions to collisions, fed by `Renderer::record_physics_event`
+struct CollisionEffects {
+    impacts: ImpactCache,
+    flashes: FlashCache,
+    hover_tint: f32, // Steady blend toward the flash color for hovered clickables
+}
+
+/// Soft dark ellipses on a ground line under circles. Purely visual: a circle
+/// resting on the ground gets a tight dark shadow, a high one a faint, spread one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowConfig {
+    pub enabled: bool,
+    pub ground: f32, // y (NDC) the shadows lie on; circles centered below it cast none
+    pub max_height: f32, // Height above the ground (NDC) where shadows have faded out
+    pub opacity: f32, // Opacity of the shadow under a circle touching the ground
+    pub offset: f32, // Sideways shift per unit of height, as from a low light
+    pub spread: f32, // Extra shadow size at `max_height`, as a fraction of the radius
+    pub squash: f32, // Shadow height as a fraction of its width
+    pub softness: f32, // Edge softness multiplier, like `Entity::with_edge_softness`
+    pub color: [f32; 3], // sRGB
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ground: -1.0,
+            max_height: 1.5,
+            opacity: 0.5,
+            offset: 0.2,
+            spread: 1.0,
+            squash: 0.25,
+            softness: 6.0,
+            color: [0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// Where and how strongly a circle's shadow is drawn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowShape {
+    pub offset: f32, // Sideways shift from the circle's x (NDC)
+    pub opacity: f32,
+    pub scale: f32, // Size relative to the circle
+}
+
+/// Shadow of a circle whose bottom is `height` above the ground, or None if it
+/// has faded out completely. Negative heights (overlapping the ground) count as touching.
+pub fn shadow_shape(height: f32, config: &ShadowConfig) -> Option<ShadowShape> {
+    let t = if config.max_height > 0.0 {
+        (height / config.max_height).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let opacity = config.opacity * (1.0 - t);
+    if opacity <= 0.0 {
+        return None;
+    }
+    Some(ShadowShape {
+        offset: config.offset * height.max(0.0),
+        opacity,
+        scale: 1.0 + config.spread * t,
+    })
+}
+
+bitflags::bitflags! {
+    /// Which debug overlays the renderer draws
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct DebugDrawFlags: u32 {
+        const CONTACTS = 1 << 0;   // Contact points and normals from the last physics update
+        const AABBS = 1 << 1;      // Entity bounding boxes
+        const VELOCITIES = 1 << 2; // Velocity arrows
+        const GRAVITY_ZONES = 1 << 3; // Gravity zone outlines, with a line along their gravity
+    }
+}
+
+/// What fills the screen before anything else is drawn. Colors are sRGB, as they appear on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+    Solid([f32; 4]),
+    Gradient { top: [f32; 4], bottom: [f32; 4] },
+}
+
+/// Startup options for the renderer
+#[derive(Clone, Copy, Debug)]
+pub struct RendererConfig {
+    pub background: Background,
+    pub sample_count: u32, // MSAA samples: 1 (off) or 4
+    pub present_mode: wgpu::PresentMode,
+    pub adapter: RendererOptions,
+    pub grid: GridConfig,
+    pub impacts: ImpactConfig,
+    pub flash: FlashConfig,
+    pub shadows: ShadowConfig,
+    pub stats_in_title: bool, // Also show compact stats in the window title
+    pub render_scale: f32, // Shapes render at this fraction of the window size, text stays native
+    pub letterbox: Option<Letterbox>, // None stretches the world over the whole window
+    pub stats_style: StatsStyle,
+    pub draw_order: DrawOrder,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            background: Background::Solid([0.0, 0.0, 0.0, 1.0]),
+            sample_count: 1,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            adapter: RendererOptions::default(),
+            grid: GridConfig::default(),
+            impacts: ImpactConfig::default(),
+            flash: FlashConfig::default(),
+            shadows: ShadowConfig::default(),
+            stats_in_title: false,
+            render_scale: 1.0,
+            letterbox: None,
+            stats_style: StatsStyle::default(),
+            draw_order: DrawOrder::default(),
+        }
+    }
+}
+
+/// Keeps the world at a fixed aspect ratio, centered in the window with bars around it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Letterbox {
+    pub aspect: f32,         // Width / height of the world region
+    pub bar_color: [f32; 4], // sRGB
+}
+
+impl Default for Letterbox {
+    fn default() -> Self {
+        Self {
+            aspect: 1.0,
+            bar_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Pixel rectangle (origin top-left) that the world's NDC square is drawn into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldViewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl WorldViewport {
+    /// The whole window
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+        }
+    }
+
+    /// Largest region of `aspect` (width / height) centered in the window
+    pub fn letterboxed(width: u32, height: u32, aspect: f32) -> Self {
+        let (window_width, window_height) = (width as f32, height as f32);
+        if window_height <= 0.0 || aspect <= 0.0 || !aspect.is_finite() {
+            return Self::full(width, height);
+        }
+        let (width, height) = if window_width / window_height > aspect {
+            (window_height * aspect, window_height) // Bars left and right
+        } else {
+            (window_width, window_width / aspect) // Bars top and bottom
+        };
+        Self {
+            x: (window_width - width) / 2.0,
+            y: (window_height - height) / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Letterboxed region if `aspect` is set, otherwise the whole window
+    pub fn for_window(width: u32, height: u32, aspect: Option<f32>) -> Self {
+        match aspect {
+            Some(aspect) => Self::letterboxed(width, height, aspect),
+            None => Self::full(width, height),
+        }
+    }
+
+    /// Pixel position of an NDC position
+    pub fn ndc_to_pixel(&self, position: [f32; 2]) -> (f32, f32) {
+        (
+            self.x + (position[0] + 1.0) / 2.0 * self.width,
+            self.y + (1.0 - position[1]) / 2.0 * self.height,
+        )
+    }
+
+    /// Clip rectangle for text drawn in the world
+    fn text_bounds(&self) -> glyphon::TextBounds {
+        glyphon::TextBounds {
+            left: self.x.floor() as i32,
+            top: self.y.floor() as i32,
+            right: (self.x + self.width).ceil() as i32,
+            bottom: (self.y + self.height).ceil() as i32,
+        }
+    }
+
+    /// NDC position of a pixel, or None if it lies outside the region (in a bar)
+    pub fn pixel_to_ndc(&self, x: f32, y: f32) -> Option<[f32; 2]> {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return None;
+        }
+        let u = (x - self.x) / self.width;
+        let v = (y - self.y) / self.height;
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return None;
+        }
+        Some([u * 2.0 - 1.0, 1.0 - v * 2.0])
+    }
+}
+
+/// World-space reference grid drawn behind every shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridConfig {
+    pub enabled: bool,
+    pub cell_size: f32,   // World units between lines
+    pub major_every: u32, // Every Nth line is a major line (0 = no major lines)
+    pub color: [f32; 3],
+    pub minor_alpha: f32,
+    pub major_alpha: f32,
+    pub thickness: f32, // NDC
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cell_size: 0.1,
+            major_every: 5,
+            color: [0.5, 0.5, 0.5],
+            minor_alpha: 0.15,
+            major_alpha: 0.4,
+            thickness: 0.003,
+        }
+    }
+}
+
+/// Which GPU the renderer asks wgpu for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RendererOptions {
+    pub power_preference: wgpu::PowerPreference,
+    pub force_fallback_adapter: bool,     // Software adapter only
+    pub backends: Option<wgpu::Backends>, // None = every backend wgpu supports
+}
+
+impl Default for RendererOptions {
+    fn default() -> Self {
+        Self {
+            // Prefer the discrete GPU on dual-GPU machines
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            backends: None,
+        }
+    }
+}
+
+impl RendererOptions {
+    fn instance_descriptor(&self) -> wgpu::InstanceDescriptor {
+        wgpu::InstanceDescriptor {
+            backends: self.backends.unwrap_or(wgpu::Backends::all()),
+            ..Default::default()
+        }
+    }
+
+    fn adapter_options<'a>(
+        &self,
+        compatible_surface: Option<&'a wgpu::Surface<'a>>,
+    ) -> wgpu::RequestAdapterOptions<'a, 'a> {
+        wgpu::RequestAdapterOptions {
+            power_preference: self.power_preference,
+            force_fallback_adapter: self.force_fallback_adapter,
+            compatible_surface,
+        }
+    }
+}
+
+// What the renderer needs from the device. Downlevel limits keep older and software
+// adapters working; texture size limits follow the adapter so large windows fit.
+const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::empty();
+// Used when the adapter has them
+const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TIMESTAMP_QUERY;
+
+fn required_limits(adapter: &wgpu::Adapter) -> wgpu::Limits {
+    wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits())
+}
+
+/// Why a renderer couldn't be created
+#[derive(Debug)]
+pub enum InitError {
+    Surface(wgpu::CreateSurfaceError),
+    NoAdapter(wgpu::RequestAdapterError),
+    MissingFeatures(wgpu::Features),
+    InsufficientLimits,
+    Device(wgpu::RequestDeviceError),
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitError::Surface(err) => write!(f, "could not create a window surface: {err}"),
+            InitError::NoAdapter(err) => write!(f, "no suitable GPU adapter found: {err}"),
+            InitError::MissingFeatures(features) => {
+                write!(f, "GPU adapter is missing required features: {features:?}")
+            }
+            InitError::InsufficientLimits => {
+                write!(f, "GPU adapter does not meet the required limits")
+            }
+            InitError::Device(err) => write!(f, "could not open the GPU device: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// Open a device on `adapter` with the renderer's explicit features and limits,
+/// checking them first so a weak adapter gets a readable error
+async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), InitError> {
+    let info = adapter.get_info();
+    println!("Using adapter: {} ({:?})", info.name, info.backend);
+
+    let missing = REQUIRED_FEATURES - adapter.features();
+    if !missing.is_empty() {
+        return Err(InitError::MissingFeatures(missing));
+    }
+    let limits = required_limits(adapter);
+    if !limits.check_limits(&adapter.limits()) {
+        return Err(InitError::InsufficientLimits);
+    }
+
+    adapter
+        .request_device(&wgpu::DeviceDescriptor {
+            label: Some("Renderer Device"),
+            required_features: REQUIRED_FEATURES | (OPTIONAL_FEATURES & adapter.features()),
+            required_limits: limits,
+            ..Default::default()
+        })
+        .await
+        .map_err(InitError::Device)
+}
+
+/// How instance colors are chosen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    Static,             // Each shape's own color
+    Speed { max: f32 }, // Blue when still, red at `max` speed (NDC/s) and above
+}
+
+// Speed used when cycling into ColorMode::Speed
+const DEFAULT_SPEED_COLOR_MAX: f32 = 2.0;
+
+/// Back-to-front order shapes are drawn in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawOrder {
+    /// Order entities were added, ignoring layers
+    Insertion,
+    /// By layer, insertion order within a layer
+    #[default]
+    Layer,
+    /// By layer, then lower entities on top for pseudo-depth
+    YSorted,
+}
+
+// Gradient stops for ColorMode::Speed, slow to fast
+const SPEED_GRADIENT: [[f32; 3]; 3] = [[0.1, 0.3, 1.0], [1.0, 0.9, 0.2], [1.0, 0.1, 0.1]];
+
+/// Map a speed onto the slow → fast gradient. Speeds above `max` clamp to the
+/// fast end; a non-positive `max` treats any motion as fast.
+pub fn speed_color(speed: f32, max: f32) -> [f32; 3] {
+    let t = if max > 0.0 {
+        (speed / max).clamp(0.0, 1.0)
+    } else if speed > 0.0 {
+        1.0
+    } else {
+        0.0
+    };
+
+    let scaled = t * (SPEED_GRADIENT.len() - 1) as f32;
+    let i = (scaled as usize).min(SPEED_GRADIENT.len() - 2);
+    let f = scaled - i as f32;
+    let (a, b) = (SPEED_GRADIENT[i], SPEED_GRADIENT[i + 1]);
+    [
+        a[0] + (b[0] - a[0]) * f,
+        a[1] + (b[1] - a[1]) * f,
+        a[2] + (b[2] - a[2]) * f,
+    ]
+}
+
+// Default halo reach past a circle's radius, as a fraction of the radius
+const DEFAULT_GLOW_EXTENT: f32 = 0.5;
+
+// Shape shader embedded at build time; dev-tools builds also watch the file
+const SHAPE_SHADER_SOURCE: &str = include_str!("../../shader.wgsl");
+#[cfg(feature = "dev-tools")]
+const SHAPE_SHADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl");
+
+// Allowed range for `Renderer::set_render_scale`
+const MIN_RENDER_SCALE: f32 = 0.5;
+const MAX_RENDER_SCALE: f32 = 2.0;
+
+// Window title, and the prefix of the title stats
+const WINDOW_TITLE: &str = "balltest";
+
+// Rings drawn around `World::selected` entities (NDC)
+const SELECTION_RING_GAP: f32 = 0.01;
+const SELECTION_RING_THICKNESS: f32 = 0.006;
+const SELECTION_RING_COLOR: [f32; 3] = [1.0, 0.85, 0.2];
+
+/// Window corner the stats overlay is anchored to
+pub type StatsCorner = Corner;
+
+/// Look of the stats overlay. Colors are sRGB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsStyle {
+    pub font_size: f32,   // Pixels
+    pub line_height: f32, // Pixels
+    pub color: [f32; 3],
+    pub corner: StatsCorner,
+    pub margin: f32, // Distance (pixels) between the text and the window edges
+    pub background: Option<[f32; 4]>, // Box behind the text for busy scenes, None for none
+}
+
+impl Default for StatsStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 24.0,
+            line_height: 32.0,
+            color: [1.0, 1.0, 160.0 / 255.0],
+            corner: StatsCorner::BottomRight,
+            margin: 12.0,
+            background: None,
+        }
+    }
+}
+
+impl StatsStyle {
+    /// Top-left pixel of a block of stats text of `extent` in a window of `size`
+    pub fn text_position(
+        &self,
+        size: winit::dpi::PhysicalSize<u32>,
+        extent: (f32, f32),
+    ) -> (f32, f32) {
+        let (left, top) = anchor_position(self.corner, size, extent, [self.margin, self.margin]);
+        (left.round(), top.round())
+    }
+
+    fn metrics(&self) -> glyphon::Metrics {
+        glyphon::Metrics::new(self.font_size, self.line_height)
+    }
+
+    fn text_color(&self) -> glyphon::Color {
+        glyphon::Color::rgb(
+            (self.color[0] * 255.0).round() as u8,
+            (self.color[1] * 255.0).round() as u8,
+            (self.color[2] * 255.0).round() as u8,
+        )
+    }
+}
+
+pub struct FrameStats {
+    pub last_present: Instant,
+    pub frame_time_accum: Duration,
+    pub frame_count: u32,
+    pub avg_frame_time_ms: f32,
+    pub present_fps: u32,
+
+    pub sim_steps_accum: u32,
+    pub sim_tps: u32,
+
+    pub render_count: u32,
+    pub render_fps: u32,
+
+    // GPU time per frame from timestamp queries; None when unsupported or not yet measured
+    pub gpu_time_accum_ms: f32,
+    pub gpu_time_count: u32,
+    pub avg_gpu_time_ms: Option<f32>,
+
+    // Latest world/physics numbers, shown at the next report
+    pub latest_entity_count: usize,
+    pub latest_physics: PhysicsDiagnostics,
+    pub latest_culled: usize,
+    pub entity_count: usize,
+    pub physics: PhysicsDiagnostics,
+    pub culled: usize, // Shapes and text skipped in the last frame for being offscreen
+
+    pub last_report: Instant,
+    pub report_dt: Duration,
+}
+
+impl FrameStats {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_present: now,
+            frame_time_accum: Duration::ZERO,
+            frame_count: 0,
+            avg_frame_time_ms: 0.0,
+            present_fps: 0,
+            sim_steps_accum: 0,
+            sim_tps: 0,
+            render_count: 0,
+            render_fps: 0,
+            gpu_time_accum_ms: 0.0,
+            gpu_time_count: 0,
+            avg_gpu_time_ms: None,
+            latest_entity_count: 0,
+            latest_physics: PhysicsDiagnostics::default(),
+            latest_culled: 0,
+            entity_count: 0,
+            physics: PhysicsDiagnostics::default(),
+            culled: 0,
+            last_report: now,
+            report_dt: Duration::from_secs(1),
+        }
+    }
+
+    pub fn needs_update(&self) -> bool {
+        Instant::now() - self.last_report >= self.report_dt
+    }
+
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let elapsed = now - self.last_report;
+        let secs = elapsed.as_secs_f32();
+
+        if self.frame_count > 0 {
+            let avg_dt = self.frame_time_accum.as_secs_f32() / self.frame_count as f32;
+            self.avg_frame_time_ms = avg_dt * 1000.0;
+            self.present_fps = (1.0 / avg_dt).round() as u32;
+        }
+
+        self.sim_tps = (self.sim_steps_accum as f32 / secs).round() as u32;
+        self.render_fps = (self.render_count as f32 / secs).round() as u32;
+        if self.gpu_time_count > 0 {
+            self.avg_gpu_time_ms = Some(self.gpu_time_accum_ms / self.gpu_time_count as f32);
+        }
+        self.entity_count = self.latest_entity_count;
+        self.physics = self.latest_physics;
+        self.culled = self.latest_culled;
+
+        self.frame_time_accum = Duration::ZERO;
+        self.frame_count = 0;
+        self.sim_steps_accum = 0;
+        self.render_count = 0;
+        self.gpu_time_accum_ms = 0.0;
+        self.gpu_time_count = 0;
+        self.last_report = now;
+    }
+
+    pub fn record_frame(&mut self, dt: Duration) {
+        self.frame_time_accum += dt;
+        self.frame_count += 1;
+    }
+
+    /// GPU time of one frame, read back from timestamp queries
+    pub fn record_gpu_time(&mut self, ms: f32) {
+        self.gpu_time_accum_ms += ms;
+        self.gpu_time_count += 1;
+    }
+
+    /// Store the latest world numbers; they appear in the overlay at the next report
+    pub fn record_physics(&mut self, entity_count: usize, diagnostics: &PhysicsDiagnostics) {
+        self.latest_entity_count = entity_count;
+        self.latest_physics = *diagnostics;
+    }
+
+    /// Overlay text for the last report interval
+    pub fn summary(&self) -> String {
+        let gpu_time = match self.avg_gpu_time_ms {
+            Some(ms) => format!("{ms:5.2} ms"),
+            None => "  n/a".to_string(),
+        };
+        format!(
+            "Frame:    {:5.2} ms ({:3} fps)\nGPU time: {}\nSim:      {:3} ticks/s\nRender:   {:3} fps\n\
+             Entities: {:5}\nCulled:   {:5}\nContacts: {:5}\nSleeping: {:5}\nKinetic:  {:8.3}",
+            self.avg_frame_time_ms,
+            self.present_fps,
+            gpu_time,
+            self.sim_tps,
+            self.render_fps,
+            self.entity_count,
+            self.culled,
+            self.physics.contacts,
+            self.physics.sleeping,
+            self.physics.kinetic_energy,
+        )
+    }
+
+    /// One-line version of the summary for the window title
+    pub fn title_summary(&self) -> String {
+        format!(
+            "{WINDOW_TITLE} — {} fps / {} tps / {} entities",
+            self.render_fps, self.sim_tps, self.entity_count
+        )
+    }
+}
+
+/// What happened to a frame passed to `Renderer::render`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameOutcome {
+    Presented,
+    Skipped(SkipReason),
+}
+
+/// Why a frame was skipped. Skips are recoverable; rendering resumes on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    SurfaceOutdated,    // Surface changed (resize etc.), reconfigured for the next frame
+    SurfaceLost,        // Swapchain lost, reconfigured for the next frame
+    Timeout,            // No frame available in time
+    SurfaceUnavailable, // Generic acquire failure, retried next frame
+    Minimized,          // Window has no area; rendering resumes once it's resized back
+}
+
+/// Failures the renderer can't recover from
+#[derive(Debug)]
+pub enum RenderError {
+    Surface(wgpu::SurfaceError),
+    Readback(wgpu::BufferAsyncError), // Mapping a readback buffer failed
+    Poll(wgpu::PollError),            // Waiting on the GPU failed, e.g. the device was lost
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Surface(err) => write!(f, "surface error: {err}"),
+            RenderError::Readback(err) => write!(f, "could not read back a frame: {err}"),
+            RenderError::Poll(err) => write!(f, "waiting on the GPU failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// Offscreen color target the scene renders into when the render scale isn't 1,
+/// then gets stretched over the window by the blit pipeline
+struct ScaledTarget {
+    size: winit::dpi::PhysicalSize<u32>,
+    view: wgpu::TextureView,
+    msaa_view: Option<wgpu::TextureView>, // Resolves into `view` when MSAA is on
+    bind_group: wgpu::BindGroup,          // `view` for the blit pass to sample
+}
+
+/// Windowed size and position saved while the window is fullscreen, so leaving
+/// fullscreen puts it back where it was
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FullscreenState {
+    windowed: Option<(
+        winit::dpi::PhysicalSize<u32>,
+        Option<winit::dpi::PhysicalPosition<i32>>, // None where the platform can't report it
+    )>,
+}
+
+impl FullscreenState {
+    /// Remember the windowed geometry. Entering twice keeps the first one, since the
+    /// second would record the fullscreen size.
+    pub fn enter(
+        &mut self,
+        size: winit::dpi::PhysicalSize<u32>,
+        position: Option<winit::dpi::PhysicalPosition<i32>>,
+    ) {
+        if self.windowed.is_none() {
+            self.windowed = Some((size, position));
+        }
+    }
+
+    /// Windowed geometry to restore, if any was saved
+    pub fn exit(
+        &mut self,
+    ) -> Option<(
+        winit::dpi::PhysicalSize<u32>,
+        Option<winit::dpi::PhysicalPosition<i32>>,
+    )> {
+        self.windowed.take()
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.windowed.is_some()
+    }
+}
+
+// Progress of a GPU readback shared by the timer and the picker
+enum ReadbackState {
+    Free,
+    Copied,
+    Mapping(Arc<OnceLock<bool>>), // Set by map_async, true on success
+}
+
+/// Polls a shader file's modification time, at most once per `interval`
+#[cfg(feature = "dev-tools")]
+struct ShaderWatcher {
+    path: PathBuf,
+    modified: Option<std::time::SystemTime>,
+    last_check: Instant,
+    interval: Duration,
+}
+
+#[cfg(feature = "dev-tools")]
+impl ShaderWatcher {
+    fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            modified: std::fs::metadata(&path).and_then(|m| m.modified()).ok(),
+            path,
+            last_check: Instant::now(),
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// New contents of the file if it changed since the last check
+    fn poll(&mut self, now: Instant) -> Option<String> {
+        if now - self.last_check < self.interval {
+            return None;
+        }
+        self.last_check = now;
+
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        match std::fs::read_to_string(&self.path) {
+            Ok(source) => Some(source),
+            Err(err) => {
+                println!("WARNING: failed to read {}: {err}", self.path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Where frames end up: a window's swapchain or an offscreen texture
+enum RenderTarget {
+    Window {
+        window: Arc<Window>,
+        surface: wgpu::Surface<'static>,
+        supported_present_modes: Vec<wgpu::PresentMode>,
+        usage: wgpu::TextureUsages, // Includes COPY_SRC when frames can be recorded
+    },
+    Offscreen {
+        texture: wgpu::Texture,
+    },
+}
+
+pub struct Renderer {
+    target: RenderTarget,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    surface_format: wgpu::TextureFormat,
+    // Format of the view we render into; pipelines and the text atlas must all match it
+    view_format: wgpu::TextureFormat,
+    present_mode: wgpu::PresentMode,
+    adapter_info: wgpu::AdapterInfo,
+    pub size: winit::dpi::PhysicalSize<u32>,
+    // Set while the window is zero-sized (minimized); `size` keeps the last real size
+    minimized: bool,
+    fullscreen: FullscreenState,
+
+    // Globals uniform, rewritten every frame
+    globals: Globals,
+    globals_buffer: wgpu::Buffer,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture_sampler: wgpu::Sampler,
+    resources: PassResources,
+    // Prepared and drawn in order every frame; later passes draw on top
+    passes: Vec<Box<dyn RenderPass>>,
+    // Rings requested for this frame only (selection highlights etc.), drained by render
+    overlay_rings: Vec<CircleInstance>,
+    // Entity indices sorted by `draw_order_mode`, reused every frame
+    draw_order: Vec<usize>,
+    draw_order_mode: DrawOrder,
+    // World the per-entity caches below (and the text caches) belong to
+    world: Option<WorldId>,
+
+    // Motion trails, recorded per sim step rather than per frame
+    trails_enabled: bool,
+    trails: TrailCache,
+    color_mode: ColorMode,
+    show_labels: bool,
+    impact_config: ImpactConfig,
+    flash_config: FlashConfig,
+    shadows: ShadowConfig,
+    effects: CollisionEffects,
+
+    // MSAA; the multisampled target is None when sample_count is 1
+    sample_count: u32,
+    msaa_view: Option<wgpu::TextureView>,
+
+    // Resolution scaling; the scene target only exists while the scale isn't 1
+    render_scale: f32,
+    scaled_target: Option<ScaledTarget>,
+    blit_pipeline: wgpu::RenderPipeline,
+
+    // Background; the gradient pipeline only exists once a gradient or letterbox is requested
+    background: Background,
+    letterbox: Option<Letterbox>,
+    gradient_pipeline: Option<wgpu::RenderPipeline>,
+    gradient_buffer: Option<wgpu::Buffer>,
+
+    // Debug overlays
+    grid: GridConfig,
+    // Lines requested for this frame only via draw_line, drained by render
+    frame_lines: Vec<LineInstance>,
+    contact_lines: Vec<LineInstance>,
+    debug_flags: DebugDrawFlags,
+    velocity_arrow_scale: f32,
+
+    // Stats overlay; the text is handed to the stats pass on the next frame it changes
+    stats_text: String,
+    text_dirty: bool,
+    show_stats: bool,
+    stats_style: StatsStyle,
+    stats_in_title: bool,
+
+    // Frame sequence export; None when not recording
+    recorder: Option<FrameRecorder>,
+    // None when the adapter has no timestamp queries
+    gpu_timer: Option<GpuTimer>,
+    // None while GPU picking is off
+    picker: Option<GpuPicker>,
+    #[cfg(feature = "dev-tools")]
+    shader_watcher: ShaderWatcher,
+    // Returned by the next frame acquisition instead of a texture
+    #[cfg(test)]
+    simulated_surface_error: Option<wgpu::SurfaceError>,
+
+    pub frame_stats: FrameStats,
+}
+
+impl Renderer {
+    pub async fn new(window: Arc<Window>, config: RendererConfig) -> Result<Self, InitError> {
+        let instance = wgpu::Instance::new(&config.adapter.instance_descriptor());
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(InitError::Surface)?;
+        let adapter = instance
+            .request_adapter(&config.adapter.adapter_options(Some(&surface)))
+            .await
+            .map_err(InitError::NoAdapter)?;
+        let (device, queue) = request_device(&adapter).await?;
+
+        let size = window.inner_size();
+        let cap = surface.get_capabilities(&adapter);
+        let surface_format = cap.formats[0];
+
+        let target = RenderTarget::Window {
+            window,
+            surface,
+            supported_present_modes: cap.present_modes,
+            // Recording copies frames out of the swapchain where the surface allows it
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (cap.usages & wgpu::TextureUsages::COPY_SRC),
+        };
+        Ok(Self::with_target(
+            &adapter,
+            device,
+            queue,
+            target,
+            surface_format,
+            size,
+            config,
+        ))
+    }
+
+    /// Renderer drawing into an offscreen texture instead of a window, for tests and
+    /// image generation. Read the result back with `read_pixels`.
+    pub async fn new_headless(
+        width: u32,
+        height: u32,
+        config: RendererConfig,
+    ) -> Result<Self, InitError> {
+        let instance = wgpu::Instance::new(&config.adapter.instance_descriptor());
+        let adapter = instance
+            .request_adapter(&config.adapter.adapter_options(None))
+            .await
+            .map_err(InitError::NoAdapter)?;
+        let (device, queue) = request_device(&adapter).await?;
+
+        let size = winit::dpi::PhysicalSize::new(width.max(1), height.max(1));
+        let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+        let target = RenderTarget::Offscreen {
+            texture: create_offscreen_texture(&device, format, size),
+        };
+        Ok(Self::with_target(
+            &adapter, device, queue, target, format, size, config,
+        ))
+    }
+
+    /// Shared setup for every target: pipelines, buffers and text rendering
+    fn with_target(
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target: RenderTarget,
+        surface_format: wgpu::TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+        config: RendererConfig,
+    ) -> Self {
+        let view_format = surface_format.add_srgb_suffix();
+        let present_mode = match &target {
+            RenderTarget::Window {
+                supported_present_modes,
+                ..
+            } => choose_present_mode(config.present_mode, supported_present_modes),
+            RenderTarget::Offscreen { .. } => config.present_mode,
+        };
+
+        // Fall back to no MSAA if the adapter can't multisample this format
+        let format_features = adapter.get_texture_format_features(view_format);
+        let sample_count = if format_features
+            .flags
+            .sample_count_supported(config.sample_count)
+        {
+            config.sample_count
+        } else {
+            println!(
+                "WARNING: {}x MSAA not supported for {:?}, using 1x",
+                config.sample_count, view_format
+            );
+            1
+        };
+        let multisample = wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        };
+
+        // Per-frame globals shared by the shape shaders at group 0
+        let globals_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Globals Buffer"),
+            size: std::mem::size_of::<Globals>() as u64,
+            // Tests copy it out to check what the shaders see
+            usage: wgpu::BufferUsages::UNIFORM
+                | wgpu::BufferUsages::COPY_DST
+                | if cfg!(test) {
+                    wgpu::BufferUsages::COPY_SRC
+                } else {
+                    wgpu::BufferUsages::empty()
+                },
+            mapped_at_creation: false,
+        });
+        let globals_bind_group_layout = create_globals_layout(&device);
+        let globals_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Globals Bind Group"),
+            layout: &globals_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: globals_buffer.as_entire_binding(),
+            }],
+        });
+
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(QUAD_VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let blit_pipeline = create_blit_pipeline(
+            &device,
+            view_format,
+            sample_count,
+            &texture_bind_group_layout,
+        );
+
+        // Text rendering setup
+        let mut font_system = glyphon::FontSystem::new();
+        let swash_cache = glyphon::SwashCache::new();
+        let cache = glyphon::Cache::new(&device);
+        let viewport = glyphon::Viewport::new(&device, &cache);
+        // glyphon colors are sRGB too; Accurate linearizes them for sRGB views
+        let text_color_mode = if view_format.is_srgb() {
+            glyphon::ColorMode::Accurate
+        } else {
+            glyphon::ColorMode::Web
+        };
+        let mut atlas = glyphon::TextAtlas::with_color_mode(
+            &device,
+            &queue,
+            &cache,
+            view_format,
+            text_color_mode,
+        );
+
+        // Draw order: grid on the background, shapes, lines, entity text and labels, stats
+        let passes: Vec<Box<dyn RenderPass>> = vec![
+            Box::new(GridPass {
+                lines: LinePass::new(&device, view_format, multisample),
+            }),
+            Box::new(ShapePass::new(
+                &device,
+                view_format,
+                multisample,
+                &globals_bind_group_layout,
+                &texture_bind_group_layout,
+            )),
+            Box::new(DebugLinePass {
+                lines: LinePass::new(&device, view_format, multisample),
+            }),
+            Box::new(TextPass::new(&device, &mut atlas, multisample)),
+            Box::new(StatsPass::new(
+                &device,
+                &mut font_system,
+                &mut atlas,
+                view_format,
+                multisample,
+                &globals_bind_group_layout,
+                size,
+                config.stats_style,
+            )),
+        ];
+
+        let gpu_timer = GpuTimer::new(&device, &queue);
+
+        let mut renderer = Self {
+            target,
+            device,
+            queue,
+            surface_format,
+            view_format,
+            present_mode,
+            adapter_info: adapter.get_info(),
+            size,
+            minimized: is_zero_sized(size),
+            fullscreen: FullscreenState::default(),
+            globals: Globals {
+                time: 0.0,
+                edge_pulse: 0.0,
+                screen_size: [size.width as f32, size.height as f32],
+                camera_position: [0.0, 0.0],
+                camera_zoom: 1.0,
+                edge_softness: 1.0,
+                glow: 0.0,
+                glow_extent: DEFAULT_GLOW_EXTENT,
+            },
+            globals_buffer,
+            texture_bind_group_layout,
+            texture_sampler,
+            resources: PassResources {
+                vertex_buffer,
+                globals_bind_group,
+                textures: Vec::new(),
+                text: TextResources {
+                    cache: TextCache::new(),
+                    font_system,
+                    swash_cache,
+                    viewport,
+                    atlas,
+                    atlas_format: view_format,
+                },
+            },
+            passes,
+            overlay_rings: Vec::new(),
+            draw_order: Vec::new(),
+            draw_order_mode: config.draw_order,
+            world: None,
+            trails_enabled: false,
+            trails: TrailCache::new(DEFAULT_TRAIL_LENGTH),
+            color_mode: ColorMode::Static,
+            show_labels: false,
+            impact_config: config.impacts,
+            flash_config: config.flash,
+            shadows: config.shadows,
+            effects: CollisionEffects {
+                impacts: ImpactCache::new(config.impacts.duration),
+                flashes: FlashCache::new(&config.flash),
+                hover_tint: DEFAULT_HOVER_TINT,
+            },
+            sample_count,
+            msaa_view: None,
+            render_scale: clamp_render_scale(config.render_scale),
+            scaled_target: None,
+            blit_pipeline,
+            background: config.background,
+            letterbox: config.letterbox,
+            gradient_pipeline: None,
+            gradient_buffer: None,
+            grid: config.grid,
+            frame_lines: Vec::new(),
+            contact_lines: Vec::new(),
+            debug_flags: DebugDrawFlags::empty(),
+            velocity_arrow_scale: 0.25,
+            stats_text: String::new(),
+            text_dirty: false,
+            show_stats: true,
+            stats_style: config.stats_style,
+            stats_in_title: config.stats_in_title,
+            recorder: None,
+            gpu_timer,
+            picker: None,
+            #[cfg(test)]
+            simulated_surface_error: None,
+            #[cfg(feature = "dev-tools")]
+            shader_watcher: ShaderWatcher::new(SHAPE_SHADER_PATH),
+            frame_stats: FrameStats::new(Instant::now()),
+        };
+
+        renderer.configure_surface();
+        renderer.create_msaa_target();
+        renderer.create_scaled_target();
+        renderer.set_background(config.background);
+        debug_assert_eq!(
+            renderer.resources.text.atlas_format, renderer.view_format,
+            "text atlas must be created for the format text is drawn into"
+        );
+        renderer
+    }
+
+    /// Configure the window surface, or recreate the offscreen texture, for the current size
+    fn configure_surface(&mut self) {
+        // A zero-sized surface is a validation error; wait for a real size instead
+        if is_zero_sized(self.size) {
+            return;
+        }
+
+        let (surface, usage) = match &self.target {
+            RenderTarget::Window { surface, usage, .. } => (surface, *usage),
+            RenderTarget::Offscreen { .. } => {
+                self.target = RenderTarget::Offscreen {
+                    texture: create_offscreen_texture(&self.device, self.view_format, self.size),
+                };
+                return;
+            }
+        };
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage,
+            format: self.surface_format,
+            view_formats: vec![self.view_format],
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            width: self.size.width,
+            height: self.size.height,
+            desired_maximum_frame_latency: 2,
+            present_mode: self.present_mode,
+        };
+        surface.configure(&self.device, &surface_config);
+    }
+
+    /// Physical pixels per logical pixel; 1 when headless
+    fn scale_factor(&self) -> f32 {
+        self.window()
+            .map_or(1.0, |window| window.scale_factor() as f32)
+    }
+
+    pub fn window(&self) -> Option<&Arc<Window>> {
+        match &self.target {
+            RenderTarget::Window { window, .. } => Some(window),
+            RenderTarget::Offscreen { .. } => None,
+        }
+    }
+
+    /// Copy the last rendered frame out of the offscreen texture as tightly packed
+    /// RGBA8 rows (top row first). Empty when rendering to a window.
+    pub fn read_pixels(&self) -> Result<Vec<u8>, RenderError> {
+        let RenderTarget::Offscreen { texture } = &self.target else {
+            return Ok(Vec::new());
+        };
+
+        let width = self.size.width;
+        let height = self.size.height;
+        let unpadded_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = unpadded_row.div_ceil(align) * align;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let mapped = Arc::new(OnceLock::new());
+        let callback_mapped = mapped.clone();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = callback_mapped.set(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(RenderError::Poll)?;
+        // Waiting indefinitely means the callback has run
+        if let Some(Err(err)) = mapped.get() {
+            return Err(RenderError::Readback(err.clone()));
+        }
+
+        let data = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_row * height) as usize);
+        for row in data.chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row as usize]);
+        }
+        drop(data);
+        buffer.unmap();
+
+        Ok(pixels)
+    }
+
+    /// (Re)create the multisampled color target to match the surface size
+    fn create_msaa_target(&mut self) {
+        if self.sample_count <= 1 {
+            self.msaa_view = None;
+            return;
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Color Target"),
+            size: wgpu::Extent3d {
+                width: self.size.width.max(1),
+                height: self.size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.view_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        self.msaa_view = Some(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+    }
+
+    /// (Re)create the scene target for the current size and render scale.
+    /// At scale 1 there is none and shapes draw straight into the frame.
+    fn create_scaled_target(&mut self) {
+        let Some(size) = scaled_size(self.size, self.render_scale) else {
+            self.scaled_target = None;
+            return;
+        };
+        let max = self.device.limits().max_texture_dimension_2d;
+        let size = winit::dpi::PhysicalSize::new(size.width.min(max), size.height.min(max));
+
+        let extent = wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scaled Scene Target"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.view_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let msaa_view = (self.sample_count > 1).then(|| {
+            self.device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Scaled MSAA Color Target"),
+                    size: extent,
+                    mip_level_count: 1,
+                    sample_count: self.sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.view_format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Scaled Scene Bind Group"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.texture_sampler),
+                },
+            ],
+        });
+
+        self.scaled_target = Some(ScaledTarget {
+            size,
+            view,
+            msaa_view,
+            bind_group,
+        });
+    }
+
+    /// Render shapes at `scale` times the window resolution (0.5–2.0) and stretch the
+    /// result over the window. Text always renders at native resolution.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = clamp_render_scale(scale);
+        self.create_scaled_target();
+        self.update_stats_text();
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Size of the offscreen scene target, or None when shapes draw at native resolution
+    pub fn scaled_target_size(&self) -> Option<winit::dpi::PhysicalSize<u32>> {
+        self.scaled_target.as_ref().map(|target| target.size)
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Switch present mode, falling back to a supported one if needed
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) {
+        let RenderTarget::Window {
+            supported_present_modes,
+            ..
+        } = &self.target
+        else {
+            return;
+        };
+
+        self.present_mode = choose_present_mode(mode, supported_present_modes);
+        self.configure_surface();
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.present_mode
+    }
+
+    /// Step to the next of Fifo/Mailbox/Immediate that the surface supports
+    pub fn cycle_present_mode(&mut self) {
+        let RenderTarget::Window {
+            supported_present_modes,
+            ..
+        } = &self.target
+        else {
+            return;
+        };
+
+        let next = next_present_mode(self.present_mode, supported_present_modes);
+        println!("Present mode: {:?}", next);
+        self.set_present_mode(next);
+    }
+
+    /// Match the new window size. A zero size (minimized) pauses rendering, keeping
+    /// the old configuration until the window comes back.
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.minimized = is_zero_sized(new_size);
+        if self.minimized {
+            return;
+        }
+
+        self.size = new_size;
+        self.configure_surface();
+        self.create_msaa_target();
+        self.create_scaled_target();
+    }
+
+    /// Switch the window between windowed and borderless fullscreen, restoring the
+    /// windowed size and position on the way back. The renderer resizes right away;
+    /// pass the new `size` to `InputSystem::update_window_size` before the next cursor
+    /// event so NDC mapping doesn't lag behind the window.
+    pub fn toggle_fullscreen(&mut self) {
+        let Some(window) = self.window().cloned() else {
+            return;
+        };
+
+        // The window is the source of truth; the user may have left fullscreen another way
+        if window.fullscreen().is_some() {
+            window.set_fullscreen(None);
+            if let Some((size, position)) = self.fullscreen.exit() {
+                let _ = window.request_inner_size(size);
+                if let Some(position) = position {
+                    window.set_outer_position(position);
+                }
+            }
+        } else {
+            self.fullscreen
+                .enter(window.inner_size(), window.outer_position().ok());
+            window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+        }
+        self.sync_window_size();
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen.is_fullscreen()
+    }
+
+    /// Drop trails, collision effects and shaped text kept for another world's
+    /// entities, so switching scenes doesn't carry them over by id
+    fn sync_world(&mut self, world: &World) {
+        if self.world == Some(world.id()) {
+            return;
+        }
+        self.world = Some(world.id());
+        self.trails.clear();
+        self.effects.impacts.clear();
+        self.effects.flashes.clear();
+        self.resources.text.cache.clear();
+        for pass in &mut self.passes {
+            pass.world_changed();
+        }
+    }
+
+    /// Pick up a window size change that hasn't arrived as a resize event yet (mode
+    /// switches apply before the event is delivered). Returns true if the size changed.
+    pub fn sync_window_size(&mut self) -> bool {
+        let Some(size) = self.window().map(|window| window.inner_size()) else {
+            return false;
+        };
+        // Minimizing still goes through the resize event
+        if size == self.size || is_zero_sized(size) {
+            return false;
+        }
+        self.resize(size);
+        true
+    }
+
+    /// Rebuild the shape pipelines from `source`. On a compile or validation error
+    /// the previous pipelines stay in use and the error is returned.
+    #[cfg(feature = "dev-tools")]
+    pub fn reload_shape_shader(&mut self, source: &str) -> Result<(), String> {
+        for pass in &mut self.passes {
+            if let Some(result) = pass.reload_shape_shader(&self.device, source) {
+                return result;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save every `every_n_frames`th presented frame to `dir` as numbered PNGs
+    /// (frame_000000.png, ...) until `stop_recording`. Frames are read back a frame
+    /// late; if the readback falls behind, frames are dropped rather than waited for.
+    pub fn start_recording(
+        &mut self,
+        dir: impl Into<PathBuf>,
+        every_n_frames: u32,
+    ) -> std::io::Result<()> {
+        if let RenderTarget::Window { usage, .. } = &self.target
+            && !usage.contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "surface frames can't be copied",
+            ));
+        }
+        let bgra = match self.surface_format.remove_srgb_suffix() {
+            wgpu::TextureFormat::Rgba8Unorm => false,
+            wgpu::TextureFormat::Bgra8Unorm => true,
+            format => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("can't record {format:?} frames"),
+                ));
+            }
+        };
+
+        self.stop_recording();
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        println!("Recording to {}", dir.display());
+        self.recorder = Some(FrameRecorder::new(dir, every_n_frames, bgra));
+        self.update_stats_text();
+        Ok(())
+    }
+
+    /// Write out frames still being read back and end the recording.
+    /// None if nothing was recording.
+    pub fn stop_recording(&mut self) -> Option<RecordingStats> {
+        let stats = self.recorder.take()?.finish(&self.device);
+        println!(
+            "Recording stopped: {} frames, {} dropped",
+            stats.captured, stats.dropped
+        );
+        self.update_stats_text();
+        Some(stats)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Totals so far for the current recording
+    pub fn recording_stats(&self) -> Option<RecordingStats> {
+        self.recorder.as_ref().map(|recorder| recorder.stats)
+    }
+
+    /// Show or hide the stats overlay. Stats keep accumulating while hidden.
+    pub fn toggle_stats(&mut self) {
+        self.show_stats = !self.show_stats;
+    }
+
+    /// Load an image file as a texture for `Entity::with_texture`
+    pub fn load_texture(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<TextureId, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Ok(self.create_texture(width, height, image.as_raw()))
+    }
+
+    /// Create a texture from tightly packed sRGB RGBA8 pixels, rows top to bottom
+    pub fn create_texture(&mut self, width: u32, height: u32, rgba: &[u8]) -> TextureId {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        // Image files store sRGB; the Srgb format makes sampling return linear color
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Shape Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            rgba,
+        );
+        let view = texture.create_view(&Default::default());
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shape Texture Bind Group"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.texture_sampler),
+                },
+            ],
+        });
+
+        self.resources.textures.push(bind_group);
+        TextureId(self.resources.textures.len() - 1)
+    }
+
+    pub fn set_trails_enabled(&mut self, enabled: bool) {
+        self.trails_enabled = enabled;
+        if !enabled {
+            self.trails.clear();
+        }
+    }
+
+    pub fn toggle_trails(&mut self) {
+        self.set_trails_enabled(!self.trails_enabled);
+    }
+
+    pub fn trails_enabled(&self) -> bool {
+        self.trails_enabled
+    }
+
+    pub fn set_impacts(&mut self, config: ImpactConfig) {
+        self.impact_config = config;
+        self.effects.impacts.duration = config.duration;
+        if !config.enabled {
+            self.effects.impacts.clear();
+        }
+    }
+
+    pub fn impacts(&self) -> ImpactConfig {
+        self.impact_config
+    }
+
+    /// Squash the circle `id` along `normal` if `impulse` is over the threshold.
+    /// Feed it both entities of every `PhysicsEvent::Impact`.
+    pub fn record_impact(&mut self, id: EntityId, normal: [f32; 2], impulse: f32) {
+        let config = self.impact_config;
+        if !config.enabled || impulse <= config.threshold {
+            return;
+        }
+
+        let squash = ((impulse - config.threshold) * config.squash_per_impulse)
+            .min(config.max_squash)
+            .clamp(0.0, 0.9);
+        self.effects
+            .impacts
+            .record(id, normal, squash, Instant::now());
+    }
+
+    pub fn set_flash(&mut self, config: FlashConfig) {
+        self.flash_config = config;
+        self.effects.flashes.color = config.color;
+        self.effects.flashes.duration = config.duration;
+        if !config.enabled {
+            self.effects.flashes.clear();
+        }
+    }
+
+    pub fn flash(&self) -> FlashConfig {
+        self.flash_config
+    }
+
+    /// How far hovered clickables (`Clickable::hovered`, see `World::update_hover`)
+    /// are blended toward the flash color, 0.0 to turn the tint off
+    pub fn set_hover_tint(&mut self, amount: f32) {
+        self.effects.hover_tint = amount.clamp(0.0, 1.0);
+    }
+
+    pub fn hover_tint(&self) -> f32 {
+        self.effects.hover_tint
+    }
+
+    pub fn set_shadows(&mut self, config: ShadowConfig) {
+        self.shadows = config;
+    }
+
+    pub fn shadows(&self) -> ShadowConfig {
+        self.shadows
+    }
+
+    /// Flash the entity `id` toward the highlight color, more strongly for bigger
+    /// impulses
+    pub fn record_flash(&mut self, id: EntityId, impulse: f32) {
+        let config = self.flash_config;
+        if !config.enabled {
+            return;
+        }
+
+        let intensity = (impulse * config.intensity_per_impulse).clamp(0.0, 1.0);
+        if intensity > 0.0 {
+            self.effects.flashes.record(id, intensity, Instant::now());
+        }
+    }
+
+    /// Feed one `PhysicsEvent` read from `World::events` into the collision effects
+    /// (impact squash and flash). Call for every event, every update.
+    pub fn record_physics_event(&mut self, event: &PhysicsEvent) {
+        if let PhysicsEvent::Impact {
+            a,
+            b,
+            normal,
+            impulse,
+        } = *event
+        {
+            for id in [a, b] {
+                self.record_impact(id, normal, impulse);
+                self.record_flash(id, impulse);
+            }
+        }
+    }
+
+    /// Draw each named entity's name above it (debug aid, off by default)
+    pub fn set_labels_enabled(&mut self, enabled: bool) {
+        self.show_labels = enabled;
+    }
+
+    pub fn toggle_labels(&mut self) {
+        self.show_labels = !self.show_labels;
+    }
+
+    pub fn labels_enabled(&self) -> bool {
+        self.show_labels
+    }
+
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    pub fn set_draw_order(&mut self, mode: DrawOrder) {
+        self.draw_order_mode = mode;
+    }
+
+    pub fn draw_order(&self) -> DrawOrder {
+        self.draw_order_mode
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// Switch between static colors and speed coloring
+    pub fn cycle_color_mode(&mut self) {
+        self.color_mode = match self.color_mode {
+            ColorMode::Static => ColorMode::Speed {
+                max: DEFAULT_SPEED_COLOR_MAX,
+            },
+            ColorMode::Speed { .. } => ColorMode::Static,
+        };
+        println!("Color mode: {:?}", self.color_mode);
+    }
+
+    /// Number of sim steps each trail covers
+    pub fn set_trail_length(&mut self, length: usize) {
+        self.trails.set_length(length);
+    }
+
+    /// Remember where every circle is. Call once per sim step (not per frame)
+    /// so trail spacing reflects actual speed.
+    pub fn record_trails(&mut self, world: &World) {
+        self.sync_world(world);
+        if !self.trails_enabled {
+            return;
+        }
+
+        for (index, entity) in world.entities().iter().enumerate() {
+            if let (Shape::Circle { .. }, Some(id)) = (entity.shape(), world.id_at(index)) {
+                self.trails.record(id, entity.transform().position);
+            }
+        }
+        self.trails.evict_unused();
+    }
+
+    /// Lay out every text entity and store its on-screen size in
+    /// `Shape::Text::measured_size`, so bounds and picking match what gets drawn.
+    /// Call after adding or editing text and after resizing.
+    pub fn measure_text(&mut self, world: &mut World) {
+        self.sync_world(world);
+        let world_viewport = self.world_viewport();
+        let text = &mut self.resources.text;
+        for index in 0..world.len() {
+            let entity = &world.entities()[index];
+            let (
+                Some(id),
+                Shape::Text {
+                    content,
+                    font_size,
+                    measured_size,
+                    ..
+                },
+            ) = (world.id_at(index), entity.shape())
+            else {
+                continue;
+            };
+            text.cache
+                .update(&mut text.font_system, id, content, *font_size, true);
+            let Some((width, height)) = text.cache.get(id).map(buffer_extent) else {
+                continue;
+            };
+            let measured = Some([
+                width * 2.0 / world_viewport.width,
+                height * 2.0 / world_viewport.height,
+            ]);
+            // Leave unchanged text out of the change tracking
+            if *measured_size != measured
+                && let Shape::Text { measured_size, .. } = world.entities_mut()[index].shape_mut()
+            {
+                *measured_size = measured;
+            }
+        }
+    }
+
+    /// Region of the world that ends up on screen. Without a camera that's the
+    /// whole NDC square.
+    fn view_bounds(&self) -> Aabb {
+        Aabb {
+            min: [-1.0, -1.0],
+            max: [1.0, 1.0],
+        }
+    }
+
+    pub fn set_grid(&mut self, grid: GridConfig) {
+        self.grid = grid;
+    }
+
+    pub fn grid(&self) -> GridConfig {
+        self.grid
+    }
+
+    pub fn toggle_grid(&mut self) {
+        self.grid.enabled = !self.grid.enabled;
+    }
+
+    /// Elapsed sim time handed to shaders; call with `TimeSystem::sim_time` before rendering
+    pub fn set_sim_time(&mut self, sim_time: Duration) {
+        self.globals.time = sim_time.as_secs_f32();
+    }
+
+    /// Width of circle edge antialiasing as a multiple of the default (1).
+    /// Circles with their own `edge_softness` ignore this.
+    pub fn set_edge_softness(&mut self, softness: f32) {
+        self.globals.edge_softness = softness.max(0.01);
+    }
+
+    /// Faint halo around every circle for a neon look. `strength` 0 turns it off;
+    /// `extent` is how far it reaches past the radius, as a fraction of the radius.
+    pub fn set_glow(&mut self, strength: f32, extent: f32) {
+        self.globals.glow = strength.max(0.0);
+        self.globals.glow_extent = extent.max(0.0);
+    }
+
+    /// Make circle edges softly pulse over sim time. 0 turns the effect off.
+    pub fn set_edge_pulse(&mut self, strength: f32) {
+        self.globals.edge_pulse = strength.max(0.0);
+    }
+
+    /// Draw a line segment this frame only (NDC, thickness included).
+    /// Zero-length lines are skipped.
+    pub fn draw_line(&mut self, start: [f32; 2], end: [f32; 2], thickness: f32, color: [f32; 3]) {
+        self.frame_lines.push(LineInstance {
+            start,
+            end,
+            thickness,
+            color: [color[0], color[1], color[2], 1.0],
+        });
+    }
+
+    /// Draw an unfilled ring this frame only, on top of the world's circles
+    pub fn push_overlay_ring(
+        &mut self,
+        center: [f32; 2],
+        radius: f32,
+        thickness: f32,
+        color: [f32; 3],
+    ) {
+        self.overlay_rings.push(CircleInstance {
+            position: center,
+            radius,
+            color: [color[0], color[1], color[2], 1.0],
+            thickness: thickness.max(0.0),
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            edge_softness: 0.0,
+            color_outer: color,
+        });
+    }
+
+    // Ring around every selected entity, drawn with this frame's overlay rings
+    fn push_selection_rings(&mut self, world: &World, alpha: f32) {
+        for &id in world.selected() {
+            let Some(entity) = world.get(id) else {
+                continue;
+            };
+            if let Shape::Text {
+                space: TextSpace::Screen { .. },
+                ..
+            } = entity.shape()
+            {
+                continue;
+            }
+            let transform = entity.transform();
+            let position = transform.interpolated_position(alpha);
+            let placed = Transform {
+                position,
+                ..*transform
+            };
+            let Aabb { min, max } = entity.shape().bounds(&placed);
+            let reach = (max[0] - min[0]).max(max[1] - min[1]) / 2.0;
+            self.push_overlay_ring(
+                position,
+                reach + SELECTION_RING_GAP,
+                SELECTION_RING_THICKNESS,
+                SELECTION_RING_COLOR,
+            );
+        }
+    }
+
+    /// Fill the screen with a single color (sRGB)
+    pub fn set_clear_color(&mut self, color: [f32; 4]) {
+        self.set_background(Background::Solid(color));
+    }
+
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+        self.upload_background();
+    }
+
+    /// Background quad colors; letterboxing draws even a solid background as a quad
+    /// so the bars can be cleared to their own color
+    fn upload_background(&mut self) {
+        let (top, bottom) = match self.background {
+            Background::Gradient { top, bottom } => (top, bottom),
+            Background::Solid(color) if self.letterbox.is_some() => (color, color),
+            Background::Solid(_) => return,
+        };
+        if self.gradient_pipeline.is_none() {
+            self.gradient_pipeline = Some(create_gradient_pipeline(
+                &self.device,
+                self.view_format,
+                self.sample_count,
+            ));
+            self.gradient_buffer = Some(self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Gradient Instance Buffer"),
+                size: std::mem::size_of::<GradientInstance>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+
+        let instance = GradientInstance {
+            top: self.output_color(top),
+            bottom: self.output_color(bottom),
+        };
+        if let Some(buffer) = &self.gradient_buffer {
+            self.queue
+                .write_buffer(buffer, 0, bytemuck::bytes_of(&instance));
+        }
+    }
+
+    pub fn background(&self) -> Background {
+        self.background
+    }
+
+    /// Keep the world at a fixed aspect ratio, centered with bars, or stretch it
+    /// over the whole window with None
+    pub fn set_letterbox(&mut self, letterbox: Option<Letterbox>) {
+        self.letterbox = letterbox;
+        self.upload_background();
+    }
+
+    pub fn letterbox(&self) -> Option<Letterbox> {
+        self.letterbox
+    }
+
+    /// Where the world is drawn in the window at the current size
+    pub fn world_viewport(&self) -> WorldViewport {
+        WorldViewport::for_window(
+            self.size.width,
+            self.size.height,
+            self.letterbox.map(|letterbox| letterbox.aspect),
+        )
+    }
+
+    fn output_color(&self, color: [f32; 4]) -> [f32; 4] {
+        output_color(color, self.view_format)
+    }
+
+    /// Flip the given debug overlays on/off, leaving the others alone
+    pub fn toggle_debug_draw(&mut self, flags: DebugDrawFlags) {
+        self.debug_flags.toggle(flags);
+        if !self.debug_flags.contains(DebugDrawFlags::CONTACTS) {
+            self.contact_lines.clear();
+        }
+    }
+
+    pub fn debug_draw(&self) -> DebugDrawFlags {
+        self.debug_flags
+    }
+
+    /// Length multiplier applied to velocities when drawing arrows
+    pub fn set_velocity_arrow_scale(&mut self, scale: f32) {
+        self.velocity_arrow_scale = scale;
+    }
+
+    /// Feed the contacts from the last physics update into the debug overlay
+    pub fn set_debug_contacts(&mut self, contacts: &[Contact]) {
+        self.contact_lines.clear();
+        if self.debug_flags.contains(DebugDrawFlags::CONTACTS) {
+            push_contact_lines(&mut self.contact_lines, contacts);
+        }
+    }
+
+    /// Show compact stats in the window title, refreshed at the stats report
+    /// interval. Works alongside the overlay. Turning it off restores the plain title.
+    pub fn set_stats_in_title(&mut self, enabled: bool) {
+        self.stats_in_title = enabled;
+        if !enabled && let Some(window) = self.window() {
+            window.set_title(WINDOW_TITLE);
+        }
+    }
+
+    pub fn set_stats_corner(&mut self, corner: StatsCorner) {
+        self.stats_style.corner = corner;
+    }
+
+    /// Takes effect on the next frame, re-laying out the stats text if needed
+    pub fn set_stats_style(&mut self, style: StatsStyle) {
+        self.stats_style = style;
+    }
+
+    pub fn stats_style(&self) -> StatsStyle {
+        self.stats_style
+    }
+
+    /// Render entity indices into an offscreen buffer every frame so `pick` and
+    /// `entity_at` can select exactly what's drawn, rotation, scale and rounded
+    /// corners included. Covers circles and rectangles; other shapes aren't pickable.
+    pub fn set_gpu_picking(&mut self, enabled: bool) {
+        if enabled && self.picker.is_none() {
+            self.picker = Some(GpuPicker::new(&self.device, self.size));
+        } else if !enabled {
+            self.picker = None;
+        }
+    }
+
+    pub fn gpu_picking(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    /// Entity drawn at `pixel` (window pixels). The answer comes from the GPU a frame
+    /// late: the first call for a pixel requests it and returns None, and once a
+    /// frame rendered after that has been read back, calls return what it showed.
+    /// The pixel stays requested every frame, so hovering keeps the result fresh.
+    /// Always None while GPU picking is off.
+    pub fn pick(&mut self, pixel: winit::dpi::PhysicalPosition<f64>) -> Option<usize> {
+        let pixel = [pixel.x as u32, pixel.y as u32];
+        self.picker
+            .as_mut()
+            .and_then(|picker| picker.lookup(&self.device, pixel))
+            .flatten()
+    }
+
+    /// Entity under `pixel` for selection: the GPU picking result when one is ready
+    /// for this pixel, otherwise the analytic `World::find_at_point` test
+    pub fn entity_at(
+        &mut self,
+        world: &World,
+        pixel: winit::dpi::PhysicalPosition<f64>,
+    ) -> Option<EntityId> {
+        let gpu = self
+            .picker
+            .as_mut()
+            .and_then(|picker| picker.lookup(&self.device, [pixel.x as u32, pixel.y as u32]));
+        if let Some(picked) = gpu {
+            // The readback can predate a removal; never resolve an index past the end
+            return picked.and_then(|index| world.id_at(index));
+        }
+        let ndc = self
+            .world_viewport()
+            .pixel_to_ndc(pixel.x as f32, pixel.y as f32)?;
+        world.find_at_point(ndc, false)
+    }
+
+    /// Name, backend and driver of the GPU in use
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.adapter_info
+    }
+
+    pub fn update_stats_text(&mut self) {
+        self.stats_text = format!(
+            "{}\nScale:    {:5.2}x\nGPU:      {} ({:?})",
+            self.frame_stats.summary(),
+            self.render_scale,
+            self.adapter_info.name,
+            self.adapter_info.backend
+        );
+        self.text_dirty = true;
+    }
+
+    /// Background quad behind the world; unletterboxed solid backgrounds are the clear color
+    fn draw_background(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        let quad =
+            matches!(self.background, Background::Gradient { .. }) || self.letterbox.is_some();
+        if let (true, Some(pipeline), Some(buffer)) =
+            (quad, &self.gradient_pipeline, &self.gradient_buffer)
+        {
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_vertex_buffer(0, self.resources.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.draw(0..6, 0..1);
+        }
+    }
+
+    /// Draw the world. `alpha` is the interpolation factor from `TimeSystem::tick`,
+    /// used to blend each entity between its previous and current sim position.
+    ///
+    /// Recoverable surface problems (outdated, lost, timeout) and a minimized window
+    /// skip the frame; only unrecoverable ones like running out of memory are errors.
+    /// Frame stats only count presented frames.
+    ///
+    /// Call `World::drain_changes` once before each frame, so the text caches only
+    /// reshape entities changed since the last one.
+    pub fn render(&mut self, world: &World, alpha: f32) -> Result<FrameOutcome, RenderError> {
+        if self.minimized {
+            // Nothing to draw into; drop this frame's one-off requests like a drawn frame would
+            self.overlay_rings.clear();
+            self.frame_lines.clear();
+            return Ok(FrameOutcome::Skipped(SkipReason::Minimized));
+        }
+        // Never draw a frame at a stale size, e.g. right after a fullscreen switch
+        self.sync_window_size();
+        self.sync_world(world);
+        self.push_selection_rings(world, alpha);
+        #[cfg(feature = "dev-tools")]
+        if let Some(source) = self.shader_watcher.poll(Instant::now()) {
+            match self.reload_shape_shader(&source) {
+                Ok(()) => println!("Reloaded {SHAPE_SHADER_PATH}"),
+                Err(err) => println!("WARNING: keeping the previous shape shader: {err}"),
+            }
+        }
+
+        match self.draw_order_mode {
+            DrawOrder::Insertion => {
+                self.draw_order.clear();
+                self.draw_order.extend(0..world.entities().len());
+            }
+            DrawOrder::Layer => world.draw_order(&mut self.draw_order),
+            DrawOrder::YSorted => world.y_sorted_draw_order(&mut self.draw_order),
+        }
+        let view = self.view_bounds();
+
+        // Shared per-frame state: shape shader globals and the text viewport
+        let scene_size = self.scaled_target_size().unwrap_or(self.size);
+        self.globals.screen_size = [scene_size.width as f32, scene_size.height as f32];
+        self.queue
+            .write_buffer(&self.globals_buffer, 0, bytemuck::bytes_of(&self.globals));
+        self.resources.text.viewport.update(
+            &self.queue,
+            glyphon::Resolution {
+                width: self.size.width,
+                height: self.size.height,
+            },
+        );
+
+        let now = Instant::now();
+        self.effects.impacts.advance(now);
+        self.effects.flashes.advance(now);
+
+        // Requested lines first, then contacts from the last physics update
+        self.frame_lines.extend_from_slice(&self.contact_lines);
+
+        let mut frame = FrameContext {
+            alpha,
+            size: self.size,
+            world_viewport: self.world_viewport(),
+            scale_factor: self.scale_factor(),
+            format: self.view_format,
+            view,
+            draw_order: &self.draw_order,
+            color_mode: self.color_mode,
+            trails: self.trails_enabled.then_some(&self.trails),
+            effects: &self.effects,
+            shadows: self.shadows.enabled.then_some(&self.shadows),
+            overlay_rings: &self.overlay_rings,
+            lines: &self.frame_lines,
+            debug_flags: self.debug_flags,
+            velocity_arrow_scale: self.velocity_arrow_scale,
+            grid: &self.grid,
+            labels: self.show_labels,
+            stats: StatsOverlay {
+                visible: self.show_stats,
+                style: self.stats_style,
+                text: self.text_dirty.then_some(self.stats_text.as_str()),
+                recording: self
+                    .recorder
+                    .as_ref()
+                    .map(|recorder| recorder.stats.dropped),
+                frozen: world.is_frozen(),
+            },
+            resources: &mut self.resources,
+            culled: 0,
+        };
+        for pass in &mut self.passes {
+            pass.prepare(&self.device, &self.queue, world, &mut frame);
+        }
+        self.frame_stats.latest_culled = frame.culled;
+        self.text_dirty = false;
+        self.overlay_rings.clear();
+        self.frame_lines.clear();
+
+        let view_descriptor = wgpu::TextureViewDescriptor {
+            format: Some(self.view_format),
+            ..Default::default()
+        };
+        debug_assert_eq!(
+            view_descriptor.format,
+            Some(self.resources.text.atlas_format),
+            "text atlas and render attachment formats differ"
+        );
+        let acquired = match &self.target {
+            RenderTarget::Window { surface, .. } => {
+                surface.get_current_texture().map(|surface_texture| {
+                    let view = surface_texture.texture.create_view(&view_descriptor);
+                    (Some(surface_texture), view)
+                })
+            }
+            RenderTarget::Offscreen { texture } => {
+                Ok((None, texture.create_view(&view_descriptor)))
+            }
+        };
+        #[cfg(test)]
+        let acquired = match self.simulated_surface_error.take() {
+            Some(err) => Err(err),
+            None => acquired,
+        };
+        let (surface_texture, view) = match acquired {
+            Ok(acquired) => acquired,
+            Err(err) => {
+                let reason = classify_surface_error(err)?;
+                if matches!(
+                    reason,
+                    SkipReason::SurfaceOutdated | SkipReason::SurfaceLost
+                ) {
+                    self.configure_surface();
+                }
+                return Ok(FrameOutcome::Skipped(reason));
+            }
+        };
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+
+        let clear_color = match (self.letterbox, self.background) {
+            (Some(Letterbox { bar_color, .. }), _) | (None, Background::Solid(bar_color)) => {
+                let [r, g, b, a] = self.output_color(bar_color);
+                wgpu::Color {
+                    r: r as f64,
+                    g: g as f64,
+                    b: b as f64,
+                    a: a as f64,
+                }
+            }
+            (None, Background::Gradient { .. }) => wgpu::Color::BLACK,
+        };
+        let aspect = self.letterbox.map(|letterbox| letterbox.aspect);
+
+        // With MSAA we draw into the multisampled target and resolve into the swapchain
+        let (target_view, resolve_target) = color_target(&view, self.msaa_view.as_ref());
+        // GPU frame time runs from the start of the first pass to the end of the last
+        let timestamps = |begin, end| {
+            self.gpu_timer
+                .as_ref()
+                .map(|timer| timer.pass_writes(begin, end))
+        };
+
+        match &self.scaled_target {
+            // Everything draws straight into the frame
+            None => {
+                let mut render_pass = begin_render_pass(
+                    &mut encoder,
+                    target_view,
+                    resolve_target,
+                    clear_color,
+                    timestamps(true, true),
+                );
+                if aspect.is_some() {
+                    set_world_viewport(&mut render_pass, self.world_viewport());
+                }
+                self.draw_background(&mut render_pass);
+                for pass in self.passes.iter().filter(|pass| !pass.overlay()) {
+                    pass.draw(&mut render_pass, &self.resources);
+                }
+                if aspect.is_some() {
+                    let full = WorldViewport::full(self.size.width, self.size.height);
+                    set_world_viewport(&mut render_pass, full);
+                }
+                for pass in self.passes.iter().filter(|pass| pass.overlay()) {
+                    pass.draw(&mut render_pass, &self.resources);
+                }
+            }
+            // Scene at the scaled size, then stretched over the frame under native overlays
+            Some(scaled) => {
+                {
+                    let (scene_view, scene_resolve) =
+                        color_target(&scaled.view, scaled.msaa_view.as_ref());
+                    let mut render_pass = begin_render_pass(
+                        &mut encoder,
+                        scene_view,
+                        scene_resolve,
+                        clear_color,
+                        timestamps(true, false),
+                    );
+                    if aspect.is_some() {
+                        let world = WorldViewport::for_window(
+                            scaled.size.width,
+                            scaled.size.height,
+                            aspect,
+                        );
+                        set_world_viewport(&mut render_pass, world);
+                    }
+                    self.draw_background(&mut render_pass);
+                    for pass in self.passes.iter().filter(|pass| !pass.overlay()) {
+                        pass.draw(&mut render_pass, &self.resources);
+                    }
+                }
+
+                let mut render_pass = begin_render_pass(
+                    &mut encoder,
+                    target_view,
+                    resolve_target,
+                    wgpu::Color::BLACK,
+                    timestamps(false, true),
+                );
+                render_pass.set_pipeline(&self.blit_pipeline);
+                render_pass.set_bind_group(0, &scaled.bind_group, &[]);
+                render_pass.set_vertex_buffer(0, self.resources.vertex_buffer.slice(..));
+                render_pass.draw(0..6, 0..1);
+                for pass in self.passes.iter().filter(|pass| pass.overlay()) {
+                    pass.draw(&mut render_pass, &self.resources);
+                }
+            }
+        }
+
+        // The finished frame, for recording
+        let frame_texture = match (&surface_texture, &self.target) {
+            (Some(surface_texture), _) => Some(&surface_texture.texture),
+            (None, RenderTarget::Offscreen { texture }) => Some(texture),
+            (None, RenderTarget::Window { .. }) => None,
+        };
+        if let (Some(recorder), Some(texture)) = (&mut self.recorder, frame_texture) {
+            recorder.capture(&self.device, &mut encoder, texture);
+        }
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.resolve(&mut encoder);
+        }
+        let world_viewport = self.world_viewport();
+        if let Some(picker) = &mut self.picker {
+            picker.resize(&self.device, self.size);
+            picker.prepare(&self.device, &self.queue, world, &self.draw_order, alpha);
+            picker.encode(&mut encoder, &self.resources.vertex_buffer, world_viewport);
+        }
+
+        self.queue.submit([encoder.finish()]);
+        if let Some(surface_texture) = surface_texture {
+            surface_texture.present();
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.map_copies();
+            recorder.collect(&self.device);
+        }
+        if let Some(picker) = &mut self.picker {
+            picker.map_copy();
+            picker.collect(&self.device);
+        }
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.map_copies();
+            let frame_stats = &mut self.frame_stats;
+            timer.collect(&self.device, |ms| frame_stats.record_gpu_time(ms));
+        }
+
+        let now = Instant::now();
+        let dt = now - self.frame_stats.last_present;
+        self.frame_stats.last_present = now;
+        self.frame_stats.record_frame(dt);
+        self.frame_stats.render_count += 1;
+
+        if self.frame_stats.needs_update() {
+            self.frame_stats.update();
+            self.update_stats_text();
+            if self.stats_in_title
+                && let Some(window) = self.window()
+            {
+                window.set_title(&self.frame_stats.title_summary());
+            }
+        }
+
+        Ok(FrameOutcome::Presented)
+    }
+}
+
+/// Build per-instance GPU data for every circle and rectangle in the world
+/// (plus transformed triangle vertices for polygons),
+/// with positions interpolated by `alpha`. Entities are visited in `order`
+/// (back-to-front), and `batches` records the runs of each shape kind so
+/// layering holds across pipelines. Later instances blend over earlier ones.
+/// Colors come from each shape unless `color_mode` says otherwise. Shapes whose
+/// bounds lie fully outside `view` are skipped and counted as culled. Circles still
+/// recovering from a hit in `effects` are squashed along the contact normal, and
+/// flashing and hovered entities are blended toward the flash color.
+#[allow(clippy::too_many_arguments)]
+fn collect_instances(
+    world: &World,
+    alpha: f32,
+    color_mode: ColorMode,
+    view: Aabb,
+    order: &[usize],
+    effects: &CollisionEffects,
+    shapes: &mut Vec<ShapeInstance>,
+    polygons: &mut Vec<PolygonVertex>,
+    batches: &mut Vec<DrawBatch>,
+) -> usize {
+    let mut culled = 0;
+
+    for &index in order {
+        let entity = &world.entities()[index];
+        let transform = entity.transform();
+        let position = transform.interpolated_position(alpha);
+        if let Shape::Text { .. } = entity.shape() {
+            continue; // Text is placed (and culled) separately
+        }
+        let Some(id) = world.id_at(index) else {
+            continue;
+        };
+        let placed = Transform {
+            position,
+            ..*transform
+        };
+        if !entity.shape().bounds(&placed).overlaps(&view) {
+            culled += 1;
+            continue;
+        }
+        let color_override = match (color_mode, entity.physics()) {
+            (ColorMode::Speed { max }, Some(physics)) => {
+                let [vx, vy] = physics.velocity;
+                Some(speed_color((vx * vx + vy * vy).sqrt(), max))
+            }
+            _ => None,
+        };
+        // Hover tint and collision flashes go on top of whichever color the shape ends up with
+        let hovered = entity
+            .clickable()
+            .is_some_and(|clickable| clickable.hovered);
+        let shape_color = |color: &[f32; 3]| {
+            let mut color = color_override.unwrap_or(*color);
+            if hovered {
+                color = effects.flashes.blend(color, effects.hover_tint);
+            }
+            effects.flashes.apply(id, color)
+        };
+        match entity.shape() {
+            Shape::Circle {
+                radius,
+                color,
+                opacity,
+                texture,
+                edge_softness,
+                color_outer,
+            } => {
+                let color_outer = shape_color(color_outer.as_ref().unwrap_or(color));
+                let color = shape_color(color);
+                let [scale_x, scale_y] = transform.scale;
+                let (rotation, scale) = match effects.impacts.get(id) {
+                    // Flatten along the normal and widen across it, keeping the area.
+                    // Ellipses keep their shape; they'd need a shear to squash off-axis.
+                    Some((angle, squash)) if scale_x == scale_y => {
+                        (angle, [scale_x * (1.0 - squash), scale_x / (1.0 - squash)])
+                    }
+                    _ => (transform.rotation, transform.scale),
+                };
+                let start = shapes.len() as u32;
+                push_batch(batches, BatchKind::Shapes, *texture, start..start + 1);
+                shapes.push(
+                    CircleInstance {
+                        position,
+                        radius: *radius,
+                        color: [color[0], color[1], color[2], *opacity],
+                        thickness: 0.0,
+                        rotation,
+                        scale,
+                        edge_softness: edge_softness.unwrap_or(0.0),
+                        color_outer,
+                    }
+                    .into(),
+                );
+            }
+            Shape::Rectangle {
+                length,
+                height,
+                color,
+                opacity,
+                texture,
+                corner_radius,
+            } => {
+                let color = shape_color(color);
+                let start = shapes.len() as u32;
+                push_batch(batches, BatchKind::Shapes, *texture, start..start + 1);
+                shapes.push(
+                    RectInstance {
+                        position,
+                        length: *length,
+                        height: *height,
+                        color: [color[0], color[1], color[2], *opacity],
+                        rotation: transform.rotation,
+                        scale: transform.scale,
+                        corner_radius: *corner_radius,
+                    }
+                    .into(),
+                );
+            }
+            Shape::Polygon {
+                triangles,
+                color,
+                opacity,
+                ..
+            } => {
+                let color = shape_color(color);
+                let color = [color[0], color[1], color[2], *opacity];
+                let (sin, cos) = transform.rotation.sin_cos();
+                let [scale_x, scale_y] = transform.scale;
+
+                // Transform on the CPU; the triangle list was built when the shape was made
+                let start = polygons.len() as u32;
+                polygons.extend(triangles.iter().map(|v| {
+                    let x = v[0] * scale_x;
+                    let y = v[1] * scale_y;
+                    PolygonVertex {
+                        position: [
+                            position[0] + x * cos - y * sin,
+                            position[1] + x * sin + y * cos,
+                        ],
+                        color,
+                    }
+                }));
+                let end = polygons.len() as u32;
+                if end > start {
+                    push_batch(batches, BatchKind::Polygons, None, start..end);
+                }
+            }
+            Shape::Text { .. } => {}
+        }
+    }
+
+    culled
+}
+
+/// Add `range` to the batch list, extending the last batch when it has the
+/// same kind and texture
+fn push_batch(
+    batches: &mut Vec<DrawBatch>,
+    kind: BatchKind,
+    texture: Option<TextureId>,
+    range: Range<u32>,
+) {
+    match batches.last_mut() {
+        Some(batch) if batch.kind == kind && batch.texture == texture => {
+            batch.range.end = range.end
+        }
+        _ => batches.push(DrawBatch {
+            kind,
+            texture,
+            range,
+        }),
+    }
+}
+
+/// Layout of the `Globals` uniform every shape pipeline reads at group 0
+fn create_globals_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Globals Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<Globals>() as u64),
+            },
+            count: None,
+        }],
+    })
+}
+
+fn create_gradient_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Background Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../background.wgsl").into()),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Background Pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                },
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<GradientInstance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        1 => Float32x4,
+                        2 => Float32x4,
+                    ],
+                },
+            ],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+/// Stretches the scaled scene texture over the whole frame
+fn create_blit_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    texture_layout: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Blit Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../blit.wgsl").into()),
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Blit Pipeline Layout"),
+        bind_group_layouts: &[texture_layout],
+        immediate_size: 0,
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Blit Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+            }],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+fn set_world_viewport(render_pass: &mut wgpu::RenderPass<'_>, viewport: WorldViewport) {
+    render_pass.set_viewport(
+        viewport.x,
+        viewport.y,
+        viewport.width,
+        viewport.height,
+        0.0,
+        1.0,
+    );
+}
+
+/// Render pass clearing `view` (or the MSAA target resolving into it) to `clear_color`,
+/// writing `timestamps` if given
+fn begin_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    resolve_target: Option<&wgpu::TextureView>,
+    clear_color: wgpu::Color,
+    timestamps: Option<wgpu::RenderPassTimestampWrites<'_>>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear_color),
+                store: wgpu::StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: timestamps,
+        ..Default::default()
+    })
+}
+
+/// Attachment to draw into and what it resolves to: the MSAA view resolving into
+/// `view` when there is one, otherwise `view` itself
+fn color_target<'a>(
+    view: &'a wgpu::TextureView,
+    msaa_view: Option<&'a wgpu::TextureView>,
+) -> (&'a wgpu::TextureView, Option<&'a wgpu::TextureView>) {
+    match msaa_view {
+        Some(msaa_view) => (msaa_view, Some(view)),
+        None => (view, None),
+    }
+}
+
+/// Scene target size for `scale`, or None at scale 1 where no target is needed
+fn scaled_size(
+    size: winit::dpi::PhysicalSize<u32>,
+    scale: f32,
+) -> Option<winit::dpi::PhysicalSize<u32>> {
+    if scale == 1.0 {
+        return None;
+    }
+    let scale_axis = |pixels: u32| ((pixels as f32 * scale).round() as u32).max(1);
+    Some(winit::dpi::PhysicalSize::new(
+        scale_axis(size.width),
+        scale_axis(size.height),
+    ))
+}
+
+fn clamp_render_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE)
+    } else {
+        1.0
+    }
+}
+
+fn create_offscreen_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: winit::dpi::PhysicalSize<u32>,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Target"),
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Map a failed frame acquisition to a skip, or to an error the caller must handle
+fn classify_surface_error(error: wgpu::SurfaceError) -> Result<SkipReason, RenderError> {
+    match error {
+        wgpu::SurfaceError::Outdated => Ok(SkipReason::SurfaceOutdated),
+        wgpu::SurfaceError::Lost => Ok(SkipReason::SurfaceLost),
+        wgpu::SurfaceError::Timeout => Ok(SkipReason::Timeout),
+        wgpu::SurfaceError::Other => Ok(SkipReason::SurfaceUnavailable),
+        wgpu::SurfaceError::OutOfMemory => Err(RenderError::Surface(error)),
+    }
+}
+
+/// Use `requested` if the surface supports it, otherwise warn and fall back to Fifo,
+/// which every surface supports. The Auto modes are resolved by wgpu itself.
+fn choose_present_mode(
+    requested: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> wgpu::PresentMode {
+    match requested {
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => requested,
+        mode if supported.contains(&mode) => mode,
+        mode => {
+            println!(
+                "WARNING: present mode {:?} not supported (have {:?}), using Fifo",
+                mode, supported
+            );
+            wgpu::PresentMode::Fifo
+        }
+    }
+}
+
+/// Next present mode in the Fifo -> Mailbox -> Immediate cycle that is supported
+fn next_present_mode(
+    current: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> wgpu::PresentMode {
+    const CYCLE: [wgpu::PresentMode; 3] = [
+        wgpu::PresentMode::Fifo,
+        wgpu::PresentMode::Mailbox,
+        wgpu::PresentMode::Immediate,
+    ];
+
+    let start = CYCLE.iter().position(|m| *m == current).unwrap_or(0);
+    (1..=CYCLE.len())
+        .map(|offset| CYCLE[(start + offset) % CYCLE.len()])
+        .find(|mode| supported.contains(mode))
+        .unwrap_or(wgpu::PresentMode::Fifo)
+}
+
+/// sRGB transfer function, inverse (one channel, 0.0–1.0)
+/// Convert an sRGB color to what the shaders should output into a view of `format`,
+/// so it shows up on screen as specified. sRGB views encode on write, so they get
+/// linear values; linear views take the sRGB values as-is. Alpha is never converted.
+fn output_color(color: [f32; 4], format: wgpu::TextureFormat) -> [f32; 4] {
+    if format.is_srgb() {
+        [
+            srgb_to_linear(color[0]),
+            srgb_to_linear(color[1]),
+            srgb_to_linear(color[2]),
+            color[3],
+        ]
+    } else {
+        color
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn is_zero_sized(size: winit::dpi::PhysicalSize<u32>) -> bool {
+    size.width == 0 || size.height == 0
+}
+
+/// Top-left pixel of a box of `extent` placed `offset` pixels in from `corner`,
+/// kept at least `offset` from the left and top edges if the window is too small
+fn anchor_position(
+    corner: Corner,
+    size: winit::dpi::PhysicalSize<u32>,
+    extent: (f32, f32),
+    offset: [f32; 2],
+) -> (f32, f32) {
+    let right = (size.width as f32 - extent.0 - offset[0]).max(offset[0]);
+    let bottom = (size.height as f32 - extent.1 - offset[1]).max(offset[1]);
+    match corner {
+        Corner::TopLeft => (offset[0], offset[1]),
+        Corner::TopRight => (right, offset[1]),
+        Corner::BottomLeft => (offset[0], bottom),
+        Corner::BottomRight => (right, bottom),
+    }
+}
+
+/// Width and height (pixels) of the text laid out in a buffer
+fn buffer_extent(buffer: &glyphon::Buffer) -> (f32, f32) {
+    buffer
+        .layout_runs()
+        .fold((0.0, 0.0), |(width, height), run| {
+            (
+                width.max(run.line_w),
+                height.max(run.line_top + run.line_height),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Entity;
+
+    const SIZE: u32 = 64;
+
+    /// Headless renderer, or None (with a note) where there's no GPU adapter at all.
+    /// The stats overlay is hidden so it can't cover the pixels under test
+    fn headless(config: RendererConfig) -> Option<Renderer> {
+        match pollster::block_on(Renderer::new_headless(SIZE, SIZE, config)) {
+            Ok(mut renderer) => {
+                renderer.show_stats = false;
+                Some(renderer)
+            }
+            Err(err) => {
+                println!("skipping headless render test: {err}");
+                None
+            }
+        }
+    }
+
+    /// RGBA of the pixel under `ndc` after rendering `world`
+    fn pixel(renderer: &mut Renderer, world: &World, ndc: [f32; 2]) -> [u8; 4] {
+        renderer.render(world, 1.0).expect("frame should render");
+        let pixels = renderer.read_pixels().expect("pixels should read back");
+        let (x, y) = renderer.world_viewport().ndc_to_pixel(ndc);
+        let x = (x as u32).min(SIZE - 1);
+        let y = (y as u32).min(SIZE - 1);
+        let index = ((y * SIZE + x) * 4) as usize;
+        pixels[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn text_atlas_matches_the_attachment_format() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        assert_eq!(renderer.resources.text.atlas_format, renderer.view_format);
+
+        // A mismatch fails wgpu validation as soon as text is drawn
+        let mut world = World::new();
+        world.add_entity(Entity::new_text(
+            [-0.9, 0.0],
+            "Text".to_string(),
+            40.0,
+            [1.0, 1.0, 1.0],
+        ));
+        renderer.measure_text(&mut world);
+        renderer.render(&world, 1.0).expect("text should render");
+        let pixels = renderer.read_pixels().unwrap();
+        assert!(
+            pixels.chunks(4).any(|pixel| pixel[0] > 128),
+            "some text should have been drawn"
+        );
+    }
+
+    #[test]
+    fn recoverable_surface_errors_skip_the_frame() {
+        let skip = |error| classify_surface_error(error).ok();
+        assert_eq!(
+            skip(wgpu::SurfaceError::Outdated),
+            Some(SkipReason::SurfaceOutdated)
+        );
+        assert_eq!(
+            skip(wgpu::SurfaceError::Lost),
+            Some(SkipReason::SurfaceLost)
+        );
+        assert_eq!(skip(wgpu::SurfaceError::Timeout), Some(SkipReason::Timeout));
+        assert_eq!(
+            skip(wgpu::SurfaceError::Other),
+            Some(SkipReason::SurfaceUnavailable)
+        );
+    }
+
+    #[test]
+    fn out_of_memory_is_an_error() {
+        assert!(matches!(
+            classify_surface_error(wgpu::SurfaceError::OutOfMemory),
+            Err(RenderError::Surface(wgpu::SurfaceError::OutOfMemory))
+        ));
+    }
+
+    #[test]
+    fn headless_frames_read_back_as_rgba_rows() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.3, [1.0, 0.0, 0.0]));
+
+        assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [255, 0, 0, 255]);
+        assert_eq!(pixel(&mut renderer, &world, [-0.95, 0.95]), [0, 0, 0, 255]);
+        let pixels = renderer.read_pixels().unwrap();
+        assert_eq!(pixels.len(), (SIZE * SIZE * 4) as usize);
+    }
+
+    #[test]
+    fn translucent_ball_blends_over_what_is_behind_it() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_rectangle([0.0, 0.0], 1.0, 1.0, [1.0, 0.0, 0.0]));
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.3, [0.0, 0.0, 1.0]).with_opacity(0.3));
+
+        let [r, g, b, _] = pixel(&mut renderer, &world, [0.0, 0.0]);
+        assert!(r > 100 && r < 255, "red should show through: {r}");
+        assert!(b > 0 && b < 255, "blue should be partial: {b}");
+        assert_eq!(g, 0);
+        // Drawn later and opaque, it would cover the rectangle entirely
+        assert!(
+            r > b,
+            "the ghost ball should be the fainter color: {r} vs {b}"
+        );
+    }
+
+    #[test]
+    fn higher_layers_draw_on_top_regardless_of_insertion_order() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        for red_on_top in [false, true] {
+            let red_layer = if red_on_top { 1 } else { 0 };
+            let mut world = World::new();
+            world.add_entity(
+                Entity::new_circle([0.0, 0.0], 0.3, [1.0, 0.0, 0.0]).with_layer(red_layer),
+            );
+            world.add_entity(
+                Entity::new_circle([0.0, 0.0], 0.3, [0.0, 1.0, 0.0]).with_layer(1 - red_layer),
+            );
+            let expected = if red_on_top {
+                [255, 0, 0, 255]
+            } else {
+                [0, 255, 0, 255]
+            };
+            assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), expected);
+
+            let top = world.find_at_point([0.0, 0.0], false).unwrap();
+            let top_color = world.get(top).unwrap().shape().color();
+            assert_eq!(top_color[0] == 1.0, red_on_top, "picking should agree");
+        }
+    }
+
+    #[test]
+    fn textured_rectangle_shows_its_texels() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        // 8x8 with a solid 4x4 quadrant each: red, green on top; blue, white below
+        let quadrants = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255; 4],
+        ];
+        let rgba: Vec<u8> = (0..8)
+            .flat_map(|y| (0..8).map(move |x| quadrants[(y / 4) * 2 + x / 4]))
+            .flatten()
+            .collect();
+        let texture = renderer.create_texture(8, 8, &rgba);
+        let mut world = World::new();
+        world.add_entity(
+            Entity::new_rectangle([0.0, 0.0], 1.6, 1.6, [1.0, 1.0, 1.0]).with_texture(texture),
+        );
+
+        // Quadrant centers, well away from where filtering mixes colors
+        let at = |renderer: &mut Renderer, ndc| pixel(renderer, &world, ndc);
+        assert_eq!(at(&mut renderer, [-0.4, 0.4]), [255, 0, 0, 255]);
+        assert_eq!(at(&mut renderer, [0.4, 0.4]), [0, 255, 0, 255]);
+        assert_eq!(at(&mut renderer, [-0.4, -0.4]), [0, 0, 255, 255]);
+        assert_eq!(at(&mut renderer, [0.4, -0.4]), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn rotated_rectangle_renders_rotated_and_picks_what_is_visible() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut square = Entity::new_rectangle([0.0, 0.0], 0.8, 0.8, [1.0, 1.0, 1.0]);
+        square.transform_mut().rotation = std::f32::consts::FRAC_PI_4;
+        let mut world = World::new();
+        let id = world.add_entity(square).id().unwrap();
+
+        // Turned into a diamond: the unrotated corner is empty, the tip past the
+        // unrotated edge is filled
+        let corner = [0.35, 0.35];
+        let tip = [0.5, 0.0];
+        assert_eq!(pixel(&mut renderer, &world, corner), [0, 0, 0, 255]);
+        assert_eq!(pixel(&mut renderer, &world, tip), [255, 255, 255, 255]);
+
+        let square = world.get(id).unwrap();
+        assert!(!square.contains_point(corner));
+        assert!(square.contains_point(tip));
+    }
+
+    #[test]
+    fn diagonal_line_covers_the_pixels_along_it() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.draw_line([-0.8, -0.8], [0.8, 0.8], 0.1, [1.0, 1.0, 1.0]);
+        renderer
+            .render(&World::new(), 1.0)
+            .expect("frame should render");
+        let pixels = renderer.read_pixels().expect("pixels should read back");
+        let at = |ndc| {
+            let (x, y) = renderer.world_viewport().ndc_to_pixel(ndc);
+            let index = ((y as u32 * SIZE + x as u32) * 4) as usize;
+            <[u8; 4]>::try_from(&pixels[index..index + 4]).unwrap()
+        };
+
+        for t in [-0.6, -0.3, 0.0, 0.3, 0.6] {
+            assert_eq!(at([t, t]), [255, 255, 255, 255], "on the line at {t}");
+        }
+        // Off the line, and past its end
+        assert_eq!(at([0.5, -0.5]), [0, 0, 0, 255]);
+        assert_eq!(at([0.95, 0.95]), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn edge_softness_widens_the_circle_boundary() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        // Pixels along the center row that are neither background nor full white
+        let partial_pixels = |renderer: &mut Renderer, world: &World| {
+            renderer.render(world, 1.0).expect("frame should render");
+            let pixels = renderer.read_pixels().expect("pixels should read back");
+            let row = (SIZE / 2 * SIZE * 4) as usize;
+            (0..SIZE as usize)
+                .filter(|x| !matches!(pixels[row + x * 4], 0 | 255))
+                .count()
+        };
+        let circle = || Entity::new_circle([0.0, 0.0], 0.5, [1.0, 1.0, 1.0]);
+        let mut world = World::new();
+        world.add_entity(circle());
+
+        let default = partial_pixels(&mut renderer, &world);
+        renderer.set_edge_softness(6.0);
+        let soft = partial_pixels(&mut renderer, &world);
+        assert!(soft > default, "{soft} soft vs {default} default");
+
+        // A per-circle override wins over the global setting
+        renderer.set_edge_softness(1.0);
+        let mut world = World::new();
+        world.add_entity(circle().with_edge_softness(6.0));
+        assert_eq!(partial_pixels(&mut renderer, &world), soft);
+    }
+
+    #[test]
+    fn glow_lights_up_past_the_radius() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [1.0, 1.0, 1.0]));
+        let outside = [0.6, 0.0];
+        assert_eq!(pixel(&mut renderer, &world, outside), [0, 0, 0, 255]);
+
+        renderer.set_glow(0.5, 0.5);
+        let halo = pixel(&mut renderer, &world, outside);
+        assert!(
+            halo[0] > 0 && halo[0] < 255,
+            "halo should be faint, got {halo:?}"
+        );
+    }
+
+    #[test]
+    fn rounded_rectangle_corners_show_the_background() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let corner = [0.55, 0.55];
+        let edge_midpoint = [0.55, 0.0];
+        // Sharp first, then rounded, then a radius past half the side (clamped)
+        for (radius, corner_color) in [
+            (0.0, [255; 4]),
+            (0.4, [0, 0, 0, 255]),
+            (5.0, [0, 0, 0, 255]),
+        ] {
+            let mut world = World::new();
+            world.add_entity(
+                Entity::new_rectangle([0.0, 0.0], 1.2, 1.2, [1.0, 1.0, 1.0])
+                    .with_corner_radius(radius),
+            );
+            assert_eq!(
+                pixel(&mut renderer, &world, corner),
+                corner_color,
+                "radius {radius}"
+            );
+            assert_eq!(
+                pixel(&mut renderer, &world, edge_midpoint),
+                [255; 4],
+                "radius {radius}"
+            );
+        }
+    }
+
+    #[test]
+    fn gradient_circle_fades_from_center_to_rim() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle_gradient(
+            [0.0, 0.0],
+            0.8,
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ));
+        // The pixel nearest the center sits a little way out, so allow a trace of blue
+        let [red, green, blue, _] = pixel(&mut renderer, &world, [0.0, 0.0]);
+        assert!(red > 240 && green == 0 && blue < 64, "center should be red");
+        let [red, green, blue, _] = pixel(&mut renderer, &world, [0.7, 0.0]);
+        assert!(blue > red && green == 0, "rim should be mostly blue");
+
+        // Same inner and outer color draws exactly like a flat circle
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle_gradient(
+            [0.0, 0.0],
+            0.8,
+            [0.2, 0.6, 1.0],
+            [0.2, 0.6, 1.0],
+        ));
+        let gradient = pixel(&mut renderer, &world, [0.5, 0.0]);
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.8, [0.2, 0.6, 1.0]));
+        assert_eq!(pixel(&mut renderer, &world, [0.5, 0.0]), gradient);
+    }
+
+    #[test]
+    fn y_sorted_order_draws_lower_entities_on_top() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_draw_order(DrawOrder::YSorted);
+        let red = [1.0, 0.0, 0.0];
+        let green = [0.0, 1.0, 0.0];
+        let center_pixel = |renderer: &mut Renderer, circles: &[([f32; 2], [f32; 3], i32)]| {
+            let mut world = World::new();
+            for &(position, color, layer) in circles {
+                world.add_entity(Entity::new_circle(position, 0.3, color).with_layer(layer));
+            }
+            pixel(renderer, &world, [0.0, 0.0])
+        };
+
+        // The lower circle wins whichever was added first
+        let (high, low) = ([0.0, 0.1], [0.0, -0.1]);
+        assert_eq!(
+            center_pixel(&mut renderer, &[(high, red, 0), (low, green, 0)])[1],
+            255
+        );
+        assert_eq!(
+            center_pixel(&mut renderer, &[(low, green, 0), (high, red, 0)])[1],
+            255
+        );
+
+        // Equal heights keep insertion order, so the later circle is on top
+        let same = [0.0, 0.0];
+        assert_eq!(
+            center_pixel(&mut renderer, &[(same, red, 0), (same, green, 0)])[1],
+            255
+        );
+        assert_eq!(
+            center_pixel(&mut renderer, &[(same, green, 0), (same, red, 0)])[0],
+            255
+        );
+
+        // Layers still come first
+        assert_eq!(
+            center_pixel(&mut renderer, &[(high, red, 1), (low, green, 0)])[0],
+            255
+        );
+    }
+
+    #[test]
+    fn gpu_pick_at_an_overlap_returns_the_top_entity() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_gpu_picking(true);
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([-0.15, 0.0], 0.3, [1.0, 0.0, 0.0]));
+        world.add_entity(Entity::new_circle([0.15, 0.0], 0.3, [0.0, 1.0, 0.0]).with_layer(1));
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.3, [0.0, 0.0, 1.0]).with_layer(-1));
+
+        let (x, y) = renderer.world_viewport().ndc_to_pixel([0.0, 0.0]);
+        let overlap = winit::dpi::PhysicalPosition::new(x as f64, y as f64);
+        // The first lookup only requests the pixel; the answer arrives a frame later
+        let mut picked = renderer.pick(overlap);
+        for _ in 0..4 {
+            if picked.is_some() {
+                break;
+            }
+            renderer.render(&world, 1.0).expect("frame should render");
+            picked = renderer.pick(overlap);
+        }
+        assert_eq!(picked, Some(1));
+        assert_eq!(renderer.entity_at(&world, overlap), world.id_at(1));
+    }
+
+    #[test]
+    fn label_cache_goes_stale_for_exactly_the_changed_entity() {
+        let mut world = World::new();
+        let ids: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .enumerate()
+            .map(|(n, name)| {
+                world
+                    .add_entity(
+                        Entity::new_circle([n as f32 * 0.3, 0.0], 0.05, [1.0, 1.0, 1.0])
+                            .with_name(name),
+                    )
+                    .id()
+                    .unwrap()
+            })
+            .collect();
+        let mut cache = TextCache::new();
+        // What a frame does: drain, check every label, then sweep
+        let mut frame = |world: &mut World| {
+            world.drain_changes();
+            let stale: Vec<_> = world
+                .iter()
+                .filter(|(_, entity)| cache.is_stale(entity))
+                .map(|(id, _)| id)
+                .collect();
+            cache.evict_unused(world.change_tick());
+            stale
+        };
+
+        assert_eq!(frame(&mut world), ids);
+        assert!(frame(&mut world).is_empty());
+
+        world.set_name(ids[1], Some("renamed".to_string()));
+        assert_eq!(frame(&mut world), [ids[1]]);
+        assert!(frame(&mut world).is_empty());
+
+        // Reading doesn't count as a change
+        let _ = world.get(ids[2]).unwrap().name();
+        assert!(frame(&mut world).is_empty());
+    }
+
+    /// Shape instances `collect_instances` builds for `world`, viewed as the
+    /// whole NDC square
+    fn instances(world: &World, alpha: f32) -> Vec<ShapeInstance> {
+        colored_instances(world, alpha, ColorMode::Static)
+    }
+
+    fn colored_instances(world: &World, alpha: f32, color_mode: ColorMode) -> Vec<ShapeInstance> {
+        let view = Aabb::from_center([0.0, 0.0], [1.0, 1.0]);
+        visible_instances(world, alpha, color_mode, view).0
+    }
+
+    /// Instances inside `view`, and how many shapes were culled
+    fn visible_instances(
+        world: &World,
+        alpha: f32,
+        color_mode: ColorMode,
+        view: Aabb,
+    ) -> (Vec<ShapeInstance>, usize) {
+        let config = RendererConfig::default();
+        let effects = CollisionEffects {
+            impacts: ImpactCache::new(config.impacts.duration),
+            flashes: FlashCache::new(&config.flash),
+            hover_tint: DEFAULT_HOVER_TINT,
+        };
+        let order: Vec<usize> = (0..world.entities().len()).collect();
+        let (mut shapes, mut polygons, mut batches) = (Vec::new(), Vec::new(), Vec::new());
+        let culled = collect_instances(
+            world,
+            alpha,
+            color_mode,
+            view,
+            &order,
+            &effects,
+            &mut shapes,
+            &mut polygons,
+            &mut batches,
+        );
+        (shapes, culled)
+    }
+
+    #[test]
+    fn instance_collection_includes_rectangles() {
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.5], 0.1, [1.0, 0.0, 0.0]));
+        world.add_entity(Entity::new_rectangle(
+            [0.0, -0.8],
+            1.6,
+            0.1,
+            [0.0, 1.0, 0.0],
+        ));
+
+        let shapes = instances(&world, 1.0);
+        assert_eq!(shapes.len(), 2);
+        assert_eq!(shapes[0].kind, SHAPE_CIRCLE);
+        let floor = shapes[1];
+        assert_eq!(floor.kind, SHAPE_RECTANGLE);
+        assert_eq!(floor.position, [0.0, -0.8]);
+        assert_eq!(floor.size, [1.6, 0.1]);
+        assert_eq!(floor.color, [0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn instances_sit_between_the_last_two_sim_positions() {
+        let mut world = World::new();
+        let mut ball = Entity::new_circle([0.2, 0.0], 0.1, [1.0, 1.0, 1.0]);
+        ball.transform_mut().prev_position = [0.1, 0.0];
+        world.add_entity(ball);
+
+        let halfway = instances(&world, 0.5)[0].position;
+        assert!((halfway[0] - 0.15).abs() < 1e-6, "{halfway:?}");
+        assert_ne!(
+            halfway,
+            [0.2, 0.0],
+            "alpha 0.5 shouldn't draw the raw position"
+        );
+        assert_eq!(instances(&world, 1.0)[0].position, [0.2, 0.0]);
+
+        // A fresh entity has no previous step, so any alpha draws it in place
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.2, 0.0], 0.1, [1.0, 1.0, 1.0]));
+        assert_eq!(instances(&world, 0.0)[0].position, [0.2, 0.0]);
+    }
+
+    #[test]
+    fn stats_summary_shows_the_reported_diagnostics() {
+        let mut stats = FrameStats::new(Instant::now());
+        let diagnostics = PhysicsDiagnostics {
+            contacts: 12,
+            sleeping: 4,
+            kinetic_energy: 1.5,
+        };
+        stats.record_physics(30, &diagnostics);
+        assert!(
+            stats.summary().contains("Entities:     0"),
+            "numbers only change at a report"
+        );
+
+        stats.update();
+        let summary = stats.summary();
+        for field in [
+            "Entities:    30",
+            "Contacts:    12",
+            "Sleeping:     4",
+            "Kinetic:     1.500",
+        ] {
+            assert!(summary.contains(field), "missing {field:?} in\n{summary}");
+        }
+    }
+
+    #[test]
+    fn clear_color_round_trips_and_gradients_build_their_pipeline_on_demand() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        assert!(renderer.gradient_pipeline.is_none());
+
+        let color = [0.5, 0.25, 0.0, 1.0];
+        renderer.set_clear_color(color);
+        assert_eq!(renderer.background(), Background::Solid(color));
+        assert!(
+            renderer.gradient_pipeline.is_none(),
+            "solid colors just clear"
+        );
+        // The sRGB color set is what lands on screen, whatever the target format
+        let [r, g, b, _] = pixel(&mut renderer, &World::new(), [0.0, 0.0]);
+        assert!(
+            r.abs_diff(128) <= 1 && g.abs_diff(64) <= 1 && b == 0,
+            "{r} {g} {b}"
+        );
+
+        let gradient = Background::Gradient {
+            top: [0.0, 0.0, 0.2, 1.0],
+            bottom: [0.0, 0.0, 0.0, 1.0],
+        };
+        renderer.set_background(gradient);
+        assert_eq!(renderer.background(), gradient);
+        assert!(renderer.gradient_pipeline.is_some());
+
+        // Asked for up front, it's built with the renderer
+        let Some(renderer) = headless(RendererConfig {
+            background: gradient,
+            ..Default::default()
+        }) else {
+            return;
+        };
+        assert_eq!(renderer.background(), gradient);
+        assert!(renderer.gradient_pipeline.is_some());
+    }
+
+    #[test]
+    fn pipelines_build_and_draw_with_and_without_msaa() {
+        for requested in [1, 4, 3] {
+            let Some(mut renderer) = headless(RendererConfig {
+                sample_count: requested,
+                ..Default::default()
+            }) else {
+                return;
+            };
+            // Counts the adapter can't do (3 never is) fall back to 1
+            let samples = renderer.sample_count();
+            assert!(
+                samples == requested || samples == 1,
+                "{requested} -> {samples}"
+            );
+            if requested == 3 {
+                assert_eq!(samples, 1);
+            }
+            assert_eq!(renderer.msaa_view.is_some(), samples > 1);
+
+            let mut world = World::new();
+            world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [1.0, 0.0, 0.0]));
+            assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [255, 0, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn present_modes_fall_back_to_what_the_surface_supports() {
+        use wgpu::PresentMode::{AutoNoVsync, AutoVsync, Fifo, Immediate, Mailbox};
+        let fifo_only = [Fifo];
+        let all = [Fifo, Mailbox, Immediate];
+
+        assert_eq!(choose_present_mode(Mailbox, &all), Mailbox);
+        assert_eq!(choose_present_mode(Mailbox, &fifo_only), Fifo);
+        assert_eq!(choose_present_mode(Immediate, &[Fifo, Mailbox]), Fifo);
+        // The Auto modes pick their own fallback, so they're always accepted
+        assert_eq!(choose_present_mode(AutoVsync, &fifo_only), AutoVsync);
+        assert_eq!(choose_present_mode(AutoNoVsync, &fifo_only), AutoNoVsync);
+
+        // Cycling skips unsupported modes and wraps around
+        assert_eq!(next_present_mode(Fifo, &all), Mailbox);
+        assert_eq!(next_present_mode(Mailbox, &all), Immediate);
+        assert_eq!(next_present_mode(Immediate, &all), Fifo);
+        assert_eq!(next_present_mode(Fifo, &[Fifo, Immediate]), Immediate);
+        assert_eq!(next_present_mode(Fifo, &fifo_only), Fifo);
+        assert_eq!(next_present_mode(AutoVsync, &all), Mailbox);
+    }
+
+    #[test]
+    fn overlay_ring_leaves_its_center_empty() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let world = World::new();
+        let ring = |renderer: &mut Renderer| {
+            renderer.push_overlay_ring([0.0, 0.0], 0.5, 0.1, [0.0, 1.0, 0.0])
+        };
+
+        ring(&mut renderer);
+        assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [0, 0, 0, 255]);
+        ring(&mut renderer);
+        assert_eq!(pixel(&mut renderer, &world, [0.45, 0.0]), [0, 255, 0, 255]);
+        // Rings last one frame
+        assert_eq!(pixel(&mut renderer, &world, [0.45, 0.0]), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn trails_keep_the_latest_positions_and_clear_on_teleport() {
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_circle([0.0, 0.0], 0.05, [1.0; 3]))
+            .id()
+            .unwrap();
+        let mut trails = TrailCache::new(3);
+        for step in 0..5 {
+            trails.record(id, [step as f32 * 0.1, 0.0]);
+        }
+        assert_eq!(
+            trails.get(id).unwrap(),
+            &VecDeque::from([[0.2, 0.0], [0.3, 0.0], [0.4, 0.0]]),
+            "only the last `length` positions stay"
+        );
+
+        trails.set_length(2);
+        assert_eq!(trails.get(id).unwrap().len(), 2);
+
+        // A jump past the teleport distance starts a fresh trail instead of a streak
+        trails.record(id, [0.4 + TRAIL_TELEPORT_DISTANCE * 1.5, 0.0]);
+        assert_eq!(trails.get(id).unwrap().len(), 1);
+
+        // Entities that stop being recorded lose their trail at the next sweep
+        trails.evict_unused();
+        assert!(trails.get(id).is_some());
+        trails.evict_unused();
+        assert!(trails.get(id).is_none());
+    }
+
+    #[test]
+    fn speed_colors_clamp_and_handle_a_zero_max() {
+        let [slow, middle, fast] = SPEED_GRADIENT;
+        let assert_color = |actual: [f32; 3], expected: [f32; 3]| {
+            let close = actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() < 1e-6);
+            assert!(close, "{actual:?} vs {expected:?}");
+        };
+        assert_color(speed_color(0.0, 2.0), slow);
+        assert_color(speed_color(1.0, 2.0), middle);
+        assert_color(speed_color(2.0, 2.0), fast);
+        assert_color(speed_color(50.0, 2.0), fast); // Clamped above max
+        assert_color(speed_color(0.0, 0.0), slow);
+        assert_color(speed_color(0.1, 0.0), fast);
+        assert_color(speed_color(0.1, -1.0), fast);
+
+        // Only bodies with physics are recolored, and the stored shapes keep their colors
+        let mut world = World::new();
+        let mut physics = crate::components::Physics::new();
+        physics.velocity = [3.0, 4.0];
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.1, [0.5; 3]).with_physics(physics));
+        world.add_entity(Entity::new_rectangle([0.0, -0.8], 1.0, 0.1, [0.5; 3]));
+        let shapes = colored_instances(&world, 1.0, ColorMode::Speed { max: 2.0 });
+        assert_color(shapes[0].color[..3].try_into().unwrap(), fast);
+        assert_eq!(shapes[1].color, [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(world.entities()[0].shape().color(), [0.5; 3]);
+    }
+
+    #[test]
+    fn pentagon_fills_with_its_color_and_follows_its_transform() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let pentagon: Vec<[f32; 2]> = (0..5)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / 5.0;
+                [0.3 * angle.cos(), 0.3 * angle.sin()]
+            })
+            .collect();
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_polygon([-0.4, 0.0], pentagon, [1.0, 0.0, 1.0]).unwrap())
+            .id()
+            .unwrap();
+        assert_eq!(
+            pixel(&mut renderer, &world, [-0.4, 0.0]),
+            [255, 0, 255, 255]
+        );
+        assert_eq!(pixel(&mut renderer, &world, [0.4, 0.0]), [0, 0, 0, 255]);
+
+        world.get_mut(id).unwrap().transform_mut().position = [0.4, 0.0];
+        assert_eq!(pixel(&mut renderer, &world, [-0.4, 0.0]), [0, 0, 0, 255]);
+        assert_eq!(pixel(&mut renderer, &world, [0.4, 0.0]), [255, 0, 255, 255]);
+
+        // A degenerate (flat) outline draws nothing and doesn't upset the frame
+        let flat = vec![[0.0, 0.0], [0.1, 0.0], [0.2, 0.0]];
+        world.add_entity(Entity::new_polygon([0.0, 0.5], flat, [1.0; 3]).unwrap());
+        assert_eq!(pixel(&mut renderer, &world, [0.4, 0.0]), [255, 0, 255, 255]);
+    }
+
+    #[test]
+    fn a_corner_view_culls_most_of_a_large_world_but_keeps_everything_visible() {
+        // 40 x 25 balls spread over the whole NDC square
+        let mut world = World::new();
+        for row in 0..25 {
+            for column in 0..40 {
+                let position = [-1.0 + column as f32 * 0.05, -1.0 + row as f32 * 0.08];
+                world.add_entity(Entity::new_circle(position, 0.02, [1.0; 3]));
+            }
+        }
+        assert_eq!(world.len(), 1000);
+
+        let corner = Aabb {
+            min: [0.61, 0.61],
+            max: [1.0, 1.0],
+        };
+        let (shapes, culled) = visible_instances(&world, 1.0, ColorMode::Static, corner);
+        assert!(
+            shapes.len() < 100,
+            "{} instances for the corner",
+            shapes.len()
+        );
+        assert_eq!(shapes.len() + culled, 1000);
+
+        // Every ball touching the view is drawn, including ones only partly inside
+        let touching: Vec<[f32; 2]> = world
+            .entities()
+            .iter()
+            .map(|entity| entity.transform().position)
+            .filter(|&[x, y]| x + 0.02 >= 0.61 && y + 0.02 >= 0.61)
+            .collect();
+        let drawn: Vec<[f32; 2]> = shapes.iter().map(|shape| shape.position).collect();
+        assert_eq!(drawn, touching);
+        assert!(
+            drawn.iter().any(|&[x, _]| x < 0.61),
+            "partly visible balls should be kept"
+        );
+    }
+
+    #[test]
+    fn renderer_options_map_onto_the_wgpu_descriptors() {
+        let defaults = RendererOptions::default();
+        assert_eq!(
+            defaults.instance_descriptor().backends,
+            wgpu::Backends::all()
+        );
+        let adapter = defaults.adapter_options(None);
+        assert_eq!(
+            adapter.power_preference,
+            wgpu::PowerPreference::HighPerformance
+        );
+        assert!(!adapter.force_fallback_adapter);
+        assert!(adapter.compatible_surface.is_none());
+
+        let options = RendererOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            force_fallback_adapter: true,
+            backends: Some(wgpu::Backends::VULKAN),
+        };
+        assert_eq!(
+            options.instance_descriptor().backends,
+            wgpu::Backends::VULKAN
+        );
+        let adapter = options.adapter_options(None);
+        assert_eq!(adapter.power_preference, wgpu::PowerPreference::LowPower);
+        assert!(adapter.force_fallback_adapter);
+    }
+
+    #[test]
+    fn grid_lines_show_up_at_their_pixels() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_grid(GridConfig {
+            enabled: true,
+            cell_size: 0.5,
+            major_every: 1,
+            color: [1.0; 3],
+            major_alpha: 1.0,
+            thickness: 0.05,
+            ..Default::default()
+        });
+        let world = World::new();
+        assert_ne!(pixel(&mut renderer, &world, [0.5, 0.25]), [0, 0, 0, 255]);
+        assert_ne!(pixel(&mut renderer, &world, [-0.25, 0.0]), [0, 0, 0, 255]);
+        assert_eq!(pixel(&mut renderer, &world, [0.25, 0.25]), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn skipped_frames_leave_the_stats_alone() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let world = World::new();
+        assert_eq!(
+            renderer.render(&world, 1.0).unwrap(),
+            FrameOutcome::Presented
+        );
+        assert_eq!(renderer.frame_stats.render_count, 1);
+        assert_eq!(renderer.frame_stats.frame_count, 1);
+
+        renderer.simulated_surface_error = Some(wgpu::SurfaceError::Outdated);
+        assert_eq!(
+            renderer.render(&world, 1.0).unwrap(),
+            FrameOutcome::Skipped(SkipReason::SurfaceOutdated)
+        );
+        assert_eq!(renderer.frame_stats.render_count, 1);
+        assert_eq!(renderer.frame_stats.frame_count, 1);
+
+        // The next frame goes through as normal
+        assert_eq!(
+            renderer.render(&world, 1.0).unwrap(),
+            FrameOutcome::Presented
+        );
+        assert_eq!(renderer.frame_stats.render_count, 2);
+
+        renderer.simulated_surface_error = Some(wgpu::SurfaceError::OutOfMemory);
+        assert!(renderer.render(&world, 1.0).is_err());
+        assert_eq!(renderer.frame_stats.render_count, 2);
+    }
+
+    #[test]
+    fn globals_buffer_holds_this_frames_values() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_sim_time(Duration::from_millis(2500));
+        renderer.set_edge_pulse(0.3);
+        renderer
+            .render(&World::new(), 1.0)
+            .expect("frame should render");
+
+        let size = std::mem::size_of::<Globals>() as wgpu::BufferAddress;
+        let readback = renderer.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Globals Readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = renderer.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(&renderer.globals_buffer, 0, &readback, 0, size);
+        renderer.queue.submit([encoder.finish()]);
+        readback.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+        renderer
+            .device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .unwrap();
+
+        let globals: Globals = bytemuck::pod_read_unaligned(&readback.slice(..).get_mapped_range());
+        assert_eq!(globals.time, 2.5);
+        assert_eq!(globals.edge_pulse, 0.3);
+        assert_eq!(globals.screen_size, [SIZE as f32, SIZE as f32]);
+    }
+
+    #[test]
+    fn globals_match_the_shader_struct() {
+        // Every member is an f32 or a vec2<f32>, so sizes add up without padding
+        let body = SHAPE_SHADER_SOURCE
+            .split("struct Globals {")
+            .nth(1)
+            .and_then(|rest| rest.split('}').next())
+            .expect("shader.wgsl should declare Globals");
+        let wgsl_size: usize = body
+            .lines()
+            .filter_map(|line| line.split("//").next()?.split(':').nth(1))
+            .map(|ty| match ty.trim().trim_end_matches(',') {
+                "f32" => 4,
+                "vec2<f32>" => 8,
+                other => panic!("unexpected Globals member type {other}"),
+            })
+            .sum();
+        assert_eq!(wgsl_size, std::mem::size_of::<Globals>());
+        assert!(
+            SHAPE_SHADER_SOURCE.contains("@group(0) @binding(0) var<uniform> globals: Globals;")
+        );
+    }
+
+    /// The pass-based renderer has to keep drawing this frame: grid and debug
+    /// lines under and over every shape kind, in layer order. Text is left out
+    /// since it depends on the installed fonts. Run with `UPDATE_GOLDEN=1` to
+    /// rewrite the reference after an intended change.
+    #[test]
+    fn frame_matches_the_golden_image() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_grid(GridConfig {
+            enabled: true,
+            cell_size: 0.5,
+            ..Default::default()
+        });
+        let mut world = World::new();
+        world.add_entity(Entity::new_rectangle(
+            [0.0, -0.7],
+            1.6,
+            0.2,
+            [0.2, 0.6, 0.2],
+        ));
+        world.add_entity(Entity::new_circle([-0.4, 0.2], 0.3, [0.9, 0.2, 0.2]).with_layer(1));
+        world.add_entity(Entity::new_circle([-0.1, 0.2], 0.3, [0.2, 0.3, 0.9]).with_opacity(0.5));
+        let triangle = vec![[0.0, 0.25], [-0.25, -0.2], [0.25, -0.2]];
+        world.add_entity(Entity::new_polygon([0.5, 0.3], triangle, [0.9, 0.8, 0.1]).unwrap());
+        renderer.push_overlay_ring([0.5, -0.3], 0.2, 0.05, [1.0, 1.0, 1.0]);
+        renderer.draw_line([-0.9, 0.9], [0.9, -0.9], 0.03, [0.0, 1.0, 1.0]);
+        renderer.render(&world, 1.0).expect("frame should render");
+        let pixels = renderer.read_pixels().expect("pixels should read back");
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/frame.png");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            image::save_buffer(&path, &pixels, SIZE, SIZE, image::ColorType::Rgba8).unwrap();
+            return;
+        }
+        let golden = image::open(&path)
+            .expect("golden image should load; run with UPDATE_GOLDEN=1 to create it")
+            .to_rgba8();
+        // Allow for rasterizers that round edges differently
+        let differing = pixels
+            .chunks(4)
+            .zip(golden.as_raw().chunks(4))
+            .filter(|(a, b)| a.iter().zip(b.iter()).any(|(a, b)| a.abs_diff(*b) > 8))
+            .count();
+        assert!(
+            differing <= (SIZE * SIZE / 100) as usize,
+            "{differing} pixels differ from {}",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn measured_label_is_clickable_across_its_width_and_drawn_centered() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let p = [0.1, -0.2];
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_text(p, "WWW".to_string(), 12.0, [1.0; 3]))
+            .id()
+            .unwrap();
+        renderer.measure_text(&mut world);
+        let Shape::Text {
+            measured_size: Some([width, height]),
+            ..
+        } = *world.get(id).unwrap().shape()
+        else {
+            panic!("text should have been measured");
+        };
+        assert!(width > 0.2, "a long label should be wide: {width}");
+
+        // Near either end is a hit; just past them is not
+        for side in [-1.0, 1.0] {
+            let inside = [p[0] + side * width / 2.0 * 0.9, p[1]];
+            let outside = [p[0] + side * width / 2.0 * 1.1, p[1]];
+            assert_eq!(world.find_at_point(inside, false), Some(id));
+            assert_eq!(world.find_at_point(outside, false), None);
+        }
+        assert_eq!(
+            world.find_at_point([p[0], p[1] + height * 0.6], false),
+            None
+        );
+
+        // The lit pixels are centered on p (glyph ink sits within the line box,
+        // so allow a little slack vertically)
+        renderer.render(&world, 1.0).expect("frame should render");
+        let pixels = renderer.read_pixels().unwrap();
+        let lit: Vec<(u32, u32)> = (0..SIZE * SIZE)
+            .filter(|i| pixels[*i as usize * 4] > 64)
+            .map(|i| (i % SIZE, i / SIZE))
+            .collect();
+        let (min_x, max_x) = (
+            lit.iter().map(|p| p.0).min().unwrap(),
+            lit.iter().map(|p| p.0).max().unwrap(),
+        );
+        let (min_y, max_y) = (
+            lit.iter().map(|p| p.1).min().unwrap(),
+            lit.iter().map(|p| p.1).max().unwrap(),
+        );
+        let (x, y) = renderer.world_viewport().ndc_to_pixel(p);
+        assert!(
+            ((min_x + max_x) as f32 / 2.0 - x).abs() <= 2.0,
+            "{min_x}..{max_x} vs {x}"
+        );
+        assert!(
+            ((min_y + max_y) as f32 / 2.0 - y).abs() <= 4.0,
+            "{min_y}..{max_y} vs {y}"
+        );
+    }
+
+    #[test]
+    fn minimizing_skips_frames_and_restoring_resumes_them() {
+        use winit::dpi::PhysicalSize;
+        assert!(is_zero_sized(PhysicalSize::new(0, 0)));
+        assert!(is_zero_sized(PhysicalSize::new(0, 600)));
+        assert!(is_zero_sized(PhysicalSize::new(800, 0)));
+        assert!(!is_zero_sized(PhysicalSize::new(1, 1)));
+
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let world = World::new();
+        renderer.resize(PhysicalSize::new(0, 0));
+        assert_eq!(
+            renderer.size,
+            PhysicalSize::new(SIZE, SIZE),
+            "keeps the last real size"
+        );
+        assert_eq!(
+            renderer.render(&world, 1.0).unwrap(),
+            FrameOutcome::Skipped(SkipReason::Minimized)
+        );
+        assert_eq!(renderer.frame_stats.render_count, 0);
+
+        renderer.resize(PhysicalSize::new(SIZE, SIZE));
+        assert_eq!(
+            renderer.render(&world, 1.0).unwrap(),
+            FrameOutcome::Presented
+        );
+    }
+
+    #[test]
+    fn half_gray_lands_as_srgb_half_gray() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [0.5, 0.5, 0.5]));
+        let [r, g, b, _] = pixel(&mut renderer, &world, [0.0, 0.0]);
+        for channel in [r, g, b] {
+            assert!(channel.abs_diff(128) <= 2, "got {channel}");
+        }
+
+        // sRGB targets get linear values to encode; linear targets take sRGB as-is
+        let gray = [0.5, 0.5, 0.5, 0.5];
+        assert_eq!(output_color(gray, wgpu::TextureFormat::Rgba8Unorm), gray);
+        let [linear, _, _, alpha] = output_color(gray, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert!((linear - 0.214).abs() < 1e-3, "{linear}");
+        assert_eq!(alpha, 0.5, "alpha is never converted");
+    }
+
+    #[test]
+    fn title_summary_is_the_compact_stats_line() {
+        let mut stats = FrameStats::new(Instant::now());
+        stats.render_fps = 120;
+        stats.sim_tps = 125;
+        stats.entity_count = 342;
+        assert_eq!(
+            stats.title_summary(),
+            format!("{WINDOW_TITLE} — 120 fps / 125 tps / 342 entities")
+        );
+        // The overlay's summary reports the same numbers
+        let summary = stats.summary();
+        assert!(summary.contains("Render:   120 fps"), "{summary}");
+        assert!(summary.contains("Sim:      125 ticks/s"), "{summary}");
+        assert!(summary.contains("Entities:   342"), "{summary}");
+    }
+
+    #[test]
+    fn impact_squash_eases_out_and_overlapping_hits_keep_the_stronger() {
+        let duration = Duration::from_millis(100);
+        let at = |ms| impact_squash(0.4, Duration::from_millis(ms), duration);
+        assert_eq!(at(0), 0.4);
+        assert!((at(50) - 0.1).abs() < 1e-6, "eases out: {}", at(50));
+        assert!(at(25) > at(50) && at(50) > at(75) && at(75) > 0.0);
+        assert_eq!(at(100), 0.0);
+        assert_eq!(at(250), 0.0);
+
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_circle([0.0, 0.0], 0.1, [1.0; 3]))
+            .id()
+            .unwrap();
+        let mut impacts = ImpactCache::new(duration);
+        let start = Instant::now();
+        impacts.record(id, [0.0, 1.0], 0.3, start);
+        // A weaker hit while the first is still strong doesn't replace or add to it
+        impacts.record(id, [1.0, 0.0], 0.1, start + Duration::from_millis(10));
+        impacts.advance(start + Duration::from_millis(10));
+        let (angle, squash) = impacts.get(id).unwrap();
+        assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!(squash < 0.3);
+        // A stronger one takes over
+        impacts.record(id, [1.0, 0.0], 0.35, start + Duration::from_millis(10));
+        assert_eq!(impacts.get(id), Some((0.0, 0.35)));
+
+        impacts.advance(start + Duration::from_millis(200));
+        assert_eq!(impacts.get(id), None);
+    }
+
+    #[test]
+    fn gentle_contacts_leave_circles_round() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        renderer.set_impacts(ImpactConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        let mut world = World::new();
+        let id = world
+            .add_entity(Entity::new_circle([0.0, 0.0], 0.1, [1.0; 3]))
+            .id()
+            .unwrap();
+        let threshold = renderer.impacts().threshold;
+        renderer.record_impact(id, [0.0, 1.0], threshold * 0.5);
+        renderer.effects.impacts.advance(Instant::now());
+        assert_eq!(renderer.effects.impacts.get(id), None);
+
+        renderer.record_impact(id, [0.0, 1.0], threshold + 0.5);
+        renderer.effects.impacts.advance(Instant::now());
+        assert!(renderer.effects.impacts.get(id).is_some());
+    }
+
+    #[test]
+    fn flashes_fade_restart_on_a_new_hit_and_leave_bystanders_alone() {
+        let duration = Duration::from_millis(200);
+        assert_eq!(flash_intensity(0.8, Duration::ZERO, duration), 0.8);
+        assert!((flash_intensity(0.8, Duration::from_millis(50), duration) - 0.6).abs() < 1e-6);
+        assert_eq!(flash_intensity(0.8, duration, duration), 0.0);
+        assert_eq!(
+            flash_intensity(0.8, Duration::from_millis(10), Duration::ZERO),
+            0.0
+        );
+
+        let mut world = World::new();
+        let hit = world
+            .add_entity(Entity::new_circle([0.0, 0.0], 0.1, [1.0; 3]))
+            .id()
+            .unwrap();
+        let bystander = world
+            .add_entity(Entity::new_circle([0.5, 0.0], 0.1, [1.0; 3]))
+            .id()
+            .unwrap();
+        let mut flashes = FlashCache::new(&FlashConfig {
+            enabled: true,
+            color: [1.0, 1.0, 1.0],
+            duration,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        flashes.record(hit, 1.0, start);
+        flashes.advance(start + Duration::from_millis(150));
+        let faded = flashes.apply(hit, [0.0, 0.0, 0.0]);
+        assert!((faded[0] - 0.25).abs() < 1e-6, "{faded:?}");
+
+        // Another hit during the fade starts over at full intensity
+        flashes.record(hit, 0.5, start + Duration::from_millis(150));
+        assert_eq!(flashes.apply(hit, [0.0, 0.0, 0.0]), [1.0, 1.0, 1.0]);
+
+        let color = [0.123, 0.456, 0.789];
+        assert_eq!(flashes.apply(bystander, color), color);
+    }
+
+    #[test]
+    fn scaled_target_follows_the_render_scale_and_native_scale_draws_directly() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        assert_eq!(
+            renderer.scaled_target_size(),
+            None,
+            "1.0 draws straight to the target"
+        );
+
+        renderer.set_render_scale(0.5);
+        assert_eq!(
+            renderer.scaled_target_size(),
+            Some(winit::dpi::PhysicalSize::new(SIZE / 2, SIZE / 2))
+        );
+        assert!(renderer.stats_text.contains("Scale:     0.50x"));
+        renderer.set_render_scale(2.0);
+        assert_eq!(
+            renderer.scaled_target_size(),
+            Some(winit::dpi::PhysicalSize::new(SIZE * 2, SIZE * 2))
+        );
+        // Scaled scenes still come out right once stretched over the window
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [1.0, 0.0, 0.0]));
+        assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [255, 0, 0, 255]);
+
+        renderer.set_render_scale(1.0);
+        assert_eq!(renderer.scaled_target_size(), None);
+        assert_eq!(clamp_render_scale(10.0), MAX_RENDER_SCALE);
+        assert_eq!(clamp_render_scale(0.0), MIN_RENDER_SCALE);
+        assert_eq!(clamp_render_scale(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn fullscreen_state_restores_the_first_windowed_geometry() {
+        use winit::dpi::{PhysicalPosition, PhysicalSize};
+        let mut state = FullscreenState::default();
+        assert!(!state.is_fullscreen());
+        assert_eq!(state.exit(), None, "nothing to restore without entering");
+
+        let windowed = (
+            PhysicalSize::new(800, 600),
+            Some(PhysicalPosition::new(40, 30)),
+        );
+        state.enter(windowed.0, windowed.1);
+        assert!(state.is_fullscreen());
+        // A second enter would otherwise save the fullscreen size over the windowed one
+        state.enter(
+            PhysicalSize::new(1920, 1080),
+            Some(PhysicalPosition::new(0, 0)),
+        );
+        assert_eq!(state.exit(), Some(windowed));
+        assert!(!state.is_fullscreen());
+        assert_eq!(state.exit(), None);
+
+        // Platforms without window positions still get their size back
+        state.enter(PhysicalSize::new(640, 480), None);
+        assert_eq!(state.exit(), Some((PhysicalSize::new(640, 480), None)));
+    }
+
+    #[test]
+    fn recording_numbers_every_nth_frame_and_flushes_on_stop() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let dir = std::env::temp_dir().join(format!("balltest-recording-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        renderer
+            .start_recording(&dir, 2)
+            .expect("recording should start");
+        assert!(renderer.is_recording());
+
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [1.0, 0.0, 0.0]));
+        for _ in 0..6 {
+            renderer.render(&world, 1.0).expect("frame should render");
+        }
+        let stats = renderer.stop_recording().expect("a recording was running");
+        assert!(!renderer.is_recording());
+        assert_eq!(
+            stats.captured + stats.dropped,
+            3,
+            "every second of 6 frames"
+        );
+        assert!(stats.captured > 0);
+
+        // Captures are numbered from 0 with no gaps, and hold what was drawn
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let expected: Vec<String> = (0..stats.captured)
+            .map(|number| format!("frame_{number:06}.png"))
+            .collect();
+        assert_eq!(names, expected);
+        let first = image::open(dir.join(&names[0])).unwrap().to_rgba8();
+        assert_eq!(first.get_pixel(SIZE / 2, SIZE / 2).0, [255, 0, 0, 255]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "dev-tools")]
+    #[test]
+    fn a_bad_shader_reload_keeps_the_working_pipeline() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, 0.0], 0.5, [1.0, 0.0, 0.0]));
+
+        let broken = SHAPE_SHADER_SOURCE.replace("fn vs_main", "fn vs_main_typo");
+        assert!(renderer.reload_shape_shader(&broken).is_err());
+        assert!(renderer.reload_shape_shader("not wgsl at all {").is_err());
+        assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [255, 0, 0, 255]);
+
+        assert_eq!(renderer.reload_shape_shader(SHAPE_SHADER_SOURCE), Ok(()));
+        assert_eq!(pixel(&mut renderer, &world, [0.0, 0.0]), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn shadows_fade_and_spread_with_height() {
+        let config = ShadowConfig {
+            enabled: true,
+            max_height: 1.0,
+            opacity: 0.5,
+            offset: 0.2,
+            spread: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            shadow_shape(0.0, &config),
+            Some(ShadowShape {
+                offset: 0.0,
+                opacity: 0.5,
+                scale: 1.0
+            })
+        );
+        let half = shadow_shape(0.5, &config).unwrap();
+        assert!((half.offset - 0.1).abs() < 1e-6);
+        assert!((half.opacity - 0.25).abs() < 1e-6);
+        assert!((half.scale - 1.5).abs() < 1e-6);
+        assert_eq!(shadow_shape(1.0, &config), None, "faded out at max height");
+        assert_eq!(shadow_shape(3.0, &config), None);
+        // Sinking into the ground counts as touching it
+        assert_eq!(shadow_shape(-0.1, &config), shadow_shape(0.0, &config));
+    }
+
+    #[test]
+    fn disabled_shadows_draw_exactly_what_no_shadows_do() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        // Shadows are dark, so they only show against a light background
+        renderer.set_clear_color([0.8, 0.8, 0.8, 1.0]);
+        let mut world = World::new();
+        world.add_entity(Entity::new_circle([0.0, -0.5], 0.2, [1.0, 0.0, 0.0]));
+        world.add_entity(Entity::new_circle([0.4, 0.5], 0.1, [0.0, 1.0, 0.0]));
+        renderer.render(&world, 1.0).unwrap();
+        let plain = renderer.read_pixels().unwrap();
+
+        renderer.set_shadows(ShadowConfig {
+            enabled: true,
+            ground: -0.9,
+            ..Default::default()
+        });
+        renderer.render(&world, 1.0).unwrap();
+        assert_ne!(
+            renderer.read_pixels().unwrap(),
+            plain,
+            "shadows should show"
+        );
+
+        renderer.set_shadows(ShadowConfig {
+            enabled: false,
+            ground: -0.9,
+            ..Default::default()
+        });
+        renderer.render(&world, 1.0).unwrap();
+        assert!(
+            renderer.read_pixels().unwrap() == plain,
+            "disabled shadows changed the frame"
+        );
+    }
+
+    #[test]
+    fn stats_text_refreshes_only_at_the_report_interval() {
+        let Some(mut renderer) = headless(RendererConfig::default()) else {
+            return;
+        };
+        let world = World::new();
+        renderer.frame_stats.report_dt = Duration::from_secs(3600);
+        renderer.render(&world, 1.0).unwrap();
+        let before = renderer.stats_text.clone();
+        renderer
+            .frame_stats
+            .record_physics(9, &PhysicsDiagnostics::default());
+        renderer.render(&world, 1.0).unwrap();
+        assert_eq!(renderer.stats_text, before, "not due yet");
+        assert!(!renderer.text_dirty);
+
+        // Once the interval has passed the next frame reports
+        renderer.frame_stats.report_dt = Duration::ZERO;
+        renderer.render(&world, 1.0).unwrap();
+        assert!(
+            renderer.stats_text.contains("Entities:     9"),
+            "{}",
+            renderer.stats_text
+        );
+        assert!(
+            renderer.text_dirty,
+            "the new text goes to the stats pass next frame"
+        );
+        renderer.frame_stats.report_dt = Duration::from_secs(3600);
+        renderer.render(&world, 1.0).unwrap();
+        assert!(!renderer.text_dirty);
+    }
+
+    #[test]
+    fn mixed_circles_and_rectangles_share_one_draw() {
+        let mut world = World::new();
+        for i in 0..100 {
+            let x = -0.9 + 0.018 * i as f32;
+            if i % 2 == 0 {
+                world.add_entity(Entity::new_circle([x, 0.0], 0.01, [1.0, 0.0, 0.0]));
+            } else {
+                world.add_entity(Entity::new_rectangle([x, 0.0], 0.02, 0.01, [0.0, 1.0, 0.0]));
+            }
+        }
+        let config = RendererConfig::default();
+        let effects = CollisionEffects {
+            impacts: ImpactCache::new(config.impacts.duration),
+            flashes: FlashCache::new(&config.flash),
+            hover_tint: DEFAULT_HOVER_TINT,
+        };
+        let order: Vec<usize> = (0..world.entities().len()).collect();
+        let (mut shapes, mut polygons, mut batches) = (Vec::new(), Vec::new(), Vec::new());
+        collect_instances(
+            &world,
+            1.0,
+            ColorMode::Static,
+            Aabb::from_center([0.0, 0.0], [1.0, 1.0]),
+            &order,
+            &effects,
+            &mut shapes,
+            &mut polygons,
+            &mut batches,
+        );
+
+        assert_eq!(batches.len(), 1, "one upload, one draw");
+        assert!(batches[0].kind == BatchKind::Shapes && batches[0].texture.is_none());
+        assert_eq!(batches[0].range, 0..100);
+        let kinds: Vec<u32> = shapes.iter().map(|shape| shape.kind).collect();
+        assert!(kinds.iter().step_by(2).all(|&kind| kind == SHAPE_CIRCLE));
+        assert!(
+            kinds
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .all(|&kind| kind == SHAPE_RECTANGLE)
+        );
+    }
+}
@@ -0,0 +1,1726 @@
+use super::{
+    BatchKind, CircleInstance, CollisionEffects, ColorMode, DebugDrawFlags, DrawBatch, GridConfig,
+    InstanceBuffer, LineInstance, PolygonVertex, RectInstance, SHAPE_SHADER_SOURCE, ShadowConfig,
+    ShapeInstance, StatsStyle, TextCache, TextPlacement, TrailCache, Vertex, WorldViewport,
+    anchor_position, buffer_extent, collect_instances, output_color, push_batch, shadow_shape,
+};
+use crate::components::{Aabb, Shape, TextSpace};
+use crate::systems::Contact;
+use crate::world::World;
+
+/// Instanced line segments expanded into quads, rebuilt every frame. Shared by
+/// the grid and debug line passes.
+pub(super) struct LinePass {
+    pipeline: wgpu::RenderPipeline,
+    buffer: InstanceBuffer,
+    lines: Vec<LineInstance>,
+}
+
+impl LinePass {
+    pub(super) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+    ) -> Self {
+        let line_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../line.wgsl").into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Line Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &line_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<LineInstance>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &wgpu::vertex_attr_array![
+                            1 => Float32x2,
+                            2 => Float32x2,
+                            3 => Float32,
+                            4 => Float32x4,
+                        ],
+                    },
+                ],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &line_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample,
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let buffer = InstanceBuffer::new(
+            device,
+            "Line Instance Buffer",
+            (std::mem::size_of::<LineInstance>() * 256) as u64,
+        );
+
+        Self {
+            pipeline,
+            buffer,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Drop degenerate lines (the shader can't orient them) and upload the rest,
+    /// with colors converted for `format`
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) {
+        self.lines.retain(|line| {
+            let dx = line.end[0] - line.start[0];
+            let dy = line.end[1] - line.start[1];
+            dx * dx + dy * dy > f32::EPSILON * f32::EPSILON
+        });
+        for line in &mut self.lines {
+            line.color = output_color(line.color, format);
+        }
+        self.buffer.upload(device, queue, &self.lines);
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, vertex_buffer: &wgpu::Buffer) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.buffer.slice());
+        render_pass.draw(0..6, 0..self.lines.len() as u32);
+    }
+}
+
+/// GPU resources passes bind while drawing, owned by the renderer
+pub(super) struct PassResources {
+    pub(super) vertex_buffer: wgpu::Buffer, // Unit quad shared by the instanced pipelines
+    pub(super) globals_bind_group: wgpu::BindGroup,
+    pub(super) textures: Vec<wgpu::BindGroup>, // Indexed by TextureId
+    pub(super) text: TextResources,
+}
+
+/// glyphon state shared by every pass that draws text
+pub(super) struct TextResources {
+    pub(super) cache: TextCache,
+    pub(super) font_system: glyphon::FontSystem,
+    pub(super) swash_cache: glyphon::SwashCache,
+    pub(super) viewport: glyphon::Viewport,
+    pub(super) atlas: glyphon::TextAtlas,
+    // What `atlas` was created for; glyphon can't be asked, and it has to match
+    // the attachment text is drawn into
+    pub(super) atlas_format: wgpu::TextureFormat,
+}
+
+/// What the stats overlay should show this frame
+pub(super) struct StatsOverlay<'a> {
+    pub(super) visible: bool,
+    pub(super) style: StatsStyle,
+    pub(super) text: Option<&'a str>, // Only set on frames where the text changed
+    pub(super) recording: Option<u32>, // Dropped frames so far while recording, shown with "REC"
+    pub(super) frozen: bool,          // World::is_frozen, shown with "FROZEN"
+}
+
+/// Everything passes read while preparing a frame, gathered once by `Renderer::render`
+pub(super) struct FrameContext<'a> {
+    pub(super) alpha: f32,
+    pub(super) size: winit::dpi::PhysicalSize<u32>,
+    pub(super) world_viewport: WorldViewport, // Where world positions land on screen, in window pixels
+    pub(super) scale_factor: f32,             // Physical pixels per logical pixel
+    pub(super) format: wgpu::TextureFormat,   // View format; sRGB colors get converted for it
+    pub(super) view: Aabb,
+    pub(super) draw_order: &'a [usize],
+    pub(super) color_mode: ColorMode,
+    pub(super) trails: Option<&'a TrailCache>, // None when trails are off
+    pub(super) effects: &'a CollisionEffects,
+    pub(super) shadows: Option<&'a ShadowConfig>, // None when shadows are off
+    pub(super) overlay_rings: &'a [CircleInstance],
+    pub(super) lines: &'a [LineInstance], // draw_line requests and contact lines
+    pub(super) debug_flags: DebugDrawFlags,
+    pub(super) velocity_arrow_scale: f32,
+    pub(super) grid: &'a GridConfig,
+    pub(super) labels: bool, // Draw entity names above their shapes
+    pub(super) stats: StatsOverlay<'a>,
+    pub(super) resources: &'a mut PassResources,
+    pub(super) culled: usize, // Passes add whatever they skip for lying outside the view
+}
+
+/// One stage of a frame. Every pass is prepared before the frame is acquired,
+/// then all of them draw in order into a single render pass.
+pub(super) trait RenderPass {
+    /// Build and upload this frame's GPU data
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        frame: &mut FrameContext,
+    );
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources);
+
+    /// Drop anything kept about the last world's entities; the next frame comes
+    /// from a different world
+    fn world_changed(&mut self) {}
+
+    /// Overlay passes draw at native resolution on top of the upscaled scene when
+    /// the render scale isn't 1, so text stays crisp
+    fn overlay(&self) -> bool {
+        false
+    }
+
+    /// Rebuild from new shape shader source. None if this pass has no shape pipelines.
+    #[cfg(feature = "dev-tools")]
+    fn reload_shape_shader(
+        &mut self,
+        _device: &wgpu::Device,
+        _source: &str,
+    ) -> Option<Result<(), String>> {
+        None
+    }
+}
+
+/// Grid lines on the background, behind every shape
+pub(super) struct GridPass {
+    pub(super) lines: LinePass,
+}
+
+impl RenderPass for GridPass {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _world: &World,
+        frame: &mut FrameContext,
+    ) {
+        self.lines.lines.clear();
+        if frame.grid.enabled {
+            push_grid_lines(&mut self.lines.lines, frame.grid, frame.view);
+        }
+        self.lines.upload(device, queue, frame.format);
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources) {
+        self.lines.draw(render_pass, &resources.vertex_buffer);
+    }
+}
+
+/// Requested lines and debug overlays, on top of shapes and below text
+pub(super) struct DebugLinePass {
+    pub(super) lines: LinePass,
+}
+
+impl RenderPass for DebugLinePass {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        frame: &mut FrameContext,
+    ) {
+        let lines = &mut self.lines.lines;
+        lines.clear();
+        lines.extend_from_slice(frame.lines);
+        if frame.debug_flags.contains(DebugDrawFlags::AABBS) {
+            push_bounds_lines(lines, world, frame.alpha);
+        }
+        if frame.debug_flags.contains(DebugDrawFlags::VELOCITIES) {
+            push_velocity_lines(lines, world, frame.alpha, frame.velocity_arrow_scale);
+        }
+        if frame.debug_flags.contains(DebugDrawFlags::GRAVITY_ZONES) {
+            push_gravity_zone_lines(lines, world, frame.alpha);
+        }
+        self.lines.upload(device, queue, frame.format);
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources) {
+        self.lines.draw(render_pass, &resources.vertex_buffer);
+    }
+}
+
+/// Circles, rectangles and polygons, plus shadows and trails behind them and
+/// overlay rings on top, drawn back-to-front. Circles and rectangles share a
+/// pipeline; batches only break for textures and polygons.
+pub(super) struct ShapePass {
+    shape_pipeline: wgpu::RenderPipeline,
+    // Shapes with a texture draw through this, one batch per texture
+    textured_shape_pipeline: wgpu::RenderPipeline,
+    polygon_pipeline: wgpu::RenderPipeline,
+    shape_instance_buffer: InstanceBuffer,
+    polygon_vertex_buffer: InstanceBuffer, // Triangle vertices rebuilt every frame
+    // Rebuilt every frame; kept to reuse their allocations
+    shapes: Vec<ShapeInstance>,
+    polygons: Vec<PolygonVertex>,
+    batches: Vec<DrawBatch>,
+    // Kept to rebuild the shape pipelines when shader.wgsl changes
+    #[cfg(feature = "dev-tools")]
+    shape_pipelines: ShapePipelineConfig,
+}
+
+#[cfg(feature = "dev-tools")]
+impl ShapePass {
+    /// Swap in shape pipelines built from `source`, keeping the current ones if it
+    /// doesn't compile
+    fn try_reload_shape_shader(
+        &mut self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Result<(), String> {
+        let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipelines = self.shape_pipelines.create(device, source);
+        if let Some(err) = pollster::block_on(scope.pop()) {
+            return Err(err.to_string());
+        }
+        (self.shape_pipeline, self.textured_shape_pipeline) = pipelines;
+        Ok(())
+    }
+}
+
+/// Everything the shape pipelines are built from besides the shader source
+struct ShapePipelineConfig {
+    shape_layout: wgpu::PipelineLayout,
+    textured_layout: wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    multisample: wgpu::MultisampleState,
+}
+
+impl ShapePipelineConfig {
+    /// Plain and textured shape pipelines from shape shader `source`
+    fn create(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> (wgpu::RenderPipeline, wgpu::RenderPipeline) {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shape Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let shape_pipeline = create_shape_pipeline(
+            device,
+            "Shape Pipeline",
+            &shader,
+            Some(&self.shape_layout),
+            "fs_main",
+            shape_instance_layout(),
+            self.format,
+            self.multisample,
+        );
+        // The textured variant shares the shader but binds a texture and sampler at group 1
+        let textured_shape_pipeline = create_shape_pipeline(
+            device,
+            "Textured Shape Pipeline",
+            &shader,
+            Some(&self.textured_layout),
+            "fs_textured",
+            shape_instance_layout(),
+            self.format,
+            self.multisample,
+        );
+        (shape_pipeline, textured_shape_pipeline)
+    }
+}
+
+impl ShapePass {
+    pub(super) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+        globals_layout: &wgpu::BindGroupLayout,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let shape_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shape Pipeline Layout"),
+            bind_group_layouts: &[globals_layout],
+            immediate_size: 0,
+        });
+        let textured_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Textured Pipeline Layout"),
+            bind_group_layouts: &[globals_layout, texture_layout],
+            immediate_size: 0,
+        });
+
+        let shape_instance_buffer = InstanceBuffer::new(
+            device,
+            "Shape Instance Buffer",
+            (std::mem::size_of::<ShapeInstance>() * 100) as u64,
+        );
+
+        let polygon_pipeline = create_polygon_pipeline(device, format, multisample);
+        let polygon_vertex_buffer = InstanceBuffer::new(
+            device,
+            "Polygon Vertex Buffer",
+            (std::mem::size_of::<PolygonVertex>() * 256) as u64,
+        );
+
+        let shape_pipelines = ShapePipelineConfig {
+            shape_layout,
+            textured_layout,
+            format,
+            multisample,
+        };
+        let (shape_pipeline, textured_shape_pipeline) =
+            shape_pipelines.create(device, SHAPE_SHADER_SOURCE);
+
+        Self {
+            shape_pipeline,
+            textured_shape_pipeline,
+            polygon_pipeline,
+            shape_instance_buffer,
+            polygon_vertex_buffer,
+            shapes: Vec::new(),
+            polygons: Vec::new(),
+            batches: Vec::new(),
+            #[cfg(feature = "dev-tools")]
+            shape_pipelines,
+        }
+    }
+}
+
+impl RenderPass for ShapePass {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        frame: &mut FrameContext,
+    ) {
+        self.shapes.clear();
+        self.polygons.clear();
+        self.batches.clear();
+
+        // Shadows, then trails, go behind every layer. Pushing them first keeps them
+        // contiguous with the shapes so they can share a draw.
+        if let Some(shadows) = frame.shadows {
+            push_shadow_instances(
+                &mut self.shapes,
+                world,
+                frame.draw_order,
+                frame.alpha,
+                shadows,
+            );
+        }
+        if let Some(trails) = frame.trails {
+            push_trail_instances(&mut self.shapes, world, frame.draw_order, trails);
+        }
+        if !self.shapes.is_empty() {
+            let range = 0..self.shapes.len() as u32;
+            push_batch(&mut self.batches, BatchKind::Shapes, None, range);
+        }
+
+        frame.culled += collect_instances(
+            world,
+            frame.alpha,
+            frame.color_mode,
+            frame.view,
+            frame.draw_order,
+            frame.effects,
+            &mut self.shapes,
+            &mut self.polygons,
+            &mut self.batches,
+        );
+
+        // Overlay rings go on top of every layer
+        if !frame.overlay_rings.is_empty() {
+            let start = self.shapes.len() as u32;
+            self.shapes.extend(
+                frame
+                    .overlay_rings
+                    .iter()
+                    .map(|&ring| ShapeInstance::from(ring)),
+            );
+            let range = start..self.shapes.len() as u32;
+            push_batch(&mut self.batches, BatchKind::Shapes, None, range);
+        }
+
+        // Shape colors are sRGB; convert once here rather than at every call site
+        for shape in &mut self.shapes {
+            shape.color = output_color(shape.color, frame.format);
+            let [r, g, b] = shape.color_outer;
+            let [r, g, b, _] = output_color([r, g, b, 1.0], frame.format);
+            shape.color_outer = [r, g, b];
+        }
+        for vertex in &mut self.polygons {
+            vertex.color = output_color(vertex.color, frame.format);
+        }
+
+        self.shape_instance_buffer
+            .upload(device, queue, &self.shapes);
+        self.polygon_vertex_buffer
+            .upload(device, queue, &self.polygons);
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources) {
+        for batch in &self.batches {
+            if batch.kind == BatchKind::Polygons {
+                render_pass.set_pipeline(&self.polygon_pipeline);
+                render_pass.set_vertex_buffer(0, self.polygon_vertex_buffer.slice());
+                render_pass.draw(batch.range.clone(), 0..1);
+                continue;
+            }
+
+            let texture = batch.texture.and_then(|id| resources.textures.get(id.0));
+            let pipeline = match texture {
+                None => &self.shape_pipeline,
+                Some(_) => &self.textured_shape_pipeline,
+            };
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &resources.globals_bind_group, &[]);
+            if let Some(bind_group) = texture {
+                render_pass.set_bind_group(1, bind_group, &[]);
+            }
+            render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.shape_instance_buffer.slice());
+            render_pass.draw(0..6, batch.range.clone());
+        }
+    }
+
+    #[cfg(feature = "dev-tools")]
+    fn reload_shape_shader(
+        &mut self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Option<Result<(), String>> {
+        Some(self.try_reload_shape_shader(device, source))
+    }
+}
+
+/// Text entities, above every shape but ordered among themselves by layer, then
+/// debug labels above them. Both go through one text renderer so a frame prepares
+/// all entity text in a single call.
+pub(super) struct TextPass {
+    renderer: glyphon::TextRenderer,
+    labels: TextCache, // Like the text entity cache, but holding names
+    // Rebuilt every frame; kept to reuse their allocations
+    text_placements: Vec<TextPlacement>,
+    label_placements: Vec<TextPlacement>,
+    prepared: bool, // False when prepare failed, so draw skips this frame's text
+}
+
+/// Pixel size of entity name labels
+const LABEL_FONT_SIZE: f32 = 14.0;
+
+const LABEL_COLOR: [f32; 3] = [0.9, 0.9, 0.9];
+
+// Gap in pixels between a label and the top of its shape
+const LABEL_MARGIN: f32 = 4.0;
+
+impl TextPass {
+    pub(super) fn new(
+        device: &wgpu::Device,
+        atlas: &mut glyphon::TextAtlas,
+        multisample: wgpu::MultisampleState,
+    ) -> Self {
+        Self {
+            renderer: glyphon::TextRenderer::new(atlas, device, multisample, None),
+            labels: TextCache::new(),
+            text_placements: Vec::new(),
+            label_placements: Vec::new(),
+            prepared: false,
+        }
+    }
+
+    /// Shape text for entities (reusing cached buffers) and record where each one goes.
+    /// Areas are built afterwards so every placement refers to its own entity's buffer.
+    fn place_text(&mut self, world: &World, frame: &mut FrameContext) {
+        let text = &mut frame.resources.text;
+        let size = frame.size;
+
+        self.text_placements.clear();
+        for &idx in frame.draw_order {
+            let entity = &world.entities()[idx];
+            if let Shape::Text {
+                content,
+                font_size,
+                color,
+                opacity,
+                space,
+                ..
+            } = entity.shape()
+            {
+                let Some(id) = world.id_at(idx) else {
+                    continue;
+                };
+                let changed = text.cache.is_stale(entity);
+                text.cache
+                    .update(&mut text.font_system, id, content, *font_size, changed);
+                let (width, height) = text.cache.get(id).map(buffer_extent).unwrap_or((0.0, 0.0));
+
+                let (left, top, scale, bounds) = match space {
+                    // Center the shaped box on the entity's position
+                    TextSpace::World => {
+                        let position = entity.transform().interpolated_position(frame.alpha);
+                        let (x, y) = frame.world_viewport.ndc_to_pixel(position);
+                        let bounds = frame.world_viewport.text_bounds();
+                        (x - width / 2.0, y - height / 2.0, 1.0, bounds)
+                    }
+                    TextSpace::Screen { anchor, offset_px } => {
+                        let scale = frame.scale_factor;
+                        let (left, top) = anchor_position(
+                            *anchor,
+                            size,
+                            (width * scale, height * scale),
+                            [offset_px[0] * scale, offset_px[1] * scale],
+                        );
+                        (left, top, scale, glyphon::TextBounds::default())
+                    }
+                };
+                let (width, height) = (width * scale, height * scale);
+
+                // Skip text whose shaped box lies fully outside the window
+                if left + width < 0.0
+                    || top + height < 0.0
+                    || left > size.width as f32
+                    || top > size.height as f32
+                {
+                    frame.culled += 1;
+                    continue;
+                }
+
+                self.text_placements.push(TextPlacement {
+                    entity: id,
+                    left,
+                    top,
+                    color: *color,
+                    scale,
+                    opacity: *opacity,
+                    bounds,
+                });
+            }
+        }
+        text.cache.evict_unused(world.change_tick());
+    }
+
+    /// Each named entity's name centered above its shape
+    fn place_labels(&mut self, world: &World, frame: &mut FrameContext) {
+        let text = &mut frame.resources.text;
+        let size = frame.size;
+
+        self.label_placements.clear();
+        if !frame.labels {
+            return;
+        }
+        for &idx in frame.draw_order {
+            let entity = &world.entities()[idx];
+            let (Some(name), Some(id)) = (entity.name(), world.id_at(idx)) else {
+                continue;
+            };
+            let changed = self.labels.is_stale(entity);
+            self.labels
+                .update(&mut text.font_system, id, name, LABEL_FONT_SIZE, changed);
+
+            // Centered horizontally, bottom edge just above the shape as drawn
+            let mut transform = *entity.transform();
+            transform.position = transform.interpolated_position(frame.alpha);
+            let top = entity.shape().bounds(&transform).max[1];
+            let (x, y) = frame
+                .world_viewport
+                .ndc_to_pixel([transform.position[0], top]);
+            let (width, height) = self.labels.get(id).map(buffer_extent).unwrap_or((0.0, 0.0));
+            let left = x - width / 2.0;
+            let top = y - LABEL_MARGIN - height;
+
+            if left + width < 0.0
+                || top + height < 0.0
+                || left > size.width as f32
+                || top > size.height as f32
+            {
+                continue;
+            }
+
+            self.label_placements.push(TextPlacement {
+                entity: id,
+                left,
+                top,
+                scale: 1.0,
+                color: LABEL_COLOR,
+                opacity: 1.0,
+                bounds: frame.world_viewport.text_bounds(),
+            });
+        }
+        self.labels.evict_unused(world.change_tick());
+    }
+}
+
+impl RenderPass for TextPass {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        frame: &mut FrameContext,
+    ) {
+        self.place_text(world, frame);
+        self.place_labels(world, frame);
+
+        let text = &mut frame.resources.text;
+        let areas = text_areas(&text.cache, &self.text_placements)
+            .chain(text_areas(&self.labels, &self.label_placements));
+
+        // A text failure only costs this frame's text, not the frame
+        self.prepared = match self.renderer.prepare(
+            device,
+            queue,
+            &mut text.font_system,
+            &mut text.atlas,
+            &text.viewport,
+            areas,
+            &mut text.swash_cache,
+        ) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("WARNING: skipping text this frame: {err}");
+                false
+            }
+        };
+    }
+
+    fn world_changed(&mut self) {
+        self.labels.clear();
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources) {
+        if self.prepared
+            && let Err(err) =
+                self.renderer
+                    .render(&resources.text.atlas, &resources.text.viewport, render_pass)
+        {
+            println!("WARNING: failed to draw text: {err}");
+        }
+    }
+
+    fn overlay(&self) -> bool {
+        true
+    }
+}
+
+/// The stats overlay in its configured corner, above everything else
+pub(super) struct StatsPass {
+    renderer: glyphon::TextRenderer,
+    buffer: glyphon::Buffer,
+    content: String, // Last stats text, kept to re-lay out when the style changes
+    size: winit::dpi::PhysicalSize<u32>, // Window size the buffer was last laid out for
+    style: StatsStyle, // Style the buffer was last laid out with
+    frozen: bool,    // Whether it was laid out with the frozen indicator
+    // Optional box behind the text, one rectangle instance
+    background_pipeline: wgpu::RenderPipeline,
+    background_buffer: wgpu::Buffer,
+    background_visible: bool,
+    prepared: bool,
+}
+
+impl StatsPass {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn new(
+        device: &wgpu::Device,
+        font_system: &mut glyphon::FontSystem,
+        atlas: &mut glyphon::TextAtlas,
+        format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+        globals_layout: &wgpu::BindGroupLayout,
+        size: winit::dpi::PhysicalSize<u32>,
+        style: StatsStyle,
+    ) -> Self {
+        let buffer = glyphon::Buffer::new(font_system, style.metrics());
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Stats Background Pipeline Layout"),
+            bind_group_layouts: &[globals_layout],
+            immediate_size: 0,
+        });
+        let background_pipeline = create_shape_pipeline(
+            device,
+            "Stats Background Pipeline",
+            &device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shape Shader"),
+                source: wgpu::ShaderSource::Wgsl(SHAPE_SHADER_SOURCE.into()),
+            }),
+            Some(&layout),
+            "fs_main",
+            shape_instance_layout(),
+            format,
+            multisample,
+        );
+        let background_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Stats Background Buffer"),
+            size: std::mem::size_of::<ShapeInstance>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut pass = Self {
+            renderer: glyphon::TextRenderer::new(atlas, device, multisample, None),
+            buffer,
+            content: "FPS: --".to_string(),
+            size,
+            style,
+            frozen: false,
+            background_pipeline,
+            background_buffer,
+            background_visible: false,
+            prepared: false,
+        };
+        pass.layout(font_system, None);
+        pass
+    }
+
+    /// Shape `content` with the current style, under red recording and blue
+    /// frozen indicators
+    fn layout(&mut self, font_system: &mut glyphon::FontSystem, recording: Option<u32>) {
+        set_stats_buffer_size(&mut self.buffer, font_system, self.size, self.style.margin);
+        let attrs = glyphon::Attrs::new()
+            .family(glyphon::Family::Monospace)
+            .color(self.style.text_color());
+        let rec = match recording {
+            Some(0) => "REC\n".to_string(),
+            Some(dropped) => format!("REC ({dropped} dropped)\n"),
+            None => String::new(),
+        };
+        let rec_attrs = attrs.clone().color(glyphon::Color::rgb(255, 60, 60));
+        let frozen = if self.frozen { "FROZEN\n" } else { "" };
+        let frozen_attrs = attrs.clone().color(glyphon::Color::rgb(90, 170, 255));
+        self.buffer.set_rich_text(
+            font_system,
+            [
+                (rec.as_str(), rec_attrs),
+                (frozen, frozen_attrs),
+                (self.content.as_str(), attrs.clone()),
+            ],
+            &attrs,
+            glyphon::Shaping::Basic,
+            None,
+        );
+        self.buffer.shape_until_scroll(font_system, false);
+    }
+}
+
+impl RenderPass for StatsPass {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _world: &World,
+        frame: &mut FrameContext,
+    ) {
+        let text = &mut frame.resources.text;
+
+        let restyled = frame.stats.style != self.style;
+        if restyled {
+            self.buffer
+                .set_metrics(&mut text.font_system, frame.stats.style.metrics());
+            self.style = frame.stats.style;
+        }
+        if let Some(content) = frame.stats.text {
+            content.clone_into(&mut self.content);
+        }
+        let refrozen = frame.stats.frozen != self.frozen;
+        if restyled || refrozen || frame.size != self.size || frame.stats.text.is_some() {
+            self.size = frame.size;
+            self.frozen = frame.stats.frozen;
+            self.layout(&mut text.font_system, frame.stats.recording);
+        }
+
+        if !frame.stats.visible {
+            self.prepared = false;
+            return;
+        }
+
+        let extent = buffer_extent(&self.buffer);
+        let (left, top) = self.style.text_position(frame.size, extent);
+        self.background_visible = match self.style.background {
+            Some(color) => {
+                let instance =
+                    stats_background(frame.size, (left, top), extent, color, frame.format);
+                queue.write_buffer(&self.background_buffer, 0, bytemuck::bytes_of(&instance));
+                true
+            }
+            None => false,
+        };
+        let area = glyphon::TextArea {
+            buffer: &self.buffer,
+            left,
+            top,
+            scale: 1.0,
+            bounds: glyphon::TextBounds::default(),
+            default_color: self.style.text_color(),
+            custom_glyphs: &[],
+        };
+
+        self.prepared = match self.renderer.prepare(
+            device,
+            queue,
+            &mut text.font_system,
+            &mut text.atlas,
+            &text.viewport,
+            [area],
+            &mut text.swash_cache,
+        ) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("WARNING: skipping stats this frame: {err}");
+                false
+            }
+        };
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, resources: &PassResources) {
+        if !self.prepared {
+            return;
+        }
+        if self.background_visible {
+            render_pass.set_pipeline(&self.background_pipeline);
+            render_pass.set_bind_group(0, &resources.globals_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.background_buffer.slice(..));
+            render_pass.draw(0..6, 0..1);
+        }
+        if let Err(err) =
+            self.renderer
+                .render(&resources.text.atlas, &resources.text.viewport, render_pass)
+        {
+            println!("WARNING: failed to draw stats: {err}");
+        }
+    }
+
+    fn overlay(&self) -> bool {
+        true
+    }
+}
+
+/// Leave room for the margin so long stats lines wrap instead of running off-screen
+fn set_stats_buffer_size(
+    buffer: &mut glyphon::Buffer,
+    font_system: &mut glyphon::FontSystem,
+    size: winit::dpi::PhysicalSize<u32>,
+    margin: f32,
+) {
+    buffer.set_size(
+        font_system,
+        Some((size.width as f32 - 2.0 * margin).max(1.0)),
+        Some(size.height as f32),
+    );
+}
+
+/// Rectangle (NDC) behind stats text laid out at `position` with `extent`, padded on every side
+fn stats_background(
+    size: winit::dpi::PhysicalSize<u32>,
+    position: (f32, f32),
+    extent: (f32, f32),
+    color: [f32; 4],
+    format: wgpu::TextureFormat,
+) -> ShapeInstance {
+    let (width, height) = (size.width.max(1) as f32, size.height.max(1) as f32);
+    let box_width = extent.0 + 2.0 * STATS_BACKGROUND_PADDING;
+    let box_height = extent.1 + 2.0 * STATS_BACKGROUND_PADDING;
+    let center_x = position.0 + extent.0 / 2.0;
+    let center_y = position.1 + extent.1 / 2.0;
+    RectInstance {
+        position: [center_x / width * 2.0 - 1.0, 1.0 - center_y / height * 2.0],
+        length: box_width / width * 2.0,
+        height: box_height / height * 2.0,
+        color: output_color(color, format),
+        rotation: 0.0,
+        scale: [1.0, 1.0],
+        corner_radius: 0.0,
+    }
+    .into()
+}
+
+// Space (pixels) between the stats text and the edges of its background box
+const STATS_BACKGROUND_PADDING: f32 = 6.0;
+
+/// Areas for every placement whose buffer is in `cache`
+fn text_areas<'a>(
+    cache: &'a TextCache,
+    placements: &'a [TextPlacement],
+) -> impl Iterator<Item = glyphon::TextArea<'a>> {
+    placements.iter().filter_map(|placement| {
+        cache
+            .get(placement.entity)
+            .map(|buffer| placement.text_area(buffer))
+    })
+}
+
+/// Pipeline for instanced quads (circles, rectangles). `layout` is None only for
+/// shaders that read no bind groups.
+#[allow(clippy::too_many_arguments)]
+fn create_shape_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    shader: &wgpu::ShaderModule,
+    layout: Option<&wgpu::PipelineLayout>,
+    fragment_entry: &str,
+    instance_layout: wgpu::VertexBufferLayout,
+    format: wgpu::TextureFormat,
+    multisample: wgpu::MultisampleState,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                },
+                instance_layout,
+            ],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(fragment_entry),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample,
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+const SHAPE_ATTRIBUTES: [wgpu::VertexAttribute; 10] = wgpu::vertex_attr_array![
+    1 => Float32x2,
+    2 => Float32x2,
+    3 => Float32x4,
+    4 => Float32x3,
+    5 => Float32,
+    6 => Float32x2,
+    7 => Float32,
+    8 => Float32,
+    9 => Float32,
+    10 => Uint32,
+];
+
+/// Instance buffer layout matching `ShapeInstance`
+fn shape_instance_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<ShapeInstance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &SHAPE_ATTRIBUTES,
+    }
+}
+
+/// Flat-color pipeline for pre-transformed polygon triangles
+fn create_polygon_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    multisample: wgpu::MultisampleState,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Polygon Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../polygon.wgsl").into()),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Polygon Pipeline"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<PolygonVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![
+                    0 => Float32x2,
+                    1 => Float32x4,
+                ],
+            }],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample,
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+/// Fading, shrinking copies of each circle at its recorded sim positions, oldest first.
+/// The newest entry is skipped since the ball itself is drawn there.
+fn push_trail_instances(
+    shapes: &mut Vec<ShapeInstance>,
+    world: &World,
+    order: &[usize],
+    trails: &TrailCache,
+) {
+    for &index in order {
+        let entity = &world.entities()[index];
+        let Shape::Circle {
+            radius,
+            color,
+            opacity,
+            ..
+        } = entity.shape()
+        else {
+            continue;
+        };
+        let Some(positions) = world.id_at(index).and_then(|id| trails.get(id)) else {
+            continue;
+        };
+
+        let count = positions.len().saturating_sub(1);
+        for (i, position) in positions.iter().take(count).enumerate() {
+            // Near 0.0 for the oldest entry, approaching 1.0 next to the ball
+            let t = (i + 1) as f32 / (count + 1) as f32;
+            shapes.push(
+                CircleInstance {
+                    position: *position,
+                    radius: radius * (0.3 + 0.6 * t),
+                    color: [color[0], color[1], color[2], opacity * 0.5 * t],
+                    thickness: 0.0,
+                    rotation: entity.transform().rotation,
+                    scale: entity.transform().scale,
+                    edge_softness: 0.0,
+                    color_outer: *color,
+                }
+                .into(),
+            );
+        }
+    }
+}
+
+fn push_shadow_instances(
+    shapes: &mut Vec<ShapeInstance>,
+    world: &World,
+    order: &[usize],
+    alpha: f32,
+    config: &ShadowConfig,
+) {
+    for &index in order {
+        let entity = &world.entities()[index];
+        let Shape::Circle {
+            radius, opacity, ..
+        } = entity.shape()
+        else {
+            continue;
+        };
+        let transform = entity.transform();
+        let [x, y] = transform.interpolated_position(alpha);
+        if y < config.ground {
+            continue;
+        }
+        let height = y - radius * transform.scale[1] - config.ground;
+        let Some(shadow) = shadow_shape(height, config) else {
+            continue;
+        };
+
+        let [r, g, b] = config.color;
+        shapes.push(
+            CircleInstance {
+                position: [x + shadow.offset, config.ground],
+                radius: radius * transform.scale[0] * shadow.scale,
+                color: [r, g, b, shadow.opacity * opacity],
+                thickness: 0.0,
+                rotation: 0.0,
+                scale: [1.0, config.squash],
+                edge_softness: config.softness,
+                color_outer: config.color,
+            }
+            .into(),
+        );
+    }
+}
+
+/// Append a marker at each contact point plus a short line along its normal.
+/// Color goes from green (shallow) to red (deep penetration).
+pub(super) fn push_contact_lines(lines: &mut Vec<LineInstance>, contacts: &[Contact]) {
+    const MARKER_SIZE: f32 = 0.01;
+    const NORMAL_LENGTH: f32 = 0.08;
+    const DEEP: f32 = 0.05;
+
+    for contact in contacts {
+        let t = (contact.depth / DEEP).clamp(0.0, 1.0);
+        let color = [t, 1.0 - t, 0.0, 1.0];
+        let [x, y] = contact.point;
+
+        // A thick, short segment reads as a square marker
+        lines.push(LineInstance {
+            start: [x - MARKER_SIZE / 2.0, y],
+            end: [x + MARKER_SIZE / 2.0, y],
+            thickness: MARKER_SIZE,
+            color,
+        });
+        lines.push(LineInstance {
+            start: contact.point,
+            end: [
+                x + contact.normal[0] * NORMAL_LENGTH,
+                y + contact.normal[1] * NORMAL_LENGTH,
+            ],
+            thickness: MARKER_SIZE / 4.0,
+            color,
+        });
+    }
+}
+
+/// Append grid lines covering `view`. Lines sit at multiples of the cell size in
+/// world space, so they stay put as the view moves.
+fn push_grid_lines(lines: &mut Vec<LineInstance>, grid: &GridConfig, view: Aabb) {
+    let cell = grid.cell_size;
+    if cell <= 0.0 {
+        return;
+    }
+    let width = view.max[0] - view.min[0];
+    let height = view.max[1] - view.min[1];
+    if width / cell > MAX_GRID_LINES || height / cell > MAX_GRID_LINES {
+        return;
+    }
+
+    let color = |index: i64| {
+        let major = grid.major_every > 0 && index.rem_euclid(grid.major_every as i64) == 0;
+        let alpha = if major {
+            grid.major_alpha
+        } else {
+            grid.minor_alpha
+        };
+        [grid.color[0], grid.color[1], grid.color[2], alpha]
+    };
+
+    // Vertical lines
+    let first = (view.min[0] / cell).ceil() as i64;
+    let last = (view.max[0] / cell).floor() as i64;
+    for index in first..=last {
+        let x = index as f32 * cell;
+        lines.push(LineInstance {
+            start: [x, view.min[1]],
+            end: [x, view.max[1]],
+            thickness: grid.thickness,
+            color: color(index),
+        });
+    }
+
+    // Horizontal lines
+    let first = (view.min[1] / cell).ceil() as i64;
+    let last = (view.max[1] / cell).floor() as i64;
+    for index in first..=last {
+        let y = index as f32 * cell;
+        lines.push(LineInstance {
+            start: [view.min[0], y],
+            end: [view.max[0], y],
+            thickness: grid.thickness,
+            color: color(index),
+        });
+    }
+}
+
+// Grids finer than this many lines per axis are skipped rather than drawn as a solid wash
+const MAX_GRID_LINES: f32 = 1000.0;
+
+/// Append an unfilled outline of every entity's bounding box
+fn push_bounds_lines(lines: &mut Vec<LineInstance>, world: &World, alpha: f32) {
+    const THICKNESS: f32 = 0.004;
+    const COLOR: [f32; 4] = [0.2, 0.8, 1.0, 1.0];
+
+    for entity in world.entities() {
+        let mut transform = *entity.transform();
+        transform.position = transform.interpolated_position(alpha);
+        let Aabb { min, max } = entity.shape().bounds(&transform);
+
+        let corners = [
+            [min[0], min[1]],
+            [max[0], min[1]],
+            [max[0], max[1]],
+            [min[0], max[1]],
+        ];
+        for i in 0..corners.len() {
+            lines.push(LineInstance {
+                start: corners[i],
+                end: corners[(i + 1) % corners.len()],
+                thickness: THICKNESS,
+                color: COLOR,
+            });
+        }
+    }
+}
+
+/// Append an arrow along each moving entity's velocity, scaled by `scale`
+fn push_velocity_lines(lines: &mut Vec<LineInstance>, world: &World, alpha: f32, scale: f32) {
+    const THICKNESS: f32 = 0.004;
+    const HEAD_LENGTH: f32 = 0.02;
+    const COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+
+    for entity in world.entities() {
+        let Some(physics) = entity.physics() else {
+            continue;
+        };
+
+        let start = entity.transform().interpolated_position(alpha);
+        let offset = [physics.velocity[0] * scale, physics.velocity[1] * scale];
+        let length = (offset[0] * offset[0] + offset[1] * offset[1]).sqrt();
+        if length <= f32::EPSILON {
+            continue;
+        }
+
+        let end = [start[0] + offset[0], start[1] + offset[1]];
+        lines.push(LineInstance {
+            start,
+            end,
+            thickness: THICKNESS,
+            color: COLOR,
+        });
+
+        // Two short barbs angled back from the tip
+        let dir = [offset[0] / length, offset[1] / length];
+        let head = HEAD_LENGTH.min(length / 2.0);
+        for side in [-1.0, 1.0] {
+            let back = [
+                -dir[0] * 0.866 - side * dir[1] * 0.5,
+                -dir[1] * 0.866 + side * dir[0] * 0.5,
+            ];
+            lines.push(LineInstance {
+                start: end,
+                end: [end[0] + back[0] * head, end[1] + back[1] * head],
+                thickness: THICKNESS,
+                color: COLOR,
+            });
+        }
+    }
+}
+
+/// Append each gravity zone's outline, and a line from its center along its
+/// gravity reaching halfway to the nearest edge
+fn push_gravity_zone_lines(lines: &mut Vec<LineInstance>, world: &World, alpha: f32) {
+    const THICKNESS: f32 = 0.004;
+    const COLOR: [f32; 4] = [0.6, 0.4, 1.0, 1.0];
+
+    for entity in world.entities() {
+        let Some(zone) = entity.gravity_zone() else {
+            continue;
+        };
+
+        let center = entity.transform().interpolated_position(alpha);
+        let Aabb { min, max } = zone.bounds(center);
+        let corners = [
+            [min[0], min[1]],
+            [max[0], min[1]],
+            [max[0], max[1]],
+            [min[0], max[1]],
+        ];
+        for i in 0..corners.len() {
+            lines.push(LineInstance {
+                start: corners[i],
+                end: corners[(i + 1) % corners.len()],
+                thickness: THICKNESS,
+                color: COLOR,
+            });
+        }
+
+        let length = (zone.gravity[0] * zone.gravity[0] + zone.gravity[1] * zone.gravity[1]).sqrt();
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let reach = zone.extents[0].min(zone.extents[1]) * 0.5 / length;
+        lines.push(LineInstance {
+            start: center,
+            end: [
+                center[0] + zone.gravity[0] * reach,
+                center[1] + zone.gravity[1] * reach,
+            ],
+            thickness: THICKNESS,
+            color: COLOR,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Renderer, RendererConfig, create_globals_layout};
+    use super::*;
+    use crate::components::Corner;
+    use crate::entity::Entity;
+
+    /// Headless renderer, or None (with a note) where there's no GPU adapter at all
+    fn headless() -> Option<Renderer> {
+        headless_sized(64, 64)
+    }
+
+    fn headless_sized(width: u32, height: u32) -> Option<Renderer> {
+        match pollster::block_on(Renderer::new_headless(
+            width,
+            height,
+            RendererConfig::default(),
+        )) {
+            Ok(renderer) => Some(renderer),
+            Err(err) => {
+                println!("skipping headless pass test: {err}");
+                None
+            }
+        }
+    }
+
+    /// The context `Renderer::render` hands its passes for `world`, drawn in insertion order
+    fn frame<'a>(renderer: &'a mut Renderer, world: &World) -> FrameContext<'a> {
+        renderer.draw_order.clear();
+        renderer.draw_order.extend(0..world.len());
+        FrameContext {
+            alpha: 1.0,
+            size: renderer.size,
+            world_viewport: renderer.world_viewport(),
+            scale_factor: renderer.scale_factor(),
+            format: renderer.view_format,
+            view: renderer.view_bounds(),
+            draw_order: &renderer.draw_order,
+            color_mode: renderer.color_mode,
+            trails: None,
+            effects: &renderer.effects,
+            shadows: None,
+            overlay_rings: &[],
+            lines: &[],
+            debug_flags: renderer.debug_flags,
+            velocity_arrow_scale: renderer.velocity_arrow_scale,
+            grid: &renderer.grid,
+            labels: renderer.show_labels,
+            stats: StatsOverlay {
+                visible: renderer.show_stats,
+                style: renderer.stats_style,
+                text: renderer.text_dirty.then_some(renderer.stats_text.as_str()),
+                recording: None,
+                frozen: false,
+            },
+            resources: &mut renderer.resources,
+            culled: 0,
+        }
+    }
+
+    /// Prepare `pass` the way one frame of `Renderer::render` would
+    fn prepare(pass: &mut dyn RenderPass, renderer: &mut Renderer, world: &World) {
+        let (device, queue) = (renderer.device.clone(), renderer.queue.clone());
+        pass.prepare(&device, &queue, world, &mut frame(renderer, world));
+    }
+
+    #[test]
+    fn each_text_placement_is_centered_on_its_own_entity() {
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        let mut world = World::new();
+        let positions = [[-0.5, 0.5], [0.0, 0.0], [0.4, -0.6]];
+        let ids: Vec<_> = positions
+            .iter()
+            .zip(["one", "two", "three"])
+            .map(|(&position, content)| {
+                let text = Entity::new_text(position, content.to_string(), 12.0, [1.0; 3]);
+                world.add_entity(text).id().unwrap()
+            })
+            .collect();
+        let mut pass = TextPass::new(
+            &renderer.device,
+            &mut renderer.resources.text.atlas,
+            wgpu::MultisampleState::default(),
+        );
+        let viewport = renderer.world_viewport();
+        let mut frame = frame(&mut renderer, &world);
+        pass.place_text(&world, &mut frame);
+
+        assert_eq!(pass.text_placements.len(), 3);
+        let text = &frame.resources.text;
+        for ((placement, id), position) in pass.text_placements.iter().zip(&ids).zip(positions) {
+            assert_eq!(placement.entity, *id);
+            let (width, height) = buffer_extent(text.cache.get(*id).unwrap());
+            let (x, y) = viewport.ndc_to_pixel(position);
+            assert!(
+                (placement.left + width / 2.0 - x).abs() < 1e-3,
+                "{position:?}"
+            );
+            assert!(
+                (placement.top + height / 2.0 - y).abs() < 1e-3,
+                "{position:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hidden_stats_prepare_no_area_and_come_back_current() {
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        let mut pass = StatsPass::new(
+            &renderer.device,
+            &mut renderer.resources.text.font_system,
+            &mut renderer.resources.text.atlas,
+            renderer.view_format,
+            wgpu::MultisampleState::default(),
+            &create_globals_layout(&renderer.device),
+            renderer.size,
+            renderer.stats_style,
+        );
+        let world = World::new();
+        let report = |renderer: &mut Renderer, entities| {
+            renderer
+                .frame_stats
+                .record_frame(std::time::Duration::from_millis(16));
+            renderer
+                .frame_stats
+                .record_physics(entities, &Default::default());
+            renderer.frame_stats.update();
+            renderer.update_stats_text();
+        };
+
+        report(&mut renderer, 3);
+        prepare(&mut pass, &mut renderer, &world);
+        assert!(pass.prepared);
+
+        renderer.toggle_stats();
+        report(&mut renderer, 5);
+        prepare(&mut pass, &mut renderer, &world);
+        assert!(!pass.prepared, "hidden stats shouldn't prepare an area");
+
+        // Reports kept coming while hidden, so the first frame back shows the latest one
+        renderer.toggle_stats();
+        report(&mut renderer, 7);
+        prepare(&mut pass, &mut renderer, &world);
+        assert!(pass.prepared);
+        assert!(pass.content.contains("Entities:     7"), "{}", pass.content);
+        assert!(pass.content.contains("62 fps"), "{}", pass.content);
+    }
+
+    #[test]
+    fn a_two_ball_collision_fills_the_contact_debug_lines() {
+        use crate::components::Physics;
+        use crate::systems::PhysicsSystem;
+        use std::time::Duration;
+
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        let mut world = World::new();
+        for x in [-0.04, 0.04] {
+            world.add_entity(
+                Entity::new_circle([x, 0.0], 0.05, [1.0; 3]).with_physics(Physics::new()),
+            );
+        }
+        let mut physics = PhysicsSystem::new();
+        physics.update(&mut world, Duration::from_millis(8));
+        let contacts = physics.contacts();
+        assert_eq!(contacts.len(), 1);
+
+        renderer.set_debug_contacts(contacts);
+        assert!(
+            renderer.contact_lines.is_empty(),
+            "contacts are off by default"
+        );
+
+        renderer.toggle_debug_draw(DebugDrawFlags::CONTACTS);
+        renderer.set_debug_contacts(contacts);
+        let [marker, normal] = renderer.contact_lines[..] else {
+            panic!(
+                "expected a marker and a normal, got {:?}",
+                renderer.contact_lines
+            );
+        };
+        let [x, y] = contacts[0].point;
+        assert_eq!(
+            [(marker.start[0] + marker.end[0]) / 2.0, marker.start[1]],
+            [x, y]
+        );
+        assert_eq!(normal.start, [x, y]);
+        // Balls side by side push apart horizontally
+        assert!(normal.end[0] > x && (normal.end[1] - y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn grid_lines_stay_on_world_multiples_as_the_view_moves() {
+        let grid = GridConfig {
+            enabled: true,
+            cell_size: 0.25,
+            major_every: 2,
+            ..Default::default()
+        };
+        let mut lines = Vec::new();
+        let panned = Aabb {
+            min: [0.1, -0.3],
+            max: [1.1, 0.3],
+        };
+        push_grid_lines(&mut lines, &grid, panned);
+
+        let vertical: Vec<f32> = lines
+            .iter()
+            .filter(|line| line.start[0] == line.end[0])
+            .map(|line| line.start[0])
+            .collect();
+        assert_eq!(vertical, [0.25, 0.5, 0.75, 1.0]);
+        let horizontal: Vec<f32> = lines
+            .iter()
+            .filter(|line| line.start[1] == line.end[1])
+            .map(|line| line.start[1])
+            .collect();
+        assert_eq!(horizontal, [-0.25, 0.0, 0.25]);
+        // Every second line from the origin is major
+        assert_eq!(lines[1].color[3], grid.major_alpha);
+        assert_eq!(lines[0].color[3], grid.minor_alpha);
+    }
+
+    #[test]
+    fn a_named_circle_gets_its_label_just_above_it() {
+        let Some(mut renderer) = headless() else {
+            return;
+        };
+        renderer.show_labels = true;
+        let mut world = World::new();
+        let ball = world
+            .add_entity(Entity::new_circle([0.2, -0.3], 0.2, [1.0; 3]).with_name("ball"))
+            .id()
+            .unwrap();
+        world.add_entity(Entity::new_circle([-0.5, 0.5], 0.1, [1.0; 3]));
+        let mut pass = TextPass::new(
+            &renderer.device,
+            &mut renderer.resources.text.atlas,
+            wgpu::MultisampleState::default(),
+        );
+        let viewport = renderer.world_viewport();
+        let mut frame = frame(&mut renderer, &world);
+        pass.place_labels(&world, &mut frame);
+
+        let [label] = &pass.label_placements[..] else {
+            panic!("only the named circle should get a label");
+        };
+        assert_eq!(label.entity, ball);
+        let (width, height) = buffer_extent(pass.labels.get(ball).unwrap());
+        let (center_x, _) = viewport.ndc_to_pixel([0.2, -0.3]);
+        let (_, circle_top) = viewport.ndc_to_pixel([0.2, -0.1]);
+        assert!((label.left + width / 2.0 - center_x).abs() < 1e-3);
+        assert!((label.top + height + LABEL_MARGIN - circle_top).abs() < 1e-3);
+
+        // Hidden labels place nothing
+        frame.labels = false;
+        pass.place_labels(&world, &mut frame);
+        assert!(pass.label_placements.is_empty());
+    }
+
+    #[test]
+    fn hud_text_keeps_its_margin_from_the_corner_at_any_size() {
+        let mut world = World::new();
+        let score = world
+            .add_entity(
+                Entity::new_text([0.0, 0.0], "Score: 0".to_string(), 12.0, [1.0; 3])
+                    .with_screen_anchor(Corner::BottomRight, [8.0, 6.0]),
+            )
+            .id()
+            .unwrap();
+
+        for (width, height) in [(128, 96), (200, 160)] {
+            let Some(mut renderer) = headless_sized(width, height) else {
+                return;
+            };
+            let mut pass = TextPass::new(
+                &renderer.device,
+                &mut renderer.resources.text.atlas,
+                wgpu::MultisampleState::default(),
+            );
+            let mut frame = frame(&mut renderer, &world);
+            let scale = frame.scale_factor;
+            pass.place_text(&world, &mut frame);
+
+            let [placement] = &pass.text_placements[..] else {
+                panic!("the HUD text should be placed");
+            };
+            let (text_width, text_height) =
+                buffer_extent(frame.resources.text.cache.get(score).unwrap());
+            let right = placement.left + text_width * scale;
+            let bottom = placement.top + text_height * scale;
+            assert!(
+                (width as f32 - right - 8.0 * scale).abs() < 1e-3,
+                "{width}x{height}"
+            );
+            assert!(
+                (height as f32 - bottom - 6.0 * scale).abs() < 1e-3,
+                "{width}x{height}"
+            );
+        }
+    }
+
+    #[test]
+    fn stats_block_sits_at_the_margin_of_each_corner() {
+        let size = winit::dpi::PhysicalSize::new(800, 600);
+        let extent = (200.0, 100.0);
+        for (corner, expected) in [
+            (Corner::TopLeft, (10.0, 10.0)),
+            (Corner::TopRight, (590.0, 10.0)),
+            (Corner::BottomLeft, (10.0, 490.0)),
+            (Corner::BottomRight, (590.0, 490.0)),
+        ] {
+            let style = StatsStyle {
+                corner,
+                margin: 10.0,
+                ..Default::default()
+            };
+            assert_eq!(style.text_position(size, extent), expected, "{corner:?}");
+        }
+
+        // The box behind the text pads it on every side
+        let style = StatsStyle::default();
+        let (left, top) = style.text_position(size, extent);
+        let background = stats_background(
+            size,
+            (left, top),
+            extent,
+            [0.0, 0.0, 0.0, 0.5],
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+        let (center_x, center_y) = WorldViewport::full(800, 600).ndc_to_pixel(background.position);
+        assert!((center_x - (left + 100.0)).abs() < 1e-3);
+        assert!((center_y - (top + 50.0)).abs() < 1e-3);
+        assert!(
+            (background.size[0] * 400.0 - (200.0 + 2.0 * STATS_BACKGROUND_PADDING)).abs() < 1e-3
+        );
+        assert_eq!(background.color, [0.0, 0.0, 0.0, 0.5]);
+    }
+}
@@ -0,0 +1,311 @@
+use super::{InstanceBuffer, ReadbackState, Vertex, WorldViewport, set_world_viewport};
+use crate::components::Shape;
+use crate::world::World;
+use std::sync::{Arc, OnceLock};
+
+/// One shape in the picking buffer
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickInstance {
+    position: [f32; 2],
+    half_size: [f32; 2], // After scaling
+    rotation: f32,
+    corner_radius: f32,
+    kind: u32, // PICK_ELLIPSE or PICK_RECTANGLE
+    id: u32,   // Entity index + 1
+}
+
+const PICK_ELLIPSE: u32 = 0;
+const PICK_RECTANGLE: u32 = 1;
+
+// Entity indices are written as index + 1 so a cleared pixel (0) means nothing
+const PICK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+/// Entity indices drawn into an offscreen texture with the same transforms as
+/// the visible circles and rectangles, so selection matches what's on screen.
+/// Reads back one requested pixel per frame without stalling.
+pub(super) struct GpuPicker {
+    pipeline: wgpu::RenderPipeline,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    instance_buffer: InstanceBuffer,
+    instances: Vec<PickInstance>,
+    readback: wgpu::Buffer,
+    state: ReadbackState,
+    // Pixel copied into `readback` while its state isn't Free
+    readback_pixel: [u32; 2],
+    // Pixel to copy out of the next frame
+    request: Option<[u32; 2]>,
+    // Latest finished readback: the pixel and the entity drawn there
+    result: Option<([u32; 2], Option<usize>)>,
+}
+
+impl GpuPicker {
+    pub(super) fn new(device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Picking Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../picking.wgsl").into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Picking Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<PickInstance>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &wgpu::vertex_attr_array![
+                            1 => Float32x2,
+                            2 => Float32x2,
+                            3 => Float32,
+                            4 => Float32,
+                            5 => Uint32,
+                            6 => Uint32,
+                        ],
+                    },
+                ],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                // Integer targets can't blend; the topmost shape simply overwrites
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: PICK_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview_mask: None,
+            cache: None,
+        });
+        let (texture, view) = Self::create_target(device, size);
+
+        Self {
+            pipeline,
+            texture,
+            view,
+            instance_buffer: InstanceBuffer::new(
+                device,
+                "Picking Instance Buffer",
+                (std::mem::size_of::<PickInstance>() * 100) as u64,
+            ),
+            instances: Vec::new(),
+            readback: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Picking Readback Buffer"),
+                size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            state: ReadbackState::Free,
+            readback_pixel: [0, 0],
+            request: None,
+            result: None,
+        }
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Picking Texture"),
+            size: wgpu::Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: PICK_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        (texture, view)
+    }
+
+    /// Match the window size; earlier results no longer line up with the pixels
+    pub(super) fn resize(&mut self, device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) {
+        let current = self.texture.size();
+        if (current.width, current.height) == (size.width.max(1), size.height.max(1)) {
+            return;
+        }
+        (self.texture, self.view) = Self::create_target(device, size);
+        self.result = None;
+    }
+
+    /// Entity at `pixel` from the latest readback, Some(None) meaning nothing is
+    /// drawn there. None until a frame rendered after the first request for this
+    /// pixel has been read back; the pixel stays requested every frame after.
+    pub(super) fn lookup(
+        &mut self,
+        device: &wgpu::Device,
+        pixel: [u32; 2],
+    ) -> Option<Option<usize>> {
+        self.request = Some(pixel);
+        self.collect(device);
+        match self.result {
+            Some((result_pixel, entity)) if result_pixel == pixel => Some(entity),
+            _ => None,
+        }
+    }
+
+    pub(super) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        world: &World,
+        order: &[usize],
+        alpha: f32,
+    ) {
+        self.instances.clear();
+        for &index in order {
+            let entity = &world.entities()[index];
+            let transform = entity.transform();
+            let (half_size, corner_radius, kind) = match entity.shape() {
+                Shape::Circle { radius, .. } => ([*radius, *radius], 0.0, PICK_ELLIPSE),
+                Shape::Rectangle {
+                    length,
+                    height,
+                    corner_radius,
+                    ..
+                } => (
+                    [length / 2.0, height / 2.0],
+                    corner_radius * transform.scale[0].min(transform.scale[1]),
+                    PICK_RECTANGLE,
+                ),
+                _ => continue,
+            };
+            self.instances.push(PickInstance {
+                position: transform.interpolated_position(alpha),
+                half_size: [
+                    half_size[0] * transform.scale[0],
+                    half_size[1] * transform.scale[1],
+                ],
+                rotation: transform.rotation,
+                corner_radius,
+                kind,
+                id: index as u32 + 1,
+            });
+        }
+        self.instance_buffer.upload(device, queue, &self.instances);
+    }
+
+    /// Draw the indices, then copy the requested pixel out if the readback buffer is free
+    pub(super) fn encode(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        vertex_buffer: &wgpu::Buffer,
+        viewport: WorldViewport,
+    ) {
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Picking Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            if !self.instances.is_empty() {
+                set_world_viewport(&mut render_pass, viewport);
+                render_pass.set_pipeline(&self.pipeline);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_vertex_buffer(1, self.instance_buffer.slice());
+                render_pass.draw(0..6, 0..self.instances.len() as u32);
+            }
+        }
+
+        let size = self.texture.size();
+        let Some(pixel) = self
+            .request
+            .filter(|pixel| pixel[0] < size.width && pixel[1] < size.height)
+        else {
+            return;
+        };
+        if !matches!(self.state, ReadbackState::Free) {
+            return;
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: pixel[0],
+                    y: pixel[1],
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.readback_pixel = pixel;
+        self.state = ReadbackState::Copied;
+    }
+
+    /// Start mapping this frame's copy; call after the frame is submitted
+    pub(super) fn map_copy(&mut self) {
+        if let ReadbackState::Copied = self.state {
+            let done = Arc::new(OnceLock::new());
+            let callback_done = done.clone();
+            self.readback
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = callback_done.set(result.is_ok());
+                });
+            self.state = ReadbackState::Mapping(done);
+        }
+    }
+
+    /// Take the readback's result if it has finished, without waiting
+    pub(super) fn collect(&mut self, device: &wgpu::Device) {
+        let _ = device.poll(wgpu::PollType::Poll);
+        let ReadbackState::Mapping(done) = &self.state else {
+            return;
+        };
+        let Some(&mapped) = done.get() else {
+            return;
+        };
+
+        if mapped {
+            let data = self.readback.slice(..).get_mapped_range();
+            let id: u32 = bytemuck::pod_read_unaligned(&data[..4]);
+            self.result = Some((self.readback_pixel, id.checked_sub(1).map(|i| i as usize)));
+        }
+        self.readback.unmap();
+        self.state = ReadbackState::Free;
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, mpsc};
+
+/// Totals for a recording, reported by `Renderer::stop_recording`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordingStats {
+    pub captured: u32, // Frames copied off the GPU and numbered
+    pub dropped: u32,  // Frames skipped because every readback buffer was still busy
+}
+
+// Readback buffers per recording; a capture is dropped when all are in flight
+const CAPTURE_SLOTS: usize = 2;
+
+/// One readback buffer and the capture it's holding, if any
+struct CaptureSlot {
+    buffer: wgpu::Buffer,
+    size: winit::dpi::PhysicalSize<u32>, // Frame size the buffer fits
+    padded_row: u32,
+    state: CaptureState,
+}
+
+enum CaptureState {
+    Free,
+    Copied {
+        number: u32,
+    }, // Copy recorded, mapped once the frame is submitted
+    Mapping {
+        number: u32,
+        done: Arc<OnceLock<bool>>,
+    }, // Set by map_async, true on success
+}
+
+/// A numbered frame handed to the writer thread
+struct CapturedFrame {
+    path: PathBuf,
+    size: winit::dpi::PhysicalSize<u32>,
+    rgba: Vec<u8>,
+}
+
+/// Copies presented frames into readback buffers and hands finished ones to a
+/// writer thread, so neither the GPU nor PNG encoding stalls the render loop
+pub(super) struct FrameRecorder {
+    dir: PathBuf,
+    every_n_frames: u32,
+    bgra: bool,     // Frames are BGRA and need swizzling to RGBA
+    presented: u64, // Frames presented since recording started
+    pub(super) stats: RecordingStats,
+    slots: Vec<CaptureSlot>,
+    writer: Option<(mpsc::Sender<CapturedFrame>, std::thread::JoinHandle<()>)>,
+}
+
+impl FrameRecorder {
+    pub(super) fn new(dir: PathBuf, every_n_frames: u32, bgra: bool) -> Self {
+        let (sender, receiver) = mpsc::channel::<CapturedFrame>();
+        let writer = std::thread::spawn(move || {
+            for frame in receiver {
+                if let Err(err) = image::save_buffer(
+                    &frame.path,
+                    &frame.rgba,
+                    frame.size.width,
+                    frame.size.height,
+                    image::ExtendedColorType::Rgba8,
+                ) {
+                    println!("WARNING: failed to write {}: {err}", frame.path.display());
+                }
+            }
+        });
+
+        Self {
+            dir,
+            every_n_frames: every_n_frames.max(1),
+            bgra,
+            presented: 0,
+            stats: RecordingStats::default(),
+            slots: Vec::new(),
+            writer: Some((sender, writer)),
+        }
+    }
+
+    /// Record a copy of `texture` if this frame is due, before the frame is submitted
+    pub(super) fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        let due = self.presented.is_multiple_of(self.every_n_frames as u64);
+        self.presented += 1;
+        if !due {
+            return;
+        }
+
+        let size = winit::dpi::PhysicalSize::new(texture.width(), texture.height());
+        let slot = match self
+            .slots
+            .iter_mut()
+            .position(|slot| matches!(slot.state, CaptureState::Free))
+        {
+            Some(index) => &mut self.slots[index],
+            None if self.slots.len() < CAPTURE_SLOTS => {
+                let index = self.slots.len();
+                self.slots.push(CaptureSlot::new(device, size));
+                &mut self.slots[index]
+            }
+            None => {
+                self.stats.dropped += 1;
+                return;
+            }
+        };
+        // Window resized since this buffer was made
+        if slot.size != size {
+            *slot = CaptureSlot::new(device, size);
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &slot.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(slot.padded_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        slot.state = CaptureState::Copied {
+            number: self.stats.captured,
+        };
+        self.stats.captured += 1;
+    }
+
+    /// Start mapping this frame's copies; call after the frame is submitted
+    pub(super) fn map_copies(&mut self) {
+        for slot in &mut self.slots {
+            if let CaptureState::Copied { number } = slot.state {
+                let done = Arc::new(OnceLock::new());
+                let callback_done = done.clone();
+                slot.buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let _ = callback_done.set(result.is_ok());
+                    });
+                slot.state = CaptureState::Mapping { number, done };
+            }
+        }
+    }
+
+    /// Hand every finished copy to the writer without waiting for the rest
+    pub(super) fn collect(&mut self, device: &wgpu::Device) {
+        let _ = device.poll(wgpu::PollType::Poll);
+        for slot in &mut self.slots {
+            let CaptureState::Mapping { number, done } = &slot.state else {
+                continue;
+            };
+            let Some(&mapped) = done.get() else {
+                continue;
+            };
+
+            if mapped {
+                let frame = CapturedFrame {
+                    path: self.dir.join(format!("frame_{number:06}.png")),
+                    size: slot.size,
+                    rgba: slot.read(self.bgra),
+                };
+                if let Some((sender, _)) = &self.writer {
+                    let _ = sender.send(frame);
+                }
+            } else {
+                println!("WARNING: failed to read back recorded frame {number}");
+            }
+            slot.buffer.unmap();
+            slot.state = CaptureState::Free;
+        }
+    }
+
+    /// Wait for copies still in flight, write them and stop the writer thread
+    pub(super) fn finish(mut self, device: &wgpu::Device) -> RecordingStats {
+        self.map_copies();
+        if self
+            .slots
+            .iter()
+            .any(|slot| !matches!(slot.state, CaptureState::Free))
+        {
+            let _ = device.poll(wgpu::PollType::wait_indefinitely());
+        }
+        self.collect(device);
+
+        if let Some((sender, writer)) = self.writer.take() {
+            drop(sender);
+            let _ = writer.join();
+        }
+        self.stats
+    }
+}
+
+impl CaptureSlot {
+    fn new(device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) -> Self {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (size.width * 4).div_ceil(align) * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Recording Readback Buffer"),
+            size: (padded_row * size.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            size,
+            padded_row,
+            state: CaptureState::Free,
+        }
+    }
+
+    /// Mapped contents as tightly packed RGBA rows
+    fn read(&self, bgra: bool) -> Vec<u8> {
+        let unpadded_row = (self.size.width * 4) as usize;
+        let data = self.buffer.slice(..).get_mapped_range();
+        let mut pixels = Vec::with_capacity(unpadded_row * self.size.height as usize);
+        for row in data.chunks(self.padded_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_row]);
+        }
+        if bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        pixels
+    }
+}
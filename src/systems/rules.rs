@@ -0,0 +1,165 @@
+use super::physics::PhysicsEvent;
+use crate::world::{EntityId, World};
+
+/// Runs the world's game rules: sensor rules (`World::on_sensor_overlap`) for
+/// this frame's `PhysicsEvent::SensorEntered` events, then refreshes text bound
+/// to counters. Call once per frame, after `Events::update`.
+pub struct RuleSystem {
+    // This frame's sensor entries, copied out so the rules can borrow the world
+    entered: Vec<(EntityId, EntityId)>,
+}
+
+impl RuleSystem {
+    pub fn new() -> Self {
+        Self {
+            entered: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, world: &mut World) {
+        self.entered.clear();
+        self.entered.extend(
+            world
+                .events()
+                .read::<PhysicsEvent>()
+                .iter()
+                .filter_map(|event| match *event {
+                    PhysicsEvent::SensorEntered { sensor, other } => Some((sensor, other)),
+                    _ => None,
+                }),
+        );
+        for &(sensor, other) in &self.entered {
+            world.run_sensor_rules(sensor, other);
+        }
+        world.refresh_counter_labels();
+    }
+}
+
+impl Default for RuleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Physics, Shape};
+    use crate::entity::Entity;
+    use crate::systems::PhysicsSystem;
+    use std::time::Duration;
+
+    /// World with a goal sensor across the middle, a score counter and a label
+    /// bound to it. Returns the world, goal, score and label ids.
+    fn goal_world() -> (World, EntityId, EntityId, EntityId) {
+        let mut world = World::new();
+        let goal = world
+            .add_entity(
+                Entity::new_rectangle([0.0, -0.3], 0.4, 0.1, [0.2, 0.8, 0.2])
+                    .with_physics(Physics::new_sensor()),
+            )
+            .id()
+            .unwrap();
+        let score = world
+            .add_entity(Entity::new_circle([0.9, 0.9], 0.01, [0.0, 0.0, 0.0]).with_counter(0))
+            .id()
+            .unwrap();
+        let label = world
+            .add_entity(Entity::new_text(
+                [-0.8, 0.9],
+                String::new(),
+                24.0,
+                [1.0, 1.0, 1.0],
+            ))
+            .id()
+            .unwrap();
+        world.bind_text_to_counter(label, score, "Score: {}");
+        (world, goal, score, label)
+    }
+
+    fn label_text(world: &World, label: EntityId) -> &str {
+        match world.get(label).unwrap().shape() {
+            Shape::Text { content, .. } => content,
+            shape => panic!("label isn't text: {shape:?}"),
+        }
+    }
+
+    fn score_of(world: &World, score: EntityId) -> i64 {
+        world.get(score).unwrap().counter().unwrap().value
+    }
+
+    /// Steps physics, then lets the rules react, once per step like a frame would
+    fn run(world: &mut World, steps: u32) {
+        let mut physics = PhysicsSystem::new();
+        let mut rules = RuleSystem::new();
+        for _ in 0..steps {
+            physics.update(world, Duration::from_millis(8));
+            world.events().update();
+            rules.update(world);
+        }
+    }
+
+    #[test]
+    fn ball_through_the_goal_scores_once_and_updates_the_label() {
+        let (mut world, goal, score, label) = goal_world();
+        assert_eq!(label_text(&world, label), "Score: 0");
+        world.on_sensor_overlap(goal, move |world, ball| {
+            if let Some(counter) = world.get_mut(score).and_then(|entity| entity.counter_mut()) {
+                counter.value += 1;
+            }
+            world.queue_removal(ball);
+            world.flush_removals();
+        });
+        let ball = world
+            .add_entity(
+                Entity::new_circle([0.0, 0.0], 0.03, [1.0, 1.0, 1.0])
+                    .with_physics(Physics::new().with_velocity([0.0, -1.0])),
+            )
+            .id()
+            .unwrap();
+        let miss = world
+            .add_entity(
+                Entity::new_circle([0.7, 0.0], 0.03, [1.0, 1.0, 1.0])
+                    .with_physics(Physics::new().with_velocity([0.0, -1.0])),
+            )
+            .id()
+            .unwrap();
+
+        run(&mut world, 150);
+
+        assert_eq!(score_of(&world, score), 1);
+        assert_eq!(label_text(&world, label), "Score: 1");
+        assert!(!world.contains(ball));
+        assert!(world.contains(miss));
+    }
+
+    #[test]
+    fn ball_resting_in_the_goal_only_counts_on_entering() {
+        let (mut world, goal, score, label) = goal_world();
+        world.on_sensor_overlap(goal, move |world, _| {
+            if let Some(counter) = world.get_mut(score).and_then(|entity| entity.counter_mut()) {
+                counter.value += 1;
+            }
+        });
+        // Drops onto a dead floor just under the goal and rests poking into it
+        world.add_entity(
+            Entity::new_rectangle([0.0, -0.365], 0.4, 0.02, [0.5, 0.5, 0.5]).with_physics(
+                Physics {
+                    restitution: 0.0,
+                    ..Physics::new_static()
+                },
+            ),
+        );
+        world.add_entity(
+            Entity::new_circle([0.0, -0.2], 0.03, [1.0, 1.0, 1.0]).with_physics(Physics {
+                restitution: 0.0,
+                ..Physics::new()
+            }),
+        );
+
+        run(&mut world, 400);
+
+        assert_eq!(score_of(&world, score), 1);
+        assert_eq!(label_text(&world, label), "Score: 1");
+    }
+}
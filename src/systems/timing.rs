@@ -50,7 +50,8 @@ impl TimeSystem {
 
         if self.paused {
             self.sim_accumulator = Duration::ZERO;
-            return (0, None, false, 0.0);
+            // Nothing is moving, so render the latest positions as-is
+            return (0, None, false, 1.0);
         }
 
         let max_frame_dt = self.sim_dt * 5;
@@ -102,3 +103,9 @@ impl TimeSystem {
         self.last_update + self.sim_dt
     }
 }
+
+impl Default for TimeSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,21 +1,1121 @@
-use crate::entity::Entity;
+use crate::components::{Aabb, Clickable, GroupId, Physics, Shape, TagId};
+use crate::entity::{Entity, EntityError};
+use crate::events::Events;
+use crate::palette::{Palette, Theme};
+use crate::prefab::Prefab;
+use crate::random::WorldRng;
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-/// World owns all entities and provides query access for systems
+/// Stable handle to an entity. Unlike an index into `World::entities`, it keeps
+/// pointing at the same entity across removals, and stops resolving once that
+/// entity is gone, even if its slot is reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntityId {
+    index: u32,      // Slot in World::slots
+    generation: u32, // Bumped every time the slot is freed
+}
+
+/// Identifies one `World` instance. Clones and loaded worlds get their own, so
+/// systems holding per-world state can tell when they're handed a different world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WorldId(u64);
+
+impl WorldId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+// A world's own id; cloning hands out a new one
+#[derive(Debug)]
+struct InstanceId(WorldId);
+
+impl Clone for InstanceId {
+    fn clone(&self) -> Self {
+        Self(WorldId::next())
+    }
+}
+
+/// Default for `World::set_max_fling_speed`, in NDC/sec
+pub const DEFAULT_MAX_FLING_SPEED: f32 = 6.0;
+
+/// Most tags a world can register; each is one bit of `Tags`
+pub const MAX_TAGS: usize = 64;
+
+/// Why a tag couldn't be registered
+#[derive(Debug)]
+pub enum TagError {
+    TooManyTags,
+}
+
+impl std::fmt::Display for TagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagError::TooManyTags => write!(f, "a world holds at most {MAX_TAGS} tags"),
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+/// Why a world couldn't be saved or loaded
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "could not access the scene file: {err}"),
+            SceneError::Json(err) => write!(f, "invalid scene JSON: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for SceneError {}
+
+/// Scene composition from `World::stats`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorldStats {
+    pub circles: usize,
+    pub rectangles: usize,
+    pub polygons: usize,
+    pub texts: usize,
+    pub with_physics: usize,
+    pub dynamic: usize,
+    pub static_bodies: usize,
+    pub at_rest: usize, // Dynamic bodies with zero velocity right now, asleep or not
+    pub total_mass: f32, // Bodies with finite mass only
+    pub bounds: Option<Aabb>, // Around every entity; None for an empty world
+}
+
+/// Ranges `World::spawn_random_balls_with` samples each ball from, all min to max
+#[derive(Clone, Copy, Debug)]
+pub struct BallRanges {
+    pub radius: [f32; 2],      // NDC
+    pub density: [f32; 2],     // Mass per NDC², so mass = density * π * radius²
+    pub restitution: [f32; 2], // Bounciness, 0 to 1
+    pub color: [[f32; 3]; 2],  // Each channel sampled separately
+    pub max_attempts: u32,     // Placement tries per ball before allowing an overlap
+}
+
+impl Default for BallRanges {
+    fn default() -> Self {
+        Self {
+            radius: [0.015, 0.04],
+            density: [400.0, 1200.0],
+            restitution: [0.5, 0.9],
+            color: [[0.2, 0.2, 0.2], [1.0, 1.0, 1.0]],
+            max_attempts: 30,
+        }
+    }
+}
+
+/// What `World::queue_ball_at` builds, e.g. for a right-click. Ranges are min to max.
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnSettings {
+    pub radius: [f32; 2],     // NDC
+    pub color: [[f32; 3]; 2], // Each channel sampled separately
+    pub speed: [f32; 2],      // NDC/sec in a random direction; dynamic balls only
+}
+
+/// Smallest and largest radius `SpawnSettings::with_radius_scaled` lets through (NDC)
+pub const SPAWN_RADIUS_LIMITS: [f32; 2] = [0.005, 0.3];
+
+impl SpawnSettings {
+    /// Same settings with both radius bounds times `factor`, as far as the range
+    /// can go while staying inside `SPAWN_RADIUS_LIMITS`
+    pub fn with_radius_scaled(mut self, factor: f32) -> Self {
+        let [min, max] = self.radius;
+        let factor = factor
+            .min(SPAWN_RADIUS_LIMITS[1] / max)
+            .max(SPAWN_RADIUS_LIMITS[0] / min);
+        self.radius = [min * factor, max * factor];
+        self
+    }
+}
+
+impl Default for SpawnSettings {
+    fn default() -> Self {
+        Self {
+            radius: [0.02, 0.05],
+            color: [[0.2, 0.2, 0.2], [1.0, 1.0, 1.0]],
+            speed: [0.0, 0.3],
+        }
+    }
+}
+
+/// What a `ClickDispatch` handler did with the click it was offered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClickResponse {
+    /// Handled; entities underneath don't see it
+    Consumed,
+    /// Offer it to the next entity down
+    PassThrough,
+}
+
+/// Delivers a click to the enabled clickables under it, topmost first (by layer,
+/// then draw order), until a handler consumes it. A click nobody consumes landed
+/// on empty space as far as the caller is concerned, e.g. to deselect or spawn.
+#[derive(Clone, Copy, Debug)]
+pub struct ClickDispatch {
+    position: [f32; 2], // NDC
+}
+
+impl ClickDispatch {
+    pub fn new(position: [f32; 2]) -> Self {
+        Self { position }
+    }
+
+    /// Call `handler` for each clickable under the click until one returns
+    /// `Consumed`, and return that entity. Handlers may change the world; an
+    /// entity removed or disabled by an earlier handler is skipped. None means
+    /// the click fell through everything.
+    pub fn run(
+        &self,
+        world: &mut World,
+        mut handler: impl FnMut(&mut World, EntityId) -> ClickResponse,
+    ) -> Option<EntityId> {
+        for id in world.find_all_at_point(self.position, true) {
+            let enabled = world
+                .get(id)
+                .and_then(Entity::clickable)
+                .is_some_and(|clickable| clickable.enabled);
+            if enabled && handler(world, id) == ClickResponse::Consumed {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// What `World::add_entity` does once `World::set_max_entities` is reached.
+/// Static and persistent entities are never evicted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvictionPolicy {
+    /// Drop the new entity
+    RejectNew,
+    /// Remove the oldest evictable entity to make room
+    EvictOldest,
+    /// Remove the oldest evictable entity outside `view`, or the oldest overall
+    /// if everything is on screen
+    EvictOffscreenFirst(Aabb),
+}
+
+/// What `World::add_entity` did with the entity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnOutcome {
+    Added(EntityId),
+    /// Added after removing `evicted` to stay at the entity cap
+    Evicted {
+        id: EntityId,
+        evicted: EntityId,
+    },
+    /// The world was at its entity cap with nothing evictable; the entity was dropped
+    Rejected,
+}
+
+impl SpawnOutcome {
+    /// Id of the added entity, None if it was rejected
+    pub fn id(self) -> Option<EntityId> {
+        match self {
+            SpawnOutcome::Added(id) | SpawnOutcome::Evicted { id, .. } => Some(id),
+            SpawnOutcome::Rejected => None,
+        }
+    }
+}
+
+/// Broken invariant found by `World::validate`
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    Entity {
+        id: EntityId,
+        error: EntityError,
+    },
+    /// The name index and the entity's own name disagree
+    NameIndex {
+        id: EntityId,
+        name: String,
+    },
+    /// A group's member list and the entity's own group disagree
+    GroupMembers {
+        id: EntityId,
+        group: GroupId,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Entity { id, error } => write!(f, "entity {id:?}: {error}"),
+            ValidationError::NameIndex { id, name } => {
+                write!(f, "entity {id:?}: name index out of sync for {name:?}")
+            }
+            ValidationError::GroupMembers { id, group } => {
+                write!(f, "entity {id:?}: member list of {group:?} out of sync")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Sent by `World::update_hover` when the cursor moves onto a clickable entity
+#[derive(Clone, Copy, Debug)]
+pub struct HoverEntered {
+    pub id: EntityId,
+}
+
+/// Sent by `World::update_hover` when the cursor leaves the hovered entity
+#[derive(Clone, Copy, Debug)]
+pub struct HoverExited {
+    pub id: EntityId, // May no longer resolve if the entity was removed
+}
+
+/// Sent by `World::add_entity` for every entity evicted under the entity cap
+#[derive(Clone, Copy, Debug)]
+pub struct EntityEvicted {
+    pub id: EntityId, // No longer resolves
+    pub last_position: [f32; 2],
+}
+
+/// Rigid rod keeping two entities `length` apart, solved by the physics system
+/// alongside collisions; see `World::add_joint`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistanceJoint {
+    pub a: EntityId,
+    pub b: EntityId,
+    pub length: f32,
+}
+
+#[derive(Clone)]
+struct Group {
+    name: String,
+    members: Vec<EntityId>, // Oldest first; kept in step with add/remove/regroup
+}
+
+/// Game rule run by `RuleSystem` when something enters a sensor; see
+/// `World::on_sensor_overlap`. Cloned along with the world, like `Behavior`.
+struct SensorRule(Box<dyn SensorFn>);
+
+// FnMut + Clone, object safe
+trait SensorFn: Send {
+    fn call(&mut self, world: &mut World, other: EntityId);
+    fn clone_box(&self) -> Box<dyn SensorFn>;
+}
+
+impl<F: FnMut(&mut World, EntityId) + Send + Clone + 'static> SensorFn for F {
+    fn call(&mut self, world: &mut World, other: EntityId) {
+        self(world, other)
+    }
+
+    fn clone_box(&self) -> Box<dyn SensorFn> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for SensorRule {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+// Text entity showing a counter, refreshed by `RuleSystem` when the value changes
+#[derive(Clone)]
+struct CounterLabel {
+    text: EntityId,
+    counter: EntityId,
+    format: String, // "{}" is replaced by the value
+    shown: Option<i64>,
+}
+
+// Entity grabbed by start_drag
+#[derive(Clone, Copy)]
+struct Drag {
+    id: EntityId,
+    offset: [f32; 2], // From the cursor to the entity's position
+    // Physics::frozen to put back on release. The drag holds the body until then,
+    // so freezing or thawing it meanwhile changes this instead.
+    frozen_on_release: bool,
+}
+
+// Slot map entry: where a live entity sits in the dense `entities` Vec
+#[derive(Clone)]
+struct Slot {
+    generation: u32,
+    dense: Option<usize>, // None while the slot is free
+}
+
+/// World owns all entities and provides query access for systems.
+///
+/// Storage only grows: removing entities keeps capacity for the next spawns, so
+/// a scene that stays around the same size stops allocating once warmed up.
+/// For large scenes, size it up front with `with_capacity` or `reserve`.
+///
+/// Entities are always visited in insertion order: removals keep the rest in
+/// order and new entities (reused slots included) go to the end. `entities`,
+/// `iter`, the physics solver, the renderer's insertion draw order and every
+/// event sent while iterating follow it, so the same calls give the same world.
+#[derive(Clone)]
 pub struct World {
+    id: InstanceId,
+    // Live entities, densely packed in insertion order
     entities: Vec<Entity>,
+    // Id of each entity in `entities`, same order
+    ids: Vec<EntityId>,
+    slots: Vec<Slot>,
+    // Free slot indices, reused before growing `slots`
+    free_slots: Vec<u32>,
+    // Entities queued for removal, applied by flush_removals
+    pending_removals: Vec<EntityId>,
+    // Entities queued for adding, applied by flush_spawns
+    pending_spawns: Vec<Entity>,
+    spawn_settings: SpawnSettings,
+    // Ids carrying each name, oldest first; kept in step with add/remove/rename
+    names: HashMap<String, Vec<EntityId>>,
+    // Registered tag names, indexed by TagId
+    tags: Vec<String>,
+    // Created groups, indexed by GroupId
+    groups: Vec<Group>,
+    // Dropped along with either of their entities
+    joints: Vec<DistanceJoint>,
+    events: Events,
+    palette: Palette,
+    rng: WorldRng,
+    // Selected entities, in selection order; removed entities drop out
+    selection: Vec<EntityId>,
+    // Entity cap and what happens when it's reached
+    max_entities: Option<(usize, EvictionPolicy)>,
+    // Stamped onto changed entities by drain_changes, then advanced
+    change_tick: u32,
+    // Sim clock, advanced by the physics update; stamped onto new entities
+    sim_steps: u64,
+    sim_time: Duration,
+    // Clickable entity under the cursor as of the last update_hover
+    hovered: Option<EntityId>,
+    drag: Option<Drag>,
+    // Set by freeze_all; entities added meanwhile start frozen
+    frozen: bool,
+    // Cap on World::fling; the solver has no speed limit of its own
+    max_fling_speed: f32,
+    // Game rules; dropped once their entities are gone
+    sensor_rules: Vec<(EntityId, SensorRule)>,
+    counter_labels: Vec<CounterLabel>,
 }
 
 impl World {
     pub fn new() -> Self {
         Self {
+            id: InstanceId(WorldId::next()),
             entities: Vec::new(),
+            ids: Vec::new(),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            pending_removals: Vec::new(),
+            pending_spawns: Vec::new(),
+            spawn_settings: SpawnSettings::default(),
+            names: HashMap::new(),
+            tags: Vec::new(),
+            groups: Vec::new(),
+            joints: Vec::new(),
+            events: Events::new(),
+            palette: Palette::default(),
+            rng: WorldRng::from_entropy(),
+            selection: Vec::new(),
+            max_entities: None,
+            change_tick: 0,
+            sim_steps: 0,
+            sim_time: Duration::ZERO,
+            hovered: None,
+            drag: None,
+            frozen: false,
+            max_fling_speed: DEFAULT_MAX_FLING_SPEED,
+            sensor_rules: Vec::new(),
+            counter_labels: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> WorldId {
+        self.id.0
+    }
+
+    /// Empty world with room for `capacity` entities before reallocating
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut world = Self::new();
+        world.reserve(capacity);
+        world
+    }
+
+    /// Make room for `additional` more entities than are currently alive
+    pub fn reserve(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+        self.ids.reserve(additional);
+        // Freed slots get reused, so only the shortfall needs new ones
+        self.slots
+            .reserve(additional.saturating_sub(self.free_slots.len()));
+    }
+
+    /// Cap the number of live entities, handling spawns past it by `policy`.
+    /// Entities already over a lowered cap stay; each add evicts at most one.
+    pub fn set_max_entities(&mut self, cap: usize, policy: EvictionPolicy) {
+        self.max_entities = Some((cap, policy));
+    }
+
+    /// Remove the entity cap
+    pub fn clear_max_entities(&mut self) {
+        self.max_entities = None;
+    }
+
+    pub fn max_entities(&self) -> Option<(usize, EvictionPolicy)> {
+        self.max_entities
+    }
+
+    /// Add `entity`, respecting the entity cap; see `set_max_entities`. Panics on
+    /// an invalid entity in debug builds; see `Entity::validate`.
+    pub fn add_entity(&mut self, entity: Entity) -> SpawnOutcome {
+        #[cfg(debug_assertions)]
+        if let Err(err) = entity.validate() {
+            panic!("add_entity: {err}");
+        }
+
+        let mut evicted = None;
+        if let Some((cap, policy)) = self.max_entities
+            && self.entities.len() >= cap
+        {
+            let Some(index) = self.eviction_candidate(policy) else {
+                return SpawnOutcome::Rejected;
+            };
+            let id = self.ids[index];
+            let last_position = self.entities[index].transform().position;
+            self.remove_entity(id);
+            self.events.send(EntityEvicted { id, last_position });
+            evicted = Some(id);
+        }
+
+        let id = self.insert(entity);
+        match evicted {
+            Some(evicted) => SpawnOutcome::Evicted { id, evicted },
+            None => SpawnOutcome::Added(id),
+        }
+    }
+
+    // Index of the entity to make room with, or None to reject the new one
+    fn eviction_candidate(&self, policy: EvictionPolicy) -> Option<usize> {
+        // Dense order is insertion order, so the first match is the oldest
+        let evictable = |entity: &Entity| {
+            !entity.is_persistent() && entity.physics().is_some_and(|physics| physics.dynamic)
+        };
+        match policy {
+            EvictionPolicy::RejectNew => None,
+            EvictionPolicy::EvictOldest => self.entities.iter().position(evictable),
+            EvictionPolicy::EvictOffscreenFirst(view) => self
+                .entities
+                .iter()
+                .position(|entity| {
+                    evictable(entity) && !view.overlaps(&entity.shape().bounds(entity.transform()))
+                })
+                .or_else(|| self.entities.iter().position(evictable)),
         }
     }
 
-    pub fn add_entity(&mut self, entity: Entity) {
+    fn insert(&mut self, mut entity: Entity) -> EntityId {
+        // New to this world, even when copied from an unchanged entity
+        entity.mark_changed();
+        entity.set_spawned(self.sim_steps, self.sim_time);
+        if self.frozen
+            && let Some(physics) = entity.physics_mut()
+        {
+            physics.frozen = physics.dynamic;
+        }
+        let dense = Some(self.entities.len());
+        let id = match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.dense = dense;
+                EntityId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    dense,
+                });
+                EntityId {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+        if let Some(name) = entity.name() {
+            link_name(&mut self.names, name, id);
+        }
+        link_group(&mut self.groups, entity.group(), id);
         self.entities.push(entity);
+        self.ids.push(id);
+        id
+    }
+
+    /// Add a copy of every part of `prefab`, offset from `position`, joined to each
+    /// other like the parts. Ids come back in the prefab's part order, root first.
+    /// Here and in the other spawn helpers, entities rejected by the entity cap are
+    /// left out, along with their joints.
+    pub fn spawn_prefab(&mut self, prefab: &Prefab, position: [f32; 2]) -> Vec<EntityId> {
+        let ids: Vec<Option<EntityId>> = prefab
+            .parts()
+            .iter()
+            .map(|part| {
+                let mut entity = part.clone();
+                let transform = entity.transform_mut();
+                transform.position[0] += position[0];
+                transform.position[1] += position[1];
+                transform.prev_position = transform.position;
+                self.add_entity(entity).id()
+            })
+            .collect();
+        for &(a, b) in prefab.joints() {
+            if let (Some(Some(a)), Some(Some(b))) = (ids.get(a), ids.get(b)) {
+                self.add_joint(*a, *b);
+            }
+        }
+        ids.into_iter().flatten().collect()
+    }
+
+    /// `cols` x `rows` copies of `template`, `spacing` apart. `origin` is the
+    /// bottom-left position; ids come back row by row from the bottom.
+    pub fn spawn_grid(
+        &mut self,
+        template: &Entity,
+        cols: usize,
+        rows: usize,
+        spacing: [f32; 2],
+        origin: [f32; 2],
+    ) -> Vec<EntityId> {
+        let mut ids = Vec::with_capacity(cols * rows);
+        for row in 0..rows {
+            for col in 0..cols {
+                let position = [
+                    origin[0] + col as f32 * spacing[0],
+                    origin[1] + row as f32 * spacing[1],
+                ];
+                ids.extend(self.spawn_at(template, position));
+            }
+        }
+        ids
     }
 
+    /// `count` copies of `template` evenly spaced on a circle, the first at +x
+    pub fn spawn_circle_ring(
+        &mut self,
+        template: &Entity,
+        count: usize,
+        ring_radius: f32,
+        center: [f32; 2],
+    ) -> Vec<EntityId> {
+        (0..count)
+            .filter_map(|i| {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                let position = [
+                    center[0] + angle.cos() * ring_radius,
+                    center[1] + angle.sin() * ring_radius,
+                ];
+                self.spawn_at(template, position)
+            })
+            .collect()
+    }
+
+    /// `count` copies of `template` at uniformly random positions in `rect`.
+    /// Draws from `rng`, so the same seed gives the same layout.
+    pub fn spawn_random_in_rect(
+        &mut self,
+        template: &Entity,
+        count: usize,
+        rect: Aabb,
+    ) -> Vec<EntityId> {
+        (0..count)
+            .filter_map(|_| {
+                let position = [
+                    self.rng.range_f32(rect.min[0], rect.max[0]),
+                    self.rng.range_f32(rect.min[1], rect.max[1]),
+                ];
+                self.spawn_at(template, position)
+            })
+            .collect()
+    }
+
+    /// `count` dynamic balls of varied size, mass, bounciness and color inside
+    /// `area`, not overlapping each other; see `BallRanges` for the defaults.
+    /// The same seed gives the same balls.
+    pub fn spawn_random_balls(&mut self, count: usize, area: Aabb, seed: u64) -> Vec<EntityId> {
+        self.spawn_random_balls_with(&BallRanges::default(), count, area, seed)
+    }
+
+    /// `spawn_random_balls` with custom ranges. When `area` is too crowded to fit
+    /// a ball after `max_attempts` tries it's placed overlapping, with a warning.
+    pub fn spawn_random_balls_with(
+        &mut self,
+        ranges: &BallRanges,
+        count: usize,
+        area: Aabb,
+        seed: u64,
+    ) -> Vec<EntityId> {
+        let mut rng = WorldRng::new(seed);
+        let mut placed: Vec<([f32; 2], f32)> = Vec::with_capacity(count);
+        let mut ids = Vec::with_capacity(count);
+        let mut overlapping = 0;
+
+        for _ in 0..count {
+            let radius = rng.range_f32(ranges.radius[0], ranges.radius[1]);
+            // Keep the whole ball inside; a ball wider than the area sits on its center line
+            let half = [
+                ((area.max[0] - area.min[0]) / 2.0 - radius).max(0.0),
+                ((area.max[1] - area.min[1]) / 2.0 - radius).max(0.0),
+            ];
+            let inner = Aabb::from_center(area.center(), half);
+
+            let mut position = [0.0; 2];
+            let mut fits = false;
+            for _ in 0..ranges.max_attempts.max(1) {
+                position = [
+                    rng.range_f32(inner.min[0], inner.max[0]),
+                    rng.range_f32(inner.min[1], inner.max[1]),
+                ];
+                fits = placed.iter().all(|&(other, other_radius)| {
+                    let (dx, dy) = (position[0] - other[0], position[1] - other[1]);
+                    let reach = radius + other_radius;
+                    dx * dx + dy * dy >= reach * reach
+                });
+                if fits {
+                    break;
+                }
+            }
+            if !fits {
+                overlapping += 1;
+            }
+            placed.push((position, radius));
+
+            let density = rng.range_f32(ranges.density[0], ranges.density[1]);
+            let color =
+                std::array::from_fn(|i| rng.range_f32(ranges.color[0][i], ranges.color[1][i]));
+            let physics = Physics {
+                mass: density * std::f32::consts::PI * radius * radius,
+                restitution: rng.range_f32(ranges.restitution[0], ranges.restitution[1]),
+                ..Physics::new()
+            };
+            let ball = Entity::new_circle(position, radius, color).with_physics(physics);
+            ids.extend(self.add_entity(ball).id());
+        }
+
+        if overlapping > 0 {
+            println!(
+                "WARNING: spawn_random_balls: area too crowded, {overlapping} of {count} balls overlap"
+            );
+        }
+        ids
+    }
+
+    /// Add up to `max` (NDC/sec) of random velocity on each axis to the
+    /// entities in `ids` that have physics, e.g. right after a bulk spawn.
+    /// Draws from `rng`.
+    pub fn jitter_velocities(&mut self, ids: &[EntityId], max: f32) {
+        for &id in ids {
+            let Some(index) = self.index_of(id) else {
+                continue;
+            };
+            if let Some(physics) = self.entities[index].physics_mut() {
+                physics.velocity[0] += self.rng.range_f32(-max, max);
+                physics.velocity[1] += self.rng.range_f32(-max, max);
+            }
+        }
+    }
+
+    /// Add a copy of `id` moved by `offset`, with every component including user
+    /// data. Accumulated acceleration is cleared, a name gets a " copy" suffix and
+    /// an emitter starts with no spawns of its own. None if `id` no longer resolves
+    /// or the copy is rejected by the entity cap.
+    pub fn duplicate(&mut self, id: EntityId, offset: [f32; 2]) -> Option<EntityId> {
+        let mut entity = self.get(id)?.clone();
+        let transform = entity.transform_mut();
+        transform.position[0] += offset[0];
+        transform.position[1] += offset[1];
+        transform.prev_position = transform.position;
+        if let Some(physics) = entity.physics_mut() {
+            physics.acceleration = [0.0, 0.0];
+        }
+        if let Some(emitter) = entity.emitter_mut() {
+            emitter.children.clear();
+        }
+        if let Some(name) = entity.name() {
+            let name = format!("{name} copy");
+            entity.set_name(Some(name));
+        }
+        self.add_entity(entity).id()
+    }
+
+    // Add a copy of `template` at `position`, with nothing to interpolate from
+    fn spawn_at(&mut self, template: &Entity, position: [f32; 2]) -> Option<EntityId> {
+        let mut entity = template.clone();
+        let transform = entity.transform_mut();
+        transform.position = position;
+        transform.prev_position = position;
+        self.add_entity(entity).id()
+    }
+
+    /// Remove one entity right away and hand it back. Entities after it shift
+    /// down one index; every other id stays valid. None if `id` no longer resolves.
+    pub fn remove_entity(&mut self, id: EntityId) -> Option<Entity> {
+        let index = self.index_of(id)?;
+        unlink_name(&mut self.names, self.entities[index].name(), id);
+        unlink_group(&mut self.groups, self.entities[index].group(), id);
+        self.free_slot(id.index);
+        self.ids.remove(index);
+        for (dense, id) in self.ids.iter().enumerate().skip(index) {
+            self.slots[id.index as usize].dense = Some(dense);
+        }
+        Some(self.entities.remove(index))
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.index_of(id).map(|index| &self.entities[index])
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.index_of(id).map(|index| &mut self.entities[index])
+    }
+
+    /// `Entity::set_physics` on `id`, if it's alive
+    pub fn set_physics(&mut self, id: EntityId, physics: Physics) {
+        if let Some(entity) = self.get_mut(id) {
+            entity.set_physics(physics);
+        }
+    }
+
+    pub fn remove_physics(&mut self, id: EntityId) -> Option<Physics> {
+        self.get_mut(id)?.remove_physics()
+    }
+
+    pub fn set_clickable(&mut self, id: EntityId, clickable: Clickable) {
+        if let Some(entity) = self.get_mut(id) {
+            entity.set_clickable(clickable);
+        }
+    }
+
+    pub fn remove_clickable(&mut self, id: EntityId) -> Option<Clickable> {
+        self.get_mut(id)?.remove_clickable()
+    }
+
+    /// Run `rule` with the entering entity every time something starts overlapping
+    /// `sensor` (a `Physics::new_sensor` entity), e.g. to bump a score and despawn
+    /// the ball. Rules run from `RuleSystem::update`, in the order they were added.
+    /// Not saved by `save_json`.
+    pub fn on_sensor_overlap(
+        &mut self,
+        sensor: EntityId,
+        rule: impl FnMut(&mut World, EntityId) + Send + Clone + 'static,
+    ) {
+        self.sensor_rules.push((sensor, SensorRule(Box::new(rule))));
+    }
+
+    /// Add `delta` to `id`'s counter and return the new value. None if `id`
+    /// doesn't resolve or has no counter.
+    pub fn add_to_counter(&mut self, id: EntityId, delta: i64) -> Option<i64> {
+        let counter = self.get_mut(id)?.counter_mut()?;
+        counter.value += delta;
+        Some(counter.value)
+    }
+
+    /// Keep the text entity `text` showing `counter`'s value, with `{}` in `format`
+    /// replaced by it (e.g. `"Score: {}"`). Updated by `RuleSystem` whenever the value
+    /// changes. Not saved by `save_json`.
+    pub fn bind_text_to_counter(
+        &mut self,
+        text: EntityId,
+        counter: EntityId,
+        format: impl Into<String>,
+    ) {
+        self.counter_labels.retain(|label| label.text != text);
+        self.counter_labels.push(CounterLabel {
+            text,
+            counter,
+            format: format.into(),
+            shown: None,
+        });
+        self.refresh_counter_labels();
+    }
+
+    // Run the rules on `sensor` for `other` entering it. Rules added meanwhile run
+    // from the next overlap on.
+    pub(crate) fn run_sensor_rules(&mut self, sensor: EntityId, other: EntityId) {
+        let mut rules = std::mem::take(&mut self.sensor_rules);
+        for (rule_sensor, rule) in &mut rules {
+            if *rule_sensor == sensor && self.contains(sensor) && self.contains(other) {
+                rule.0.call(self, other);
+            }
+        }
+        rules.append(&mut self.sensor_rules);
+        rules.retain(|(sensor, _)| self.contains(*sensor));
+        self.sensor_rules = rules;
+    }
+
+    // Rewrite bound labels whose counter changed since they were last written
+    pub(crate) fn refresh_counter_labels(&mut self) {
+        let mut labels = std::mem::take(&mut self.counter_labels);
+        labels.retain(|label| self.contains(label.text) && self.contains(label.counter));
+        for label in &mut labels {
+            let Some(value) = self
+                .get(label.counter)
+                .and_then(|e| e.counter())
+                .map(|c| c.value)
+            else {
+                continue;
+            };
+            if label.shown == Some(value) {
+                continue;
+            }
+            label.shown = Some(value);
+            if let Some(entity) = self.get_mut(label.text)
+                && let Shape::Text { content, .. } = entity.shape_mut()
+            {
+                *content = label.format.replace("{}", &value.to_string());
+            }
+        }
+        self.counter_labels = labels;
+    }
+
+    /// Oldest live entity named `name`. Names needn't be unique; see `find_all_by_name`.
+    pub fn find_by_name(&self, name: &str) -> Option<EntityId> {
+        self.find_all_by_name(name).first().copied()
+    }
+
+    /// Every live entity named `name`, oldest first
+    pub fn find_all_by_name(&self, name: &str) -> &[EntityId] {
+        self.names.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Rename `id`, or unname it with None. Does nothing if `id` no longer resolves.
+    pub fn set_name(&mut self, id: EntityId, name: Option<String>) {
+        let Some(index) = self.index_of(id) else {
+            return;
+        };
+        let entity = &mut self.entities[index];
+        unlink_name(&mut self.names, entity.name(), id);
+        if let Some(name) = &name {
+            link_name(&mut self.names, name, id);
+        }
+        entity.set_name(name);
+    }
+
+    /// Id for the tag `name`, registering it on first use. Registering a name
+    /// again returns the same id.
+    pub fn register_tag(&mut self, name: &str) -> Result<TagId, TagError> {
+        if let Some(tag) = self.tag(name) {
+            return Ok(tag);
+        }
+        if self.tags.len() >= MAX_TAGS {
+            return Err(TagError::TooManyTags);
+        }
+        self.tags.push(name.to_owned());
+        Ok(TagId(self.tags.len() as u8 - 1))
+    }
+
+    /// Id of an already registered tag
+    pub fn tag(&self, name: &str) -> Option<TagId> {
+        self.tags
+            .iter()
+            .position(|tag| tag == name)
+            .map(|index| TagId(index as u8))
+    }
+
+    /// Id for the group `name`, creating it on first use. Creating a name again
+    /// returns the same id.
+    pub fn create_group(&mut self, name: &str) -> GroupId {
+        if let Some(group) = self.group(name) {
+            return group;
+        }
+        self.groups.push(Group {
+            name: name.to_owned(),
+            members: Vec::new(),
+        });
+        GroupId(self.groups.len() as u32 - 1)
+    }
+
+    /// Id of an already created group
+    pub fn group(&self, name: &str) -> Option<GroupId> {
+        self.groups
+            .iter()
+            .position(|group| group.name == name)
+            .map(|index| GroupId(index as u32))
+    }
+
+    /// Move `id` into `group`, leaving any group it was in. Does nothing if `id`
+    /// no longer resolves.
+    pub fn add_to_group(&mut self, group: GroupId, id: EntityId) {
+        self.set_group(id, Some(group));
+    }
+
+    /// Regroup `id`, or take it out of its group with None
+    pub fn set_group(&mut self, id: EntityId, group: Option<GroupId>) {
+        let Some(index) = self.index_of(id) else {
+            return;
+        };
+        let entity = &mut self.entities[index];
+        unlink_group(&mut self.groups, entity.group(), id);
+        link_group(&mut self.groups, group, id);
+        entity.set_group(group);
+    }
+
+    /// Live members of `group`, oldest first
+    pub fn group_members(&self, group: GroupId) -> &[EntityId] {
+        self.groups
+            .get(group.0 as usize)
+            .map_or(&[], |group| group.members.as_slice())
+    }
+
+    /// Remove every member of `group`; returns how many were removed. The group
+    /// itself stays, empty.
+    pub fn despawn_group(&mut self, group: GroupId) -> usize {
+        let Some(members) = self.groups.get_mut(group.0 as usize) else {
+            return 0;
+        };
+        // Flush only the members; removals already queued stay queued
+        let members = std::mem::take(&mut members.members);
+        let count = members.len();
+        let queued = std::mem::replace(&mut self.pending_removals, members);
+        self.flush_removals();
+        self.pending_removals = queued;
+        count
+    }
+
+    /// Hold every dynamic body in place, e.g. for an edit mode. They keep their
+    /// velocities and collide as immovable obstacles until `unfreeze_all`, which
+    /// carries on as if no time had passed. Bodies added meanwhile start frozen.
+    pub fn freeze_all(&mut self) {
+        self.frozen = true;
+        for index in 0..self.entities.len() {
+            self.set_frozen(index, true);
+        }
+    }
+
+    pub fn unfreeze_all(&mut self) {
+        self.frozen = false;
+        for index in 0..self.entities.len() {
+            self.set_frozen(index, false);
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Hold every dynamic member of `group` in place, like `freeze_all` does for
+    /// the whole world. Members keep their velocities for `unfreeze_group`.
+    pub fn freeze_group(&mut self, group: GroupId) {
+        for id in self.group_members(group).to_vec() {
+            if let Some(index) = self.index_of(id) {
+                self.set_frozen(index, true);
+            }
+        }
+    }
+
+    /// Let the members of `group` move again. While the whole world is frozen
+    /// they stay held until `unfreeze_all`.
+    pub fn unfreeze_group(&mut self, group: GroupId) {
+        if self.frozen {
+            return;
+        }
+        for id in self.group_members(group).to_vec() {
+            if let Some(index) = self.index_of(id) {
+                self.set_frozen(index, false);
+            }
+        }
+    }
+
+    // Hold or release the dynamic body at dense `index`. A dragged body stays held
+    // by the drag and takes the new state when it's let go.
+    fn set_frozen(&mut self, index: usize, frozen: bool) {
+        let Some(physics) = self.entities[index].physics_mut() else {
+            return;
+        };
+        if frozen && !physics.dynamic {
+            return;
+        }
+        match &mut self.drag {
+            Some(drag) if drag.id == self.ids[index] => drag.frozen_on_release = frozen,
+            _ => physics.frozen = frozen,
+        }
+    }
+
+    /// Add `impulse` (mass × NDC/sec) to every dynamic, finite-mass member
+    pub fn apply_impulse_to_group(&mut self, group: GroupId, impulse: [f32; 2]) {
+        self.for_each_member(group, |entity| {
+            if let Some(physics) = entity.physics_mut()
+                && physics.dynamic
+                && physics.mass.is_finite()
+            {
+                physics.velocity[0] += impulse[0] / physics.mass;
+                physics.velocity[1] += impulse[1] / physics.mass;
+            }
+        });
+    }
+
+    pub fn set_group_color(&mut self, group: GroupId, color: [f32; 3]) {
+        self.for_each_member(group, |entity| entity.shape_mut().set_color(color));
+    }
+
+    fn for_each_member(&mut self, group: GroupId, mut f: impl FnMut(&mut Entity)) {
+        let Some(group) = self.groups.get(group.0 as usize) else {
+            return;
+        };
+        for &id in &group.members {
+            if let Some(index) = self.slots[id.index as usize].dense {
+                f(&mut self.entities[index]);
+            }
+        }
+    }
+
+    /// Live entities carrying `tag`, in index order
+    pub fn iter_tagged(&self, tag: TagId) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.iter().filter(move |(_, entity)| entity.has_tag(tag))
+    }
+
+    /// Current index of `id` in `entities()`, or None if it was removed
+    pub fn index_of(&self, id: EntityId) -> Option<usize> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.dense)
+    }
+
+    /// True while `id` refers to a live entity
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.index_of(id).is_some()
+    }
+
+    /// Id of the entity at `index` in `entities()`
+    pub fn id_at(&self, index: usize) -> Option<EntityId> {
+        self.ids.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Live entities in insertion order; indices match `id_at`
     pub fn entities(&self) -> &[Entity] {
         &self.entities
     }
@@ -24,7 +1124,2421 @@ impl World {
         &mut self.entities
     }
 
-    pub fn clear(&mut self) {
-        self.entities.clear();
+    /// Live entities with their ids, in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.ids.iter().copied().zip(&self.entities)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Entity)> {
+        self.ids.iter().copied().zip(&mut self.entities)
+    }
+
+    /// Every broken invariant, in one pass: entities failing `Entity::validate`
+    /// (e.g. a NaN velocity, or a shape edited through `shape_mut`), and name and
+    /// group indexes out of step with the entities. Empty when all is well. Debug
+    /// builds run it after every physics update and behavior step; see `debug_validate`.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        // Every membership at once, so checking an entity doesn't scan its group
+        let memberships: HashSet<(EntityId, GroupId)> = self
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, group)| {
+                let group_id = GroupId(index as u32);
+                group.members.iter().map(move |&id| (id, group_id))
+            })
+            .collect();
+        for (id, entity) in self.iter() {
+            if let Err(error) = entity.validate() {
+                errors.push(ValidationError::Entity { id, error });
+            }
+            if let Some(name) = entity.name()
+                && !self.find_all_by_name(name).contains(&id)
+            {
+                let name = name.to_string();
+                errors.push(ValidationError::NameIndex { id, name });
+            }
+            // Groups from another world are ignored, as everywhere else
+            if let Some(group) = entity.group()
+                && (group.0 as usize) < self.groups.len()
+                && !memberships.contains(&(id, group))
+            {
+                errors.push(ValidationError::GroupMembers { id, group });
+            }
+        }
+
+        for (name, ids) in &self.names {
+            for &id in ids {
+                if self.get(id).and_then(Entity::name) != Some(name.as_str()) {
+                    let name = name.clone();
+                    errors.push(ValidationError::NameIndex { id, name });
+                }
+            }
+        }
+        for (index, group) in self.groups.iter().enumerate() {
+            let group_id = GroupId(index as u32);
+            for &id in &group.members {
+                if self.get(id).and_then(Entity::group) != Some(group_id) {
+                    errors.push(ValidationError::GroupMembers {
+                        id,
+                        group: group_id,
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    /// In debug builds, print every `validate` error with `after` naming the step
+    /// that just ran, so a bad value is reported on the frame it appears. Does
+    /// nothing in release builds.
+    pub fn debug_validate(&self, after: &str) {
+        if cfg!(debug_assertions) {
+            for error in self.validate() {
+                println!("WARNING: invalid world after {after}: {error}");
+            }
+        }
+    }
+
+    /// Move the sim clock one step of `dt` forward. `PhysicsSystem::update` calls
+    /// this; paused time never reaches it, so entities don't age while paused.
+    pub fn advance_sim_clock(&mut self, dt: Duration) {
+        self.sim_steps += 1;
+        self.sim_time += dt;
+    }
+
+    /// Sim steps taken so far
+    pub fn sim_steps(&self) -> u64 {
+        self.sim_steps
+    }
+
+    /// Sim time elapsed so far
+    pub fn sim_time(&self) -> Duration {
+        self.sim_time
+    }
+
+    /// Sim time since `id` was added, or None if it no longer resolves
+    pub fn entity_age(&self, id: EntityId) -> Option<Duration> {
+        self.get(id)
+            .map(|entity| self.sim_time.saturating_sub(entity.spawned_at()))
+    }
+
+    /// Longest-lived entity. Entities stay in the order they were added, so this
+    /// is also the first one `EvictionPolicy::EvictOldest` looks at.
+    pub fn oldest(&self) -> Option<EntityId> {
+        self.ids.first().copied()
+    }
+
+    /// Most recently added entity
+    pub fn newest(&self) -> Option<EntityId> {
+        self.ids.last().copied()
+    }
+
+    /// Ids of entities changed through a mutable accessor (or added) since the last
+    /// call, in index order. Stamps them with the current `change_tick`, then
+    /// starts the next one; call once per frame.
+    pub fn drain_changes(&mut self) -> Vec<EntityId> {
+        let tick = self.change_tick;
+        let changed = self
+            .iter_mut()
+            .filter_map(|(id, entity)| entity.stamp_change(tick).then_some(id))
+            .collect();
+        self.change_tick = self.change_tick.wrapping_add(1);
+        changed
+    }
+
+    /// Tick that changes made now will be stamped with by `drain_changes`.
+    /// Remember it to ask `changed_since` later.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick
+    }
+
+    /// Entities changed since the world's change tick was `tick`, drained or not
+    pub fn changed_since(&self, tick: u32) -> impl Iterator<Item = EntityId> {
+        self.iter()
+            .filter(move |(_, entity)| entity.changed_since(tick))
+            .map(|(id, _)| id)
+    }
+
+    /// Count what's in the world, in one pass over the entities
+    pub fn stats(&self) -> WorldStats {
+        let mut stats = WorldStats::default();
+        for entity in &self.entities {
+            match entity.shape() {
+                Shape::Circle { .. } => stats.circles += 1,
+                Shape::Rectangle { .. } => stats.rectangles += 1,
+                Shape::Polygon { .. } => stats.polygons += 1,
+                Shape::Text { .. } => stats.texts += 1,
+            }
+
+            if let Some(physics) = entity.physics() {
+                stats.with_physics += 1;
+                if physics.dynamic {
+                    stats.dynamic += 1;
+                    if physics.velocity == [0.0, 0.0] {
+                        stats.at_rest += 1;
+                    }
+                } else {
+                    stats.static_bodies += 1;
+                }
+                if physics.mass.is_finite() {
+                    stats.total_mass += physics.mass;
+                }
+            }
+
+            let bounds = entity.shape().bounds(entity.transform());
+            stats.bounds = Some(match stats.bounds {
+                Some(total) => total.union(&bounds),
+                None => bounds,
+            });
+        }
+        stats
+    }
+
+    /// Event bus systems publish to; see `Events` for when events become readable
+    pub fn events(&mut self) -> &mut Events {
+        &mut self.events
+    }
+
+    /// Random numbers for spawning and jitter. Seeded from entropy unless `set_seed`
+    /// is called; part of snapshots, so a restored world replays the same draws.
+    pub fn rng(&mut self) -> &mut WorldRng {
+        &mut self.rng
+    }
+
+    /// Restart `rng` from `seed`, for a reproducible run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = WorldRng::new(seed);
+    }
+
+    /// Colors for spawning code; see `Entity::with_palette_color`
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn palette_mut(&mut self) -> &mut Palette {
+        &mut self.palette
+    }
+
+    /// Switch the palette theme and recolor every entity created from a palette slot.
+    /// Explicitly colored entities keep their colors.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.palette.set_theme(theme);
+        for entity in &mut self.entities {
+            if let Some(slot) = entity.palette_slot() {
+                entity.shape_mut().set_color(self.palette.color(slot));
+            }
+        }
+    }
+
+    /// Fill `order` with entity indices sorted back-to-front by layer.
+    /// Entities on the same layer keep insertion order.
+    pub fn draw_order(&self, order: &mut Vec<usize>) {
+        order.clear();
+        order.extend(0..self.entities.len());
+        // Index as tie-breaker keeps the unstable (non-allocating) sort stable
+        order.sort_unstable_by_key(|&index| (self.entities[index].transform().layer, index));
+    }
+
+    /// Like `draw_order`, but within a layer entities higher up are drawn first,
+    /// so lower entities overlap them. Equal heights keep insertion order.
+    pub fn y_sorted_draw_order(&self, order: &mut Vec<usize>) {
+        order.clear();
+        order.extend(0..self.entities.len());
+        order.sort_unstable_by(|&a, &b| {
+            let (ta, tb) = (self.entities[a].transform(), self.entities[b].transform());
+            ta.layer
+                .cmp(&tb.layer)
+                .then(tb.position[1].total_cmp(&ta.position[1]))
+                .then(a.cmp(&b))
+        });
+    }
+
+    /// Topmost entity containing `point` (NDC), by `draw_order`. With
+    /// `clickable_only`, entities without an enabled `Clickable` are skipped,
+    /// so whatever is under them can be picked instead.
+    pub fn find_at_point(&self, point: [f32; 2], clickable_only: bool) -> Option<EntityId> {
+        self.hits(point, clickable_only)
+            .max_by_key(|&(index, entity)| (entity.transform().layer, index))
+            .map(|(index, _)| self.ids[index])
+    }
+
+    /// Set `Clickable::hovered` on the topmost enabled clickable under `cursor` (NDC)
+    /// and clear it everywhere else, sending `HoverExited` / `HoverEntered` when that
+    /// changes. Pass `InputSystem::cursor_ndc` once per frame; None (cursor outside
+    /// the window or over the letterbox) clears the hover. Returns the hovered entity.
+    pub fn update_hover(&mut self, cursor: Option<[f32; 2]>) -> Option<EntityId> {
+        let hovered = cursor.and_then(|point| self.find_at_point(point, true));
+        for (id, entity) in self.iter_mut() {
+            let is_hovered = Some(id) == hovered;
+            if entity
+                .clickable()
+                .is_some_and(|clickable| clickable.hovered != is_hovered)
+                && let Some(clickable) = entity.clickable_mut()
+            {
+                clickable.hovered = is_hovered;
+            }
+        }
+
+        if hovered != self.hovered {
+            if let Some(id) = self.hovered {
+                self.events.send(HoverExited { id });
+            }
+            if let Some(id) = hovered {
+                self.events.send(HoverEntered { id });
+            }
+            self.hovered = hovered;
+        }
+        hovered
+    }
+
+    /// Grab the topmost enabled clickable at `point` (NDC) so `drag_to` moves it,
+    /// keeping where on it it was grabbed. A dynamic body stops and is held like a
+    /// frozen one until `end_drag`, pushing others aside as it's moved; anything
+    /// else is moved directly. Releases any entity grabbed before.
+    pub fn start_drag(&mut self, point: [f32; 2]) -> Option<EntityId> {
+        self.end_drag();
+        let id = self.find_at_point(point, true)?;
+        let entity = self.get_mut(id)?;
+        let position = entity.transform().position;
+        let frozen_on_release = entity.physics().is_some_and(|physics| physics.frozen);
+        if let Some(physics) = entity.physics_mut()
+            && physics.dynamic
+        {
+            physics.velocity = [0.0, 0.0];
+            physics.acceleration = [0.0, 0.0];
+            physics.frozen = true;
+        }
+        self.drag = Some(Drag {
+            id,
+            offset: [position[0] - point[0], position[1] - point[1]],
+            frozen_on_release,
+        });
+        Some(id)
+    }
+
+    /// Move the grabbed entity along with the cursor, now at `point` (NDC)
+    pub fn drag_to(&mut self, point: [f32; 2]) {
+        let Some(drag) = self.drag else {
+            return;
+        };
+        let Some(entity) = self.get_mut(drag.id) else {
+            self.drag = None;
+            return;
+        };
+        entity.transform_mut().position = [point[0] + drag.offset[0], point[1] + drag.offset[1]];
+    }
+
+    /// Drop the grabbed entity where it is, at rest. Returns it, if it's still alive.
+    pub fn end_drag(&mut self) -> Option<EntityId> {
+        self.fling([0.0, 0.0])
+    }
+
+    /// Release the grabbed entity, throwing it with `velocity` (NDC/sec, e.g.
+    /// `InputSystem::cursor_velocity`) capped at `max_fling_speed` if it's a
+    /// dynamic body. Returns it, if it's still alive.
+    pub fn fling(&mut self, velocity: [f32; 2]) -> Option<EntityId> {
+        let drag = self.drag.take()?;
+        let max_speed = self.max_fling_speed;
+        let entity = self.get_mut(drag.id)?;
+        if let Some(physics) = entity.physics_mut()
+            && physics.dynamic
+        {
+            physics.frozen = drag.frozen_on_release;
+            let speed = (velocity[0] * velocity[0] + velocity[1] * velocity[1]).sqrt();
+            let scale = if speed > max_speed {
+                max_speed / speed
+            } else {
+                1.0
+            };
+            physics.velocity = [velocity[0] * scale, velocity[1] * scale];
+        }
+        Some(drag.id)
+    }
+
+    /// Fastest `fling` throws, in NDC/sec; `DEFAULT_MAX_FLING_SPEED` unless set.
+    /// Match it to what the sim step can resolve without balls tunneling through
+    /// each other, since the physics system doesn't cap speeds itself.
+    pub fn set_max_fling_speed(&mut self, speed: f32) {
+        self.max_fling_speed = speed.max(0.0);
+    }
+
+    pub fn max_fling_speed(&self) -> f32 {
+        self.max_fling_speed
+    }
+
+    /// Entity grabbed by `start_drag`, until `end_drag`
+    pub fn dragged(&self) -> Option<EntityId> {
+        self.drag.map(|drag| drag.id)
+    }
+
+    /// Clickable entity under the cursor as of the last `update_hover`
+    pub fn hovered(&self) -> Option<EntityId> {
+        self.hovered
+    }
+
+    /// Every entity containing `point` (NDC), topmost first; see `find_at_point`
+    pub fn find_all_at_point(&self, point: [f32; 2], clickable_only: bool) -> Vec<EntityId> {
+        let mut hits: Vec<(i32, usize)> = self
+            .hits(point, clickable_only)
+            .map(|(index, entity)| (entity.transform().layer, index))
+            .collect();
+        hits.sort_unstable_by(|a, b| b.cmp(a));
+        hits.into_iter().map(|(_, index)| self.ids[index]).collect()
+    }
+
+    fn hits(
+        &self,
+        point: [f32; 2],
+        clickable_only: bool,
+    ) -> impl Iterator<Item = (usize, &Entity)> {
+        self.entities.iter().enumerate().filter(move |(_, entity)| {
+            (!clickable_only
+                || entity
+                    .clickable()
+                    .is_some_and(|clickable| clickable.enabled))
+                && entity.contains_point(point)
+        })
+    }
+
+    /// Add `id` to the selection. Ids that don't resolve are ignored.
+    pub fn select(&mut self, id: EntityId) {
+        if self.index_of(id).is_some() && !self.selection.contains(&id) {
+            self.selection.push(id);
+        }
+    }
+
+    pub fn deselect(&mut self, id: EntityId) {
+        self.selection.retain(|&selected| selected != id);
+    }
+
+    /// Select `id` if it isn't selected, otherwise deselect it
+    pub fn toggle_selected(&mut self, id: EntityId) {
+        if self.is_selected(id) {
+            self.deselect(id);
+        } else {
+            self.select(id);
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    pub fn is_selected(&self, id: EntityId) -> bool {
+        self.selection.contains(&id)
+    }
+
+    /// Selected entities, in the order they were selected. Removing an entity
+    /// deselects it.
+    pub fn selected(&self) -> &[EntityId] {
+        &self.selection
+    }
+
+    /// Queue an entity for removal without invalidating indices mid-iteration
+    pub fn queue_removal(&mut self, id: EntityId) {
+        self.pending_removals.push(id);
+    }
+
+    /// Queue `entity` to be added by the next `flush_spawns`, so spawning from input
+    /// handlers never lands in the middle of a step. `BehaviorSystem::update` flushes.
+    pub fn queue_spawn(&mut self, entity: Entity) {
+        self.pending_spawns.push(entity);
+    }
+
+    /// Add every queued entity, in queue order; returns the ids of those accepted
+    pub fn flush_spawns(&mut self) -> Vec<EntityId> {
+        let spawns = std::mem::take(&mut self.pending_spawns);
+        spawns
+            .into_iter()
+            .filter_map(|entity| self.add_entity(entity).id())
+            .collect()
+    }
+
+    pub fn spawn_settings(&self) -> &SpawnSettings {
+        &self.spawn_settings
+    }
+
+    pub fn set_spawn_settings(&mut self, settings: SpawnSettings) {
+        self.spawn_settings = settings;
+    }
+
+    /// Queue a clickable ball at `position` drawn from `spawn_settings` with the
+    /// world RNG: dynamic with a random velocity, or static with `static_body`
+    pub fn queue_ball_at(&mut self, position: [f32; 2], static_body: bool) {
+        let settings = self.spawn_settings;
+        let rng = &mut self.rng;
+        let radius = rng.range_f32(settings.radius[0], settings.radius[1]);
+        let color =
+            std::array::from_fn(|i| rng.range_f32(settings.color[0][i], settings.color[1][i]));
+        let physics = if static_body {
+            Physics::new_static()
+        } else {
+            let speed = rng.range_f32(settings.speed[0], settings.speed[1]);
+            let [x, y] = rng.unit_vec2();
+            Physics::new().with_velocity([x * speed, y * speed])
+        };
+        let ball = Entity::new_circle(position, radius, color)
+            .with_physics(physics)
+            .with_clickable(Clickable::new());
+        self.queue_spawn(ball);
+    }
+
+    /// Remove all queued entities. Remaining entities keep their relative order,
+    /// though their indices shift; their ids stay valid.
+    pub fn flush_removals(&mut self) {
+        if self.pending_removals.is_empty() {
+            return;
+        }
+
+        // Free the slots first; stale or repeated ids no longer resolve and are skipped.
+        // Indexing rather than draining keeps the queue's allocation for next time.
+        for i in 0..self.pending_removals.len() {
+            let id = self.pending_removals[i];
+            if let Some(index) = self.index_of(id) {
+                unlink_name(&mut self.names, self.entities[index].name(), id);
+                unlink_group(&mut self.groups, self.entities[index].group(), id);
+                self.free_slot(id.index);
+            }
+        }
+        self.pending_removals.clear();
+
+        // Compact in one pass, then point the surviving slots at their new indices
+        let slots = &self.slots;
+        let mut live = self
+            .ids
+            .iter()
+            .map(|id| slots[id.index as usize].dense.is_some());
+        self.entities.retain(|_| live.next().unwrap_or(false));
+        self.ids
+            .retain(|id| self.slots[id.index as usize].dense.is_some());
+        for (dense, id) in self.ids.iter().enumerate() {
+            self.slots[id.index as usize].dense = Some(dense);
+        }
+    }
+
+    /// Remove every entity for which `keep` returns false, along with any removals
+    /// already queued. Survivors keep their order and ids.
+    pub fn retain(&mut self, mut keep: impl FnMut(EntityId, &Entity) -> bool) {
+        for (&id, entity) in self.ids.iter().zip(&self.entities) {
+            if !keep(id, entity) {
+                self.pending_removals.push(id);
+            }
+        }
+        self.flush_removals();
+    }
+
+    /// Join `a` and `b` with a rod as long as they are apart right now. False,
+    /// adding nothing, if either doesn't resolve or they're the same entity. Joints
+    /// go away with either entity and aren't saved by `save_json`.
+    pub fn add_joint(&mut self, a: EntityId, b: EntityId) -> bool {
+        let (Some(entity_a), Some(entity_b)) = (self.get(a), self.get(b)) else {
+            return false;
+        };
+        if a == b {
+            return false;
+        }
+        let [ax, ay] = entity_a.transform().position;
+        let [bx, by] = entity_b.transform().position;
+        let length = ((bx - ax) * (bx - ax) + (by - ay) * (by - ay)).sqrt();
+        self.joints.push(DistanceJoint { a, b, length });
+        true
+    }
+
+    pub fn joints(&self) -> &[DistanceJoint] {
+        &self.joints
+    }
+
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() as u32 {
+            if self.slots[index as usize].dense.is_some() {
+                self.free_slot(index);
+            }
+        }
+        self.entities.clear();
+        self.ids.clear();
+        self.pending_removals.clear();
+        self.pending_spawns.clear();
+        self.names.clear();
+        self.joints.clear();
+        for group in &mut self.groups {
+            group.members.clear();
+        }
+        self.events.clear();
+        self.selection.clear();
+        self.sensor_rules.clear();
+        self.counter_labels.clear();
+    }
+
+    /// Full copy of the world, ids included, to go back to with `restore`
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            world: self.clone(),
+        }
+    }
+
+    /// Put the world back exactly as it was when `snapshot` was taken. Ids handed
+    /// out since then may stop resolving. The world gets a new `id`, so the physics
+    /// system and renderer drop their state from before the rewind on their next update.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        self.clone_from(&snapshot.world);
+    }
+
+    /// Write every entity, the registered tags and groups, and the palette to `path` as JSON
+    #[cfg(feature = "serde")]
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        let file = std::fs::File::create(path).map_err(SceneError::Io)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self).map_err(SceneError::Json)
+    }
+
+    /// Read a world written by `save_json`. Entities get fresh ids, in the saved order.
+    #[cfg(feature = "serde")]
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let file = std::fs::File::open(path).map_err(SceneError::Io)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(SceneError::Json)
+    }
+
+    // Invalidate every id for the slot and make it available for reuse
+    fn free_slot(&mut self, index: u32) {
+        // Any selected id on this slot is the one going away (or already stale)
+        self.selection.retain(|selected| selected.index != index);
+        self.joints.retain(|joint| {
+            let attached = joint.a.index == index || joint.b.index == index;
+            if attached {
+                println!("Dropped joint {:?}-{:?}: entity removed", joint.a, joint.b);
+            }
+            !attached
+        });
+        let slot = &mut self.slots[index as usize];
+        slot.dense = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(index);
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saved world state from `World::snapshot`
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    world: World,
+}
+
+/// Ring buffer of automatic snapshots, one every `interval` sim steps, for
+/// stepping back through recent history
+pub struct SnapshotHistory {
+    snapshots: VecDeque<WorldSnapshot>, // Oldest first
+    capacity: usize,
+    interval: u32,
+    steps: u32, // Sim steps since the last snapshot
+}
+
+impl SnapshotHistory {
+    pub fn new(capacity: usize, interval: u32) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            interval: interval.max(1),
+            steps: 0,
+        }
+    }
+
+    /// Call once per sim step, after the systems have run. Drops the oldest
+    /// snapshot once full.
+    pub fn record(&mut self, world: &World) {
+        self.steps += 1;
+        if self.steps < self.interval || self.capacity == 0 {
+            return;
+        }
+        self.steps = 0;
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(world.snapshot());
+    }
+
+    /// Restore the newest snapshot and drop it, so repeated calls walk further
+    /// back. Returns false once the history is exhausted.
+    pub fn step_back(&mut self, world: &mut World) -> bool {
+        let Some(snapshot) = self.snapshots.pop_back() else {
+            return false;
+        };
+        world.restore(&snapshot);
+        self.steps = 0;
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.steps = 0;
+    }
+}
+
+// Groups not created in this world are ignored
+fn link_group(groups: &mut [Group], group: Option<GroupId>, id: EntityId) {
+    if let Some(group) = group.and_then(|group| groups.get_mut(group.0 as usize)) {
+        group.members.push(id);
+    }
+}
+
+fn unlink_group(groups: &mut [Group], group: Option<GroupId>, id: EntityId) {
+    if let Some(group) = group.and_then(|group| groups.get_mut(group.0 as usize)) {
+        group.members.retain(|&member| member != id);
+    }
+}
+
+fn link_name(names: &mut HashMap<String, Vec<EntityId>>, name: &str, id: EntityId) {
+    names.entry(name.to_owned()).or_default().push(id);
+}
+
+fn unlink_name(names: &mut HashMap<String, Vec<EntityId>>, name: Option<&str>, id: EntityId) {
+    let Some(name) = name else {
+        return;
+    };
+    if let Some(ids) = names.get_mut(name) {
+        ids.retain(|&other| other != id);
+        if ids.is_empty() {
+            names.remove(name);
+        }
+    }
+}
+
+// What a saved world holds. Ids, the slot map, the name index and queued
+// removals are runtime bookkeeping; they're rebuilt on load.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct SavedWorldRef<'a> {
+    entities: &'a [Entity],
+    tags: &'a [String],
+    groups: Vec<&'a str>,
+    palette: &'a Palette,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedWorld {
+    entities: Vec<Entity>,
+    tags: Vec<String>,
+    #[serde(default)] // Worlds saved before groups existed
+    groups: Vec<String>,
+    palette: Palette,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for World {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedWorldRef {
+            entities: &self.entities,
+            tags: &self.tags,
+            groups: self
+                .groups
+                .iter()
+                .map(|group| group.name.as_str())
+                .collect(),
+            palette: &self.palette,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for World {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedWorld::deserialize(deserializer)?;
+        if saved.tags.len() > MAX_TAGS {
+            return Err(serde::de::Error::custom(TagError::TooManyTags));
+        }
+        let mut world = World::new();
+        world.tags = saved.tags;
+        world.palette = saved.palette;
+        for name in &saved.groups {
+            world.create_group(name);
+        }
+        for (index, entity) in saved.entities.into_iter().enumerate() {
+            // add_entity only checks in debug builds, and panics there; a bad file
+            // should be an error in both
+            entity
+                .validate()
+                .map_err(|err| serde::de::Error::custom(format!("entity {index}: {err}")))?;
+            world.add_entity(entity);
+        }
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ball(position: [f32; 2]) -> Entity {
+        Entity::new_circle(position, 0.05, [1.0, 1.0, 1.0]).with_physics(Physics::new())
+    }
+
+    fn spawn(world: &mut World, entity: Entity) -> EntityId {
+        world
+            .add_entity(entity)
+            .id()
+            .expect("entity should be added")
+    }
+
+    #[test]
+    fn tags_can_be_added_removed_and_queried() {
+        let mut world = World::new();
+        let enemy = world.register_tag("enemy").unwrap();
+        let boss = world.register_tag("boss").unwrap();
+        assert_eq!(world.register_tag("enemy").unwrap(), enemy);
+        assert_eq!(world.tag("boss"), Some(boss));
+        assert_eq!(world.tag("player"), None);
+
+        let grunt = spawn(&mut world, ball([0.0, 0.0]).with_tag(enemy));
+        let big = spawn(&mut world, ball([0.5, 0.0]).with_tag(enemy).with_tag(boss));
+        let player = spawn(&mut world, ball([-0.5, 0.0]));
+
+        let tagged = |world: &World, tag| world.iter_tagged(tag).map(|(id, _)| id).collect();
+        let enemies: Vec<_> = tagged(&world, enemy);
+        assert_eq!(enemies, [grunt, big]);
+        assert!(!world.get(player).unwrap().has_tag(enemy));
+
+        world.get_mut(big).unwrap().remove_tag(enemy);
+        world.get_mut(player).unwrap().add_tag(enemy);
+        let enemies: Vec<_> = tagged(&world, enemy);
+        assert_eq!(enemies, [grunt, player]);
+        let bosses: Vec<_> = tagged(&world, boss);
+        assert_eq!(bosses, [big]);
+    }
+
+    #[test]
+    fn registering_more_than_max_tags_fails() {
+        let mut world = World::new();
+        for index in 0..MAX_TAGS {
+            world.register_tag(&format!("tag{index}")).unwrap();
+        }
+        assert!(matches!(
+            world.register_tag("one too many"),
+            Err(TagError::TooManyTags)
+        ));
+        // Existing names still resolve once full
+        assert!(world.register_tag("tag0").is_ok());
+    }
+
+    // Load a saved world holding `entity` after `patch` has edited its JSON
+    #[cfg(feature = "serde")]
+    fn load_patched(
+        entity: Entity,
+        patch: impl FnOnce(&mut serde_json::Value),
+    ) -> Result<World, serde_json::Error> {
+        let mut entity = serde_json::to_value(entity).unwrap();
+        patch(&mut entity);
+        let mut saved = serde_json::to_value(World::new()).unwrap();
+        saved["entities"] = serde_json::Value::Array(vec![entity]);
+        serde_json::from_value(saved)
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn loading_rejects_each_kind_of_invalid_entity() {
+        // JSON has no infinity, but 1e39 overflows an f32 into one
+        type Patch = fn(&mut serde_json::Value);
+        let cases: [(Entity, Patch, EntityError); 6] = [
+            (
+                ball([0.0, 0.0]),
+                |entity| entity["transform"]["position"][0] = 1e39.into(),
+                EntityError::NonFinitePosition([f32::INFINITY, 0.0]),
+            ),
+            (
+                ball([0.0, 0.0]),
+                |entity| entity["physics"]["velocity"][1] = (-1e39).into(),
+                EntityError::NonFiniteVelocity([0.0, f32::NEG_INFINITY]),
+            ),
+            (
+                ball([0.0, 0.0]),
+                |entity| entity["physics"]["mass"] = (-1.0).into(),
+                EntityError::BadMass(-1.0),
+            ),
+            (
+                ball([0.0, 0.0]),
+                |entity| entity["shape"]["Circle"]["radius"] = 0.0.into(),
+                EntityError::BadSize {
+                    field: "radius",
+                    value: 0.0,
+                },
+            ),
+            (
+                Entity::new_rectangle([0.0, 0.0], 0.2, 0.1, [1.0, 1.0, 1.0]),
+                |entity| entity["shape"]["Rectangle"]["height"] = (-0.1).into(),
+                EntityError::BadSize {
+                    field: "height",
+                    value: -0.1,
+                },
+            ),
+            (
+                Entity::new_polygon(
+                    [0.0, 0.0],
+                    vec![[0.0, 0.0], [0.1, 0.0], [0.0, 0.1]],
+                    [1.0, 1.0, 1.0],
+                )
+                .unwrap(),
+                |entity| {
+                    entity["shape"]["Polygon"]["vertices"]
+                        .as_array_mut()
+                        .unwrap()
+                        .pop();
+                },
+                EntityError::TooFewVertices(2),
+            ),
+        ];
+        for (entity, patch, expected) in cases {
+            let Err(err) = load_patched(entity, patch) else {
+                panic!("{expected} should fail to load");
+            };
+            assert_eq!(err.to_string(), format!("entity 0: {expected}"));
+        }
+
+        // Untouched, the same entity loads
+        let world = load_patched(ball([0.0, 0.0]), |_| {}).unwrap();
+        assert_eq!(world.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_world_reloads_and_simulates_identically() {
+        use crate::systems::PhysicsSystem;
+
+        let mut original = World::new();
+        let floor = Entity::new_rectangle([0.0, -0.8], 1.8, 0.1, [0.5, 0.5, 0.5])
+            .with_physics(Physics::new_static());
+        spawn(&mut original, floor);
+        for i in 0..8 {
+            let x = -0.7 + i as f32 * 0.2;
+            let mut entity = ball([x, 0.2 + (i % 3) as f32 * 0.15]);
+            entity.physics_mut().unwrap().velocity = [0.3 - x, -0.5];
+            spawn(&mut original, entity);
+        }
+        spawn(
+            &mut original,
+            Entity::new_rectangle([0.2, 0.5], 0.2, 0.1, [1.0, 0.0, 0.0])
+                .with_physics(Physics::new()),
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("balltest-roundtrip-{}.json", std::process::id()));
+        original.save_json(&path).unwrap();
+        let copy = World::load_json(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mut copy = copy.unwrap();
+        assert_eq!(copy.len(), original.len());
+        let (_, floor) = copy.iter().next().unwrap();
+        assert_eq!(floor.physics().unwrap().mass, f32::INFINITY);
+
+        let positions = |world: &World| -> Vec<[f32; 2]> {
+            world.iter().map(|(_, e)| e.transform().position).collect()
+        };
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let (mut physics_a, mut physics_b) = (PhysicsSystem::new(), PhysicsSystem::new());
+        for step in 0..100 {
+            physics_a.update(&mut original, dt);
+            physics_b.update(&mut copy, dt);
+            assert_eq!(
+                positions(&original),
+                positions(&copy),
+                "diverged at step {step}"
+            );
+        }
+    }
+
+    #[test]
+    fn group_operations_leave_the_rest_of_the_world_untouched() {
+        use crate::systems::PhysicsSystem;
+
+        let mut world = World::new();
+        let debris = world.create_group("debris");
+        for i in 0..100 {
+            let id = spawn(
+                &mut world,
+                ball([-0.9 + (i % 10) as f32 * 0.2, (i / 10) as f32 * 0.1]),
+            );
+            world.add_to_group(debris, id);
+        }
+        let others: Vec<_> = (0..5)
+            .map(|i| spawn(&mut world, ball([-0.5 + i as f32 * 0.25, -0.8])))
+            .collect();
+        assert_eq!(world.group_members(debris).len(), 100);
+
+        let positions = |world: &World, ids: &[EntityId]| -> Vec<[f32; 2]> {
+            ids.iter()
+                .map(|&id| world.get(id).unwrap().transform().position)
+                .collect()
+        };
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let mut physics = PhysicsSystem::new();
+
+        // Frozen members hold still but keep the impulse for later
+        world.freeze_group(debris);
+        world.apply_impulse_to_group(debris, [0.0, 0.5]);
+        let members = world.group_members(debris).to_vec();
+        let (before, others_before) = (positions(&world, &members), positions(&world, &others));
+        physics.update(&mut world, dt);
+        assert_eq!(positions(&world, &members), before);
+        assert_ne!(positions(&world, &others), others_before);
+        assert!(
+            members
+                .iter()
+                .all(|&id| world.get(id).unwrap().physics().unwrap().velocity[1] > 0.0)
+        );
+
+        // Thawing a group doesn't beat a world-wide freeze
+        world.freeze_all();
+        world.unfreeze_group(debris);
+        physics.update(&mut world, dt);
+        assert_eq!(positions(&world, &members), before);
+        world.unfreeze_all();
+        world.unfreeze_group(debris);
+        physics.update(&mut world, dt);
+        assert_ne!(positions(&world, &members), before);
+
+        // An unrelated removal queued beforehand is left pending
+        let others_before = positions(&world, &others);
+        world.queue_removal(others[0]);
+        assert_eq!(world.despawn_group(debris), 100);
+        assert!(world.group_members(debris).is_empty());
+        assert_eq!(world.len(), others.len());
+        assert_eq!(positions(&world, &others), others_before);
+        world.flush_removals();
+        assert!(!world.contains(others[0]));
+        assert_eq!(world.len(), others.len() - 1);
+    }
+
+    #[test]
+    fn unfreezing_resumes_as_if_no_time_had_passed() {
+        use crate::systems::PhysicsSystem;
+
+        let scene = || {
+            let mut world = World::new();
+            for i in 0..10 {
+                let mut entity = ball([-0.6 + i as f32 * 0.13, 0.4 - (i % 4) as f32 * 0.1]);
+                entity.physics_mut().unwrap().velocity = [0.4 - i as f32 * 0.08, 0.2];
+                spawn(&mut world, entity);
+            }
+            world
+        };
+        let positions = |world: &World| -> Vec<[f32; 2]> {
+            world.iter().map(|(_, e)| e.transform().position).collect()
+        };
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let (mut frozen, mut control) = (scene(), scene());
+        let (mut physics, mut control_physics) = (PhysicsSystem::new(), PhysicsSystem::new());
+        for _ in 0..30 {
+            physics.update(&mut frozen, dt);
+            control_physics.update(&mut control, dt);
+        }
+
+        // Mid-flight freeze: nothing moves for 100 steps
+        frozen.freeze_all();
+        let held = positions(&frozen);
+        for _ in 0..100 {
+            physics.update(&mut frozen, dt);
+            assert_eq!(positions(&frozen), held);
+        }
+
+        frozen.unfreeze_all();
+        for step in 0..100 {
+            physics.update(&mut frozen, dt);
+            control_physics.update(&mut control, dt);
+            assert_eq!(
+                positions(&frozen),
+                positions(&control),
+                "diverged at step {step}"
+            );
+        }
+    }
+
+    #[test]
+    fn dragging_holds_a_body_until_release_whatever_the_freeze_state() {
+        let mut world = World::new();
+        let id = spawn(
+            &mut world,
+            ball([0.0, 0.0]).with_clickable(Clickable::new()),
+        );
+        let frozen = |world: &World| world.get(id).unwrap().physics().unwrap().frozen;
+
+        // Thawing the world mid-drag leaves the body held, and it's free once let go
+        world.freeze_all();
+        assert_eq!(world.start_drag([0.0, 0.0]), Some(id));
+        world.unfreeze_all();
+        assert!(frozen(&world));
+        world.end_drag();
+        assert!(!frozen(&world));
+
+        // Freezing the world mid-drag applies on release
+        world.start_drag([0.0, 0.0]);
+        world.freeze_all();
+        world.fling([1.0, 0.0]);
+        assert!(frozen(&world));
+
+        // Same for a group freeze, which an unfrozen world then lets go
+        world.unfreeze_all();
+        let group = world.create_group("held");
+        world.add_to_group(group, id);
+        world.start_drag([0.0, 0.0]);
+        world.freeze_group(group);
+        world.end_drag();
+        assert!(frozen(&world));
+        world.start_drag([0.0, 0.0]);
+        world.unfreeze_group(group);
+        assert!(frozen(&world));
+        world.end_drag();
+        assert!(!frozen(&world));
+    }
+
+    #[test]
+    fn validate_reports_a_bad_entity() {
+        let mut world = World::new();
+        let id = spawn(&mut world, ball([0.0, 0.0]));
+        assert!(world.validate().is_empty());
+
+        if let Shape::Circle { radius, .. } = world.get_mut(id).unwrap().shape_mut() {
+            *radius = -1.0;
+        }
+        assert_eq!(
+            world.validate(),
+            [ValidationError::Entity {
+                id,
+                error: EntityError::BadSize {
+                    field: "radius",
+                    value: -1.0,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_reports_a_name_index_out_of_sync() {
+        let mut world = World::new();
+        let id = spawn(&mut world, ball([0.0, 0.0]));
+        world.set_name(id, Some("player".to_string()));
+        assert!(world.validate().is_empty());
+
+        // Renamed behind the index's back: the entity misses its new name's
+        // entry, and the old entry points at an entity without that name
+        world
+            .get_mut(id)
+            .unwrap()
+            .set_name(Some("ghost".to_string()));
+        let errors = world.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.contains(&ValidationError::NameIndex {
+            id,
+            name: "ghost".to_string(),
+        }));
+        assert!(errors.contains(&ValidationError::NameIndex {
+            id,
+            name: "player".to_string(),
+        }));
+    }
+
+    #[test]
+    fn validate_reports_group_members_out_of_sync() {
+        let mut world = World::new();
+        let red = world.create_group("red");
+        let blue = world.create_group("blue");
+        let id = spawn(&mut world, ball([0.0, 0.0]));
+        let other = spawn(&mut world, ball([0.5, 0.0]));
+        world.add_to_group(red, id);
+        world.add_to_group(red, other);
+        assert!(world.validate().is_empty());
+
+        // Listed under red but claiming blue: wrong on both sides
+        world.get_mut(id).unwrap().set_group(Some(blue));
+        let errors = world.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.contains(&ValidationError::GroupMembers { id, group: blue }));
+        assert!(errors.contains(&ValidationError::GroupMembers { id, group: red }));
+    }
+
+    #[test]
+    fn selection_survives_unrelated_removals() {
+        let mut world = World::new();
+        let ids: Vec<_> = (0..5)
+            .map(|i| spawn(&mut world, ball([i as f32 * 0.2, 0.0])))
+            .collect();
+        world.select(ids[1]);
+        world.toggle_selected(ids[3]);
+        world.toggle_selected(ids[4]);
+        world.toggle_selected(ids[4]);
+        assert_eq!(world.selected(), [ids[1], ids[3]]);
+
+        // Removing entities in front of and between the selected ones shifts them
+        world.queue_removal(ids[0]);
+        world.queue_removal(ids[2]);
+        world.flush_removals();
+        assert_eq!(world.selected(), [ids[1], ids[3]]);
+        assert!(world.get(ids[3]).is_some());
+
+        world.deselect(ids[1]);
+        assert_eq!(world.selected(), [ids[3]]);
+        world.clear_selection();
+        assert!(world.selected().is_empty());
+    }
+
+    #[test]
+    fn despawning_a_selected_entity_deselects_it() {
+        let mut world = World::new();
+        let kept = spawn(&mut world, ball([0.0, 0.0]));
+        let doomed = spawn(&mut world, ball([0.5, 0.0]));
+        world.select(kept);
+        world.select(doomed);
+
+        world.queue_removal(doomed);
+        world.flush_removals();
+        assert_eq!(world.selected(), [kept]);
+        assert!(!world.is_selected(doomed));
+
+        // A newcomer reusing the slot doesn't inherit the selection
+        let newcomer = spawn(&mut world, ball([0.5, 0.0]));
+        assert!(!world.is_selected(newcomer));
+        assert_eq!(world.selected(), [kept]);
+    }
+
+    #[test]
+    fn click_dispatch_offers_the_click_topmost_first_until_consumed() {
+        let mut world = World::new();
+        let clickable = |layer| {
+            ball([0.0, 0.0])
+                .with_clickable(Clickable::new())
+                .with_layer(layer)
+        };
+        // Inserted out of order: layer decides which is on top
+        let middle = spawn(&mut world, clickable(1));
+        let top = spawn(&mut world, clickable(2));
+        let bottom = spawn(&mut world, clickable(0));
+        let click = ClickDispatch::new([0.0, 0.0]);
+
+        // Only the top one hears about a consumed click
+        let mut offered = Vec::new();
+        let consumer = click.run(&mut world, |_, id| {
+            offered.push(id);
+            ClickResponse::Consumed
+        });
+        assert_eq!((consumer, offered), (Some(top), vec![top]));
+
+        // Passing it on reaches the next one down, which stops it there
+        let mut offered = Vec::new();
+        let consumer = click.run(&mut world, |_, id| {
+            offered.push(id);
+            if id == top {
+                ClickResponse::PassThrough
+            } else {
+                ClickResponse::Consumed
+            }
+        });
+        assert_eq!((consumer, offered), (Some(middle), vec![top, middle]));
+
+        // A disabled top entity is skipped entirely
+        world.get_mut(top).unwrap().clickable_mut().unwrap().enabled = false;
+        let mut offered = Vec::new();
+        let consumer = click.run(&mut world, |_, id| {
+            offered.push(id);
+            ClickResponse::Consumed
+        });
+        assert_eq!((consumer, offered), (Some(middle), vec![middle]));
+
+        // Nobody consuming means the click fell through to empty space
+        let mut offered = Vec::new();
+        let consumer = click.run(&mut world, |_, id| {
+            offered.push(id);
+            ClickResponse::PassThrough
+        });
+        assert_eq!((consumer, offered), (None, vec![middle, bottom]));
+    }
+
+    #[test]
+    fn restoring_a_snapshot_replays_identically() {
+        use crate::systems::PhysicsSystem;
+
+        let mut world = World::new();
+        let floor = Entity::new_rectangle([0.0, -0.9], 1.8, 0.1, [0.5; 3])
+            .with_physics(Physics::new_static());
+        spawn(&mut world, floor);
+        world.spawn_random_balls(30, Aabb::from_center([0.0, 0.3], [0.7, 0.5]), 0x5a5);
+        let dt = Duration::from_millis(8);
+        let mut physics = PhysicsSystem::new();
+        for _ in 0..50 {
+            physics.update(&mut world, dt);
+        }
+
+        let snapshot = world.snapshot();
+        let run = |world: &mut World, physics: &mut PhysicsSystem| {
+            for _ in 0..500 {
+                physics.update(world, dt);
+            }
+            let state: Vec<_> = world
+                .iter()
+                .map(|(id, e)| (id, e.transform().position, e.physics().map(|p| p.velocity)))
+                .collect();
+            (state, world.sim_steps())
+        };
+        let first = run(&mut world, &mut physics);
+        world.restore(&snapshot);
+        assert_eq!(world.sim_steps(), 50);
+        let second = run(&mut world, &mut physics);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn snapshot_history_steps_back_through_recorded_states() {
+        let mut world = World::new();
+        let mut history = SnapshotHistory::new(2, 10);
+        let id = spawn(&mut world, ball([0.0, 0.0]));
+        let mut x_at_snapshots = Vec::new();
+        for step in 1..=30 {
+            world.get_mut(id).unwrap().transform_mut().position[0] = step as f32 * 0.01;
+            history.record(&world);
+            if step % 10 == 0 {
+                x_at_snapshots.push(step as f32 * 0.01);
+            }
+        }
+        // Capacity 2: the snapshot at step 10 was dropped
+        assert_eq!(history.len(), 2);
+        let x = |world: &World| world.get(id).unwrap().transform().position[0];
+        assert!(history.step_back(&mut world));
+        assert_eq!(x(&world), x_at_snapshots[2]);
+        assert!(history.step_back(&mut world));
+        assert_eq!(x(&world), x_at_snapshots[1]);
+        assert!(!history.step_back(&mut world));
+    }
+
+    fn positions_of(world: &World, ids: &[EntityId]) -> Vec<[f32; 2]> {
+        ids.iter()
+            .map(|&id| world.get(id).unwrap().transform().position)
+            .collect()
+    }
+
+    #[test]
+    fn grid_layout_fills_rows_from_the_bottom_left() {
+        let mut world = World::new();
+        let ids = world.spawn_grid(&ball([0.0, 0.0]), 3, 2, [0.25, 0.5], [-0.5, -0.5]);
+        assert_eq!(ids.len(), 6);
+        assert_eq!(world.len(), 6);
+        assert_eq!(
+            positions_of(&world, &ids),
+            [
+                [-0.5, -0.5],
+                [-0.25, -0.5],
+                [0.0, -0.5],
+                [-0.5, 0.0],
+                [-0.25, 0.0],
+                [0.0, 0.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn ring_layout_starts_at_plus_x_and_goes_around() {
+        let mut world = World::new();
+        let ids = world.spawn_circle_ring(&ball([0.0, 0.0]), 4, 0.5, [0.1, 0.2]);
+        assert_eq!(ids.len(), 4);
+        let expected = [[0.6, 0.2], [0.1, 0.7], [-0.4, 0.2], [0.1, -0.3]];
+        for (position, expected) in positions_of(&world, &ids).into_iter().zip(expected) {
+            assert!(
+                (position[0] - expected[0]).abs() < 1e-6
+                    && (position[1] - expected[1]).abs() < 1e-6,
+                "{position:?} vs {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn random_layout_stays_in_its_rect_and_repeats_with_the_seed() {
+        let rect = Aabb::from_center([0.2, -0.1], [0.3, 0.2]);
+        let layout = |seed| {
+            let mut world = World::new();
+            world.set_seed(seed);
+            let ids = world.spawn_random_in_rect(&ball([0.0, 0.0]), 50, rect);
+            assert_eq!(ids.len(), 50);
+            positions_of(&world, &ids)
+        };
+        let first = layout(3);
+        assert!(first.iter().all(|&position| rect.contains_point(position)));
+        assert_eq!(first, layout(3));
+        assert_ne!(first, layout(4));
+    }
+
+    #[test]
+    fn same_seed_and_calls_give_the_same_world() {
+        let build = |seed| {
+            let mut world = World::new();
+            world.set_seed(seed);
+            let ids = world.spawn_random_in_rect(
+                &ball([0.0, 0.0]),
+                20,
+                Aabb::from_center([0.0; 2], [0.8; 2]),
+            );
+            world.jitter_velocities(&ids, 0.5);
+            let direction = world.rng().unit_vec2();
+            let state: Vec<_> = world
+                .iter()
+                .map(|(_, e)| (e.transform().position, e.physics().unwrap().velocity))
+                .collect();
+            (state, direction)
+        };
+        assert_eq!(build(42), build(42));
+        assert_ne!(build(42), build(43));
+
+        let mut world = World::new();
+        world.set_seed(42);
+        assert_eq!(world.rng().seed(), 42);
+    }
+
+    #[test]
+    fn user_data_comes_back_by_type_and_leaves_with_its_entity() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Score(u32);
+
+        let mut world = World::new();
+        let scored = spawn(&mut world, ball([0.0, 0.0]).with_user_data(Score(10)));
+        let plain = spawn(&mut world, ball([0.5, 0.0]));
+
+        let entity = world.get(scored).unwrap();
+        assert_eq!(entity.user_data::<Score>(), Some(&Score(10)));
+        // Asking for the wrong type, or an entity without data, is just None
+        assert_eq!(entity.user_data::<u32>(), None);
+        assert_eq!(world.get(plain).unwrap().user_data::<Score>(), None);
+
+        world
+            .get_mut(scored)
+            .unwrap()
+            .user_data_mut::<Score>()
+            .unwrap()
+            .0 += 5;
+        let copy = world.duplicate(scored, [0.1, 0.0]).unwrap();
+        assert_eq!(
+            world.get(copy).unwrap().user_data::<Score>(),
+            Some(&Score(15))
+        );
+
+        // Removal hands the data back with the entity, and the slot's next owner starts clean
+        let removed = world.remove_entity(scored).unwrap();
+        assert_eq!(removed.user_data::<Score>(), Some(&Score(15)));
+        let newcomer = spawn(&mut world, ball([0.0, 0.0]));
+        assert_eq!(world.get(newcomer).unwrap().user_data::<Score>(), None);
+        assert_eq!(
+            world.get(copy).unwrap().user_data::<Score>(),
+            Some(&Score(15))
+        );
+    }
+
+    #[test]
+    fn find_at_point_prefers_the_topmost_and_skips_disabled_clickables() {
+        let mut world = World::new();
+        // Circle added after the rectangle, so drawn over it
+        let rect = spawn(
+            &mut world,
+            Entity::new_rectangle([0.0, 0.0], 0.6, 0.4, [0.5; 3]).with_clickable(Clickable::new()),
+        );
+        let circle = spawn(
+            &mut world,
+            Entity::new_circle([0.1, 0.0], 0.1, [1.0; 3]).with_clickable(Clickable::new()),
+        );
+        assert_eq!(world.find_at_point([0.1, 0.0], false), Some(circle));
+        assert_eq!(world.find_at_point([-0.2, 0.0], false), Some(rect));
+        assert_eq!(world.find_at_point([0.5, 0.5], false), None);
+        assert_eq!(world.find_all_at_point([0.1, 0.0], false), [circle, rect]);
+
+        // A disabled top entity still counts for plain picking, but not for clicks
+        world
+            .get_mut(circle)
+            .unwrap()
+            .clickable_mut()
+            .unwrap()
+            .enabled = false;
+        assert_eq!(world.find_at_point([0.1, 0.0], false), Some(circle));
+        assert_eq!(world.find_at_point([0.1, 0.0], true), Some(rect));
+
+        // A higher layer wins over insertion order
+        world.get_mut(rect).unwrap().transform_mut().layer = 1;
+        assert_eq!(world.find_at_point([0.1, 0.0], false), Some(rect));
+    }
+
+    fn radius_of(world: &World, id: EntityId) -> f32 {
+        world.get(id).unwrap().shape().half_extents()[0]
+    }
+
+    #[test]
+    fn random_balls_fill_the_count_inside_the_area_within_their_ranges() {
+        let mut world = World::new();
+        let area = Aabb::from_center([0.2, -0.1], [0.5, 0.4]);
+        let ranges = BallRanges::default();
+        let ids = world.spawn_random_balls(40, area, 7);
+
+        assert_eq!(ids.len(), 40);
+        assert_eq!(world.len(), 40);
+        for &id in &ids {
+            let entity = world.get(id).unwrap();
+            let bounds = entity.shape().bounds(entity.transform());
+            assert!(
+                bounds.min[0] >= area.min[0] - 1e-5
+                    && bounds.min[1] >= area.min[1] - 1e-5
+                    && bounds.max[0] <= area.max[0] + 1e-5
+                    && bounds.max[1] <= area.max[1] + 1e-5,
+                "{bounds:?} pokes out of {area:?}"
+            );
+            let radius = radius_of(&world, id);
+            assert!((ranges.radius[0]..=ranges.radius[1]).contains(&radius));
+            let physics = entity.physics().unwrap();
+            assert!(physics.dynamic);
+            assert!((ranges.restitution[0]..=ranges.restitution[1]).contains(&physics.restitution));
+            let density = physics.mass / (std::f32::consts::PI * radius * radius);
+            assert!(density >= ranges.density[0] * 0.999 && density <= ranges.density[1] * 1.001);
+        }
+    }
+
+    #[test]
+    fn random_balls_do_not_overlap_when_there_is_room() {
+        let mut world = World::new();
+        let ids = world.spawn_random_balls(60, Aabb::from_center([0.0, 0.0], [0.8, 0.8]), 3);
+
+        for (n, &a) in ids.iter().enumerate() {
+            for &b in &ids[n + 1..] {
+                let (pa, pb) = (
+                    world.get(a).unwrap().transform().position,
+                    world.get(b).unwrap().transform().position,
+                );
+                let gap = ((pa[0] - pb[0]).powi(2) + (pa[1] - pb[1]).powi(2)).sqrt()
+                    - radius_of(&world, a)
+                    - radius_of(&world, b);
+                assert!(gap >= -1e-5, "{a:?} and {b:?} overlap by {}", -gap);
+            }
+        }
+    }
+
+    #[test]
+    fn random_balls_still_spawn_all_of_them_in_a_crowded_area() {
+        let mut world = World::new();
+        let area = Aabb::from_center([0.0, 0.0], [0.05, 0.05]);
+        let ids = world.spawn_random_balls(50, area, 11);
+
+        assert_eq!(ids.len(), 50);
+        for &id in &ids {
+            let position = world.get(id).unwrap().transform().position;
+            assert!(
+                area.contains_point(position),
+                "{position:?} outside {area:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn random_balls_are_the_same_for_the_same_seed() {
+        let area = Aabb::from_center([0.0, 0.0], [0.6, 0.6]);
+        let layout = |seed: u64| {
+            let mut world = World::new();
+            let ids = world.spawn_random_balls(30, area, seed);
+            ids.iter()
+                .map(|&id| {
+                    let entity = world.get(id).unwrap();
+                    let physics = entity.physics().unwrap();
+                    (
+                        entity.transform().position,
+                        radius_of(&world, id),
+                        physics.mass,
+                        physics.restitution,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(layout(42), layout(42));
+        assert_ne!(layout(42), layout(43));
+    }
+
+    /// Spawns `count` balls at `position(n)` and returns what `add_entity` said
+    fn spawn_many(
+        world: &mut World,
+        count: usize,
+        position: impl Fn(usize) -> [f32; 2],
+    ) -> Vec<SpawnOutcome> {
+        (0..count)
+            .map(|n| world.add_entity(ball(position(n))))
+            .collect()
+    }
+
+    fn ids_of(outcomes: &[SpawnOutcome]) -> Vec<EntityId> {
+        outcomes.iter().filter_map(|outcome| outcome.id()).collect()
+    }
+
+    fn surviving(world: &World) -> Vec<EntityId> {
+        world.iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn reject_new_keeps_the_first_entities_at_the_cap() {
+        let mut world = World::new();
+        world.set_max_entities(10, EvictionPolicy::RejectNew);
+        let outcomes = spawn_many(&mut world, 50, |n| [n as f32 * 0.01, 0.0]);
+
+        assert_eq!(world.len(), 10);
+        assert_eq!(surviving(&world), ids_of(&outcomes[..10]));
+        assert!(
+            outcomes[10..]
+                .iter()
+                .all(|&outcome| outcome == SpawnOutcome::Rejected)
+        );
+        world.events().update();
+        assert!(world.events().read::<EntityEvicted>().is_empty());
+    }
+
+    #[test]
+    fn evict_oldest_keeps_the_newest_entities_at_the_cap() {
+        let mut world = World::new();
+        world.set_max_entities(10, EvictionPolicy::EvictOldest);
+        let outcomes = spawn_many(&mut world, 50, |n| [n as f32 * 0.01, 0.0]);
+        let ids = ids_of(&outcomes);
+
+        assert_eq!(ids.len(), 50);
+        assert_eq!(surviving(&world), ids[40..]);
+        // Each spawn past the cap pushed out the oldest one left
+        for (n, outcome) in outcomes.iter().enumerate().skip(10) {
+            assert_eq!(
+                *outcome,
+                SpawnOutcome::Evicted {
+                    id: ids[n],
+                    evicted: ids[n - 10],
+                }
+            );
+        }
+        world.events().update();
+        let evicted: Vec<_> = world
+            .events()
+            .read::<EntityEvicted>()
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(evicted, ids[..40]);
+    }
+
+    #[test]
+    fn evict_offscreen_first_gives_up_onscreen_entities_last() {
+        let mut world = World::new();
+        let view = Aabb::from_center([0.0, 0.0], [1.0, 1.0]);
+        world.set_max_entities(10, EvictionPolicy::EvictOffscreenFirst(view));
+        // 10 on screen, then 40 well off it
+        let outcomes = spawn_many(&mut world, 50, |n| {
+            if n < 10 {
+                [n as f32 * 0.1 - 0.5, 0.0]
+            } else {
+                [5.0 + n as f32 * 0.1, 0.0]
+            }
+        });
+        let ids = ids_of(&outcomes);
+
+        // The first offscreen spawn has nothing offscreen to evict, so the oldest
+        // goes; after that each offscreen spawn replaces the one before it
+        let mut expected = ids[1..10].to_vec();
+        expected.push(ids[49]);
+        assert_eq!(surviving(&world), expected);
+    }
+
+    #[test]
+    fn static_and_persistent_entities_are_never_evicted() {
+        let mut world = World::new();
+        let floor = spawn(
+            &mut world,
+            Entity::new_rectangle([0.0, -0.9], 1.8, 0.05, [1.0, 1.0, 1.0])
+                .with_physics(Physics::new_static()),
+        );
+        let decoration = spawn(
+            &mut world,
+            Entity::new_circle([0.0, 0.9], 0.05, [1.0, 1.0, 1.0]),
+        );
+        let keeper = spawn(&mut world, ball([0.0, 0.5]).with_persistent());
+        world.set_max_entities(10, EvictionPolicy::EvictOldest);
+        let ids = ids_of(&spawn_many(&mut world, 50, |n| [n as f32 * 0.01, 0.0]));
+
+        let mut expected = vec![floor, decoration, keeper];
+        expected.extend_from_slice(&ids[43..]);
+        assert_eq!(surviving(&world), expected);
+
+        // With only exempt entities left there's nothing to make room with
+        let mut world = World::new();
+        world.set_max_entities(1, EvictionPolicy::EvictOldest);
+        spawn(&mut world, ball([0.0, 0.0]).with_persistent());
+        assert_eq!(world.add_entity(ball([0.5, 0.0])), SpawnOutcome::Rejected);
+    }
+
+    #[test]
+    fn duplicate_moves_independently_of_the_original() {
+        let moving = || {
+            let mut physics = Physics::new();
+            physics.velocity = [0.3, 0.5];
+            Entity::new_circle([-0.5, 0.0], 0.05, [1.0, 0.5, 0.0]).with_physics(physics)
+        };
+        let trajectory = |world: &mut World, id: EntityId| {
+            let mut physics_system = crate::systems::PhysicsSystem::new();
+            (0..60)
+                .map(|_| {
+                    physics_system.update(world, Duration::from_millis(8));
+                    world.get(id).unwrap().transform().position
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut alone = World::new();
+        let reference = spawn(&mut alone, moving());
+        let expected = trajectory(&mut alone, reference);
+
+        let mut world = World::new();
+        let original = spawn(&mut world, moving());
+        let copy = world.duplicate(original, [1.0, 0.0]).unwrap();
+        assert_ne!(copy, original);
+        assert_eq!(world.get(copy).unwrap().transform().position, [0.5, 0.0]);
+        assert_eq!(
+            world.get(copy).unwrap().physics().unwrap().velocity,
+            [0.3, 0.5]
+        );
+
+        // Sending the copy the other way leaves the original on its own path
+        world.get_mut(copy).unwrap().physics_mut().unwrap().velocity = [0.0, -2.0];
+        assert_eq!(trajectory(&mut world, original), expected);
+        assert_eq!(
+            world.get(original).unwrap().physics().unwrap().velocity,
+            alone.get(reference).unwrap().physics().unwrap().velocity
+        );
+        assert_ne!(world.get(copy).unwrap().transform().position, expected[59]);
+    }
+
+    #[test]
+    fn duplicate_keeps_components_and_suffixes_the_name() {
+        let mut world = World::new();
+        let enemy = world.register_tag("enemy").unwrap();
+        let original = spawn(
+            &mut world,
+            ball([0.0, 0.0])
+                .with_name("hero")
+                .with_tag(enemy)
+                .with_clickable(Clickable::new()),
+        );
+        let copy = world.duplicate(original, [0.2, 0.0]).unwrap();
+
+        let entity = world.get(copy).unwrap();
+        assert_eq!(entity.name(), Some("hero copy"));
+        assert!(entity.has_tag(enemy));
+        assert!(entity.clickable().is_some());
+        assert_eq!(world.find_by_name("hero"), Some(original));
+        assert_eq!(world.find_by_name("hero copy"), Some(copy));
+    }
+
+    #[test]
+    fn duplicating_a_removed_entity_returns_none() {
+        let mut world = World::new();
+        let id = spawn(&mut world, ball([0.0, 0.0]));
+        world.remove_entity(id);
+
+        assert_eq!(world.duplicate(id, [0.1, 0.0]), None);
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    fn reading_an_entity_does_not_mark_it_changed() {
+        let mut world = World::new();
+        let id = spawn(&mut world, ball([0.0, 0.0]).with_name("ball"));
+        assert_eq!(world.drain_changes(), [id]);
+
+        let entity = world.get(id).unwrap();
+        let _ = (
+            entity.transform(),
+            entity.physics(),
+            entity.shape(),
+            entity.name(),
+        );
+        let _ = world.iter().count();
+        let _ = world.find_at_point([0.0, 0.0], false);
+        assert!(world.drain_changes().is_empty());
+    }
+
+    #[test]
+    fn writes_mark_only_the_written_entity_changed() {
+        let mut world = World::new();
+        let a = spawn(&mut world, ball([0.0, 0.0]));
+        let b = spawn(&mut world, ball([0.5, 0.0]));
+        let c = spawn(&mut world, ball([-0.5, 0.0]));
+        world.drain_changes();
+
+        world.get_mut(b).unwrap().transform_mut().position[1] = 0.2;
+        assert_eq!(world.drain_changes(), [b]);
+        // Drained changes don't come back
+        assert!(world.drain_changes().is_empty());
+
+        world.get_mut(c).unwrap().physics_mut().unwrap().velocity = [1.0, 0.0];
+        world.get_mut(a).unwrap().shape_mut().set_opacity(0.5);
+        assert_eq!(world.drain_changes(), [a, c]);
+
+        // Every component handed out mutably counts, whatever is done with it
+        *world.get_mut(a).unwrap().lifetime_mut() =
+            Some(crate::components::Lifetime::new(Duration::from_secs(1)));
+        assert_eq!(world.drain_changes(), [a]);
+        world.get_mut(b).unwrap().color_animation_mut().take();
+        assert_eq!(world.drain_changes(), [b]);
+        world.get_mut(c).unwrap().behavior_mut().take();
+        assert_eq!(world.drain_changes(), [c]);
+        world.get_mut(a).unwrap().emitter_mut().take();
+        assert_eq!(world.drain_changes(), [a]);
+
+        // Systems only reach for the components an entity has: just `a`'s lifetime ticks
+        use crate::systems::{BehaviorSystem, ColorAnimationSystem, EmitterSystem, LifetimeSystem};
+        let dt = Duration::from_millis(8);
+        LifetimeSystem::new().update(&mut world, dt);
+        ColorAnimationSystem::new().update(&mut world, dt);
+        BehaviorSystem::new().update(&mut world, Duration::ZERO, dt);
+        EmitterSystem::new().update(&mut world, dt);
+        assert_eq!(world.drain_changes(), [a]);
+    }
+
+    #[test]
+    fn changed_since_covers_every_change_after_the_tick() {
+        let mut world = World::new();
+        let a = spawn(&mut world, ball([0.0, 0.0]));
+        let b = spawn(&mut world, ball([0.5, 0.0]));
+        world.drain_changes();
+        let tick = world.change_tick();
+
+        world.get_mut(a).unwrap().transform_mut().position[0] = 0.1;
+        world.drain_changes();
+        world.get_mut(b).unwrap().transform_mut().position[0] = 0.6;
+
+        // Both count, drained or not
+        let changed: Vec<_> = world.changed_since(tick).collect();
+        assert_eq!(changed, [a, b]);
+        let later: Vec<_> = world.changed_since(world.change_tick()).collect();
+        assert_eq!(later, [b]);
+    }
+
+    #[test]
+    fn entity_ages_count_sim_steps_and_stop_while_paused() {
+        use crate::systems::{PhysicsSystem, TimeSystem};
+        use std::time::Instant;
+
+        let mut world = World::new();
+        let mut time = TimeSystem::new();
+        let mut physics = PhysicsSystem::new();
+        let dt = time.sim_dt();
+        let mut now = Instant::now();
+        // Runs `frames` frames 40ms apart, returning how many sim steps they took
+        let mut run = |world: &mut World, time: &mut TimeSystem, frames: u32| {
+            let mut steps = 0;
+            for _ in 0..frames {
+                now += Duration::from_millis(40);
+                let (due, ..) = time.tick(now);
+                for _ in 0..due {
+                    physics.update(world, dt);
+                }
+                steps += due;
+            }
+            steps
+        };
+
+        let first = spawn(&mut world, ball([0.0, 0.0]));
+        assert_eq!(world.entity_age(first), Some(Duration::ZERO));
+        let before_second = run(&mut world, &mut time, 4);
+        assert!(before_second > 0);
+        let second = spawn(&mut world, ball([0.5, 0.0]));
+
+        time.toggle_pause();
+        assert_eq!(run(&mut world, &mut time, 10), 0);
+        // Spawned while paused, so it shares the second's spawn time
+        let third = spawn(&mut world, ball([-0.5, 0.0]));
+        assert_eq!(world.entity_age(first), Some(dt * before_second));
+        assert_eq!(world.entity_age(second), Some(Duration::ZERO));
+        time.toggle_pause();
+
+        let after_pause = run(&mut world, &mut time, 3);
+        assert!(after_pause > 0);
+        assert_eq!(world.sim_steps(), u64::from(before_second + after_pause));
+        assert_eq!(
+            world.entity_age(first),
+            Some(dt * (before_second + after_pause))
+        );
+        assert_eq!(world.entity_age(second), Some(dt * after_pause));
+        assert_eq!(world.entity_age(third), Some(dt * after_pause));
+        assert_eq!(world.oldest(), Some(first));
+        assert_eq!(world.newest(), Some(third));
+
+        world.remove_entity(first);
+        assert_eq!(world.entity_age(first), None);
+        assert_eq!(world.oldest(), Some(second));
+    }
+
+    #[test]
+    fn iteration_stays_in_insertion_order_across_removal_and_re_adding() {
+        // The same removals and re-adds, run twice, give the same order
+        let build = || {
+            let mut world = World::new();
+            let ids: Vec<_> = (0..6)
+                .map(|n| spawn(&mut world, ball([n as f32 * 0.1, 0.0])))
+                .collect();
+            world.remove_entity(ids[1]);
+            world.queue_removal(ids[4]);
+            world.queue_removal(ids[0]);
+            world.flush_removals();
+            let first = spawn(&mut world, ball([0.0, 0.5]));
+            let second = spawn(&mut world, ball([0.1, 0.5]));
+            (world, ids, first, second)
+        };
+        let (mut world, ids, first, second) = build();
+
+        // Survivors keep their relative order and new entities go last, even
+        // though they reuse freed slots
+        let order: Vec<_> = world.iter().map(|(id, _)| id).collect();
+        assert_eq!(order, [ids[2], ids[3], ids[5], first, second]);
+        assert!(ids.iter().any(|id| id.index == first.index));
+        assert!(!ids.contains(&first) && !ids.contains(&second));
+        let order_mut: Vec<_> = world.iter_mut().map(|(id, _)| id).collect();
+        assert_eq!(order_mut, order);
+        for (index, &id) in order.iter().enumerate() {
+            assert_eq!(world.index_of(id), Some(index));
+            assert_eq!(world.id_at(index), Some(id));
+        }
+
+        let (again, ..) = build();
+        let positions = |world: &World| {
+            world
+                .iter()
+                .map(|(_, entity)| entity.transform().position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&again), positions(&world));
+        let replay: Vec<_> = again.iter().map(|(id, _)| id).collect();
+        assert_eq!(replay, order);
+    }
+
+    /// Hover `cursor`, then the exits and entries it caused
+    fn hover(world: &mut World, cursor: Option<[f32; 2]>) -> (Vec<EntityId>, Vec<EntityId>) {
+        world.update_hover(cursor);
+        world.events().update();
+        let exited = world
+            .events()
+            .read::<HoverExited>()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        let entered = world
+            .events()
+            .read::<HoverEntered>()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        (exited, entered)
+    }
+
+    fn hovered(world: &World) -> Vec<EntityId> {
+        world
+            .iter()
+            .filter(|(_, entity)| {
+                entity
+                    .clickable()
+                    .is_some_and(|clickable| clickable.hovered)
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    #[test]
+    fn hover_follows_the_cursor_over_the_topmost_clickable() {
+        let mut world = World::new();
+        let panel = spawn(
+            &mut world,
+            Entity::new_rectangle([0.0, 0.0], 0.6, 0.4, [0.5; 3]).with_clickable(Clickable::new()),
+        );
+        let button = spawn(
+            &mut world,
+            Entity::new_circle([0.1, 0.0], 0.1, [1.0; 3]).with_clickable(Clickable::new()),
+        );
+        spawn(&mut world, Entity::new_circle([-0.6, 0.0], 0.1, [1.0; 3]));
+        let mut disabled = Clickable::new();
+        disabled.enabled = false;
+        spawn(
+            &mut world,
+            Entity::new_circle([0.6, 0.0], 0.1, [1.0; 3]).with_clickable(disabled),
+        );
+
+        // Empty space, then onto the panel, then onto the button over it
+        assert_eq!(hover(&mut world, Some([0.0, 0.8])), (vec![], vec![]));
+        assert_eq!(hover(&mut world, Some([-0.2, 0.0])), (vec![], vec![panel]));
+        assert_eq!(hovered(&world), [panel]);
+        assert_eq!(
+            hover(&mut world, Some([0.1, 0.0])),
+            (vec![panel], vec![button])
+        );
+        assert_eq!(hovered(&world), [button]);
+        // Moving within the button changes nothing
+        assert_eq!(hover(&mut world, Some([0.12, 0.02])), (vec![], vec![]));
+
+        // Not clickable, then disabled: neither is ever hovered
+        assert_eq!(hover(&mut world, Some([-0.6, 0.0])), (vec![button], vec![]));
+        assert_eq!(hover(&mut world, Some([0.6, 0.0])), (vec![], vec![]));
+        assert!(hovered(&world).is_empty());
+
+        // Leaving the window clears the hover
+        hover(&mut world, Some([-0.2, 0.0]));
+        assert_eq!(hover(&mut world, None), (vec![panel], vec![]));
+        assert!(hovered(&world).is_empty());
+    }
+
+    #[test]
+    fn dragged_ball_is_held_at_the_cursor_and_dropped_at_rest() {
+        let mut world = World::new();
+        let id = spawn(
+            &mut world,
+            Entity::new_circle([0.0, 0.0], 0.1, [1.0; 3])
+                .with_physics(Physics::new().with_velocity([0.5, 0.0]))
+                .with_clickable(Clickable::new()),
+        );
+        let mut physics = crate::systems::PhysicsSystem::new();
+
+        // Grabbed off-center, it keeps that offset from the cursor
+        assert_eq!(world.start_drag([0.05, 0.0]), Some(id));
+        world.drag_to([0.45, 0.5]);
+        for _ in 0..30 {
+            physics.update(&mut world, Duration::from_millis(8));
+        }
+        let entity = world.get(id).unwrap();
+        let position = entity.transform().position;
+        assert!((position[0] - 0.4).abs() < 1e-6 && position[1] == 0.5);
+        assert_eq!(entity.physics().unwrap().velocity, [0.0, 0.0]);
+
+        assert_eq!(world.end_drag(), Some(id));
+        assert_eq!(world.dragged(), None);
+        physics.update(&mut world, Duration::from_millis(8));
+        let entity = world.get(id).unwrap();
+        assert!(!entity.physics().unwrap().frozen);
+        assert!(
+            entity.transform().position[1] < 0.5,
+            "dropped ball should fall"
+        );
+    }
+
+    #[test]
+    fn balls_resting_on_a_dragged_floor_react_once_it_moves() {
+        let mut world = World::new();
+        let floor = spawn(
+            &mut world,
+            Entity::new_rectangle([0.0, -0.5], 1.0, 0.1, [0.5; 3])
+                .with_physics(Physics::new_static())
+                .with_clickable(Clickable::new()),
+        );
+        let resting = spawn(&mut world, ball([0.0, -0.4]));
+        let mut physics = crate::systems::PhysicsSystem::new();
+        let mut run = |world: &mut World| {
+            for _ in 0..600 {
+                physics.update(world, Duration::from_millis(8));
+            }
+        };
+        run(&mut world);
+        let settled = world.get(resting).unwrap().transform().position;
+        assert!((settled[1] + 0.4).abs() < 0.01, "ball at {settled:?}");
+
+        // Static bodies move directly; lower the floor out from under the ball
+        world.start_drag([0.3, -0.5]);
+        world.drag_to([0.3, -0.8]);
+        world.end_drag();
+        assert_eq!(world.get(floor).unwrap().transform().position, [0.0, -0.8]);
+        run(&mut world);
+        let landed = world.get(resting).unwrap().transform().position;
+        assert!((landed[1] + 0.7).abs() < 0.01, "ball at {landed:?}");
+    }
+
+    #[test]
+    fn queued_balls_spawn_from_the_settings_on_flush() {
+        let mut world = World::new();
+        world.set_seed(9);
+        let settings = SpawnSettings {
+            radius: [0.03, 0.04],
+            color: [[0.5, 0.0, 0.0], [1.0, 0.2, 0.0]],
+            speed: [0.2, 0.3],
+        };
+        world.set_spawn_settings(settings);
+
+        world.queue_ball_at([0.5, 0.5], false);
+        world.queue_ball_at([-0.5, 0.5], true);
+        // Nothing's added until the queue is flushed
+        assert!(world.is_empty());
+        let ids = world.flush_spawns();
+        assert_eq!(ids.len(), 2);
+
+        for (&id, position) in ids.iter().zip([[0.5, 0.5], [-0.5, 0.5]]) {
+            let entity = world.get(id).unwrap();
+            assert_eq!(entity.transform().position, position);
+            assert!(entity.clickable().is_some());
+            let Shape::Circle { radius, color, .. } = *entity.shape() else {
+                panic!("spawned {:?}", entity.shape());
+            };
+            assert!((settings.radius[0]..=settings.radius[1]).contains(&radius));
+            for (channel, value) in color.iter().enumerate() {
+                assert!((settings.color[0][channel]..=settings.color[1][channel]).contains(value));
+            }
+        }
+
+        let dynamic = world.get(ids[0]).unwrap().physics().unwrap();
+        assert!(dynamic.dynamic);
+        let [x, y] = dynamic.velocity;
+        let speed = (x * x + y * y).sqrt();
+        assert!((0.2 - 1e-5..=0.3 + 1e-5).contains(&speed), "speed {speed}");
+
+        // Shift spawns a static ball that stays put
+        let fixed = world.get(ids[1]).unwrap().physics().unwrap();
+        assert!(!fixed.dynamic);
+        assert_eq!(fixed.velocity, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn fling_throws_a_dragged_ball_capped_at_max_speed() {
+        let mut world = World::new();
+        let id = spawn(
+            &mut world,
+            ball([0.0, 0.0]).with_clickable(Clickable::new()),
+        );
+        let velocity = |world: &World| world.get(id).unwrap().physics().unwrap().velocity;
+
+        world.start_drag([0.0, 0.0]);
+        assert_eq!(world.fling([1.5, -2.0]), Some(id));
+        assert_eq!(velocity(&world), [1.5, -2.0]);
+
+        // Too fast keeps the direction at the capped speed
+        assert_eq!(world.max_fling_speed(), DEFAULT_MAX_FLING_SPEED);
+        world.start_drag([0.0, 0.0]);
+        world.fling([DEFAULT_MAX_FLING_SPEED * 3.0, DEFAULT_MAX_FLING_SPEED * 4.0]);
+        let [x, y] = velocity(&world);
+        assert!((x - DEFAULT_MAX_FLING_SPEED * 0.6).abs() < 1e-4);
+        assert!((y - DEFAULT_MAX_FLING_SPEED * 0.8).abs() < 1e-4);
+
+        // A lower cap applies from the next throw
+        world.set_max_fling_speed(1.0);
+        world.start_drag([0.0, 0.0]);
+        world.fling([0.0, -4.0]);
+        assert_eq!(velocity(&world), [0.0, -1.0]);
+
+        // Nothing grabbed, nothing thrown
+        assert_eq!(world.fling([1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn theme_switch_recolors_palette_entities_only() {
+        let mut world = World::new();
+        let palette = world.palette().clone();
+        let themed = spawn(
+            &mut world,
+            Entity::new_circle([0.0, 0.0], 0.05, [0.0; 3]).with_palette_color(&palette, 2),
+        );
+        let explicit = spawn(
+            &mut world,
+            Entity::new_circle([0.5, 0.0], 0.05, [0.3, 0.2, 0.1]),
+        );
+
+        world.set_theme(Theme::Pastel);
+        assert_eq!(world.palette().theme(), Theme::Pastel);
+        let pastel = Palette::new(Theme::Pastel).color(2);
+        assert_ne!(pastel, palette.color(2));
+        assert_eq!(world.get(themed).unwrap().shape().color(), pastel);
+        assert_eq!(
+            world.get(explicit).unwrap().shape().color(),
+            [0.3, 0.2, 0.1]
+        );
+    }
+
+    #[test]
+    fn removing_first_middle_and_last_mid_simulation_leaves_the_rest_on_course() {
+        use crate::systems::PhysicsSystem;
+
+        let build = || {
+            let mut world = World::new();
+            let ids: Vec<_> = (0..7)
+                .map(|i| {
+                    let mut entity = ball([-0.9 + i as f32 * 0.3, 0.5 - i as f32 * 0.05])
+                        .with_name(format!("ball {i}"));
+                    entity.physics_mut().unwrap().velocity = [0.1 * i as f32, 0.2];
+                    spawn(&mut world, entity)
+                })
+                .collect();
+            (world, ids)
+        };
+        let (mut world, ids) = build();
+        let (mut control, control_ids) = build();
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let (mut physics, mut control_physics) = (PhysicsSystem::new(), PhysicsSystem::new());
+        for _ in 0..30 {
+            physics.update(&mut world, dt);
+            control_physics.update(&mut control, dt);
+        }
+
+        for &removed in &[ids[0], ids[3], ids[6]] {
+            assert!(world.remove_entity(removed).is_some());
+            assert!(world.get(removed).is_none());
+        }
+        assert_eq!(world.len(), 4);
+
+        for _ in 0..60 {
+            physics.update(&mut world, dt);
+            control_physics.update(&mut control, dt);
+        }
+        for i in [1, 2, 4, 5] {
+            let (kept, reference) = (
+                world.get(ids[i]).unwrap(),
+                control.get(control_ids[i]).unwrap(),
+            );
+            assert_eq!(kept.name(), Some(format!("ball {i}").as_str()));
+            assert_eq!(world.find_by_name(&format!("ball {i}")), Some(ids[i]));
+            assert_eq!(kept.shape().color(), reference.shape().color());
+            assert_eq!(
+                kept.transform().position,
+                reference.transform().position,
+                "ball {i} left its trajectory"
+            );
+            assert_eq!(
+                kept.physics().unwrap().velocity,
+                reference.physics().unwrap().velocity
+            );
+        }
+    }
+
+    #[test]
+    fn names_track_duplicates_renames_and_removals() {
+        let mut world = World::new();
+        let left = spawn(&mut world, ball([-0.5, 0.0]).with_name("anchor"));
+        let right = spawn(&mut world, ball([0.5, 0.0]).with_name("anchor"));
+        let other = spawn(&mut world, ball([0.0, 0.5]).with_name("paddle"));
+        spawn(&mut world, ball([0.0, -0.5]));
+
+        // Duplicates all resolve, oldest first
+        assert_eq!(world.find_by_name("anchor"), Some(left));
+        assert_eq!(world.find_all_by_name("anchor"), &[left, right]);
+        assert!(world.find_all_by_name("missing").is_empty());
+
+        world.set_name(left, Some("anchor_left".into()));
+        assert_eq!(world.find_by_name("anchor_left"), Some(left));
+        assert_eq!(world.find_all_by_name("anchor"), &[right]);
+        assert_eq!(world.get(left).unwrap().name(), Some("anchor_left"));
+        world.set_name(other, None);
+        assert_eq!(world.find_by_name("paddle"), None);
+        assert_eq!(world.get(other).unwrap().name(), None);
+
+        world.remove_entity(right);
+        assert_eq!(world.find_by_name("anchor"), None);
+        world.queue_removal(left);
+        assert_eq!(
+            world.find_by_name("anchor_left"),
+            Some(left),
+            "still pending"
+        );
+        world.flush_removals();
+        assert_eq!(world.find_by_name("anchor_left"), None);
+
+        // A new entity can take a removed entity's name
+        let reused = spawn(&mut world, ball([0.0, 0.0]).with_name("anchor"));
+        assert_eq!(world.find_all_by_name("anchor"), &[reused]);
+        world.clear();
+        assert_eq!(world.find_by_name("anchor"), None);
+    }
+
+    #[test]
+    fn prefab_spawns_are_independent_copies() {
+        use crate::prefab::Prefab;
+        use crate::systems::PhysicsSystem;
+
+        let paddle = Prefab::from_entity(ball([0.0, 0.0]).with_name("paddle"))
+            .with_child(ball([0.0, 0.0]), [0.12, 0.0]);
+        let mut world = World::new();
+        let spawned: Vec<Vec<EntityId>> = [-0.6, 0.0, 0.6]
+            .iter()
+            .map(|&x| world.spawn_prefab(&paddle, [x, 0.5]))
+            .collect();
+        assert_eq!(world.len(), 6);
+        assert_eq!(world.find_all_by_name("paddle").len(), 3);
+        for (ids, x) in spawned.iter().zip([-0.6, 0.0, 0.6]) {
+            assert_eq!(world.get(ids[0]).unwrap().transform().position, [x, 0.5]);
+            assert_eq!(
+                world.get(ids[1]).unwrap().transform().position,
+                [x + 0.12, 0.5]
+            );
+        }
+        assert!(paddle.parts()[0].transform().position == [0.0, 0.0]);
+
+        // Kick only the middle copy; the outer ones fall as if it weren't there
+        world
+            .get_mut(spawned[1][0])
+            .unwrap()
+            .physics_mut()
+            .unwrap()
+            .velocity = [0.0, 2.0];
+        let mut control = World::new();
+        let alone = control.spawn_prefab(&paddle, [-0.6, 0.5]);
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let (mut physics, mut control_physics) = (PhysicsSystem::new(), PhysicsSystem::new());
+        for _ in 0..60 {
+            physics.update(&mut world, dt);
+            control_physics.update(&mut control, dt);
+        }
+        for (&id, &reference) in spawned[0].iter().zip(&alone) {
+            assert_eq!(
+                world.get(id).unwrap().transform().position,
+                control.get(reference).unwrap().transform().position
+            );
+        }
+        let [left, middle] = [&spawned[0], &spawned[1]]
+            .map(|ids| world.get(ids[0]).unwrap().transform().position[1]);
+        assert!(middle > left, "only the kicked copy rose");
+    }
+
+    #[test]
+    fn joints_are_dropped_with_either_entity_on_every_removal_path() {
+        let mut world = World::new();
+        let ids: Vec<_> = (0..6)
+            .map(|i| spawn(&mut world, ball([-0.5 + i as f32 * 0.2, 0.0])))
+            .collect();
+        assert!(!world.add_joint(ids[0], ids[0]));
+        for pair in ids.chunks(2) {
+            assert!(world.add_joint(pair[0], pair[1]));
+        }
+        assert!((world.joints()[0].length - 0.2).abs() < 1e-6);
+
+        world.remove_entity(ids[1]);
+        assert_eq!(world.joints().len(), 2);
+        world.queue_removal(ids[2]);
+        world.flush_removals();
+        assert_eq!(world.joints().len(), 1);
+        world.retain(|id, _| id != ids[5]);
+        assert!(world.joints().is_empty());
+        assert!(!world.add_joint(ids[4], ids[5]), "ids[5] is gone");
+
+        // Reusing a freed slot doesn't bring its joint back
+        let reused = spawn(&mut world, ball([0.0, 0.5]));
+        assert!(world.joints().is_empty());
+        assert!(world.add_joint(ids[3], reused));
+        world.clear();
+        assert!(world.joints().is_empty());
+    }
+
+    #[test]
+    fn prefab_joints_join_each_copys_own_parts() {
+        use crate::prefab::Prefab;
+        use crate::systems::PhysicsSystem;
+
+        let paddle = Prefab::from_entity(ball([0.0, 0.0]))
+            .with_child(ball([0.0, 0.0]), [0.15, 0.0])
+            .with_child(ball([0.0, 0.0]), [0.3, 0.0])
+            .with_joint(0, 1)
+            .with_joint(1, 2);
+        let mut world = World::new();
+        let copies: Vec<Vec<EntityId>> = [-0.7, 0.2]
+            .iter()
+            .map(|&x| world.spawn_prefab(&paddle, [x, 0.5]))
+            .collect();
+        let joined: Vec<(EntityId, EntityId)> = world
+            .joints()
+            .iter()
+            .map(|joint| (joint.a, joint.b))
+            .collect();
+        assert_eq!(
+            joined,
+            [
+                (copies[0][0], copies[0][1]),
+                (copies[0][1], copies[0][2]),
+                (copies[1][0], copies[1][1]),
+                (copies[1][1], copies[1][2]),
+            ]
+        );
+
+        // Kick one end; the compound stays in one piece
+        world
+            .get_mut(copies[0][0])
+            .unwrap()
+            .physics_mut()
+            .unwrap()
+            .velocity = [0.0, 1.5];
+        let dt = Duration::from_secs_f64(1.0 / 120.0);
+        let mut physics = PhysicsSystem::new();
+        for _ in 0..60 {
+            physics.update(&mut world, dt);
+        }
+        let position = |id: EntityId| world.get(id).unwrap().transform().position;
+        for pair in copies[0].windows(2) {
+            let ([ax, ay], [bx, by]) = (position(pair[0]), position(pair[1]));
+            let length = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+            assert!((length - 0.15).abs() < 1e-3, "parts drifted to {length}");
+        }
+    }
+
+    #[test]
+    fn stats_count_every_field_of_a_mixed_scene() {
+        let mut world = World::new();
+        assert_eq!(world.stats(), WorldStats::default());
+
+        let moving = Physics {
+            mass: 2.0,
+            ..Physics::new().with_velocity([0.3, 0.0])
+        };
+        world
+            .add_entity(Entity::new_circle([-0.5, 0.0], 0.1, [1.0, 0.0, 0.0]).with_physics(moving));
+        world.add_entity(
+            Entity::new_circle([0.5, 0.5], 0.05, [0.0, 1.0, 0.0]).with_physics(Physics::new()),
+        );
+        world.add_entity(
+            Entity::new_rectangle([0.0, -0.8], 0.4, 0.2, [0.5, 0.5, 0.5])
+                .with_physics(Physics::new_static()),
+        );
+        let triangle = vec![[-0.1, -0.1], [0.1, -0.1], [0.0, 0.1]];
+        world.add_entity(Entity::new_polygon([0.7, -0.3], triangle, [0.0, 0.0, 1.0]).unwrap());
+        world.add_entity(Entity::new_text([0.0, 0.9], "Score".into(), 20.0, [1.0; 3]));
+
+        assert_eq!(
+            world.stats(),
+            WorldStats {
+                circles: 2,
+                rectangles: 1,
+                polygons: 1,
+                texts: 1,
+                with_physics: 3,
+                dynamic: 2,
+                static_bodies: 1,
+                at_rest: 1,
+                total_mass: 3.0, // The static rectangle's mass is infinite
+                // Left of the big circle, right of the triangle, rectangle to text
+                bounds: Some(Aabb {
+                    min: [-0.5 - 0.1, -0.8 - 0.1],
+                    max: [0.7 + 0.1, 0.9 + 0.1],
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn scaling_the_spawn_radius_keeps_the_range_inside_the_limits() {
+        let settings = SpawnSettings::default();
+        let [min, max] = settings.with_radius_scaled(2.0).radius;
+        assert!((min - 0.04).abs() < 1e-6 && (max - 0.1).abs() < 1e-6);
+
+        // The whole range stops together, keeping its proportions
+        let [min, max] = settings.with_radius_scaled(100.0).radius;
+        assert_eq!(max, SPAWN_RADIUS_LIMITS[1]);
+        assert!((min / max - 0.4).abs() < 1e-6);
+        let [min, max] = settings.with_radius_scaled(0.01).radius;
+        assert_eq!(min, SPAWN_RADIUS_LIMITS[0]);
+        assert!((min / max - 0.4).abs() < 1e-6);
+
+        // Scrolling back undoes scrolling forward
+        let there_and_back = settings
+            .with_radius_scaled(1.1)
+            .with_radius_scaled(1.0 / 1.1);
+        assert!((there_and_back.radius[1] - settings.radius[1]).abs() < 1e-6);
     }
 }
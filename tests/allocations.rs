@@ -0,0 +1,92 @@
+//! Heap allocation counts for paths that should reuse their buffers. Lives in its
+//! own test binary since the counting allocator replaces the global one.
+
+use balltest_rs::{components::Physics, entity::Entity, systems::PhysicsSystem, world::World};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::time::Duration;
+
+struct CountingAllocator;
+
+thread_local! {
+    // Per thread, so tests running alongside don't count toward each other
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Heap allocations (including reallocations) made by `f` on this thread
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn ball(i: usize) -> Entity {
+    let position = [
+        -0.95 + (i % 40) as f32 * 0.048,
+        -0.95 + (i / 40) as f32 * 0.048,
+    ];
+    Entity::new_circle(position, 0.02, [1.0, 1.0, 1.0]).with_physics(Physics::new())
+}
+
+#[test]
+fn spawning_up_to_the_reserved_capacity_allocates_nothing() {
+    const CAPACITY: usize = 1000;
+    let mut world = World::with_capacity(CAPACITY);
+    let balls: Vec<Entity> = (0..CAPACITY).map(ball).collect();
+
+    let count = allocations(|| {
+        for entity in balls {
+            world.add_entity(entity);
+        }
+    });
+    assert_eq!(count, 0);
+    assert_eq!(world.len(), CAPACITY);
+
+    // One past it has to grow
+    let extra = ball(0);
+    assert!(
+        allocations(|| {
+            world.add_entity(extra);
+        }) > 0
+    );
+}
+
+#[test]
+fn steady_state_physics_updates_allocate_nothing() {
+    let mut world = World::with_capacity(400);
+    for i in 0..400 {
+        world.add_entity(ball(i));
+    }
+    let mut physics = PhysicsSystem::new();
+    let dt = Duration::from_millis(8);
+    // Let the buffers grow to fit while the balls fall and start touching
+    for _ in 0..200 {
+        physics.update(&mut world, dt);
+    }
+
+    let count = allocations(|| {
+        for _ in 0..10 {
+            physics.update(&mut world, dt);
+        }
+    });
+    assert_eq!(count, 0);
+}